@@ -0,0 +1,151 @@
+//! A flock of boids, where every boid steers by the flockmates that a range query finds around it.
+//!
+//! Run with `cargo run --release --example boids`, optionally with the amount of frames to simulate, e.g. `cargo run --release --example boids -- 500`. Every 50th frame is drawn in the terminal.
+
+use whquadtree::{IdentityPoint, Point, QuadTree, R32};
+
+const BOIDS: u32 = 400;
+const SIZE: f32 = 128.0;
+const VIEW_RANGE: f32 = 8.0;
+const SEPARATION_RANGE: f32 = 2.0;
+const MAX_SPEED: f32 = 1.0;
+const COLUMNS: usize = 64;
+const ROWS: usize = 32;
+
+/// The velocity of a boid
+#[derive(Clone, Copy, Default)]
+struct Velocity {
+    x: f32,
+    y: f32,
+}
+
+fn main() {
+    let frames = std::env::args()
+        .nth(1)
+        .and_then(|frames| frames.parse().ok())
+        .unwrap_or(200);
+    let mut tree = QuadTree::<Velocity, u32, 8>::new(Point::zero(), Point::new(SIZE, SIZE));
+    let mut random = Random(0x9e37_79b9);
+    for identity in 0..BOIDS {
+        let point = Point::new(random.next() * SIZE, random.next() * SIZE);
+        let velocity = Velocity {
+            x: random.next() * 2.0 - 1.0,
+            y: random.next() * 2.0 - 1.0,
+        };
+        tree.insert(IdentityPoint { identity, point }, velocity);
+    }
+
+    let mut moves = Vec::with_capacity(BOIDS as usize);
+    for frame in 0..frames {
+        moves.clear();
+        let mut neighbors = 0;
+        tree.find_range(
+            Point::new(SIZE / 2.0, SIZE / 2.0),
+            R32::new(SIZE),
+            |identity, point, velocity| {
+                let (velocity, seen) = steer(&tree, *identity, point, *velocity);
+                neighbors += seen;
+                moves.push((*identity, point, velocity));
+            },
+        );
+        for &(identity, point, velocity) in &moves {
+            // the flock wraps around the edges of the tree
+            let x = (point.x.raw() + velocity.x).rem_euclid(SIZE);
+            let y = (point.y.raw() + velocity.y).rem_euclid(SIZE);
+            tree.update_point_and_value(identity, Point::new(x, y), |old| *old = velocity);
+        }
+        if frame % 50 == 0 || frame + 1 == frames {
+            println!(
+                "frame {frame}: every boid sees {:.1} flockmates on average",
+                neighbors as f32 / BOIDS as f32
+            );
+            draw(&tree);
+        }
+    }
+}
+
+/// The new velocity of the boid `identity`, and the amount of flockmates that it sees
+fn steer(
+    tree: &QuadTree<Velocity, u32, 8>,
+    identity: u32,
+    point: Point,
+    velocity: Velocity,
+) -> (Velocity, usize) {
+    let (mut seen, mut center, mut heading, mut away) = (
+        0,
+        Velocity::default(),
+        Velocity::default(),
+        Velocity::default(),
+    );
+    tree.find_range(
+        point,
+        R32::new(VIEW_RANGE),
+        |other, other_point, other_velocity| {
+            if *other == identity {
+                return;
+            }
+            let dx = other_point.x.raw() - point.x.raw();
+            let dy = other_point.y.raw() - point.y.raw();
+            seen += 1;
+            center.x += dx;
+            center.y += dy;
+            heading.x += other_velocity.x;
+            heading.y += other_velocity.y;
+            if dx * dx + dy * dy < SEPARATION_RANGE * SEPARATION_RANGE {
+                away.x -= dx;
+                away.y -= dy;
+            }
+        },
+    );
+    let mut new = velocity;
+    if seen > 0 {
+        let seen = seen as f32;
+        // cohesion, alignment and separation
+        new.x += center.x / seen * 0.01 + (heading.x / seen - velocity.x) * 0.05 + away.x * 0.05;
+        new.y += center.y / seen * 0.01 + (heading.y / seen - velocity.y) * 0.05 + away.y * 0.05;
+    }
+    let speed = (new.x * new.x + new.y * new.y).sqrt();
+    if speed > MAX_SPEED {
+        new.x *= MAX_SPEED / speed;
+        new.y *= MAX_SPEED / speed;
+    }
+    (new, seen)
+}
+
+/// Draw the boids, with a digit for the amount of boids in every cell of the terminal
+fn draw(tree: &QuadTree<Velocity, u32, 8>) {
+    let mut counts = [[0_u8; COLUMNS]; ROWS];
+    tree.find_range(
+        Point::new(SIZE / 2.0, SIZE / 2.0),
+        R32::new(SIZE),
+        |_, point, _| {
+            let column = ((point.x.raw() / SIZE * COLUMNS as f32) as usize).min(COLUMNS - 1);
+            let row = ((point.y.raw() / SIZE * ROWS as f32) as usize).min(ROWS - 1);
+            counts[row][column] = counts[row][column].saturating_add(1);
+        },
+    );
+    for line in counts {
+        let line = line
+            .iter()
+            .map(|count| match count {
+                0 => ' ',
+                1..=9 => char::from(b'0' + count),
+                _ => '#',
+            })
+            .collect::<String>();
+        println!("{line}");
+    }
+}
+
+/// A xorshift generator, which is good enough to scatter the boids
+struct Random(u32);
+
+impl Random {
+    /// A number between 0 and 1
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+}
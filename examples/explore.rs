@@ -0,0 +1,140 @@
+//! Watch a quad tree split and merge in the terminal.
+//!
+//! Run with `cargo run --example explore` and type commands, or pipe a script into it:
+//!
+//! - `add X Y` inserts a point, `add N` inserts `N` random points
+//! - `move ID X Y` moves a point, `del ID` removes it
+//! - `query X Y RANGE` highlights the points in range, and shows how many nodes the query visited
+//! - `quit` stops
+//!
+//! The tree covers `0, 0` to `64, 64`. Every leaf is drawn with its edges, points are drawn as `o`, and points that were found by the last query as `@`.
+
+use std::io::{self, BufRead, Write};
+use whquadtree::{IdentityPoint, NodeVisit, Point, QuadTree, R32};
+
+const COLUMNS: usize = 65;
+const ROWS: usize = 33;
+const SIZE: f32 = 64.0;
+
+fn main() -> io::Result<()> {
+    let mut tree = QuadTree::<(), u32, 4>::new(Point::zero(), Point::new(SIZE, SIZE));
+    let mut next_identity = 0;
+    let mut random = Random(0x2545_f491);
+    let mut found = Vec::new();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    draw(&mut out, &tree, &found)?;
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let numbers = words
+            .iter()
+            .skip(1)
+            .filter_map(|word| word.parse::<f32>().ok())
+            .collect::<Vec<_>>();
+        found.clear();
+        match (words.first().copied(), numbers.as_slice()) {
+            (Some("add"), &[x, y]) => {
+                insert(&mut tree, &mut next_identity, Point::new(x, y));
+            }
+            (Some("add"), &[count]) => {
+                for _ in 0..count as usize {
+                    let point = Point::new(random.next() * SIZE, random.next() * SIZE);
+                    insert(&mut tree, &mut next_identity, point);
+                }
+            }
+            (Some("move"), &[identity, x, y]) => {
+                if !tree.update(identity as u32, Point::new(x, y)) {
+                    writeln!(out, "point {identity} does not exist")?;
+                }
+            }
+            (Some("del"), &[identity]) => {
+                if tree.try_remove(&(identity as u32)).is_none() {
+                    writeln!(out, "point {identity} does not exist")?;
+                }
+            }
+            (Some("query"), &[x, y, range]) => {
+                let (center, range) = (Point::new(x, y), R32::new(range));
+                tree.find_range(center, range, |identity, _, ()| found.push(*identity));
+                let trace = tree.explain_find_range(center, range);
+                writeln!(
+                    out,
+                    "found {} points, compared {} points in {} nodes",
+                    trace.matches(),
+                    trace.comparisons(),
+                    trace.nodes().len()
+                )?;
+            }
+            (Some("quit"), _) => break,
+            _ => writeln!(
+                out,
+                "commands: add X Y, add N, move ID X Y, del ID, query X Y RANGE, quit"
+            )?,
+        }
+        draw(&mut out, &tree, &found)?;
+    }
+    Ok(())
+}
+
+fn insert(tree: &mut QuadTree<(), u32, 4>, next_identity: &mut u32, point: Point) {
+    tree.insert(
+        IdentityPoint {
+            identity: *next_identity,
+            point,
+        },
+        (),
+    );
+    *next_identity += 1;
+}
+
+/// Draw the leaves and points of `tree`, with the points in `found` highlighted
+fn draw(out: &mut impl Write, tree: &QuadTree<(), u32, 4>, found: &[u32]) -> io::Result<()> {
+    let mut canvas = [[' '; COLUMNS]; ROWS];
+    let column =
+        |x: R32| ((x.raw() / SIZE * (COLUMNS - 1) as f32).round() as usize).min(COLUMNS - 1);
+    let row = |y: R32| ((y.raw() / SIZE * (ROWS - 1) as f32).round() as usize).min(ROWS - 1);
+    // a query that covers the whole tree visits every node
+    let trace = tree.explain_find_range(Point::new(SIZE / 2.0, SIZE / 2.0), R32::new(SIZE));
+    for node in trace.nodes() {
+        if !matches!(node.visit, NodeVisit::Leaf { .. }) {
+            continue;
+        }
+        let (top_left, bottom_right) = (node.rect.top_left(), node.rect.bottom_right());
+        let (left, right) = (column(top_left.x), column(bottom_right.x));
+        let (top, bottom) = (row(top_left.y), row(bottom_right.y));
+        canvas[top][left..=right].fill('-');
+        canvas[bottom][left..=right].fill('-');
+        for line in canvas.iter_mut().take(bottom + 1).skip(top) {
+            line[left] = '|';
+            line[right] = '|';
+        }
+        for (x, y) in [(left, top), (right, top), (left, bottom), (right, bottom)] {
+            canvas[y][x] = '+';
+        }
+    }
+    tree.find_range(
+        Point::new(SIZE / 2.0, SIZE / 2.0),
+        R32::new(SIZE),
+        |identity, point, ()| {
+            canvas[row(point.y)][column(point.x)] =
+                if found.contains(identity) { '@' } else { 'o' };
+        },
+    );
+    for line in canvas {
+        writeln!(out, "{}", line.iter().collect::<String>())?;
+    }
+    out.flush()
+}
+
+/// A xorshift generator, which is good enough to scatter points
+struct Random(u32);
+
+impl Random {
+    /// A number between 0 and 1
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+}
@@ -0,0 +1,285 @@
+//! A headless stress test, which runs a workload against a quad tree and prints how long the updates and queries took.
+//!
+//! Run with `cargo run --release --example stress -- [OPTIONS]`:
+//!
+//! - `--profile uniform|clustered|swarm|boundary` picks the workload, `swarm` by default:
+//!   - `uniform` moves random entries to random positions
+//!   - `clustered` moves entries around a few dense clusters
+//!   - `swarm` moves every entry a small step every frame
+//!   - `boundary` moves entries back and forth over the edges between the four children of the root, so their leaves split and merge all the time
+//! - `--entries N` is the amount of entries, 10000 by default
+//! - `--frames N` is the amount of frames, 500 by default
+//! - `--queries N` is the amount of range queries every frame, 100 by default
+//! - `--bucket N` is the bucket size of the tree: 1, 2, 4, 8, 16 or 32, 8 by default
+//! - `--deferred-merges N` defers merges, and does the merges of at most `N` nodes at the end of every frame
+//! - `--seed N` changes the random numbers, so the same options always give the same workload
+//!
+//! Every 50 frames the average time of a frame, the amount of entries that the queries found, and the amount of nodes of the tree are printed.
+
+use std::time::{Duration, Instant};
+use whquadtree::{IdentityPoint, MaintenanceBudget, Point, QuadTree, R32};
+
+const SIZE: f32 = 1024.0;
+const QUERY_RANGE: f32 = 16.0;
+const REPORT_EVERY: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Profile {
+    Uniform,
+    Clustered,
+    Swarm,
+    Boundary,
+}
+
+struct Options {
+    profile: Profile,
+    entries: u32,
+    frames: usize,
+    queries: usize,
+    bucket: usize,
+    deferred_merges: Option<usize>,
+    seed: u32,
+}
+
+fn main() {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}, see the documentation at the top of examples/stress.rs");
+            std::process::exit(1);
+        }
+    };
+    match options.bucket {
+        1 => run::<1>(&options),
+        2 => run::<2>(&options),
+        4 => run::<4>(&options),
+        8 => run::<8>(&options),
+        16 => run::<16>(&options),
+        32 => run::<32>(&options),
+        bucket => {
+            eprintln!("a bucket size of {bucket} is not supported");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        profile: Profile::Swarm,
+        entries: 10_000,
+        frames: 500,
+        queries: 100,
+        bucket: 8,
+        deferred_merges: None,
+        seed: 1,
+    };
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("{arg} needs a value"))?;
+        let number = || {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("{arg} needs a number, not {value}"))
+        };
+        match arg.as_str() {
+            "--profile" => {
+                options.profile = match value.as_str() {
+                    "uniform" => Profile::Uniform,
+                    "clustered" => Profile::Clustered,
+                    "swarm" => Profile::Swarm,
+                    "boundary" => Profile::Boundary,
+                    _ => return Err(format!("unknown profile {value}")),
+                }
+            }
+            "--entries" => {
+                options.entries = u32::try_from(number()?).map_err(|error| error.to_string())?;
+            }
+            "--frames" => options.frames = number()?,
+            "--queries" => options.queries = number()?,
+            "--bucket" => options.bucket = number()?,
+            "--deferred-merges" => options.deferred_merges = Some(number()?),
+            "--seed" => {
+                options.seed = u32::try_from(number()?).map_err(|error| error.to_string())?
+            }
+            _ => return Err(format!("unknown option {arg}")),
+        }
+    }
+    Ok(options)
+}
+
+fn run<const N: usize>(options: &Options) {
+    let mut random = Random(options.seed.max(1));
+    let clusters = (0..8)
+        .map(|_| (random.next() * SIZE, random.next() * SIZE))
+        .collect::<Vec<_>>();
+    let mut workload = Workload {
+        profile: options.profile,
+        clusters,
+        velocities: Vec::new(),
+        random,
+    };
+    let mut tree = QuadTree::<u32, u32, N>::new(Point::zero(), Point::new(SIZE, SIZE));
+    if options.deferred_merges.is_some() {
+        tree.set_deferred_merges(true);
+    }
+    let mut points = Vec::with_capacity(options.entries as usize);
+    for identity in 0..options.entries {
+        let point = workload.spawn();
+        points.push(point);
+        let velocity = workload.velocity();
+        workload.velocities.push(velocity);
+        tree.insert(IdentityPoint { identity, point }, identity);
+    }
+    println!(
+        "{:?} workload, {} entries, bucket size {N}, merges {}",
+        options.profile,
+        options.entries,
+        options
+            .deferred_merges
+            .map_or("right away".to_owned(), |budget| format!(
+                "deferred with a budget of {budget} nodes"
+            ))
+    );
+    println!("frames     update ms   query ms   maintenance ms   found/query   nodes");
+
+    let (mut update, mut query, mut maintenance, mut found) =
+        (Duration::ZERO, Duration::ZERO, Duration::ZERO, 0);
+    for frame in 1..=options.frames {
+        let start = Instant::now();
+        for (identity, point) in (0..).zip(points.iter_mut()) {
+            if let Some(new_point) = workload.step(frame, identity, *point) {
+                *point = new_point;
+                tree.update(identity, new_point);
+            }
+        }
+        update += start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..options.queries {
+            let center = points[workload.random.below(points.len())];
+            tree.find_range(center, R32::new(QUERY_RANGE), |_, _, _| found += 1);
+        }
+        query += start.elapsed();
+
+        let start = Instant::now();
+        if let Some(budget) = options.deferred_merges {
+            tree.maintenance(MaintenanceBudget::nodes(budget));
+        }
+        maintenance += start.elapsed();
+
+        if frame % REPORT_EVERY == 0 || frame == options.frames {
+            let frames = (frame - 1) % REPORT_EVERY + 1;
+            let per_frame = |duration: Duration| duration.as_secs_f64() * 1000.0 / frames as f64;
+            let nodes = tree
+                .estimate_range_cost(Point::new(SIZE / 2.0, SIZE / 2.0), R32::new(SIZE))
+                .nodes;
+            println!(
+                "{:>6}   {:>11.3}   {:>8.3}   {:>14.3}   {:>11.1}   {nodes:>5}",
+                frame,
+                per_frame(update),
+                per_frame(query),
+                per_frame(maintenance),
+                found as f64 / (frames * options.queries.max(1)) as f64,
+            );
+            (update, query, maintenance, found) =
+                (Duration::ZERO, Duration::ZERO, Duration::ZERO, 0);
+        }
+    }
+}
+
+struct Workload {
+    profile: Profile,
+    clusters: Vec<(f32, f32)>,
+    velocities: Vec<(f32, f32)>,
+    random: Random,
+}
+
+impl Workload {
+    /// The position of a new entry
+    fn spawn(&mut self) -> Point {
+        match self.profile {
+            Profile::Uniform | Profile::Swarm => self.anywhere(),
+            Profile::Clustered => self.in_cluster(),
+            Profile::Boundary => self.near_boundary(),
+        }
+    }
+
+    /// The velocity of a new entry, which only matters for swarms
+    fn velocity(&mut self) -> (f32, f32) {
+        (
+            self.random.next() * 2.0 - 1.0,
+            self.random.next() * 2.0 - 1.0,
+        )
+    }
+
+    /// The new position of the entry `identity`, or `None` if it does not move during `frame`
+    fn step(&mut self, frame: usize, identity: u32, point: Point) -> Option<Point> {
+        match self.profile {
+            // a few percent of the entries move every frame
+            Profile::Uniform => (self.random.below(32) == 0).then(|| self.anywhere()),
+            Profile::Clustered => (self.random.below(32) == 0).then(|| self.in_cluster()),
+            Profile::Swarm => {
+                let velocity = &mut self.velocities[identity as usize];
+                let mut x = point.x.raw() + velocity.0;
+                let mut y = point.y.raw() + velocity.1;
+                if !(0.0..SIZE).contains(&x) {
+                    velocity.0 = -velocity.0;
+                    x = x.clamp(0.0, SIZE);
+                }
+                if !(0.0..SIZE).contains(&y) {
+                    velocity.1 = -velocity.1;
+                    y = y.clamp(0.0, SIZE);
+                }
+                Some(Point::new(x, y))
+            }
+            Profile::Boundary => {
+                // mirror the entry over the middle of the tree every frame, which moves it into another child of the root
+                let middle = SIZE / 2.0;
+                let flip = |value: f32| middle - (value - middle);
+                (frame % 2 == identity as usize % 2)
+                    .then(|| Point::new(flip(point.x.raw()), flip(point.y.raw())))
+            }
+        }
+    }
+
+    fn anywhere(&mut self) -> Point {
+        Point::new(self.random.next() * SIZE, self.random.next() * SIZE)
+    }
+
+    fn in_cluster(&mut self) -> Point {
+        let (x, y) = self.clusters[self.random.below(self.clusters.len())];
+        // the sum of a few random numbers is close to a normal distribution
+        let offset = |random: &mut Random| (0..4).map(|_| random.next() - 0.5).sum::<f32>() * 32.0;
+        let x = (x + offset(&mut self.random)).clamp(0.0, SIZE);
+        let y = (y + offset(&mut self.random)).clamp(0.0, SIZE);
+        Point::new(x, y)
+    }
+
+    fn near_boundary(&mut self) -> Point {
+        let middle = SIZE / 2.0;
+        let near = |random: &mut Random| middle + (random.next() - 0.5) * 2.0;
+        let along = self.random.next() * SIZE;
+        if self.random.below(2) == 0 {
+            Point::new(near(&mut self.random), along)
+        } else {
+            Point::new(along, near(&mut self.random))
+        }
+    }
+}
+
+/// A xorshift generator, which makes the workloads reproducible
+struct Random(u32);
+
+impl Random {
+    /// A number between 0 and 1
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// A number below `limit`
+    fn below(&mut self, limit: usize) -> usize {
+        ((self.next() * limit as f32) as usize).min(limit.saturating_sub(1))
+    }
+}
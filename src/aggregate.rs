@@ -0,0 +1,246 @@
+//! [`AggregateQuadTree`], which keeps the smallest and largest key of the values below every node, so the entry with the smallest or largest key in range can skip whole subtrees.
+
+use crate::{
+    bucket::Bucket, index::Index, point::Quadrant, FindRangeCtx, IdentityPoint, Point, QuadTree,
+    Rect, R32,
+};
+use std::cmp::Ordering;
+
+/// A [`QuadTree`] that keeps the smallest and largest key of the values in every node, e.g. the strength of an enemy.
+///
+/// The key of every value is extracted with the function that is passed to [`AggregateQuadTree::new`]. After every change, only the nodes that changed since the last change are aggregated again, which is found with the same generations as [`QuadTree::save_incremental`] uses. Only the most common methods are wrapped, [`AggregateQuadTree::tree`] can be used for every other query.
+///
+/// The aggregates only compare keys with [`Ord`], and ties are broken by the lowest identity, so the results do not depend on the order of the changes, the layout of the tree or the platform. Two trees with the same entries always return the same entries, which makes them safe to compare in a replay.
+#[derive(Clone, Debug)]
+pub struct AggregateQuadTree<T, ID, V, const N: usize> {
+    tree: QuadTree<T, ID, N>,
+    key: fn(&T) -> V,
+    /// The aggregate of every node, at the same index as the node
+    nodes: Vec<NodeAggregate<V>>,
+}
+
+#[derive(Clone, Debug)]
+struct NodeAggregate<V> {
+    /// The generation of the tree when this aggregate was computed
+    generation: u64,
+    /// The smallest and largest key below this node, or `None` if there are no entries below it
+    min_max: Option<(V, V)>,
+}
+
+impl<V> Default for NodeAggregate<V> {
+    fn default() -> Self {
+        Self {
+            generation: 0,
+            min_max: None,
+        }
+    }
+}
+
+impl<T, ID, V, const N: usize> AggregateQuadTree<T, ID, V, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+    V: Ord + Clone,
+{
+    /// Create a new tree which covers the area between `top_left` and `bottom_right`, see [`QuadTree::new`], which aggregates the `key` of its values
+    #[must_use]
+    pub fn new(top_left: Point, bottom_right: Point, key: fn(&T) -> V) -> Self {
+        Self {
+            tree: QuadTree::new(top_left, bottom_right),
+            key,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// The tree without the aggregates, for the queries that are not wrapped
+    #[must_use]
+    pub fn tree(&self) -> &QuadTree<T, ID, N> {
+        &self.tree
+    }
+
+    /// Insert a value `value` at the given `point`, see [`QuadTree::insert`]
+    pub fn insert(&mut self, point: IdentityPoint<ID>, value: T) {
+        self.tree.insert(point, value);
+        self.refresh();
+    }
+
+    /// Update the given identity to the new point, see [`QuadTree::update`].
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise
+    pub fn update(&mut self, identity: ID, point: Point) -> bool {
+        let updated = self.tree.update(identity, point);
+        self.refresh();
+        updated
+    }
+
+    /// Update the given identity to the new point, with the opportunity to update the value, see [`QuadTree::update_point_and_value`]. The key of the value is extracted again after `callback` returns.
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise
+    pub fn update_point_and_value(
+        &mut self,
+        identity: ID,
+        point: Point,
+        callback: impl FnOnce(&mut T),
+    ) -> bool {
+        let updated = self.tree.update_point_and_value(identity, point, callback);
+        self.refresh();
+        updated
+    }
+
+    /// Try to remove the entry with the given identity from this tree. Will return the entry and its position if it's found, `None` otherwise.
+    pub fn try_remove(&mut self, identity: &ID) -> Option<(T, Point)> {
+        let removed = self.tree.try_remove(identity);
+        self.refresh();
+        removed
+    }
+
+    /// The entry with the largest key within `range` of point `center`, or `None` if no entry is in range. If multiple entries have the largest key, the one with the lowest identity is returned.
+    ///
+    /// Subtrees whose largest key is smaller than the key of the best entry so far are skipped. Entries outside of this tree are always compared.
+    #[must_use]
+    pub fn max_in_range(&self, center: Point, range: R32) -> Option<(&ID, Point, &T)> {
+        self.best_in_range(center, range, Ordering::Greater)
+    }
+
+    /// The entry with the smallest key within `range` of point `center`, or `None` if no entry is in range. If multiple entries have the smallest key, the one with the lowest identity is returned.
+    ///
+    /// Subtrees whose smallest key is larger than the key of the best entry so far are skipped. Entries outside of this tree are always compared.
+    #[must_use]
+    pub fn min_in_range(&self, center: Point, range: R32) -> Option<(&ID, Point, &T)> {
+        self.best_in_range(center, range, Ordering::Less)
+    }
+
+    /// The entry in range whose key compares as `better` to every other key
+    fn best_in_range(
+        &self,
+        center: Point,
+        range: R32,
+        better: Ordering,
+    ) -> Option<(&ID, Point, &T)> {
+        let ctx = FindRangeCtx::new(center, range);
+        let mut best = None;
+        self.best_in_range_inner(self.tree.rect, Index::ROOT, &ctx, better, &mut best);
+        for (identity, (value, point)) in &self.tree.outside_of_range {
+            if ctx.point_in_range(*point) {
+                self.offer(better, (identity, *point, value), &mut best);
+            }
+        }
+        best.map(|(_, entry)| entry)
+    }
+
+    fn best_in_range_inner<'a>(
+        &'a self,
+        rect: Rect,
+        index: Index,
+        ctx: &FindRangeCtx,
+        better: Ordering,
+        best: &mut Option<Best<'a, T, ID, V>>,
+    ) {
+        if Rect::new(ctx.center, ctx.center).min_distance_squared(rect) > ctx.range_squared {
+            return;
+        }
+        let Some((min, max)) = self
+            .nodes
+            .get(index.to_idx())
+            .and_then(|node| node.min_max.as_ref())
+        else {
+            return;
+        };
+        let bound = if better == Ordering::Greater {
+            max
+        } else {
+            min
+        };
+        // a subtree with the same key as the best entry can still have an entry with a lower identity
+        if matches!(best, Some((key, _)) if bound.cmp(key) == better.reverse()) {
+            return;
+        }
+        match self.tree.items.get(index.to_idx()) {
+            Some(Bucket::Owned(entries)) => {
+                for (ip, value) in entries {
+                    if ctx.point_in_range(ip.point) {
+                        self.offer(better, (&ip.identity, ip.point, value), best);
+                    }
+                }
+            }
+            Some(Bucket::Nested) => {
+                for child in Quadrant::all() {
+                    if let Some(child_index) = index.checked_child_at(child) {
+                        self.best_in_range_inner(
+                            rect.get_child_at(child),
+                            child_index,
+                            ctx,
+                            better,
+                            best,
+                        );
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn offer<'a>(
+        &self,
+        better: Ordering,
+        entry: (&'a ID, Point, &'a T),
+        best: &mut Option<Best<'a, T, ID, V>>,
+    ) {
+        let key = (self.key)(entry.2);
+        let replace = match best {
+            None => true,
+            Some((best_key, (best_identity, _, _))) => match key.cmp(best_key) {
+                Ordering::Equal => entry.0 < *best_identity,
+                ordering => ordering == better,
+            },
+        };
+        if replace {
+            *best = Some((key, entry));
+        }
+    }
+
+    /// Aggregate the nodes that changed since the last refresh again
+    fn refresh(&mut self) {
+        let generation = self.tree.generation();
+        self.refresh_node(Index::ROOT, generation, false);
+    }
+
+    fn refresh_node(&mut self, index: Index, generation: u64, rewritten: bool) -> Option<(V, V)> {
+        let idx = index.to_idx();
+        if self.nodes.len() <= idx {
+            self.nodes.resize_with(idx + 1, NodeAggregate::default);
+        }
+        let changed = self.tree.dirty.node(index);
+        let computed = self.nodes[idx].generation;
+        if !rewritten && changed.subtree <= computed {
+            return self.nodes[idx].min_max.clone();
+        }
+        // the children of a rewritten node were created or moved without being marked themselves
+        let rewritten = rewritten || changed.rewritten > computed;
+        let min_max = match self.tree.items.get(idx) {
+            Some(Bucket::Owned(entries)) => {
+                let mut keys = entries.iter().map(|(_, value)| (self.key)(value));
+                keys.next().map(|first| {
+                    keys.fold((first.clone(), first), |(min, max), key| {
+                        (min.min(key.clone()), max.max(key))
+                    })
+                })
+            }
+            Some(Bucket::Nested) => Quadrant::all()
+                .into_iter()
+                .filter_map(|child| index.checked_child_at(child))
+                .filter_map(|child| self.refresh_node(child, generation, rewritten))
+                .reduce(|(min, max), (child_min, child_max)| {
+                    (min.min(child_min), max.max(child_max))
+                }),
+            None => None,
+        };
+        self.nodes[idx] = NodeAggregate {
+            generation,
+            min_max: min_max.clone(),
+        };
+        min_max
+    }
+}
+
+/// The best entry that was found so far, with its key
+type Best<'a, T, ID, V> = (V, (&'a ID, Point, &'a T));
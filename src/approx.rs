@@ -0,0 +1,93 @@
+//! [`QuadTree::find_range_approx`], a range query that does not compare the entries of nodes near the edge of the range, e.g. for heatmaps that do not need an exact edge.
+
+use crate::{
+    bucket::Bucket, index::Index, point::clamped_r32, point::Quadrant, FindRangeCtx, Point,
+    QuadTree, Rect, R32,
+};
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Find the entries within `range` of point `center`, like [`QuadTree::find_range`], but allow an error of `epsilon` at the edge of the range:
+    ///
+    /// - every entry less than `range - epsilon` away from `center` is found
+    /// - every entry that is found is at most `range + epsilon` away from `center`
+    ///
+    /// A node that lies completely within `range + epsilon` is found with all its entries, and a node that lies completely outside of `range - epsilon` is skipped, without comparing their entries. Only the entries of the remaining nodes are compared with the range, so a larger `epsilon` compares fewer entries. Entries outside of this tree are always compared.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `epsilon` is negative.
+    pub fn find_range_approx<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        epsilon: R32,
+        mut callback: impl FnMut(&'a ID, Point, &'a T),
+    ) {
+        assert!(epsilon >= 0.0, "Epsilon {epsilon} should not be negative");
+        let ctx = FindRangeCtx::new(center, range);
+        let inner = (range.raw() - epsilon.raw()).max(0.0);
+        let outer = range.raw() + epsilon.raw();
+        let bounds = Bounds {
+            inner_squared: clamped_r32(inner * inner),
+            outer_squared: clamped_r32(outer * outer),
+        };
+        self.find_range_approx_inner(self.rect, Index::ROOT, &ctx, &bounds, &mut callback);
+        for (identity, (value, point)) in &self.outside_of_range {
+            if ctx.point_in_range(*point) {
+                callback(identity, *point, value);
+            }
+        }
+    }
+
+    fn find_range_approx_inner<'a>(
+        &'a self,
+        rect: Rect,
+        index: Index,
+        ctx: &FindRangeCtx,
+        bounds: &Bounds,
+        callback: &mut impl FnMut(&'a ID, Point, &'a T),
+    ) {
+        if !ctx.contains_rect(rect) {
+            return;
+        }
+        if rect.max_distance_squared(ctx.center) <= bounds.outer_squared {
+            self.for_each_entry_below(index, callback);
+            return;
+        }
+        if Rect::new(ctx.center, ctx.center).min_distance_squared(rect) > bounds.inner_squared {
+            return;
+        }
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Owned(items)) => {
+                for (ident, value) in items {
+                    if ctx.point_in_range(ident.point) {
+                        callback(&ident.identity, ident.point, value);
+                    }
+                }
+            }
+            Some(Bucket::Nested) => {
+                for child in Quadrant::all() {
+                    if let Some(child_index) = index.checked_child_at(child) {
+                        self.find_range_approx_inner(
+                            rect.get_child_at(child),
+                            child_index,
+                            ctx,
+                            bounds,
+                            callback,
+                        );
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// The squared distances within which every node is found, and outside of which every node is skipped
+struct Bounds {
+    inner_squared: R32,
+    outer_squared: R32,
+}
@@ -0,0 +1,293 @@
+//! Print, check and query trees that were saved with `QuadTree::save_incremental`, without writing a program for it.
+//!
+//! Run with `cargo run --features cli --bin quadtree-inspect -- COMMAND [OPTIONS] FILE...`. The files are applied in order, so a full save can be followed by the deltas that were saved after it.
+//!
+//! Commands:
+//!
+//! - `stats` prints the amount of entries and nodes, and how deep and how full the leaves are
+//! - `validate` checks the internal state of the tree, and exits with an error if it is inconsistent
+//! - `svg` writes an image of the leaves and entries to standard output
+//! - `range X Y RANGE` prints the entries within `RANGE` of `X, Y`
+//! - `rect X1 Y1 X2 Y2` prints the entries in the rect between the corners `X1, Y1` and `X2, Y2`
+//!
+//! The saved entries do not describe their own identity and value, so they are read as:
+//!
+//! - `--id-bytes N`: an unsigned little endian integer of `N` bytes, 1 to 8, 4 by default
+//! - `--value-bytes N`: `N` bytes which are printed as hex, 0 by default
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Write},
+};
+use whquadtree::{FlatTree, Point, QuadTree, FLAT_NODE_NESTED, R32};
+
+/// The tree that the files are loaded into. The layout of the tree is read from the files, so the bucket size only matters for entries that are inserted after loading.
+type Tree = QuadTree<Vec<u8>, u64, 4>;
+
+enum Command {
+    Stats,
+    Validate,
+    Svg,
+    Range(Point, R32),
+    Rect(Point, Point),
+}
+
+struct Options {
+    command: Command,
+    id_bytes: usize,
+    value_bytes: usize,
+    files: Vec<String>,
+}
+
+fn main() {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}, see the documentation at the top of src/bin/quadtree-inspect.rs");
+            std::process::exit(2);
+        }
+    };
+    if let Err(error) = run(&options) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let command = args.next().ok_or("missing command")?;
+    let mut numbers = |count: usize| {
+        (0..count)
+            .map(|_| {
+                let arg = args
+                    .next()
+                    .ok_or(format!("{command} needs {count} numbers"))?;
+                arg.parse::<f32>()
+                    .ok()
+                    .filter(|number| number.is_finite())
+                    .ok_or(format!("{command} needs numbers, not {arg}"))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let command = match command.as_str() {
+        "stats" => Command::Stats,
+        "validate" => Command::Validate,
+        "svg" => Command::Svg,
+        "range" => {
+            let numbers = numbers(3)?;
+            Command::Range(Point::new(numbers[0], numbers[1]), R32::new(numbers[2]))
+        }
+        "rect" => {
+            let numbers = numbers(4)?;
+            Command::Rect(
+                Point::new(numbers[0], numbers[1]),
+                Point::new(numbers[2], numbers[3]),
+            )
+        }
+        _ => return Err(format!("unknown command {command}")),
+    };
+    let mut options = Options {
+        command,
+        id_bytes: 4,
+        value_bytes: 0,
+        files: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            options.files.push(arg);
+            continue;
+        }
+        let value = args.next().ok_or(format!("{arg} needs a value"))?;
+        let number = value
+            .parse::<usize>()
+            .map_err(|_| format!("{arg} needs a number, not {value}"))?;
+        match arg.as_str() {
+            "--id-bytes" if (1..=8).contains(&number) => options.id_bytes = number,
+            "--id-bytes" => return Err(format!("{arg} should be 1 to 8, not {number}")),
+            "--value-bytes" => options.value_bytes = number,
+            _ => return Err(format!("unknown option {arg}")),
+        }
+    }
+    if options.files.is_empty() {
+        return Err("missing file".to_string());
+    }
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let (tree, generation) = load(options)?;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let result = match options.command {
+        Command::Stats => stats(&mut out, &tree, generation),
+        Command::Validate => {
+            tree.validate().map_err(|error| error.to_string())?;
+            writeln!(out, "ok: {} entries are consistent", tree.len())
+        }
+        Command::Svg => svg(&mut out, &tree.export_flat()),
+        Command::Range(center, range) => {
+            let mut found = Vec::new();
+            tree.find_range(center, range, |identity, point, value| {
+                found.push((*identity, point, value));
+            });
+            print_entries(&mut out, &found)
+        }
+        Command::Rect(a, b) => {
+            let mut found = Vec::new();
+            tree.find_rect(a, b, |identity, point, value| {
+                found.push((*identity, point, value));
+            });
+            print_entries(&mut out, &found)
+        }
+    };
+    result.map_err(|error| error.to_string())
+}
+
+/// Apply every file to a new tree that covers the area in the header of the first file, and return it with the generation of the last file
+fn load(options: &Options) -> Result<(Tree, u64), String> {
+    let open = |path: &String| {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|error| format!("{path}: {error}"))
+    };
+    // the header is `b"WHQD"`, the generation as a `u64`, and the left, top, right and bottom of the tree as `f32`s
+    let mut header = [0; 28];
+    open(&options.files[0])?
+        .read_exact(&mut header)
+        .map_err(|_| format!("{}: not a quad tree delta", options.files[0]))?;
+    let coordinate = |offset: usize| {
+        f32::from_le_bytes(header[offset..offset + 4].try_into().unwrap_or_default())
+    };
+    let corner = |offset: usize| {
+        Point::try_new(coordinate(offset), coordinate(offset + 4))
+            .map_err(|error| format!("{}: {error}", options.files[0]))
+    };
+    let mut tree = Tree::new(corner(12)?, corner(20)?);
+
+    let mut generation = 0;
+    for path in &options.files {
+        generation = tree
+            .apply_incremental(&mut open(path)?, |reader| {
+                let mut id = [0; 8];
+                reader.read_exact(&mut id[..options.id_bytes])?;
+                let mut value = vec![0; options.value_bytes];
+                reader.read_exact(&mut value)?;
+                Ok((u64::from_le_bytes(id), value))
+            })
+            .map_err(|error| format!("{path}: {error}"))?;
+    }
+    Ok((tree, generation))
+}
+
+fn stats(out: &mut impl Write, tree: &Tree, generation: u64) -> io::Result<()> {
+    let flat = tree.export_flat();
+    let mut leaves = Vec::new();
+    collect_leaves(&flat, 0, 0, &mut leaves);
+    let rect = flat.rect;
+    writeln!(
+        out,
+        "area:         ({}, {}) to ({}, {})",
+        rect.left, rect.top, rect.right, rect.bottom
+    )?;
+    writeln!(out, "generation:   {generation}")?;
+    writeln!(out, "entries:      {}", flat.points.len())?;
+    writeln!(
+        out,
+        "outside:      {}",
+        flat.points.len() - flat.outside_start as usize
+    )?;
+    writeln!(out, "nodes:        {}", flat.nodes.len())?;
+    writeln!(out, "leaves:       {}", leaves.len())?;
+    writeln!(
+        out,
+        "empty leaves: {}",
+        leaves.iter().filter(|(_, count)| *count == 0).count()
+    )?;
+    let max_depth = leaves.iter().map(|(depth, _)| *depth).max().unwrap_or(0);
+    writeln!(out, "max depth:    {max_depth}")?;
+    writeln!(
+        out,
+        "largest leaf: {}",
+        leaves.iter().map(|(_, count)| *count).max().unwrap_or(0)
+    )?;
+    for depth in 0..=max_depth {
+        let (count, entries) = leaves
+            .iter()
+            .filter(|(leaf_depth, _)| *leaf_depth == depth)
+            .fold((0, 0), |(count, entries), (_, leaf_entries)| {
+                (count + 1, entries + leaf_entries)
+            });
+        if count > 0 {
+            writeln!(
+                out,
+                "depth {depth:>2}:     {count} leaves, {entries} entries"
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Push the depth and the amount of entries of every leaf below `node`
+fn collect_leaves(
+    flat: &FlatTree<'_, Vec<u8>, u64>,
+    node: usize,
+    depth: usize,
+    leaves: &mut Vec<(usize, usize)>,
+) {
+    let node = flat.nodes[node];
+    if node.kind == FLAT_NODE_NESTED {
+        for child in node.first..node.first + node.count {
+            collect_leaves(flat, child as usize, depth + 1, leaves);
+        }
+    } else {
+        leaves.push((depth, node.count as usize));
+    }
+}
+
+fn svg(out: &mut impl Write, flat: &FlatTree<'_, Vec<u8>, u64>) -> io::Result<()> {
+    let rect = flat.rect;
+    let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+    let radius = width.max(height) / 400.;
+    // the entries outside of the tree are left out of the image, so they do not shrink the tree to a dot
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {width} {height}">"#,
+        rect.left, rect.top
+    )?;
+    for node in flat
+        .nodes
+        .iter()
+        .filter(|node| node.kind != FLAT_NODE_NESTED)
+    {
+        let node_rect = node.rect;
+        writeln!(
+            out,
+            r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="gray" stroke-width="1" vector-effect="non-scaling-stroke"/>"#,
+            node_rect.left,
+            node_rect.top,
+            node_rect.right - node_rect.left,
+            node_rect.bottom - node_rect.top
+        )?;
+    }
+    for point in &flat.points[..flat.outside_start as usize] {
+        writeln!(
+            out,
+            r#"  <circle cx="{}" cy="{}" r="{radius}" fill="black"/>"#,
+            point.x, point.y
+        )?;
+    }
+    writeln!(out, "</svg>")
+}
+
+fn print_entries(out: &mut impl Write, found: &[(u64, Point, &Vec<u8>)]) -> io::Result<()> {
+    for (identity, point, value) in found {
+        write!(out, "{identity} {} {}", point.x, point.y)?;
+        if !value.is_empty() {
+            write!(out, " ")?;
+            for byte in *value {
+                write!(out, "{byte:02x}")?;
+            }
+        }
+        writeln!(out)?;
+    }
+    writeln!(out, "found: {}", found.len())
+}
@@ -1,4 +1,4 @@
-use crate::Point;
+use crate::{CorruptionError, Point};
 use smallvec::SmallVec;
 
 /// A bucket that stores information on this bucket
@@ -10,32 +10,43 @@ use smallvec::SmallVec;
 #[repr(u8)]
 pub enum Bucket<T, ID, const N: usize> {
     Nested = 1,
-    Owned(SmallVec<[(IdentityPoint<ID>, T); N]>) = 2,
+    Owned(Entries<T, ID, N>) = 2,
 }
+
+/// The entries that are stored in a single [`Bucket`]
+pub type Entries<T, ID, const N: usize> = SmallVec<[(IdentityPoint<ID>, T); N]>;
 impl<T, ID, const N: usize> Bucket<T, ID, N> {
-    pub(crate) fn as_owned_mut(&mut self) -> OwnedMut<T, ID, N> {
+    pub(crate) fn as_owned_mut(&mut self) -> Result<OwnedMut<'_, T, ID, N>, CorruptionError> {
         if let Self::Owned(v) = self {
-            OwnedMut(v)
+            Ok(OwnedMut(v))
         } else {
-            panic!("Bucket is not owned")
+            Err(CorruptionError::new("bucket is not owned"))
         }
     }
 }
 
 #[derive(Debug)]
-pub struct OwnedMut<'a, T, ID, const N: usize>(&'a mut SmallVec<[(IdentityPoint<ID>, T); N]>);
+pub struct OwnedMut<'a, T, ID, const N: usize>(&'a mut Entries<T, ID, N>);
 
 impl<'a, T, ID, const N: usize> OwnedMut<'a, T, ID, N>
 where
     ID: std::cmp::PartialEq<ID>,
 {
-    pub(crate) fn remove_by_identity(&mut self, identity: &ID) -> T {
+    pub(crate) fn remove_by_identity(&mut self, identity: &ID) -> Result<T, CorruptionError> {
         let idx = self
             .0
             .iter()
             .position(|(p, _)| &p.identity == identity)
-            .unwrap();
-        self.0.remove(idx).1
+            .ok_or(CorruptionError::new("entry is missing from its bucket"))?;
+        Ok(self.0.remove(idx).1)
+    }
+
+    pub(crate) fn value_by_identity(self, identity: &ID) -> Result<&'a mut T, CorruptionError> {
+        self.0
+            .iter_mut()
+            .find(|(p, _)| &p.identity == identity)
+            .map(|(_, value)| value)
+            .ok_or(CorruptionError::new("entry is missing from its bucket"))
     }
 
     // pub(crate) fn push(&mut self, ident: IdentityPoint, value: T) {
@@ -61,7 +72,7 @@ where
         }
     }
 
-    pub(crate) fn into_inner(self) -> &'a mut SmallVec<[(IdentityPoint<ID>, T); N]> {
+    pub(crate) fn into_inner(self) -> &'a mut Entries<T, ID, N> {
         self.0
     }
 }
@@ -80,9 +91,9 @@ fn assert_smallvec_size() {
 /// The quad tree will assume that entries with the same [`identity`] can be safely overwritten.
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct IdentityPoint<ID> {
-    ///
+    /// The identity of this entry
     pub identity: ID,
-    ///
+    /// The position of this entry
     // TODO: Can we get rid of this?
     pub point: Point,
 }
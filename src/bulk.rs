@@ -0,0 +1,354 @@
+//! Building a [`QuadTree`] from many entries at once, instead of inserting them one by one.
+//!
+//! [`QuadTree::from_iter_bulk`] builds a tree from entries in memory, by splitting them over the nodes top-down so every node is written once. Collecting entries into a [`QuadTree`] does the same, with the rect around the entries as the area of the tree, and [`QuadTree::optimize`] rebuilds an existing tree the same way. [`BulkBuilder`] builds a tree from more unsorted entries than fit in memory at once, which are read in two passes. The first pass spills every entry to a temporary file for the area of the tree that contains it, the second pass reads those files back one at a time in Z-order, so every part of the tree is built from entries that are close together.
+
+use crate::{
+    bucket::Bucket, curve::morton_code, ensure_index_valid, expect_consistent, index::Index,
+    point::Quadrant, CorruptionError, IdentityPoint, Point, QuadTree, Rect,
+};
+use smallvec::SmallVec;
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The deepest partitioning that is allowed, which keeps up to `4 ^ 4 + 1 = 257` spill files open at the same time
+const MAX_PARTITION_DEPTH: u8 = 4;
+
+/// Used to give the spill files of every build a unique name
+static BUILD_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Builds a [`QuadTree`] from a stream of unsorted entries by partitioning them on disk first, see the [module documentation](self).
+///
+/// Only the input is streamed, the finished tree is still stored in memory.
+#[derive(Clone, Debug)]
+pub struct BulkBuilder {
+    rect: Rect,
+    spill_dir: PathBuf,
+    partition_depth: u8,
+}
+
+impl BulkBuilder {
+    /// Create a builder for a tree that covers the area between `top_left` and `bottom_right`, see [`QuadTree::new`].
+    ///
+    /// By default the entries are spilled to [`std::env::temp_dir`], partitioned into `4 ^ 3 = 64` areas.
+    #[must_use]
+    pub fn new(top_left: Point, bottom_right: Point) -> Self {
+        Self {
+            rect: Rect::new(top_left, bottom_right),
+            spill_dir: std::env::temp_dir(),
+            partition_depth: 3,
+        }
+    }
+
+    /// Spill the entries to files in `dir` instead
+    #[must_use]
+    pub fn spill_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.spill_dir = dir.into();
+        self
+    }
+
+    /// Partition the entries into `4 ^ depth` areas, with a maximum depth of 4. More areas use less memory in the second pass, but create more files.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `depth` is larger than 4.
+    #[must_use]
+    pub fn partition_depth(mut self, depth: u8) -> Self {
+        assert!(
+            depth <= MAX_PARTITION_DEPTH,
+            "partition depth {depth} is larger than {MAX_PARTITION_DEPTH}"
+        );
+        self.partition_depth = depth;
+        self
+    }
+
+    /// Build a tree from all entries in `reader`.
+    ///
+    /// `read_entry` is called to read the next entry from `reader`, and should return `Ok(None)` at the end of the input. The spill files are written with `write_entry` and read back with `read_entry`, so `write_entry` must write the format that `read_entry` reads. The spill files are removed again before this returns, also when an error is returned.
+    ///
+    /// If an identity occurs more than once, it is not specified which of its entries is kept.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `reader`, `read_entry` or `write_entry` fail, or if the spill files can not be created, written or read.
+    pub fn from_reader<T, ID, const N: usize>(
+        &self,
+        mut reader: impl Read,
+        mut read_entry: impl FnMut(&mut dyn Read) -> io::Result<Option<(IdentityPoint<ID>, T)>>,
+        mut write_entry: impl FnMut(&mut dyn Write, &IdentityPoint<ID>, &T) -> io::Result<()>,
+    ) -> io::Result<QuadTree<T, ID, N>>
+    where
+        ID: Ord + std::fmt::Debug + Clone,
+    {
+        let mut spill = Spill::new(&self.spill_dir, 1 << (2 * self.partition_depth));
+        while let Some((point, value)) = read_entry(&mut reader)? {
+            let partition = self.partition_of(point.point);
+            write_entry(spill.writer(partition)?, &point, &value)?;
+        }
+
+        let mut tree = QuadTree::new(self.rect.top_left(), self.rect.bottom_right());
+        for partition in 0..spill.files.len() {
+            let mut entries = Vec::new();
+            if let Some(mut reader) = spill.reader(partition)? {
+                while let Some(entry) = read_entry(&mut reader)? {
+                    entries.push(entry);
+                }
+            }
+            entries.sort_by_key(|(ip, _)| morton_code(self.rect, ip.point));
+            for (point, value) in entries {
+                tree.insert(point, value);
+            }
+        }
+        Ok(tree)
+    }
+
+    /// The partition that `point` is spilled to. Points outside of the tree go to the last partition.
+    fn partition_of(&self, point: Point) -> usize {
+        let partitions = 1 << (2 * self.partition_depth);
+        if !self.rect.contains(point) {
+            return partitions;
+        }
+        // the highest bits of the Morton code are the quadrants of the highest nodes
+        let shift = 32 - 2 * u32::from(self.partition_depth);
+        morton_code(self.rect, point)
+            .checked_shr(shift)
+            .unwrap_or(0) as usize
+    }
+}
+
+/// The spill files of a single build, which removes them when dropped
+struct Spill {
+    paths: Vec<PathBuf>,
+    files: Vec<Option<BufWriter<File>>>,
+}
+
+impl Spill {
+    fn new(dir: &Path, partitions: usize) -> Self {
+        let build = BUILD_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let paths = (0..=partitions)
+            .map(|partition| {
+                dir.join(format!(
+                    "whquadtree-{}-{build}-{partition}.spill",
+                    std::process::id()
+                ))
+            })
+            .collect();
+        Self {
+            paths,
+            files: (0..=partitions).map(|_| None).collect(),
+        }
+    }
+
+    fn writer(&mut self, partition: usize) -> io::Result<&mut BufWriter<File>> {
+        let file = &mut self.files[partition];
+        if file.is_none() {
+            *file = Some(BufWriter::new(
+                File::options()
+                    .write(true)
+                    .create_new(true)
+                    .open(&self.paths[partition])?,
+            ));
+        }
+        Ok(file.as_mut().expect("the file was just created"))
+    }
+
+    /// Open `partition` for reading, or `None` if nothing was spilled to it
+    fn reader(&mut self, partition: usize) -> io::Result<Option<BufReader<File>>> {
+        let Some(writer) = self.files[partition].take() else {
+            return Ok(None);
+        };
+        writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+        let file = File::open(&self.paths[partition])?;
+        Ok(Some(BufReader::new(file)))
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            // most partitions are never created, and there is nothing to do if removing one fails
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Build a tree which covers the area between `top_left` and `bottom_right`, see [`QuadTree::new`], from all `entries` at once.
+    ///
+    /// The entries are split over the nodes from the root down, and every node is filled once, instead of splitting leaves and moving their entries again for every [`QuadTree::insert`]. The layout of a tree that is built by inserts depends on the order of the inserts, this layout follows the same rules without that order: a leaf only has more than `N` entries if it can not be split, or if all of its entries are in the same child. If an identity occurs more than once, the last of its entries is kept, the same as for [`QuadTree::insert`].
+    #[must_use]
+    pub fn from_iter_bulk(
+        top_left: Point,
+        bottom_right: Point,
+        entries: impl IntoIterator<Item = (IdentityPoint<ID>, T)>,
+    ) -> Self {
+        let mut tree = Self::new(top_left, bottom_right);
+        tree.fill_bulk(entries.into_iter().collect());
+        tree
+    }
+
+    /// Rebuild the nodes of this tree from its current entries, the same way as [`QuadTree::from_iter_bulk`].
+    ///
+    /// The layout of a tree that changed for a long time follows the order of its changes: removed entries can leave nested nodes with only a few entries below them, e.g. when merges are deferred, and entries that moved away leave deep leaves behind. After this, the layout only depends on the entries, and every nested node has more than `N` entries below it. The entries outside of the range of this tree and the generations of all entries stay the same, see [`QuadTree::weak_ref`], and pending merges are dropped because no node is left to merge.
+    pub fn optimize(&mut self) {
+        let mut entries = Vec::with_capacity(self.identity_to_point.len());
+        for bucket in std::mem::take(&mut self.items) {
+            if let Bucket::Owned(bucket) = bucket {
+                entries.extend(bucket);
+            }
+        }
+        // the same order as `fill_bulk`, so the leaves are the same as those of a new tree
+        entries.sort_unstable_by(|(a, _), (b, _)| a.identity.cmp(&b.identity));
+        self.items = vec![Bucket::Owned(SmallVec::new_const())];
+        if let Some(pending) = &mut self.pending_merges {
+            pending.clear();
+        }
+        expect_consistent(self.fill_bulk_node(Index::ROOT, self.rect, entries));
+        self.dirty.touch(Index::ROOT);
+    }
+
+    /// Fill this tree, which must be empty, with `entries`, see [`QuadTree::from_iter_bulk`]
+    fn fill_bulk(&mut self, mut entries: Vec<(IdentityPoint<ID>, T)>) {
+        // the sort is stable, so the last entry of every identity is the last one that was given
+        entries.sort_by(|(a, _), (b, _)| a.identity.cmp(&b.identity));
+        let mut inside = Vec::with_capacity(entries.len());
+        let mut previous: Option<(IdentityPoint<ID>, T)> = None;
+        for entry in entries {
+            if let Some(previous) = previous.take() {
+                if previous.0.identity != entry.0.identity {
+                    self.push_bulk(previous, &mut inside);
+                }
+            }
+            previous = Some(entry);
+        }
+        if let Some(previous) = previous {
+            self.push_bulk(previous, &mut inside);
+        }
+        expect_consistent(self.fill_bulk_node(Index::ROOT, self.rect, inside));
+        self.dirty.touch(Index::ROOT);
+        if !self.outside_of_range.is_empty() {
+            self.dirty.touch_outside();
+        }
+    }
+
+    /// Store `entry` outside of this tree if it is outside of its rect, or push it to `inside` otherwise
+    fn push_bulk(
+        &mut self,
+        (ip, value): (IdentityPoint<ID>, T),
+        inside: &mut Vec<(IdentityPoint<ID>, T)>,
+    ) {
+        if self.rect.contains(ip.point) {
+            inside.push((ip, value));
+        } else {
+            let generation = self.new_generation();
+            self.identity_to_point
+                .insert(ip.identity.clone(), (ip.point, None, generation));
+            self.outside_of_range.insert(ip.identity, (value, ip.point));
+        }
+    }
+
+    /// Make the node at `index` a leaf with `entries`, or nest it and split `entries` over its children, with the same rules as a split after an insert
+    fn fill_bulk_node(
+        &mut self,
+        index: Index,
+        rect: Rect,
+        entries: Vec<(IdentityPoint<ID>, T)>,
+    ) -> Result<(), CorruptionError> {
+        let first_quadrant = entries.first().map(|(ip, _)| rect.get_quadrant(ip.point).1);
+        if entries.len() <= N
+            || index.children().is_none()
+            || rect.extent() < self.min_cell_extent.raw()
+            || Quadrant::all()
+                .into_iter()
+                .any(|quadrant| rect.get_child_at(quadrant) == rect)
+            || entries
+                .iter()
+                .all(|(ip, _)| Some(rect.get_quadrant(ip.point).1) == first_quadrant)
+        {
+            for (ip, _) in &entries {
+                // an entry that was already in this tree keeps its generation, it is only stored somewhere else
+                if let Some((_, leaf, _)) = self.identity_to_point.get_mut(&ip.identity) {
+                    *leaf = Some(index);
+                } else {
+                    let generation = self.new_generation();
+                    self.identity_to_point
+                        .insert(ip.identity.clone(), (ip.point, Some(index), generation));
+                }
+            }
+            *ensure_index_valid(&mut self.items, index)? =
+                Bucket::Owned(entries.into_iter().collect());
+            return Ok(());
+        }
+
+        *ensure_index_valid(&mut self.items, index)? = Bucket::Nested;
+        let mut children: [Vec<_>; 4] = Default::default();
+        for (ip, value) in entries {
+            let quadrant = rect.get_quadrant(ip.point).1;
+            children[quadrant as usize].push((ip, value));
+        }
+        for (quadrant, entries) in Quadrant::all().into_iter().zip(children) {
+            self.fill_bulk_node(
+                index.child_at(quadrant),
+                rect.get_child_at(quadrant),
+                entries,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, ID, const N: usize> Extend<(IdentityPoint<ID>, T)> for QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Insert all `entries`. An empty tree is filled in one pass, like [`QuadTree::from_iter_bulk`], and every other tree inserts the entries one by one.
+    fn extend<I: IntoIterator<Item = (IdentityPoint<ID>, T)>>(&mut self, entries: I) {
+        if self.is_empty() {
+            // the nodes of an empty tree that are not merged yet are replaced as a whole
+            self.items = vec![Bucket::Owned(SmallVec::new_const())];
+            if let Some(pending) = &mut self.pending_merges {
+                pending.clear();
+            }
+            let entries = entries
+                .into_iter()
+                .map(|(mut point, value)| {
+                    point.point = self.filter_position(point.point);
+                    (point, value)
+                })
+                .collect();
+            self.fill_bulk(entries);
+        } else {
+            for (point, value) in entries {
+                self.insert(point, value);
+            }
+        }
+    }
+}
+
+impl<T, ID, const N: usize> FromIterator<(IdentityPoint<ID>, T)> for QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Build a tree that covers exactly the rect around the points of all `entries`, so none of them are outside of its range, like [`QuadTree::from_iter_bulk`].
+    ///
+    /// A tree without entries only covers the point `0, 0`. Entries that are inserted later are not in this rect if they are outside of the rect of the first entries, so [`QuadTree::from_iter_bulk`] should be used when the area of all future entries is known.
+    fn from_iter<I: IntoIterator<Item = (IdentityPoint<ID>, T)>>(entries: I) -> Self {
+        let entries = entries.into_iter().collect::<Vec<_>>();
+        let mut points = entries.iter().map(|(ip, _)| ip.point);
+        let rect = points.next().map_or_else(
+            || Rect::new(Point::default(), Point::default()),
+            |first| points.fold(Rect::new(first, first), Rect::expand_to),
+        );
+        let mut tree = Self::new(rect.top_left(), rect.bottom_right());
+        tree.fill_bulk(entries);
+        tree
+    }
+}
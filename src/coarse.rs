@@ -0,0 +1,126 @@
+//! [`QuadTree::find_range_coarse`], a range query that summarizes small nodes instead of reporting their entries, e.g. to render distant regions of a map.
+
+use crate::{
+    bucket::Bucket, index::Index, point::Quadrant, FindRangeCtx, NodeId, Point, QuadTree, Rect, R32,
+};
+
+/// Something that is found by [`QuadTree::find_range_coarse`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoarseHit<'a, T, ID> {
+    /// An entry in range, in a node that is at least as large as the minimum cell size
+    Entry {
+        /// The identity of the entry
+        identity: &'a ID,
+        /// The position of the entry
+        point: Point,
+        /// The value of the entry
+        value: &'a T,
+    },
+    /// A node that is smaller than the minimum cell size, with at least one entry
+    Cell {
+        /// The node that is summarized
+        node: NodeId,
+        /// The area of the node, which overlaps the range
+        rect: Rect,
+        /// The amount of entries in the node and its descendants, including the entries that are not in range
+        count: usize,
+    },
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Find everything within `range` of point `center`, like [`QuadTree::find_range`], but stop at nodes whose width and height are smaller than `min_cell_size`. The entries of those nodes are counted and passed to `callback` as a single [`CoarseHit::Cell`], instead of one by one.
+    ///
+    /// This does not compare the entries in small nodes with the range, so a cell can count entries that are out of range. Entries outside of this tree are always passed one by one.
+    pub fn find_range_coarse<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        min_cell_size: R32,
+        mut callback: impl FnMut(CoarseHit<'a, T, ID>),
+    ) {
+        let ctx = FindRangeCtx::new(center, range);
+        self.find_range_coarse_inner(self.rect, Index::ROOT, &ctx, min_cell_size, &mut callback);
+        for (identity, (value, point)) in &self.outside_of_range {
+            if ctx.point_in_range(*point) {
+                callback(CoarseHit::Entry {
+                    identity,
+                    point: *point,
+                    value,
+                });
+            }
+        }
+    }
+
+    fn find_range_coarse_inner<'a>(
+        &'a self,
+        rect: Rect,
+        index: Index,
+        ctx: &FindRangeCtx,
+        min_cell_size: R32,
+        callback: &mut impl FnMut(CoarseHit<'a, T, ID>),
+    ) {
+        if !ctx.contains_rect(rect) {
+            return;
+        }
+        let (top_left, bottom_right) = (rect.top_left(), rect.bottom_right());
+        if bottom_right.x.raw() - top_left.x.raw() < min_cell_size.raw()
+            && bottom_right.y.raw() - top_left.y.raw() < min_cell_size.raw()
+        {
+            // the cell is only reported if it overlaps the range itself, not only the square around it
+            let in_range =
+                Rect::new(ctx.center, ctx.center).min_distance_squared(rect) <= ctx.range_squared;
+            let count = self.count_entries(index);
+            if in_range && count > 0 {
+                callback(CoarseHit::Cell {
+                    node: NodeId(self.public_index(index)),
+                    rect,
+                    count,
+                });
+            }
+            return;
+        }
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Owned(items)) => {
+                for (ident, value) in items {
+                    if ctx.point_in_range(ident.point) {
+                        callback(CoarseHit::Entry {
+                            identity: &ident.identity,
+                            point: ident.point,
+                            value,
+                        });
+                    }
+                }
+            }
+            Some(Bucket::Nested) => {
+                for child in Quadrant::all() {
+                    if let Some(child_index) = index.checked_child_at(child) {
+                        self.find_range_coarse_inner(
+                            rect.get_child_at(child),
+                            child_index,
+                            ctx,
+                            min_cell_size,
+                            callback,
+                        );
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// The amount of entries in the node at `index` and its descendants
+    pub(crate) fn count_entries(&self, index: Index) -> usize {
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Owned(items)) => items.len(),
+            Some(Bucket::Nested) => Quadrant::all()
+                .into_iter()
+                .filter_map(|child| index.checked_child_at(child))
+                .map(|child| self.count_entries(child))
+                .sum(),
+            None => 0,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! [`QuadTree::find_range_grouped`], a range query that reports the entries at the same position together, e.g. to draw a single marker with a count for entries that would overlap.
+
+use crate::{Point, QuadTree, R32};
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Find all entries with a distance less than `range` away from point `center`, like [`QuadTree::find_range`], and call `callback` once for every position with the identities and values of all entries at exactly that position.
+    ///
+    /// The positions are passed in ascending order of [`Point`], and the entries at a position in ascending order of their identity. Unlike [`QuadTree::find_range`], this allocates to sort the entries that are found, and clones their identities.
+    pub fn find_range_grouped<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        mut callback: impl FnMut(Point, &[(&ID, &'a T)]),
+    ) {
+        let mut found = Vec::new();
+        self.find_range_with_leaf(center, range, |identity, point, value, _| {
+            found.push((point, identity.clone(), value));
+        });
+        found.sort_unstable_by(|(a, a_identity, _), (b, b_identity, _)| {
+            a.cmp(b).then_with(|| a_identity.cmp(b_identity))
+        });
+
+        let mut group = Vec::new();
+        for (n, (point, identity, value)) in found.iter().enumerate() {
+            group.push((identity, *value));
+            if !matches!(found.get(n + 1), Some((next, _, _)) if next == point) {
+                callback(*point, &group);
+                group.clear();
+            }
+        }
+    }
+}
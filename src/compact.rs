@@ -0,0 +1,205 @@
+//! A read-only [`QuadTree`] that stores the position of every entry as two 16-bit offsets within its leaf.
+
+use crate::{
+    bucket::Bucket,
+    curve::{dequantize, quantize},
+    flat::to_u32,
+    index::Index,
+    point::Quadrant,
+    FindRangeCtx, Point, QuadTree, Rect, R32,
+};
+
+/// A read-only [`QuadTree`] with quantized positions, created by [`QuadTree::into_compact`].
+///
+/// Every position inside of the tree is stored as two `u16` offsets within the leaf that contains it, which is half the size of a [`Point`]. The positions are stored separately from the identities and values, so scanning a leaf only touches 4 bytes per entry.
+///
+/// A position that is read back is at most `leaf size / 65535` away from the original position on each axis, where `leaf size` is the width or height of the leaf that the entry is stored in. Entries outside of the tree are stored with their exact position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactQuadTree<T, ID> {
+    rect: Rect,
+    nodes: Vec<CompactNode>,
+    points: Vec<[u16; 2]>,
+    entries: Vec<(ID, T)>,
+    outside_of_range: Vec<(ID, T, Point)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CompactNode {
+    /// The 4 children of this node are stored at `first..first + 4`, in quadrant order
+    Nested { first: u32 },
+    /// The entries of this leaf are stored at `first..first + count`
+    Leaf { first: u32, count: u32 },
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N> {
+    /// Turn this tree into a [`CompactQuadTree`], which uses less memory but can not be changed and has approximate positions.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if this tree contains more than `u32::MAX` nodes or entries.
+    #[must_use]
+    pub fn into_compact(mut self) -> CompactQuadTree<T, ID> {
+        let mut compact = CompactQuadTree {
+            rect: self.rect,
+            nodes: vec![CompactNode::Leaf { first: 0, count: 0 }],
+            points: Vec::new(),
+            entries: Vec::new(),
+            outside_of_range: self
+                .outside_of_range
+                .into_iter()
+                .map(|(identity, (value, point))| (identity, value, point))
+                .collect(),
+        };
+        compact_node(&mut self.items, &mut compact, Index::ROOT, self.rect, 0);
+        compact
+    }
+}
+
+fn compact_node<T, ID, const N: usize>(
+    items: &mut [Bucket<T, ID, N>],
+    compact: &mut CompactQuadTree<T, ID>,
+    index: Index,
+    rect: Rect,
+    slot: usize,
+) {
+    let bucket = items
+        .get_mut(index.to_idx())
+        .map(|bucket| std::mem::replace(bucket, Bucket::Nested));
+    if let Some(Bucket::Nested) = bucket {
+        let first = compact.nodes.len();
+        compact
+            .nodes
+            .resize(first + 4, CompactNode::Leaf { first: 0, count: 0 });
+        compact.nodes[slot] = CompactNode::Nested {
+            first: to_u32(first),
+        };
+        for (offset, quadrant) in Quadrant::all().into_iter().enumerate() {
+            compact_node(
+                items,
+                compact,
+                index.child_at(quadrant),
+                rect.get_child_at(quadrant),
+                first + offset,
+            );
+        }
+    } else {
+        let first = compact.entries.len();
+        if let Some(Bucket::Owned(entries)) = bucket {
+            for (ip, value) in entries {
+                let (x, y) = quantize(rect, ip.point);
+                compact.points.push([x, y]);
+                compact.entries.push((ip.identity, value));
+            }
+        }
+        compact.nodes[slot] = CompactNode::Leaf {
+            first: to_u32(first),
+            count: to_u32(compact.entries.len() - first),
+        };
+    }
+}
+
+impl<T, ID> CompactQuadTree<T, ID> {
+    /// The area that this tree covers
+    #[must_use]
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// The amount of entries in this tree
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len() + self.outside_of_range.len()
+    }
+
+    /// Returns `true` if this tree has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Call `callback` for every entry in this tree, with its approximate position.
+    pub fn for_each<'a>(&'a self, mut callback: impl FnMut(&'a ID, Point, &'a T)) {
+        self.for_each_leaf(0, self.rect, &mut |rect, first, count| {
+            for idx in first..first + count {
+                let (identity, value) = &self.entries[idx];
+                callback(identity, dequantize(rect, self.point(idx)), value);
+            }
+        });
+        for (identity, value, point) in &self.outside_of_range {
+            callback(identity, *point, value);
+        }
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, see [`QuadTree::find_range`].
+    ///
+    /// The distance is calculated with the approximate position of every entry, so entries close to the edge of the range may be included or excluded differently than in the original tree.
+    pub fn find_range<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        mut callback: impl FnMut(&'a ID, Point, &'a T),
+    ) {
+        let ctx = FindRangeCtx::new(center, range);
+        self.find_range_inner(0, self.rect, &ctx, &mut callback);
+        for (identity, value, point) in &self.outside_of_range {
+            if ctx.point_in_range(*point) {
+                callback(identity, *point, value);
+            }
+        }
+    }
+
+    fn find_range_inner<'a>(
+        &'a self,
+        node: usize,
+        rect: Rect,
+        ctx: &FindRangeCtx,
+        callback: &mut impl FnMut(&'a ID, Point, &'a T),
+    ) {
+        if !ctx.contains_rect(rect) {
+            return;
+        }
+        match self.nodes[node] {
+            CompactNode::Nested { first } => {
+                for (offset, quadrant) in Quadrant::all().into_iter().enumerate() {
+                    let child = first as usize + offset;
+                    self.find_range_inner(child, rect.get_child_at(quadrant), ctx, callback);
+                }
+            }
+            CompactNode::Leaf { first, count } => {
+                let first = first as usize;
+                for idx in first..first + count as usize {
+                    let point = dequantize(rect, self.point(idx));
+                    if ctx.point_in_range(point) {
+                        let (identity, value) = &self.entries[idx];
+                        callback(identity, point, value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn for_each_leaf(
+        &self,
+        node: usize,
+        rect: Rect,
+        callback: &mut impl FnMut(Rect, usize, usize),
+    ) {
+        match self.nodes[node] {
+            CompactNode::Nested { first } => {
+                for (offset, quadrant) in Quadrant::all().into_iter().enumerate() {
+                    self.for_each_leaf(
+                        first as usize + offset,
+                        rect.get_child_at(quadrant),
+                        callback,
+                    );
+                }
+            }
+            CompactNode::Leaf { first, count } => callback(rect, first as usize, count as usize),
+        }
+    }
+
+    fn point(&self, idx: usize) -> (u16, u16) {
+        let [x, y] = self.points[idx];
+        (x, y)
+    }
+}
@@ -0,0 +1,204 @@
+//! A [`ConcurrentQuadForest`], which can be read and written from multiple threads at the same time.
+
+use crate::{
+    forest::{shard_of, shard_rects},
+    IdentityPoint, Point, QuadTree, Rect, R32,
+};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    hash::{Hash, Hasher},
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// A [`QuadForest`](crate::QuadForest) with a lock for every shard, so every method takes `&self`.
+///
+/// Changes to different shards, e.g. entities that move around in different parts of the world, don't wait for each other. The shard of every identity is stored in stripes of their own, which are picked by the hash of the identity.
+///
+/// Every method locks at most one shard at a time, so a query that runs while an entry moves to another shard can miss that entry or find it twice. A panic in a callback that is passed to this forest does not poison it, but an entry that was being moved to another shard by [`ConcurrentQuadForest::update_point_and_value`] is lost.
+#[derive(Debug)]
+pub struct ConcurrentQuadForest<T, ID, const N: usize> {
+    rect: Rect,
+    shards_per_axis: usize,
+    /// The shards, row by row from the top-left
+    shards: Vec<RwLock<QuadTree<T, ID, N>>>,
+    /// A tree with only the entries outside of the forest, which has index `shards.len()`
+    outside: RwLock<QuadTree<T, ID, N>>,
+    /// The shard of every identity
+    identity_to_shard: Vec<RwLock<BTreeMap<ID, usize>>>,
+}
+
+impl<T, ID, const N: usize> ConcurrentQuadForest<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone + Hash,
+{
+    /// Create a new forest that covers the area between `top_left` and `bottom_right`, split into `shards_per_axis × shards_per_axis` shards.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `shards_per_axis` is 0.
+    #[must_use]
+    pub fn new(top_left: Point, bottom_right: Point, shards_per_axis: usize) -> Self {
+        assert!(shards_per_axis > 0, "A forest needs at least 1 shard");
+        let rect = Rect::new(top_left, bottom_right);
+        let shards = shard_rects(rect, shards_per_axis)
+            .map(|rect| RwLock::new(QuadTree::new(rect.top_left(), rect.bottom_right())))
+            .collect();
+        let identity_to_shard = (0..shards_per_axis * shards_per_axis)
+            .map(|_| RwLock::new(BTreeMap::new()))
+            .collect();
+        Self {
+            rect,
+            shards_per_axis,
+            shards,
+            outside: RwLock::new(QuadTree::new(top_left, bottom_right)),
+            identity_to_shard,
+        }
+    }
+
+    /// The area that this forest covers
+    #[must_use]
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// The amount of shards on each axis
+    #[must_use]
+    pub fn shards_per_axis(&self) -> usize {
+        self.shards_per_axis
+    }
+
+    /// The amount of entries in this forest. The stripes are counted one at a time, so this is only exact if no entries are inserted or removed at the same time.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.identity_to_shard
+            .iter()
+            .map(|stripe| read(stripe).len())
+            .sum()
+    }
+
+    /// Returns `true` if this forest has no entries, see [`ConcurrentQuadForest::len`]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.identity_to_shard
+            .iter()
+            .all(|stripe| read(stripe).is_empty())
+    }
+
+    /// The index of the shard that contains `point`, or `None` if `point` is outside of this forest, see [`QuadForest::shard_of`](crate::QuadForest::shard_of).
+    #[must_use]
+    pub fn shard_of(&self, point: Point) -> Option<usize> {
+        shard_of(self.rect, self.shards_per_axis, point)
+    }
+
+    /// Read shard `shard`, which is locked until `callback` returns. This can be used for queries that this forest does not have itself.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `shard` is not smaller than `shards_per_axis * shards_per_axis`.
+    pub fn with_shard<R>(
+        &self,
+        shard: usize,
+        callback: impl FnOnce(&QuadTree<T, ID, N>) -> R,
+    ) -> R {
+        assert!(
+            shard < self.shards.len(),
+            "shard {shard} is not in this forest"
+        );
+        callback(&read(&self.shards[shard]))
+    }
+
+    /// Insert a value `value` at the given `point`. If `point.identity` already exists, it will be updated instead, see [`QuadTree::insert`].
+    pub fn insert(&self, point: IdentityPoint<ID>, value: T) {
+        let shard = self.location_of(point.point);
+        let mut stripe = write(self.stripe(&point.identity));
+        if let Some(old_shard) = stripe.insert(point.identity.clone(), shard) {
+            if old_shard != shard {
+                write(self.shard(old_shard)).try_remove(&point.identity);
+            }
+        }
+        write(self.shard(shard)).insert(point, value);
+    }
+
+    /// Update the given identity to the new point, see [`QuadTree::update`].
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise
+    pub fn update(&self, identity: ID, point: Point) -> bool {
+        self.update_point_and_value(identity, point, |_| {})
+    }
+
+    /// Update the given identity to the new point, with the opportunity to update the value, see [`QuadTree::update_point_and_value`].
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise
+    pub fn update_point_and_value(
+        &self,
+        identity: ID,
+        point: Point,
+        callback: impl FnOnce(&mut T),
+    ) -> bool {
+        let shard = self.location_of(point);
+        let mut stripe = write(self.stripe(&identity));
+        let Some(old_shard) = stripe.get_mut(&identity) else {
+            return false;
+        };
+        if *old_shard == shard {
+            return write(self.shard(shard)).update_point_and_value(identity, point, callback);
+        }
+        let Some((mut value, _)) = write(self.shard(*old_shard)).try_remove(&identity) else {
+            return false;
+        };
+        *old_shard = shard;
+        callback(&mut value);
+        write(self.shard(shard)).insert(IdentityPoint { identity, point }, value);
+        true
+    }
+
+    /// Try to remove the entry with the given identity from this forest. Will return the entry and its position if it's found, `None` otherwise.
+    pub fn try_remove(&self, identity: &ID) -> Option<(T, Point)> {
+        let mut stripe = write(self.stripe(identity));
+        let shard = stripe.remove(identity)?;
+        write(self.shard(shard)).try_remove(identity)
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, see [`QuadTree::find_range`].
+    ///
+    /// Every shard is locked for reading while its entries are passed to `callback`, so `callback` must not change this forest.
+    pub fn find_range(&self, center: Point, range: R32, mut callback: impl FnMut(&ID, Point, &T)) {
+        let range_squared = range * range;
+        let rects = shard_rects(self.rect, self.shards_per_axis);
+        for (shard, rect) in self.shards.iter().zip(rects) {
+            // only shards that could contain a result are locked
+            if Rect::new(center, center).min_distance_squared(rect) <= range_squared {
+                read(shard).find_range(center, range, &mut callback);
+            }
+        }
+        read(&self.outside).find_range(center, range, callback);
+    }
+
+    /// The index of the shard for `point`, which is `shards.len()` for the outside tree
+    fn location_of(&self, point: Point) -> usize {
+        self.shard_of(point).unwrap_or(self.shards.len())
+    }
+
+    fn shard(&self, location: usize) -> &RwLock<QuadTree<T, ID, N>> {
+        self.shards.get(location).unwrap_or(&self.outside)
+    }
+
+    fn stripe(&self, identity: &ID) -> &RwLock<BTreeMap<ID, usize>> {
+        let mut hasher = DefaultHasher::new();
+        identity.hash(&mut hasher);
+        let stripes = self.identity_to_shard.len() as u64;
+        // the remainder is smaller than the amount of stripes, which is a usize
+        #[allow(clippy::cast_possible_truncation)]
+        &self.identity_to_shard[(hasher.finish() % stripes) as usize]
+    }
+}
+
+/// Lock `lock` for reading. The trees are only changed by this crate, so they are still valid after a panic in a callback.
+fn read<L>(lock: &RwLock<L>) -> RwLockReadGuard<'_, L> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Lock `lock` for writing, see [`read`]
+fn write<L>(lock: &RwLock<L>) -> RwLockWriteGuard<'_, L> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,72 @@
+//! Groups adjacent leaves of a [`QuadTree`] into connected regions.
+
+use crate::{
+    bucket::{Bucket, IdentityPoint},
+    index::Index,
+    neighbors::{for_each_leaf, for_each_leaf_neighbor, NodeKind},
+    point::Rect,
+    QuadTree,
+};
+use std::collections::{BTreeMap, VecDeque};
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N> {
+    /// What is stored at `index`, for the traversals in [`neighbors`](crate::neighbors)
+    pub(crate) fn node_kind(&self, index: Index) -> NodeKind {
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => NodeKind::Nested,
+            Some(Bucket::Owned(_)) => NodeKind::Leaf,
+            None => NodeKind::Missing,
+        }
+    }
+
+    /// Group all adjacent leaves that satisfy `leaf_predicate` into connected regions.
+    ///
+    /// `leaf_predicate` is called once for every leaf in this tree, with the rect of that leaf and the entries stored in it. Leaves are considered adjacent when they share (a part of) an edge, leaves that only touch at a corner are not connected.
+    ///
+    /// Entries that are outside of the range of this tree are not part of any leaf, and are not passed to `leaf_predicate`.
+    pub fn connected_regions(
+        &self,
+        mut leaf_predicate: impl FnMut(Rect, &[(IdentityPoint<ID>, T)]) -> bool,
+    ) -> Vec<Vec<Rect>> {
+        let node_kind = |index| self.node_kind(index);
+
+        // every matching leaf, and whether it has been assigned to a region yet
+        let mut matching = BTreeMap::<Index, (Rect, bool)>::new();
+        let mut order = Vec::new();
+        for_each_leaf(self.rect, &node_kind, |index, rect| {
+            if let Some(Bucket::Owned(entries)) = self.items.get(index.to_idx()) {
+                if leaf_predicate(rect, entries) {
+                    matching.insert(index, (rect, false));
+                    order.push(index);
+                }
+            }
+        });
+
+        let mut regions = Vec::new();
+        let mut queue = VecDeque::new();
+        for start in order {
+            let Some((rect, visited)) = matching.get_mut(&start) else {
+                continue;
+            };
+            if *visited {
+                continue;
+            }
+            *visited = true;
+            let mut region = vec![*rect];
+            queue.push_back(start);
+            while let Some(index) = queue.pop_front() {
+                for_each_leaf_neighbor(self.rect, index, &node_kind, |neighbor, _| {
+                    if let Some((rect, visited)) = matching.get_mut(&neighbor) {
+                        if !*visited {
+                            *visited = true;
+                            region.push(*rect);
+                            queue.push_back(neighbor);
+                        }
+                    }
+                });
+            }
+            regions.push(region);
+        }
+        regions
+    }
+}
@@ -0,0 +1,156 @@
+//! Async versions of long operations, which yield to the executor every now and then so they don't block other tasks on a single-threaded executor, e.g. on WASM or embedded targets.
+//!
+//! These work with every executor, because the default yield point only wakes its own task. A yield hook can be passed to use the yield point of a specific executor instead.
+
+use crate::{
+    bucket::Bucket, index::Index, point::Quadrant, FindRangeCtx, IdentityPoint, Point, QuadTree,
+    R32,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A future that returns [`Poll::Pending`] once, which gives the executor a chance to run other tasks. This is the default yield hook.
+#[derive(Debug, Default)]
+#[must_use = "futures do nothing unless they are awaited"]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// Yield to the executor once, see [`YieldNow`]
+pub fn yield_now() -> YieldNow {
+    YieldNow::default()
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// The same as [`QuadTree::find_range`], but yields to the executor after every `yield_every` visited nodes and entries outside of this tree.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `yield_every` is 0.
+    pub async fn find_range_async<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        yield_every: usize,
+        callback: impl FnMut(&ID, Point, &'a T),
+    ) {
+        self.find_range_yielding(center, range, yield_every, yield_now, callback)
+            .await;
+    }
+
+    /// The same as [`QuadTree::find_range_async`], but awaits the future of `yield_hook` to yield.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `yield_every` is 0.
+    pub async fn find_range_yielding<'a, F>(
+        &'a self,
+        center: Point,
+        range: R32,
+        yield_every: usize,
+        mut yield_hook: impl FnMut() -> F,
+        mut callback: impl FnMut(&ID, Point, &'a T),
+    ) where
+        F: Future<Output = ()>,
+    {
+        assert!(yield_every > 0, "yield_every should be at least 1");
+        let ctx = FindRangeCtx::new(center, range);
+        let mut steps = 0;
+        // the children are pushed in reverse, so they are visited in the same order as `find_range`
+        let mut stack = vec![(self.rect, Index::ROOT)];
+        while let Some((rect, index)) = stack.pop() {
+            steps += 1;
+            if steps % yield_every == 0 {
+                yield_hook().await;
+            }
+            if !ctx.contains_rect(rect) {
+                continue;
+            }
+            match self.items.get(index.to_idx()) {
+                Some(Bucket::Owned(items)) => {
+                    for (ident, val) in items {
+                        if ctx.point_in_range(ident.point) {
+                            callback(&ident.identity, ident.point, val);
+                        }
+                    }
+                }
+                Some(Bucket::Nested) => {
+                    for child in Quadrant::all().into_iter().rev() {
+                        if let Some(index) = index.checked_child_at(child) {
+                            stack.push((rect.get_child_at(child), index));
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
+        for (ip, (value, point)) in &self.outside_of_range {
+            steps += 1;
+            if steps % yield_every == 0 {
+                yield_hook().await;
+            }
+            if ctx.point_in_range(*point) {
+                callback(ip, *point, value);
+            }
+        }
+    }
+
+    /// Build a tree that covers the area between `top_left` and `bottom_right` from `entries`, and yield to the executor after every `yield_every` inserted entries.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `yield_every` is 0.
+    pub async fn build_async(
+        top_left: Point,
+        bottom_right: Point,
+        entries: impl IntoIterator<Item = (IdentityPoint<ID>, T)>,
+        yield_every: usize,
+    ) -> Self {
+        Self::build_yielding(top_left, bottom_right, entries, yield_every, yield_now).await
+    }
+
+    /// The same as [`QuadTree::build_async`], but awaits the future of `yield_hook` to yield.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `yield_every` is 0.
+    pub async fn build_yielding<F>(
+        top_left: Point,
+        bottom_right: Point,
+        entries: impl IntoIterator<Item = (IdentityPoint<ID>, T)>,
+        yield_every: usize,
+        mut yield_hook: impl FnMut() -> F,
+    ) -> Self
+    where
+        F: Future<Output = ()>,
+    {
+        assert!(yield_every > 0, "yield_every should be at least 1");
+        let mut tree = Self::new(top_left, bottom_right);
+        for (steps, (point, value)) in entries.into_iter().enumerate() {
+            tree.insert(point, value);
+            if (steps + 1) % yield_every == 0 {
+                yield_hook().await;
+            }
+        }
+        tree
+    }
+}
@@ -0,0 +1,57 @@
+//! [`QuadTree::estimate_range_cost`], to decide how to schedule a query before running it.
+
+use crate::{
+    bucket::Bucket, index::Index, point::Quadrant, FindRangeCtx, Point, QuadTree, Rect, R32,
+};
+
+/// The work that a [`QuadTree::find_range`] does, which is returned by [`QuadTree::estimate_range_cost`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CostEstimate {
+    /// The amount of nodes that are visited, including the nodes that are skipped
+    pub nodes: usize,
+    /// The amount of entries that are compared with the range, including the entries outside of the tree
+    pub entries: usize,
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Estimate the cost of [`QuadTree::find_range`] with the same `center` and `range`. This is the same as [`QueryTrace::nodes`](crate::QueryTrace::nodes) and [`QueryTrace::comparisons`](crate::QueryTrace::comparisons) of [`QuadTree::explain_find_range`].
+    ///
+    /// This only visits nodes and counts the entries in the leaves, without comparing them, so it is cheaper than the query itself. Like the query, it does not allocate. The callback of the query is not part of the estimate.
+    #[must_use]
+    pub fn estimate_range_cost(&self, center: Point, range: R32) -> CostEstimate {
+        let ctx = FindRangeCtx::new(center, range);
+        let mut cost = CostEstimate {
+            nodes: 0,
+            entries: self.outside_of_range.len(),
+        };
+        self.estimate_inner(self.rect, Index::ROOT, &ctx, &mut cost);
+        cost
+    }
+
+    fn estimate_inner(
+        &self,
+        rect: Rect,
+        index: Index,
+        ctx: &FindRangeCtx,
+        cost: &mut CostEstimate,
+    ) {
+        cost.nodes += 1;
+        if !ctx.contains_rect(rect) {
+            return;
+        }
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Owned(items)) => cost.entries += items.len(),
+            Some(Bucket::Nested) => {
+                for child in Quadrant::all() {
+                    if let Some(child_index) = index.checked_child_at(child) {
+                        self.estimate_inner(rect.get_child_at(child), child_index, ctx, cost);
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+}
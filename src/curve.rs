@@ -0,0 +1,79 @@
+//! Quantization and space-filling curve codes for points inside a [`Rect`].
+
+use crate::{Point, Rect};
+
+/// Quantize `point` to a 16-bit position on each axis within `rect`. Points outside of `rect` are clamped to its edge.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn quantize(rect: Rect, point: Point) -> (u16, u16) {
+    let top_left = rect.top_left();
+    let bottom_right = rect.bottom_right();
+    let axis = |value: f32, min: f32, max: f32| {
+        let size = max - min;
+        if size <= 0.0 {
+            return 0;
+        }
+        let normalized = ((value - min) / size).clamp(0.0, 1.0);
+        // `normalized` is between 0 and 1, so this fits
+        (normalized * f32::from(u16::MAX)) as u16
+    };
+    (
+        axis(point.x.raw(), top_left.x.raw(), bottom_right.x.raw()),
+        axis(point.y.raw(), top_left.y.raw(), bottom_right.y.raw()),
+    )
+}
+
+/// The inverse of [`quantize`]. The result is at most `rect size / 65535` away from the original point on each axis.
+pub(crate) fn dequantize(rect: Rect, (x, y): (u16, u16)) -> Point {
+    let top_left = rect.top_left();
+    let bottom_right = rect.bottom_right();
+    let axis = |value: u16, min: f32, max: f32| {
+        (min + f32::from(value) / f32::from(u16::MAX) * (max - min)).clamp(min, max)
+    };
+    Point::new(
+        axis(x, top_left.x.raw(), bottom_right.x.raw()),
+        axis(y, top_left.y.raw(), bottom_right.y.raw()),
+    )
+}
+
+/// Spread the lower 16 bits of `n` out so there is a 0 bit between every bit
+const fn spread_bits(mut n: u32) -> u32 {
+    n &= 0x0000_FFFF;
+    n = (n | (n << 8)) & 0x00FF_00FF;
+    n = (n | (n << 4)) & 0x0F0F_0F0F;
+    n = (n | (n << 2)) & 0x3333_3333;
+    n = (n | (n << 1)) & 0x5555_5555;
+    n
+}
+
+/// The Morton (Z-order) code of `point` within `rect`.
+///
+/// This uses the same quadrant order as the tree itself: the `x` bit is the low bit and the `y` bit is the high bit of every pair.
+pub(crate) fn morton_code(rect: Rect, point: Point) -> u32 {
+    let (x, y) = quantize(rect, point);
+    spread_bits(u32::from(x)) | (spread_bits(u32::from(y)) << 1)
+}
+
+/// The Hilbert curve code of `point` within `rect`.
+///
+/// The curve starts in the top-left quadrant and ends in the top-right quadrant. Points that are next to each other on the curve are always next to each other in space, which is not true for [`morton_code`].
+pub(crate) fn hilbert_code(rect: Rect, point: Point) -> u32 {
+    let (x, y) = quantize(rect, point);
+    let (mut x, mut y) = (u32::from(x), u32::from(y));
+    let mut code = 0;
+    let mut size = 1 << 15;
+    while size > 0 {
+        let rx = u32::from(x & size != 0);
+        let ry = u32::from(y & size != 0);
+        code += size * size * ((3 * rx) ^ ry);
+        // rotate the lower bits so the next quadrant is in the orientation of the curve
+        if ry == 0 {
+            if rx == 1 {
+                x = size - 1 - (x & (size - 1));
+                y = size - 1 - (y & (size - 1));
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        size >>= 1;
+    }
+    code
+}
@@ -0,0 +1,22 @@
+//! Deduplicating the results of multiple queries.
+
+use crate::Point;
+use std::collections::BTreeSet;
+
+/// Wrap `callback` so it is only called for the first entry that is found with each identity.
+///
+/// A single query on a [`QuadTree`](crate::QuadTree) or [`QuadForest`](crate::QuadForest) never finds an entry twice, not even entries on the edge between two nodes or shards. This is for combining the results of multiple queries whose areas overlap, by passing the same wrapped callback to all of them.
+pub fn dedup_by_identity<ID, V>(
+    mut callback: impl FnMut(&ID, Point, V),
+) -> impl FnMut(&ID, Point, V)
+where
+    ID: Ord + Clone,
+{
+    let mut seen = BTreeSet::new();
+    move |identity: &ID, point, value| {
+        if !seen.contains(identity) {
+            seen.insert(identity.clone());
+            callback(identity, point, value);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! [`QuadTree::depth_usage`], which shows how much of the 15 levels of a tree the leaves below a node use, to notice clustered data before its leaves stop splitting.
+
+use crate::{bucket::Bucket, index::Index, point::Quadrant, NodeId, QuadTree};
+
+/// How deep the leaves below a node are, which is returned by [`QuadTree::depth_usage`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DepthUsage {
+    /// The depth of the deepest leaf below the node, or the depth of the node itself if it is a leaf
+    pub deepest: usize,
+    /// The amount of leaves below the node that hold more than `N` entries, because their entries can not be separated by a split
+    pub overflowing_leaves: usize,
+    /// The amount of entries in leaves at [`DepthUsage::MAX_DEPTH`], which are never split again
+    pub entries_at_max_depth: usize,
+}
+
+impl DepthUsage {
+    /// The maximum depth of a node, below which a leaf is never split
+    pub const MAX_DEPTH: usize = 15;
+
+    /// The amount of levels that the leaves below the node can still be split, before they reach [`DepthUsage::MAX_DEPTH`]
+    #[must_use]
+    pub fn headroom(&self) -> usize {
+        Self::MAX_DEPTH - self.deepest
+    }
+
+    /// Returns `true` if the deepest leaf below the node is at most `levels` above [`DepthUsage::MAX_DEPTH`]
+    #[must_use]
+    pub fn is_near_limit(&self, levels: usize) -> bool {
+        self.headroom() <= levels
+    }
+
+    fn merge(self, other: Self) -> Self {
+        Self {
+            deepest: self.deepest.max(other.deepest),
+            overflowing_leaves: self.overflowing_leaves + other.overflowing_leaves,
+            entries_at_max_depth: self.entries_at_max_depth + other.entries_at_max_depth,
+        }
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// How deep the leaves below `node` are. A node that is below a leaf of this tree is reported as an empty leaf at its own depth.
+    ///
+    /// This visits every node below `node`, so it should be checked every few seconds rather than after every change, e.g. to log a warning when [`DepthUsage::is_near_limit`] returns `true`.
+    #[must_use]
+    pub fn depth_usage(&self, node: NodeId) -> DepthUsage {
+        let index = self.public_index(node.0);
+        let mut ancestor = index;
+        // a node below a leaf does not exist, even if the vec happens to be long enough
+        while let Some(parent) = ancestor.parent() {
+            if !matches!(self.items.get(parent.to_idx()), Some(Bucket::Nested)) {
+                return DepthUsage {
+                    deepest: node.depth(),
+                    ..DepthUsage::default()
+                };
+            }
+            ancestor = parent;
+        }
+        self.depth_usage_inner(index, node.depth())
+    }
+
+    /// The [`DepthUsage`] of every node at `depth` that exists in this tree, e.g. of the 4 children of the root for a depth of 1, to find the areas whose leaves are the deepest
+    #[must_use]
+    pub fn subtree_depth_usage(&self, depth: usize) -> Vec<(NodeId, DepthUsage)> {
+        let mut nodes = vec![Index::ROOT];
+        for _ in 0..depth.min(DepthUsage::MAX_DEPTH) {
+            nodes = nodes
+                .into_iter()
+                .filter(|index| matches!(self.items.get(index.to_idx()), Some(Bucket::Nested)))
+                .flat_map(|index| Quadrant::all().map(|quadrant| index.child_at(quadrant)))
+                .collect();
+        }
+        let mut usage = nodes
+            .into_iter()
+            .map(|index| {
+                (
+                    NodeId(self.public_index(index)),
+                    self.depth_usage_inner(index, depth),
+                )
+            })
+            .collect::<Vec<_>>();
+        usage.sort_unstable_by_key(|(node, _)| *node);
+        usage
+    }
+
+    /// The depth of the deepest leaf of this tree, which is `0` if the root is not split
+    #[must_use]
+    pub fn effective_max_depth(&self) -> usize {
+        self.depth_usage_inner(Index::ROOT, 0).deepest
+    }
+
+    fn depth_usage_inner(&self, index: Index, depth: usize) -> DepthUsage {
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => Quadrant::all()
+                .into_iter()
+                .filter_map(|quadrant| index.checked_child_at(quadrant))
+                .map(|child| self.depth_usage_inner(child, depth + 1))
+                .fold(
+                    DepthUsage {
+                        deepest: depth,
+                        ..DepthUsage::default()
+                    },
+                    DepthUsage::merge,
+                ),
+            bucket => {
+                let entries = match bucket {
+                    Some(Bucket::Owned(entries)) => entries.len(),
+                    _ => 0,
+                };
+                DepthUsage {
+                    deepest: depth,
+                    overflowing_leaves: usize::from(entries > N),
+                    entries_at_max_depth: if depth == DepthUsage::MAX_DEPTH {
+                        entries
+                    } else {
+                        0
+                    },
+                }
+            }
+        }
+    }
+}
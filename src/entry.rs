@@ -0,0 +1,97 @@
+//! [`EntryMut`], a guard for an entry that gives mutable access to its value and its position with a single lookup, and only moves the entry when it is dropped.
+
+use crate::{expect_consistent, Point, QuadTree};
+use std::ops::{Deref, DerefMut};
+
+/// Mutable access to the value and the position of an entry of a [`QuadTree`], created by [`QuadTree::entry_mut`].
+///
+/// The value is changed in place. A new position set with [`EntryMut::set_point`] is only applied when the guard is dropped, and the entry is only moved to another leaf if the new position is outside of its current leaf, the same way as [`QuadTree::update`].
+pub struct EntryMut<'a, T, ID, const N: usize>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    tree: &'a mut QuadTree<T, ID, N>,
+    identity: ID,
+    old_point: Point,
+    point: Point,
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// An [`EntryMut`] for the entry with the given identity, or `None` if the identity is not found.
+    ///
+    /// The leaf of the entry is marked as changed for [`QuadTree::save_incremental`], whether or not the entry is changed.
+    pub fn entry_mut(&mut self, identity: &ID) -> Option<EntryMut<'_, T, ID, N>> {
+        let (point, _) = self.get_mut(identity)?;
+        Some(EntryMut {
+            tree: self,
+            identity: identity.clone(),
+            old_point: point,
+            point,
+        })
+    }
+}
+
+impl<T, ID, const N: usize> EntryMut<'_, T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// The identity of the entry
+    #[must_use]
+    pub fn identity(&self) -> &ID {
+        &self.identity
+    }
+
+    /// The position of the entry, which is the new position if it was changed with [`EntryMut::set_point`]
+    #[must_use]
+    pub fn point(&self) -> Point {
+        self.point
+    }
+
+    /// Move the entry to `point` when this guard is dropped, after the position filter of the tree, see [`QuadTree::set_position_filter`]
+    pub fn set_point(&mut self, point: Point) {
+        self.point = self.tree.filter_position(point);
+    }
+}
+
+impl<T, ID, const N: usize> Deref for EntryMut<'_, T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        let Some((_, value)) = self.tree.get(&self.identity) else {
+            panic!("Identity {:?} not found", self.identity)
+        };
+        value
+    }
+}
+
+impl<T, ID, const N: usize> DerefMut for EntryMut<'_, T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        let Some((_, value)) = self.tree.get_mut(&self.identity) else {
+            panic!("Identity {:?} not found", self.identity)
+        };
+        value
+    }
+}
+
+impl<T, ID, const N: usize> Drop for EntryMut<'_, T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    fn drop(&mut self) {
+        if self.point != self.old_point {
+            expect_consistent(
+                self.tree
+                    .move_entry(self.identity.clone(), self.point, |_| {}),
+            );
+        }
+    }
+}
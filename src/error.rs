@@ -0,0 +1,63 @@
+//! The errors of this crate: invalid coordinates, and a tree whose internal state is inconsistent.
+
+use crate::Point;
+use std::fmt;
+
+/// The internal state of a [`QuadTree`](crate::QuadTree) is inconsistent. This is returned by the `checked_*` methods that are enabled with the `paranoid` feature.
+///
+/// The operation that returned this may have been applied partially, so the tree should not be used anymore.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptionError {
+    message: &'static str,
+}
+
+impl CorruptionError {
+    pub(crate) const fn new(message: &'static str) -> Self {
+        Self { message }
+    }
+}
+
+impl fmt::Display for CorruptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Quad tree is corrupted: {}", self.message)
+    }
+}
+
+impl std::error::Error for CorruptionError {}
+
+/// A point or a rect that can not be used in a [`QuadTree`](crate::QuadTree), returned by [`Point::try_new`] and [`QuadTree::try_new`](crate::QuadTree::try_new)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoundsError {
+    /// A coordinate is `NaN` or `Infinity`
+    NotFinite {
+        /// The x coordinate that was given
+        x: f32,
+        /// The y coordinate that was given
+        y: f32,
+    },
+    /// The top-left corner is not strictly above and to the left of the bottom-right corner, so the rect has no area. [`Rect::from_corners`](crate::Rect::from_corners) can be used to create a rect from any two opposite corners.
+    NotOrdered {
+        /// The top-left corner that was given
+        top_left: Point,
+        /// The bottom-right corner that was given
+        bottom_right: Point,
+    },
+}
+
+impl fmt::Display for BoundsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFinite { x, y } => write!(f, "Point ({x}, {y}) is not finite"),
+            Self::NotOrdered {
+                top_left,
+                bottom_right,
+            } => write!(
+                f,
+                "Top-left corner ({}, {}) is not above and to the left of bottom-right corner ({}, {})",
+                top_left.x, top_left.y, bottom_right.x, bottom_right.y
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BoundsError {}
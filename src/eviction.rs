@@ -0,0 +1,72 @@
+//! Shedding entries from nodes that can not be split any further.
+//!
+//! A leaf can only split if its entries are in more than one quadrant, so a leaf with many entries at the same position keeps growing. [`QuadTree::insert_bounded`] caps the size of such leaves by evicting entries from them.
+
+use crate::{bucket::Bucket, expect_consistent, CorruptionError, IdentityPoint, Point, QuadTree};
+
+/// Decides which entry [`QuadTree::insert_bounded`] evicts from a leaf that is too large
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Evict the entry that is the farthest from the center of the leaf. Of entries that are equally far away, the oldest one is evicted.
+    FarthestFromCenter,
+    /// Evict the entry that has been in the leaf the longest. Moving an entry to another leaf makes it the newest entry in that leaf.
+    Oldest,
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Insert an entry, see [`QuadTree::insert`], and evict entries with `policy` if the leaf it ends up in has more than `limit` entries. Every evicted entry is removed from this tree and passed to `on_evict`, which can include the entry that was just inserted.
+    ///
+    /// This only evicts from leaves that could not split, because all of their entries are in the same quadrant. A `limit` smaller than `N` is the same as a `limit` of `N`. Entries outside of this tree are never evicted.
+    pub fn insert_bounded(
+        &mut self,
+        point: IdentityPoint<ID>,
+        value: T,
+        limit: usize,
+        policy: EvictionPolicy,
+        mut on_evict: impl FnMut(ID, Point, T),
+    ) {
+        let identity = point.identity.clone();
+        self.insert(point, value);
+        expect_consistent(self.evict(&identity, limit.max(N), policy, &mut on_evict));
+    }
+
+    fn evict(
+        &mut self,
+        identity: &ID,
+        limit: usize,
+        policy: EvictionPolicy,
+        on_evict: &mut impl FnMut(ID, Point, T),
+    ) -> Result<(), CorruptionError> {
+        let Some(&(_, Some(index), _)) = self.identity_to_point.get(identity) else {
+            return Ok(());
+        };
+        let center = self.rect.get_index_rect(index).middle();
+        let Some(Bucket::Owned(entries)) = self.items.get_mut(index.to_idx()) else {
+            return Err(CorruptionError::new("entry is missing from its bucket"));
+        };
+        if entries.len() <= limit {
+            return Ok(());
+        }
+        while entries.len() > limit {
+            // new entries are pushed to the end of a leaf, so the first entry is the oldest one
+            let victim = match policy {
+                EvictionPolicy::Oldest => 0,
+                EvictionPolicy::FarthestFromCenter => entries
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(n, (ip, _))| {
+                        (ip.point.distance_squared_to(center), std::cmp::Reverse(*n))
+                    })
+                    .map_or(0, |(n, _)| n),
+            };
+            let (ip, value) = entries.remove(victim);
+            self.identity_to_point.remove(&ip.identity);
+            on_evict(ip.identity, ip.point, value);
+        }
+        self.dirty.touch(index);
+        Ok(())
+    }
+}
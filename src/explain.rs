@@ -0,0 +1,212 @@
+//! [`QuadTree::explain_find_range`], which records what a [`QuadTree::find_range`] does, to find out why a query is slow or returns nothing.
+
+use crate::{
+    bucket::Bucket, index::Index, point::Quadrant, FindRangeCtx, NodeId, Point, QuadTree, Rect, R32,
+};
+use std::fmt;
+
+/// What [`QuadTree::explain_find_range`] did with a node
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NodeVisit {
+    /// The node is nested, so its children are visited next
+    Nested,
+    /// The node is a leaf, so every entry in it is compared with the range
+    Leaf {
+        /// The amount of entries in this leaf
+        compared: usize,
+        /// The amount of entries in this leaf that are in range
+        matched: usize,
+    },
+    /// The node is skipped
+    Pruned(PruneReason),
+}
+
+/// Why [`QuadTree::explain_find_range`] skipped a node
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PruneReason {
+    /// The rect of the node does not overlap the square around the range
+    OutOfRange,
+    /// The node does not exist, which only happens in a corrupted tree
+    Missing,
+    /// The node is nested at the maximum depth of 15, which only happens in a corrupted tree. Its children are skipped.
+    TooDeep,
+}
+
+/// A node that was visited by [`QuadTree::explain_find_range`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TracedNode {
+    /// The node that was visited
+    pub node: NodeId,
+    /// The area of the node
+    pub rect: Rect,
+    /// What was done with the node
+    pub visit: NodeVisit,
+}
+
+/// The trace of a [`QuadTree::find_range`], which is returned by [`QuadTree::explain_find_range`].
+///
+/// The [`Display`](fmt::Display) implementation renders the visited nodes as an indented tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryTrace {
+    center: Point,
+    range: R32,
+    nodes: Vec<TracedNode>,
+    outside_compared: usize,
+    outside_matched: usize,
+}
+
+impl QueryTrace {
+    /// The center of the query
+    #[must_use]
+    pub fn center(&self) -> Point {
+        self.center
+    }
+
+    /// The range of the query
+    #[must_use]
+    pub fn range(&self) -> R32 {
+        self.range
+    }
+
+    /// Every visited node, in the order in which they were visited. A nested node comes right before its children.
+    #[must_use]
+    pub fn nodes(&self) -> &[TracedNode] {
+        &self.nodes
+    }
+
+    /// The amount of entries that were compared with the range, including the entries outside of the tree
+    #[must_use]
+    pub fn comparisons(&self) -> usize {
+        self.leaves().map(|(compared, _)| compared).sum::<usize>() + self.outside_compared
+    }
+
+    /// The amount of entries that were in range, which is the amount of entries that [`QuadTree::find_range`] finds
+    #[must_use]
+    pub fn matches(&self) -> usize {
+        self.leaves().map(|(_, matched)| matched).sum::<usize>() + self.outside_matched
+    }
+
+    /// The amount of nodes that were skipped
+    #[must_use]
+    pub fn pruned(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| matches!(node.visit, NodeVisit::Pruned(_)))
+            .count()
+    }
+
+    fn leaves(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.nodes.iter().filter_map(|node| match node.visit {
+            NodeVisit::Leaf { compared, matched } => Some((compared, matched)),
+            NodeVisit::Nested | NodeVisit::Pruned(_) => None,
+        })
+    }
+}
+
+impl fmt::Display for QueryTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "find_range around ({}, {}) with range {}: {} matches, {} comparisons, {} pruned nodes",
+            self.center.x,
+            self.center.y,
+            self.range,
+            self.matches(),
+            self.comparisons(),
+            self.pruned()
+        )?;
+        for node in &self.nodes {
+            let (top_left, bottom_right) = (node.rect.top_left(), node.rect.bottom_right());
+            write!(
+                f,
+                "{:indent$}{:?} ({}, {})..({}, {}): ",
+                "",
+                node.node,
+                top_left.x,
+                top_left.y,
+                bottom_right.x,
+                bottom_right.y,
+                indent = node.node.depth() * 2
+            )?;
+            match node.visit {
+                NodeVisit::Nested => writeln!(f, "nested")?,
+                NodeVisit::Leaf { compared, matched } => {
+                    writeln!(f, "leaf, {matched} of {compared} entries in range")?;
+                }
+                NodeVisit::Pruned(PruneReason::OutOfRange) => writeln!(f, "pruned, out of range")?,
+                NodeVisit::Pruned(PruneReason::Missing) => writeln!(f, "pruned, missing")?,
+                NodeVisit::Pruned(PruneReason::TooDeep) => {
+                    writeln!(f, "pruned, nested at the maximum depth")?;
+                }
+            }
+        }
+        writeln!(
+            f,
+            "outside of the tree: {} of {} entries in range",
+            self.outside_matched, self.outside_compared
+        )
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Run [`QuadTree::find_range`] and record which nodes it visited, which nodes it skipped and why, and how many entries it compared with the range.
+    #[must_use]
+    pub fn explain_find_range(&self, center: Point, range: R32) -> QueryTrace {
+        let ctx = FindRangeCtx::new(center, range);
+        let mut trace = QueryTrace {
+            center,
+            range,
+            nodes: Vec::new(),
+            outside_compared: self.outside_of_range.len(),
+            outside_matched: self
+                .outside_of_range
+                .values()
+                .filter(|(_, point)| ctx.point_in_range(*point))
+                .count(),
+        };
+        self.explain_inner(self.rect, Index::ROOT, &ctx, &mut trace.nodes);
+        trace
+    }
+
+    fn explain_inner(
+        &self,
+        rect: Rect,
+        index: Index,
+        ctx: &FindRangeCtx,
+        nodes: &mut Vec<TracedNode>,
+    ) {
+        let visit = if ctx.contains_rect(rect) {
+            match self.items.get(index.to_idx()) {
+                Some(Bucket::Owned(items)) => NodeVisit::Leaf {
+                    compared: items.len(),
+                    matched: items
+                        .iter()
+                        .filter(|(ident, _)| ctx.point_in_range(ident.point))
+                        .count(),
+                },
+                Some(Bucket::Nested) if index.checked_child_at(Quadrant::TopLeft).is_none() => {
+                    NodeVisit::Pruned(PruneReason::TooDeep)
+                }
+                Some(Bucket::Nested) => NodeVisit::Nested,
+                None => NodeVisit::Pruned(PruneReason::Missing),
+            }
+        } else {
+            NodeVisit::Pruned(PruneReason::OutOfRange)
+        };
+        nodes.push(TracedNode {
+            node: NodeId(self.public_index(index)),
+            rect,
+            visit,
+        });
+        if visit == NodeVisit::Nested {
+            for child in Quadrant::all() {
+                if let Some(child_index) = index.checked_child_at(child) {
+                    self.explain_inner(rect.get_child_at(child), child_index, ctx, nodes);
+                }
+            }
+        }
+    }
+}
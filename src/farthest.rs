@@ -0,0 +1,111 @@
+//! Farthest entry queries, which are the inverse of nearest neighbor queries: a node is skipped if every point in it is closer than the farthest entry that was found so far.
+
+use crate::{bucket::Bucket, index::Index, point::Quadrant, Point, QuadTree, Rect, R32};
+use noisy_float::prelude::Float;
+use std::cmp::Reverse;
+
+/// The farthest entry that was found so far with its squared distance, ordered so the farthest entry, and then the lowest identity, is the largest
+type Farthest<'a, ID> = (R32, Reverse<&'a ID>, Point);
+
+impl<T, ID: Ord, const N: usize> QuadTree<T, ID, N> {
+    /// Find the entry that is farthest away from `point`, and its distance to `point`.
+    ///
+    /// If multiple entries have the same distance, the entry with the lowest identity is returned. Returns `None` if this tree is empty. This does not allocate.
+    #[must_use]
+    pub fn farthest_from(&self, point: Point) -> Option<(&ID, Point, R32)> {
+        self.farthest_excluding(point, None)
+            .map(|(distance, Reverse(identity), point)| (identity, point, distance.sqrt()))
+    }
+
+    /// Find the two entries that are farthest away from each other, and the distance between them.
+    ///
+    /// If multiple pairs have the same distance, the pair with the lowest identities is returned. The first identity of the pair is always lower than the second. Returns `None` if this tree contains less than 2 entries.
+    #[must_use]
+    pub fn diameter(&self) -> Option<(&ID, &ID, R32)> {
+        let mut diameter: Option<(R32, Reverse<(&ID, &ID)>)> = None;
+        for (identity, (point, _, _)) in &self.identity_to_point {
+            let Some((distance, Reverse(other), _)) =
+                self.farthest_excluding(*point, Some(identity))
+            else {
+                continue;
+            };
+            let pair = (
+                distance,
+                Reverse((identity.min(other), identity.max(other))),
+            );
+            diameter = Some(diameter.map_or(pair, |diameter| diameter.max(pair)));
+        }
+        diameter.map(|(distance, Reverse((a, b)))| (a, b, distance.sqrt()))
+    }
+
+    fn farthest_excluding(&self, point: Point, exclude: Option<&ID>) -> Option<Farthest<'_, ID>> {
+        let mut farthest = None;
+        for (identity, (_, other)) in &self.outside_of_range {
+            offer(point, exclude, identity, *other, &mut farthest);
+        }
+        self.farthest_inner(Index::ROOT, self.rect, point, exclude, &mut farthest);
+        farthest
+    }
+
+    fn farthest_inner<'a>(
+        &'a self,
+        index: Index,
+        rect: Rect,
+        point: Point,
+        exclude: Option<&ID>,
+        farthest: &mut Option<Farthest<'a, ID>>,
+    ) {
+        if matches!(farthest, Some((distance, _, _)) if rect.max_distance_squared(point) < *distance)
+        {
+            return;
+        }
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => {
+                let mut children = Quadrant::all().map(|quadrant| {
+                    let child_rect = rect.get_child_at(quadrant);
+                    (
+                        Reverse(child_rect.max_distance_squared(point)),
+                        quadrant,
+                        child_rect,
+                    )
+                });
+                // visit the farthest children first, so more of the other children can be skipped
+                children.sort_unstable_by_key(|(distance, _, _)| *distance);
+                for (_, quadrant, child_rect) in children {
+                    self.farthest_inner(
+                        index.child_at(quadrant),
+                        child_rect,
+                        point,
+                        exclude,
+                        farthest,
+                    );
+                }
+            }
+            Some(Bucket::Owned(entries)) => {
+                for (ip, _) in entries {
+                    offer(point, exclude, &ip.identity, ip.point, farthest);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+fn offer<'a, ID: Ord>(
+    point: Point,
+    exclude: Option<&ID>,
+    identity: &'a ID,
+    other: Point,
+    farthest: &mut Option<Farthest<'a, ID>>,
+) {
+    if exclude == Some(identity) {
+        return;
+    }
+    let candidate = (point.distance_squared_to(other), Reverse(identity), other);
+    if let Some(farthest) = farthest {
+        if (farthest.0, farthest.1) >= (candidate.0, candidate.1) {
+            return;
+        }
+    }
+    *farthest = Some(candidate);
+}
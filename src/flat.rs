@@ -0,0 +1,231 @@
+//! A flat, `#[repr(C)]` export of a [`QuadTree`], so a baked tree can be consumed from other languages.
+//!
+//! The layout of every exported struct is fixed and documented, and with the `ffi` feature a matching C header can be generated with [`c_header`].
+//!
+//! [`c_header`]: fn.c_header.html
+
+use crate::{bucket::Bucket, index::Index, point::Quadrant, Point, QuadTree, Rect};
+use std::collections::BTreeMap;
+
+/// The [`FlatNode::kind`] of a node that is split into 4 children
+pub const FLAT_NODE_NESTED: u32 = 1;
+/// The [`FlatNode::kind`] of a node that stores entries
+pub const FLAT_NODE_LEAF: u32 = 2;
+
+/// A point as 2 `f32`s. 8 bytes, aligned to 4 bytes.
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct FlatPoint {
+    /// The horizontal coordinate
+    pub x: f32,
+    /// The vertical coordinate
+    pub y: f32,
+}
+
+/// A rect as 4 `f32`s. 16 bytes, aligned to 4 bytes.
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct FlatRect {
+    /// The left edge
+    pub left: f32,
+    /// The top edge
+    pub top: f32,
+    /// The right edge
+    pub right: f32,
+    /// The bottom edge
+    pub bottom: f32,
+}
+
+/// A single node in [`FlatTree::nodes`]. 28 bytes, aligned to 4 bytes.
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct FlatNode {
+    /// The area that this node covers
+    pub rect: FlatRect,
+    /// Either [`FLAT_NODE_NESTED`] or [`FLAT_NODE_LEAF`]
+    pub kind: u32,
+    /// For nested nodes the index of the first child in [`FlatTree::nodes`]. The 4 children are stored next to each other, in the order top-left, top-right, bottom-left, bottom-right.
+    ///
+    /// For leaves the index of the first entry in [`FlatTree::points`] and [`FlatTree::value_indices`].
+    pub first: u32,
+    /// For nested nodes this is always 4, for leaves this is the amount of entries in this leaf.
+    pub count: u32,
+}
+
+/// A [`QuadTree`] exported into contiguous arrays, created by [`QuadTree::export_flat`].
+///
+/// The root node is always the first node. Entries are stored in [`points`] and [`value_indices`], which have the same length. Entries from `outside_start` onwards are outside of the root rect, and are not part of any leaf.
+///
+/// [`points`]: #structfield.points
+/// [`value_indices`]: #structfield.value_indices
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatTree<'a, T, ID> {
+    /// The area that the root node covers
+    pub rect: FlatRect,
+    /// All nodes in the tree, starting with the root node
+    pub nodes: Vec<FlatNode>,
+    /// The position of every entry
+    pub points: Vec<FlatPoint>,
+    /// For every entry, the index of its value in [`values`](#structfield.values)
+    pub value_indices: Vec<u32>,
+    /// The index of the first entry in `points` that is outside of the root rect
+    pub outside_start: u32,
+    /// Every identity and value in this tree, ordered by identity
+    pub values: Vec<(&'a ID, &'a T)>,
+}
+
+impl From<Point> for FlatPoint {
+    fn from(point: Point) -> Self {
+        Self {
+            x: point.x.raw(),
+            y: point.y.raw(),
+        }
+    }
+}
+
+impl From<Rect> for FlatRect {
+    fn from(rect: Rect) -> Self {
+        let top_left = rect.top_left();
+        let bottom_right = rect.bottom_right();
+        Self {
+            left: top_left.x.raw(),
+            top: top_left.y.raw(),
+            right: bottom_right.x.raw(),
+            bottom: bottom_right.y.raw(),
+        }
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord,
+{
+    /// Export this tree into a [`FlatTree`] with a fixed `#[repr(C)]` layout.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if this tree contains more than `u32::MAX` nodes or entries.
+    #[must_use]
+    pub fn export_flat(&self) -> FlatTree<'_, T, ID> {
+        let mut entries = Vec::new();
+        let mut nodes = vec![FlatNode::default()];
+        let mut queue = vec![(Index::ROOT, self.rect, 0)];
+        // breadth-first, so the 4 children of a node are always stored next to each other
+        let mut next = 0;
+        while let Some(&(index, rect, flat_index)) = queue.get(next) {
+            next += 1;
+            let node = match self.items.get(index.to_idx()) {
+                Some(Bucket::Nested) => {
+                    let first = to_u32(nodes.len());
+                    for quadrant in Quadrant::all() {
+                        queue.push((
+                            index.child_at(quadrant),
+                            rect.get_child_at(quadrant),
+                            nodes.len(),
+                        ));
+                        nodes.push(FlatNode::default());
+                    }
+                    FlatNode {
+                        rect: rect.into(),
+                        kind: FLAT_NODE_NESTED,
+                        first,
+                        count: 4,
+                    }
+                }
+                Some(Bucket::Owned(bucket)) => {
+                    let first = to_u32(entries.len());
+                    entries.extend(
+                        bucket
+                            .iter()
+                            .map(|(ip, value)| (&ip.identity, ip.point, value)),
+                    );
+                    FlatNode {
+                        rect: rect.into(),
+                        kind: FLAT_NODE_LEAF,
+                        first,
+                        count: to_u32(bucket.len()),
+                    }
+                }
+                None => FlatNode {
+                    rect: rect.into(),
+                    kind: FLAT_NODE_LEAF,
+                    first: to_u32(entries.len()),
+                    count: 0,
+                },
+            };
+            nodes[flat_index] = node;
+        }
+
+        let outside_start = to_u32(entries.len());
+        entries.extend(
+            self.outside_of_range
+                .iter()
+                .map(|(identity, (value, point))| (identity, *point, value)),
+        );
+
+        let mut values: Vec<(&ID, &T)> =
+            entries.iter().map(|(id, _, value)| (*id, *value)).collect();
+        values.sort_by(|a, b| a.0.cmp(b.0));
+        let value_order: BTreeMap<&ID, u32> = values
+            .iter()
+            .enumerate()
+            .map(|(idx, (id, _))| (*id, to_u32(idx)))
+            .collect();
+
+        FlatTree {
+            rect: self.rect.into(),
+            nodes,
+            points: entries
+                .iter()
+                .map(|(_, point, _)| (*point).into())
+                .collect(),
+            value_indices: entries.iter().map(|(id, _, _)| value_order[id]).collect(),
+            outside_start,
+            values,
+        }
+    }
+}
+
+pub(crate) fn to_u32(n: usize) -> u32 {
+    u32::try_from(n).expect("Tree is too large to be exported")
+}
+
+/// Generate a C header that declares the structs and constants of a [`FlatTree`], with the same layout as the Rust types.
+#[cfg(feature = "ffi")]
+#[must_use]
+pub fn c_header() -> String {
+    String::from(
+        "\
+#ifndef WHQUADTREE_FLAT_H
+#define WHQUADTREE_FLAT_H
+
+#include <stdint.h>
+
+#define FLAT_NODE_NESTED 1
+#define FLAT_NODE_LEAF 2
+
+typedef struct FlatPoint {
+    float x;
+    float y;
+} FlatPoint;
+
+typedef struct FlatRect {
+    float left;
+    float top;
+    float right;
+    float bottom;
+} FlatRect;
+
+/* For nested nodes `first` is the index of the first of 4 children (top-left, top-right, bottom-left, bottom-right).
+ * For leaves `first` is the index of the first entry in the points and value_indices arrays. */
+typedef struct FlatNode {
+    FlatRect rect;
+    uint32_t kind;
+    uint32_t first;
+    uint32_t count;
+} FlatNode;
+
+#endif
+",
+    )
+}
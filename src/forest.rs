@@ -0,0 +1,252 @@
+//! A [`QuadForest`], which splits its area into a grid of independent [`QuadTree`]s.
+
+use crate::{IdentityPoint, Point, QuadTree, Rect, R32};
+use std::collections::BTreeMap;
+
+/// A grid of `M × M` independent [`QuadTree`]s, called shards, with the same API as a single tree.
+///
+/// Every shard has its own depth limit, so a forest can store more entries than a single tree. Every entry is stored in exactly one shard, and queries only visit the shards that could contain a result. Entries that are outside of the area of the forest are not stored in any shard.
+///
+/// The shards can be read directly with [`QuadForest::shards`], e.g. to query them in parallel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuadForest<T, ID, const N: usize> {
+    rect: Rect,
+    shards_per_axis: usize,
+    /// The shards, row by row from the top-left
+    shards: Vec<QuadTree<T, ID, N>>,
+    outside_of_range: BTreeMap<ID, (T, Point)>,
+    /// The shard of every identity, or `None` if it is outside of the forest
+    identity_to_shard: BTreeMap<ID, Option<usize>>,
+}
+
+impl<T, ID, const N: usize> QuadForest<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Create a new forest that covers the area between `top_left` and `bottom_right`, split into `shards_per_axis × shards_per_axis` shards.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `shards_per_axis` is 0.
+    #[must_use]
+    pub fn new(top_left: Point, bottom_right: Point, shards_per_axis: usize) -> Self {
+        assert!(shards_per_axis > 0, "A forest needs at least 1 shard");
+        let rect = Rect::new(top_left, bottom_right);
+        let shards = shard_rects(rect, shards_per_axis)
+            .map(|rect| QuadTree::new(rect.top_left(), rect.bottom_right()))
+            .collect();
+        Self {
+            rect,
+            shards_per_axis,
+            shards,
+            outside_of_range: BTreeMap::new(),
+            identity_to_shard: BTreeMap::new(),
+        }
+    }
+
+    /// The area that this forest covers
+    #[must_use]
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// The amount of shards on each axis
+    #[must_use]
+    pub fn shards_per_axis(&self) -> usize {
+        self.shards_per_axis
+    }
+
+    /// All shards, row by row starting at the top-left shard
+    #[must_use]
+    pub fn shards(&self) -> &[QuadTree<T, ID, N>] {
+        &self.shards
+    }
+
+    /// The amount of entries in this forest
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.identity_to_shard.len()
+    }
+
+    /// Returns `true` if this forest has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.identity_to_shard.is_empty()
+    }
+
+    /// The index in [`QuadForest::shards`] of the shard that contains `point`, or `None` if `point` is outside of this forest.
+    ///
+    /// Points on the edge between two shards are in the shard to the right or bottom, the same as for the nodes of a tree.
+    #[must_use]
+    pub fn shard_of(&self, point: Point) -> Option<usize> {
+        shard_of(self.rect, self.shards_per_axis, point)
+    }
+
+    /// Insert a value `value` at the given `point`. If `point.identity` already exists, it will be updated instead, see [`QuadTree::insert`].
+    pub fn insert(&mut self, point: IdentityPoint<ID>, value: T) {
+        let shard = self.shard_of(point.point);
+        match self.identity_to_shard.insert(point.identity.clone(), shard) {
+            // updated in-place, without moving between shards
+            Some(old_shard) if old_shard == shard => {}
+            Some(old_shard) => {
+                self.remove_from(&point.identity, old_shard);
+            }
+            None => {}
+        }
+        match shard {
+            Some(shard) => self.shards[shard].insert(point, value),
+            None => {
+                self.outside_of_range
+                    .insert(point.identity, (value, point.point));
+            }
+        }
+    }
+
+    /// Update the given identity to the new point, see [`QuadTree::update`].
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise
+    pub fn update(&mut self, identity: ID, point: Point) -> bool {
+        self.update_point_and_value(identity, point, |_| {})
+    }
+
+    /// Update the given identity to the new point, with the opportunity to update the value, see [`QuadTree::update_point_and_value`].
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise
+    pub fn update_point_and_value(
+        &mut self,
+        identity: ID,
+        point: Point,
+        callback: impl FnOnce(&mut T),
+    ) -> bool {
+        let Some(&old_shard) = self.identity_to_shard.get(&identity) else {
+            return false;
+        };
+        let shard = self.shard_of(point);
+        if old_shard == shard {
+            if let Some(shard) = shard {
+                return self.shards[shard].update_point_and_value(identity, point, callback);
+            }
+            let Some(entry) = self.outside_of_range.get_mut(&identity) else {
+                return false;
+            };
+            callback(&mut entry.0);
+            entry.1 = point;
+            return true;
+        }
+        let Some((mut value, _)) = self.remove_from(&identity, old_shard) else {
+            return false;
+        };
+        callback(&mut value);
+        self.insert(IdentityPoint { identity, point }, value);
+        true
+    }
+
+    /// Remove an entry with the given identity from this forest, see [`QuadTree::remove`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the identity is not found in this forest.
+    pub fn remove(&mut self, identity: &ID) -> (T, Point) {
+        self.try_remove(identity)
+            .unwrap_or_else(|| panic!("Identity {identity:?} not found"))
+    }
+
+    /// Try to remove the entry with the given identity from this forest. Will return the entry and its position if it's found, `None` otherwise.
+    pub fn try_remove(&mut self, identity: &ID) -> Option<(T, Point)> {
+        let shard = self.identity_to_shard.remove(identity)?;
+        self.remove_from(identity, shard)
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, see [`QuadTree::find_range`].
+    ///
+    /// Every entry is stored in a single shard, so every entry is found at most once.
+    pub fn find_range<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        mut callback: impl FnMut(&ID, Point, &'a T),
+    ) {
+        let range_squared = range * range;
+        for shard in &self.shards {
+            if Rect::new(center, center).min_distance_squared(shard.rect) <= range_squared {
+                shard.find_range(center, range, &mut callback);
+            }
+        }
+        for (identity, (value, point)) in &self.outside_of_range {
+            if center.distance_squared_to(*point) <= range_squared {
+                callback(identity, *point, value);
+            }
+        }
+    }
+
+    fn remove_from(&mut self, identity: &ID, shard: Option<usize>) -> Option<(T, Point)> {
+        match shard {
+            Some(shard) => self.shards[shard].try_remove(identity),
+            None => self.outside_of_range.remove(identity),
+        }
+    }
+}
+
+/// The rects of the shards when splitting `rect` into `shards_per_axis × shards_per_axis` shards, row by row from the top-left
+pub(crate) fn shard_rects(rect: Rect, shards_per_axis: usize) -> impl Iterator<Item = Rect> {
+    let (top_left, bottom_right) = (rect.top_left(), rect.bottom_right());
+    (0..shards_per_axis).flat_map(move |y| {
+        (0..shards_per_axis).map(move |x| {
+            Rect::new(
+                Point::new_noisy_float(
+                    bound(top_left.x, bottom_right.x, shards_per_axis, x),
+                    bound(top_left.y, bottom_right.y, shards_per_axis, y),
+                ),
+                Point::new_noisy_float(
+                    bound(top_left.x, bottom_right.x, shards_per_axis, x + 1),
+                    bound(top_left.y, bottom_right.y, shards_per_axis, y + 1),
+                ),
+            )
+        })
+    })
+}
+
+/// The index of the shard in [`shard_rects`] that contains `point`, or `None` if `point` is outside of `rect`
+pub(crate) fn shard_of(rect: Rect, shards_per_axis: usize, point: Point) -> Option<usize> {
+    if !rect.contains(point) {
+        return None;
+    }
+    let (top_left, bottom_right) = (rect.top_left(), rect.bottom_right());
+    let x = cell(top_left.x, bottom_right.x, shards_per_axis, point.x);
+    let y = cell(top_left.y, bottom_right.y, shards_per_axis, point.y);
+    Some(y * shards_per_axis + x)
+}
+
+/// The position of the edge before cell `n` when splitting `min..max` into `cells` cells
+#[allow(clippy::cast_precision_loss)] // the amount of shards is small
+fn bound(min: R32, max: R32, cells: usize, n: usize) -> R32 {
+    if n == cells {
+        // exactly the edge of the forest, without any rounding
+        return max;
+    }
+    min + (max - min) * (n as f32 / cells as f32)
+}
+
+/// The cell of `min..max` split into `cells` cells that contains `value`, which must be between `min` and `max`
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn cell(min: R32, max: R32, cells: usize, value: R32) -> usize {
+    let size = (max - min).raw();
+    let estimate = if size > 0.0 {
+        (((value - min).raw() / size) * cells as f32) as usize
+    } else {
+        0
+    };
+    // the estimate can be off by one because of rounding, so correct it with the same bounds that the shards use
+    let mut cell = estimate.min(cells - 1);
+    while cell > 0 && value < bound(min, max, cells, cell) {
+        cell -= 1;
+    }
+    while cell + 1 < cells && value >= bound(min, max, cells, cell + 1) {
+        cell += 1;
+    }
+    cell
+}
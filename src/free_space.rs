@@ -0,0 +1,49 @@
+//! [`QuadTree::nearest_empty_point`], finding the closest point that is not near any entry, e.g. to spawn a unit without overlapping the others.
+
+use crate::{point::clamped_r32, point::Quadrant, Point, QuadTree, Rect, R32};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// Areas are split until they are smaller than the clearance divided by this, so a free spot that is smaller than that can be missed
+const RESOLUTION: f32 = 16.0;
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Find the point in the rect of this tree that is closest to `center` and has no entry that is less than `clearance` away from it. Returns `None` if there is no such point.
+    ///
+    /// The areas of the tree are visited from the closest to `center` outwards. An area is skipped as soon as a single entry is close to every point in it, so crowded areas only cost a few range queries. Areas are split until they are `clearance / 16` wide, which is the accuracy of the result.
+    #[must_use]
+    pub fn nearest_empty_point(&self, center: Point, clearance: R32) -> Option<Point> {
+        let clearance_squared = clamped_r32(clearance.raw() * clearance.raw());
+        let resolution = clearance.raw() / RESOLUTION;
+        let distance = |rect: Rect| Rect::new(center, center).min_distance_squared(rect);
+        let mut areas = vec![self.rect];
+        let mut open = BinaryHeap::new();
+        open.push(Reverse((distance(self.rect), 0)));
+        while let Some(Reverse((_, area))) = open.pop() {
+            let rect = areas[area];
+            let candidate = rect.clamp(center);
+            let Some(blocker) = self
+                .iter_range(candidate, clearance)
+                .map(|(_, point, _)| point)
+                .find(|point| point.distance_squared_to(candidate) < clearance_squared)
+            else {
+                return Some(candidate);
+            };
+            let (top_left, bottom_right) = (rect.top_left(), rect.bottom_right());
+            if rect.max_distance_squared(blocker) < clearance_squared
+                || (bottom_right.x.raw() - top_left.x.raw() <= resolution
+                    && bottom_right.y.raw() - top_left.y.raw() <= resolution)
+            {
+                continue;
+            }
+            for quadrant in Quadrant::all() {
+                let child = rect.get_child_at(quadrant);
+                open.push(Reverse((distance(child), areas.len())));
+                areas.push(child);
+            }
+        }
+        None
+    }
+}
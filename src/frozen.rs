@@ -0,0 +1,633 @@
+//! A read-only [`QuadTree`] that is stored in a single buffer, so it can be written to a file once and queried directly from that file later.
+//!
+//! The file is written by [`QuadTree::write_frozen`] and read by [`FrozenQuadTree::from_bytes`]. [`QuadTree::to_bytes`] and [`QuadTree::from_bytes`] use the same format to save a tree and load it into a [`QuadTree`] again, which copies the nodes and entries as they are instead of inserting them. With the `mmap` feature, [`FrozenQuadTree::open`] memory-maps the file instead of reading it, so only the parts of the file that are visited by a query are loaded.
+//!
+//! All numbers are stored in the native byte order of the machine that wrote the file, so the nodes and entries can be used without copying them. A file that was written on a machine with a different byte order is rejected.
+//!
+//! # Format
+//!
+//! Every section starts at an offset that is a multiple of 16, the gap before it is filled with zeroes.
+//!
+//! ```text
+//! header:   magic: b"WHQF", byte order: u32 0x01020304, version: u32 1,
+//!           identity size: u32, identity alignment: u32, value size: u32, value alignment: u32,
+//!           node count: u32, entry count: u32, outside start: u32, rect: FlatRect
+//! nodes:    node count * FlatNode
+//! points:   entry count * FlatPoint
+//! identity: entry count * ID
+//! values:   entry count * T
+//! ```
+//!
+//! The nodes and points are the same as in [`FlatTree`](crate::FlatTree). The identity and value of an entry are stored at the same index as its point.
+
+use crate::{
+    bucket::Bucket, ensure_index_valid, index::Index, point::Quadrant, FindRangeCtx, FlatNode,
+    FlatPoint, IdentityPoint, Point, QuadTree, Rect, R32,
+};
+use smallvec::SmallVec;
+use std::{
+    io::{self, Write},
+    marker::PhantomData,
+    mem::{align_of, size_of},
+};
+
+const MAGIC: &[u8; 4] = b"WHQF";
+const BYTE_ORDER: u32 = 0x0102_0304;
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 56;
+/// Every section starts at a multiple of this, so the types in it can be read in place
+const SECTION_ALIGN: usize = 16;
+
+/// A type that can be read from any properly aligned bytes, which is required to store it in a [`FrozenQuadTree`].
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid value of this type, and the type must not contain any padding, pointers or references; e.g. integers, floats and arrays of them.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($ty:ty),*) => {
+        $(unsafe impl Pod for $ty {})*
+    };
+}
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+// these are `#[repr(C)]` and only contain 4-byte numbers, so they have no padding either
+unsafe impl Pod for FlatNode {}
+unsafe impl Pod for FlatPoint {}
+
+/// The byte offsets of every section in the buffer of a [`FrozenQuadTree`]
+#[derive(Clone, Copy, Debug)]
+struct Layout {
+    node_count: usize,
+    entry_count: usize,
+    outside_start: usize,
+    nodes: usize,
+    points: usize,
+    identities: usize,
+    values: usize,
+    end: usize,
+}
+
+impl Layout {
+    const fn new<T, ID>(node_count: usize, entry_count: usize, outside_start: usize) -> Self {
+        let nodes = align_up(HEADER_SIZE);
+        let points = align_up(nodes + node_count * size_of::<FlatNode>());
+        let identities = align_up(points + entry_count * size_of::<FlatPoint>());
+        let values = align_up(identities + entry_count * size_of::<ID>());
+        Self {
+            node_count,
+            entry_count,
+            outside_start,
+            nodes,
+            points,
+            identities,
+            values,
+            end: values + entry_count * size_of::<T>(),
+        }
+    }
+}
+
+const fn align_up(offset: usize) -> usize {
+    offset.div_ceil(SECTION_ALIGN) * SECTION_ALIGN
+}
+
+/// A read-only [`QuadTree`] that is read in place from a buffer written by [`QuadTree::write_frozen`], see the [module documentation](self).
+///
+/// `B` is the buffer that stores the tree, e.g. a `&[u8]`, or a memory-mapped file with the `mmap` feature.
+pub struct FrozenQuadTree<T, ID, B> {
+    bytes: B,
+    rect: Rect,
+    layout: Layout,
+    types: PhantomData<fn() -> (T, ID)>,
+}
+
+impl<T: Pod, ID: Pod, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord,
+{
+    /// Write this tree to `writer` in the format that is read by [`FrozenQuadTree`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `writer` fails, or an error of kind [`io::ErrorKind::InvalidInput`] if `T` or `ID` need an alignment larger than 16 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if this tree contains more than `u32::MAX` nodes or entries.
+    pub fn write_frozen(&self, writer: &mut impl Write) -> io::Result<()> {
+        if align_of::<T>() > SECTION_ALIGN || align_of::<ID>() > SECTION_ALIGN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the alignment of the identity or value is too large",
+            ));
+        }
+        let flat = self.export_flat();
+        let layout = Layout::new::<T, ID>(
+            flat.nodes.len(),
+            flat.points.len(),
+            flat.outside_start as usize,
+        );
+
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&BYTE_ORDER.to_ne_bytes());
+        header.extend_from_slice(&VERSION.to_ne_bytes());
+        for n in [
+            size_of::<ID>(),
+            align_of::<ID>(),
+            size_of::<T>(),
+            align_of::<T>(),
+            layout.node_count,
+            layout.entry_count,
+            layout.outside_start,
+        ]
+        .map(|n| u32::try_from(n).expect("Tree is too large to be exported"))
+        {
+            header.extend_from_slice(&n.to_ne_bytes());
+        }
+        for n in [
+            flat.rect.left,
+            flat.rect.top,
+            flat.rect.right,
+            flat.rect.bottom,
+        ] {
+            header.extend_from_slice(&n.to_ne_bytes());
+        }
+        writer.write_all(&header)?;
+
+        let mut written = HEADER_SIZE;
+        let mut section = |writer: &mut dyn Write, offset: usize, bytes: &[u8]| {
+            writer.write_all(&[0; SECTION_ALIGN][..offset - written])?;
+            writer.write_all(bytes)?;
+            written = offset + bytes.len();
+            io::Result::Ok(())
+        };
+        section(writer, layout.nodes, as_bytes(&flat.nodes))?;
+        section(writer, layout.points, as_bytes(&flat.points))?;
+        let (identities, values): (Vec<ID>, Vec<T>) = flat
+            .value_indices
+            .iter()
+            .map(|idx| {
+                let (identity, value) = flat.values[*idx as usize];
+                (*identity, *value)
+            })
+            .unzip();
+        section(writer, layout.identities, as_bytes(&identities))?;
+        section(writer, layout.values, as_bytes(&values))
+    }
+}
+
+impl<T: Pod, ID: Pod + Ord + std::fmt::Debug, const N: usize> QuadTree<T, ID, N> {
+    /// The bytes that [`QuadTree::write_frozen`] writes, which can be queried in place with [`FrozenQuadTree::from_bytes`] or loaded into a tree again with [`QuadTree::from_bytes`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `T` or `ID` need an alignment larger than 16 bytes, or if this tree contains more than `u32::MAX` nodes or entries.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // writing to a `Vec` can not fail, only the types can be rejected
+        self.write_frozen(&mut bytes)
+            .unwrap_or_else(|error| panic!("{error}"));
+        bytes
+    }
+
+    /// Load a tree from `bytes` that were written by [`QuadTree::to_bytes`] or [`QuadTree::write_frozen`] with the same `T` and `ID`. The nodes are copied as they are, so the new tree has the same layout as the tree that was saved.
+    ///
+    /// `bytes` does not have to be aligned, it is copied once if it is not. Only the entries and the rect are stored, so the y axis, [`QuadTree::min_cell_extent`], the position filter and deferred merges have to be set again after loading, and every entry gets a new generation.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error of kind [`io::ErrorKind::InvalidData`] if `bytes` is not a valid tree, see [`FrozenQuadTree::from_bytes`], if a node is nested deeper than 15 levels, if an entry is not inside of its leaf or an entry outside of the tree is inside of it, or if an identity is stored twice.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let copy;
+        let bytes = if bytes.as_ptr().align_offset(SECTION_ALIGN) == 0 {
+            bytes
+        } else {
+            copy = AlignedBytes::new(bytes);
+            copy.as_slice()
+        };
+        let frozen = FrozenQuadTree::<T, ID, _>::from_bytes(bytes)?;
+        let rect = frozen.rect();
+        let mut tree = Self::new(rect.top_left(), rect.bottom_right());
+        tree.load_frozen_node(&frozen, 0, Index::ROOT, rect)?;
+        for idx in frozen.layout.outside_start..frozen.len() {
+            let point = frozen.point(idx);
+            if rect.contains(point) {
+                return Err(invalid_data("outside entry is inside of the tree"));
+            }
+            let identity = frozen.identities()[idx];
+            let generation = tree.new_generation();
+            if tree
+                .identity_to_point
+                .insert(identity, (point, None, generation))
+                .is_some()
+            {
+                return Err(invalid_data("identity is stored twice"));
+            }
+            tree.outside_of_range
+                .insert(identity, (frozen.values()[idx], point));
+        }
+        tree.dirty.touch(Index::ROOT);
+        if !tree.outside_of_range.is_empty() {
+            tree.dirty.touch_outside();
+        }
+        Ok(tree)
+    }
+
+    /// Copy the flat node at `node` and its children into the node at `index`, which covers `rect`
+    fn load_frozen_node(
+        &mut self,
+        frozen: &FrozenQuadTree<T, ID, &[u8]>,
+        node: usize,
+        index: Index,
+        rect: Rect,
+    ) -> io::Result<()> {
+        let flat = frozen.nodes()[node];
+        let (first, count) = (flat.first as usize, flat.count as usize);
+        let bucket = if flat.kind == crate::FLAT_NODE_NESTED {
+            let children = index
+                .children()
+                .ok_or_else(|| invalid_data("nested node is too deep"))?;
+            for (n, (quadrant, child)) in Quadrant::all().into_iter().zip(children).enumerate() {
+                self.load_frozen_node(frozen, first + n, child, rect.get_child_at(quadrant))?;
+            }
+            Bucket::Nested
+        } else {
+            let mut entries = SmallVec::new();
+            for idx in first..first + count {
+                let point = frozen.point(idx);
+                if !rect.contains(point) {
+                    return Err(invalid_data("entry is outside of its node"));
+                }
+                let identity = frozen.identities()[idx];
+                let generation = self.new_generation();
+                if self
+                    .identity_to_point
+                    .insert(identity, (point, Some(index), generation))
+                    .is_some()
+                {
+                    return Err(invalid_data("identity is stored twice"));
+                }
+                entries.push((IdentityPoint { identity, point }, frozen.values()[idx]));
+            }
+            Bucket::Owned(entries)
+        };
+        *ensure_index_valid(&mut self.items, index).map_err(io::Error::other)? = bucket;
+        Ok(())
+    }
+}
+
+/// A copy of bytes that starts at a multiple of 16, for [`QuadTree::from_bytes`]
+struct AlignedBytes {
+    blocks: Vec<Block>,
+    len: usize,
+}
+
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct Block([u8; SECTION_ALIGN]);
+
+impl AlignedBytes {
+    fn new(bytes: &[u8]) -> Self {
+        let blocks = bytes
+            .chunks(SECTION_ALIGN)
+            .map(|chunk| {
+                let mut block = [0; SECTION_ALIGN];
+                block[..chunk.len()].copy_from_slice(chunk);
+                Block(block)
+            })
+            .collect();
+        Self {
+            blocks,
+            len: bytes.len(),
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        // SAFETY: a block is 16 bytes without padding, and `len` is at most the amount of bytes in all blocks
+        unsafe { std::slice::from_raw_parts(self.blocks.as_ptr().cast::<u8>(), self.len) }
+    }
+}
+
+fn as_bytes<P: Pod>(items: &[P]) -> &[u8] {
+    // SAFETY: `P` has no padding, so every byte of `items` is initialized
+    unsafe { std::slice::from_raw_parts(items.as_ptr().cast::<u8>(), std::mem::size_of_val(items)) }
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl<T: Pod, ID: Pod, B: AsRef<[u8]>> FrozenQuadTree<T, ID, B> {
+    /// Read a tree from `bytes`, which must be written by [`QuadTree::write_frozen`] with the same `T` and `ID`.
+    ///
+    /// The start of `bytes` must be aligned to 16 bytes. The whole tree is validated once, so queries never have to check it again.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error of kind [`io::ErrorKind::InvalidData`] if `bytes` is not aligned, is not a valid tree, or stores a different `T` or `ID`.
+    pub fn from_bytes(bytes: B) -> io::Result<Self> {
+        let data = bytes.as_ref();
+        if data.as_ptr().align_offset(SECTION_ALIGN) != 0 {
+            return Err(invalid_data("the buffer is not aligned to 16 bytes"));
+        }
+        if data.len() < HEADER_SIZE || &data[..4] != MAGIC {
+            return Err(invalid_data("not a frozen quad tree"));
+        }
+        let field = |n: usize| {
+            let offset = 4 + n * 4;
+            u32::from_ne_bytes([
+                data[offset],
+                data[offset + 1],
+                data[offset + 2],
+                data[offset + 3],
+            ])
+        };
+        if field(0) != BYTE_ORDER {
+            return Err(invalid_data(
+                "the tree was written with a different byte order",
+            ));
+        }
+        if field(1) != VERSION {
+            return Err(invalid_data("unsupported version"));
+        }
+        let types = [
+            size_of::<ID>(),
+            align_of::<ID>(),
+            size_of::<T>(),
+            align_of::<T>(),
+        ];
+        if (2..6)
+            .map(field)
+            .ne(types.map(|n| u32::try_from(n).unwrap_or(u32::MAX)))
+        {
+            return Err(invalid_data("the identity or value type does not match"));
+        }
+        let [node_count, entry_count, outside_start] = [6, 7, 8].map(|n| field(n) as usize);
+        let [left, top, right, bottom] = [9, 10, 11, 12].map(|n| f32::from_bits(field(n)));
+        let point =
+            |x: f32, y: f32| Some(Point::new_noisy_float(R32::try_new(x)?, R32::try_new(y)?));
+        let rect = point(left, top)
+            .zip(point(right, bottom))
+            .map(|(top_left, bottom_right)| Rect::new(top_left, bottom_right))
+            .ok_or_else(|| invalid_data("invalid rect"))?;
+
+        // the counts are read from a `u32`, so this can not overflow on 64-bit platforms
+        let layout = Layout::new::<T, ID>(node_count, entry_count, outside_start);
+        if layout.end > data.len() || node_count == 0 || outside_start > entry_count {
+            return Err(invalid_data("the tree is truncated"));
+        }
+        let tree = Self {
+            bytes,
+            rect,
+            layout,
+            types: PhantomData,
+        };
+        tree.validate()?;
+        Ok(tree)
+    }
+
+    /// Make sure that every traversal of the nodes ends, and that every node and entry can be read
+    fn validate(&self) -> io::Result<()> {
+        let nodes = self.nodes();
+        for (index, node) in nodes.iter().enumerate() {
+            let (first, count) = (node.first as usize, node.count as usize);
+            let valid = match node.kind {
+                // children are always stored after their parent, so there can't be a cycle
+                crate::FLAT_NODE_NESTED => count == 4 && first > index && first + 4 <= nodes.len(),
+                crate::FLAT_NODE_LEAF => first + count <= self.layout.outside_start,
+                _ => false,
+            };
+            if !valid {
+                return Err(invalid_data("invalid node"));
+            }
+        }
+        if self
+            .points()
+            .iter()
+            .any(|point| R32::try_new(point.x).is_none() || R32::try_new(point.y).is_none())
+        {
+            return Err(invalid_data("invalid point"));
+        }
+        Ok(())
+    }
+
+    /// The area that this tree covers
+    #[must_use]
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// The amount of entries in this tree
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.layout.entry_count
+    }
+
+    /// Returns `true` if this tree has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Call `callback` for every entry in this tree
+    pub fn for_each<'a>(&'a self, mut callback: impl FnMut(&'a ID, Point, &'a T)) {
+        for idx in 0..self.len() {
+            self.entry(idx, &mut callback);
+        }
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, see [`QuadTree::find_range`].
+    pub fn find_range<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        mut callback: impl FnMut(&'a ID, Point, &'a T),
+    ) {
+        let ctx = FindRangeCtx::new(center, range);
+        self.find_range_inner(0, self.rect, &ctx, &mut callback);
+        for idx in self.layout.outside_start..self.len() {
+            if ctx.point_in_range(self.point(idx)) {
+                self.entry(idx, &mut callback);
+            }
+        }
+    }
+
+    fn find_range_inner<'a>(
+        &'a self,
+        node: usize,
+        rect: Rect,
+        ctx: &FindRangeCtx,
+        callback: &mut impl FnMut(&'a ID, Point, &'a T),
+    ) {
+        if !ctx.contains_rect(rect) {
+            return;
+        }
+        let FlatNode {
+            kind, first, count, ..
+        } = self.nodes()[node];
+        let first = first as usize;
+        if kind == crate::FLAT_NODE_NESTED {
+            for (offset, quadrant) in Quadrant::all().into_iter().enumerate() {
+                self.find_range_inner(first + offset, rect.get_child_at(quadrant), ctx, callback);
+            }
+        } else {
+            for idx in first..first + count as usize {
+                if ctx.point_in_range(self.point(idx)) {
+                    self.entry(idx, callback);
+                }
+            }
+        }
+    }
+
+    fn entry<'a>(&'a self, idx: usize, callback: &mut impl FnMut(&'a ID, Point, &'a T)) {
+        callback(
+            &self.identities()[idx],
+            self.point(idx),
+            &self.values()[idx],
+        );
+    }
+
+    fn point(&self, idx: usize) -> Point {
+        let FlatPoint { x, y } = self.points()[idx];
+        // all points were checked in `validate`
+        Point::new(x, y)
+    }
+
+    fn nodes(&self) -> &[FlatNode] {
+        // SAFETY: every section starts at a multiple of 16
+        unsafe { self.section(self.layout.nodes, self.layout.node_count) }
+    }
+
+    fn points(&self) -> &[FlatPoint] {
+        // SAFETY: every section starts at a multiple of 16
+        unsafe { self.section(self.layout.points, self.layout.entry_count) }
+    }
+
+    fn identities(&self) -> &[ID] {
+        // SAFETY: every section starts at a multiple of 16, which is checked to be enough for `ID`
+        unsafe { self.section(self.layout.identities, self.layout.entry_count) }
+    }
+
+    fn values(&self) -> &[T] {
+        // SAFETY: every section starts at a multiple of 16, which is checked to be enough for `T`
+        unsafe { self.section(self.layout.values, self.layout.entry_count) }
+    }
+
+    /// # Safety
+    ///
+    /// `offset` must be aligned for `P` within the buffer.
+    unsafe fn section<P: Pod>(&self, offset: usize, len: usize) -> &[P] {
+        let bytes = &self.bytes.as_ref()[offset..offset + len * size_of::<P>()];
+        std::slice::from_raw_parts(bytes.as_ptr().cast::<P>(), len)
+    }
+}
+
+#[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+pub use mmap::Mmap;
+
+#[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+mod mmap {
+    use super::{FrozenQuadTree, Pod};
+    use std::{
+        ffi::{c_int, c_void},
+        fs::File,
+        io,
+        os::unix::io::AsRawFd,
+        path::Path,
+        ptr::NonNull,
+    };
+
+    const PROT_READ: c_int = 1;
+    const MAP_PRIVATE: c_int = 2;
+
+    extern "C" {
+        fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: i64,
+        ) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+
+    /// A read-only memory-mapped file, which is unmapped when dropped
+    #[derive(Debug)]
+    pub struct Mmap {
+        ptr: NonNull<u8>,
+        len: usize,
+    }
+
+    // SAFETY: the mapping is read-only, so it can be shared between threads
+    unsafe impl Send for Mmap {}
+    unsafe impl Sync for Mmap {}
+
+    impl Mmap {
+        /// Map the whole file at `path` into memory
+        ///
+        /// # Safety
+        ///
+        /// The file must not be changed while it is mapped, because the mapping would change with it.
+        ///
+        /// # Errors
+        ///
+        /// Will return an error if the file can not be opened or mapped, or if it is empty.
+        pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+            let file = File::open(path)?;
+            let len = usize::try_from(file.metadata()?.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "file is too large"))?;
+            if len == 0 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "file is empty"));
+            }
+            let ptr = mmap(
+                std::ptr::null_mut(),
+                len,
+                PROT_READ,
+                MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            );
+            // `MAP_FAILED` is -1
+            if ptr as isize == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            let ptr = NonNull::new(ptr.cast()).ok_or_else(io::Error::last_os_error)?;
+            Ok(Self { ptr, len })
+        }
+    }
+
+    impl AsRef<[u8]> for Mmap {
+        fn as_ref(&self) -> &[u8] {
+            // SAFETY: the mapping is `len` bytes long and lives as long as `self`
+            unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+        }
+    }
+
+    impl Drop for Mmap {
+        fn drop(&mut self) {
+            // SAFETY: this mapping was created by `mmap` with this length, and is not used after this
+            unsafe {
+                munmap(self.ptr.as_ptr().cast(), self.len);
+            }
+        }
+    }
+
+    impl<T: Pod, ID: Pod> FrozenQuadTree<T, ID, Mmap> {
+        /// Memory-map the file at `path`, which was written by [`QuadTree::write_frozen`](crate::QuadTree::write_frozen), see [`FrozenQuadTree::from_bytes`].
+        ///
+        /// # Safety
+        ///
+        /// The file must not be changed while the tree exists, see [`Mmap::open`].
+        ///
+        /// # Errors
+        ///
+        /// Will return an error if the file can not be mapped, or if it is not a valid tree with this `T` and `ID`.
+        pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+            Self::from_bytes(Mmap::open(path)?)
+        }
+    }
+}
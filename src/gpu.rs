@@ -0,0 +1,154 @@
+//! A flattened export of a [`QuadTree`] that can be uploaded directly into GPU storage buffers.
+//!
+//! Both structs follow the `std430` layout rules, so they can be declared in GLSL/WGSL as:
+//!
+//! ```text
+//! struct GpuNode {        // 32 bytes, aligned to 16
+//!     vec4 rect;          // left, top, right, bottom
+//!     uint kind;          // 1 = nested, 2 = leaf
+//!     uint first;         // nested: index of the first of 4 children, leaf: index of the first entry
+//!     uint count;         // nested: 4, leaf: amount of entries
+//!     uint _padding;
+//! };
+//! struct GpuEntry {       // 8 bytes, aligned to 8
+//!     vec2 point;
+//! };
+//! ```
+
+use crate::{
+    bucket::Bucket, curve::morton_code, flat::to_u32, index::Index, point::Quadrant, QuadTree,
+    Rect, FLAT_NODE_LEAF, FLAT_NODE_NESTED,
+};
+
+/// A single node in [`GpuBuffers::nodes`]. 32 bytes, aligned to 16 bytes.
+#[repr(C, align(16))]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct GpuNode {
+    /// The area of this node as `left, top, right, bottom`
+    pub rect: [f32; 4],
+    /// Either [`FLAT_NODE_NESTED`] or [`FLAT_NODE_LEAF`]
+    pub kind: u32,
+    /// For nested nodes the index of the first of the 4 children, which are stored next to each other in the order top-left, top-right, bottom-left, bottom-right.
+    ///
+    /// For leaves the index of the first entry in [`GpuBuffers::entries`].
+    pub first: u32,
+    /// For nested nodes this is always 4, for leaves this is the amount of entries in this leaf.
+    pub count: u32,
+    /// Padding to match the `std430` layout
+    pub padding: u32,
+}
+
+/// A single entry in [`GpuBuffers::entries`]. 8 bytes, aligned to 8 bytes.
+#[repr(C, align(8))]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct GpuEntry {
+    /// The position of this entry as `x, y`
+    pub point: [f32; 2],
+}
+
+/// The buffers created by [`QuadTree::to_gpu_buffers`].
+///
+/// Entries are stored in Morton order: leaves are laid out in depth-first quadrant order, and the entries within a leaf are sorted by their Morton code. `values[i]` belongs to `entries[i]`.
+///
+/// Entries from `outside_start` onwards are outside of the root rect, and are not part of any node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuBuffers<'a, T, ID> {
+    /// All nodes in the tree, starting with the root node
+    pub nodes: Vec<GpuNode>,
+    /// The position of every entry
+    pub entries: Vec<GpuEntry>,
+    /// The identity and value of every entry, in the same order as `entries`
+    pub values: Vec<(&'a ID, &'a T)>,
+    /// The index of the first entry that is outside of the root rect
+    pub outside_start: u32,
+}
+
+fn gpu_rect(rect: Rect) -> [f32; 4] {
+    let top_left = rect.top_left();
+    let bottom_right = rect.bottom_right();
+    [
+        top_left.x.raw(),
+        top_left.y.raw(),
+        bottom_right.x.raw(),
+        bottom_right.y.raw(),
+    ]
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N> {
+    /// Export this tree into buffers that can be uploaded to the GPU as-is. See [`GpuBuffers`] for the layout.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if this tree contains more than `u32::MAX` nodes or entries.
+    #[must_use]
+    pub fn to_gpu_buffers(&self) -> GpuBuffers<'_, T, ID> {
+        let mut buffers = GpuBuffers {
+            nodes: vec![GpuNode::default()],
+            entries: Vec::new(),
+            values: Vec::new(),
+            outside_start: 0,
+        };
+        self.write_gpu_node(&mut buffers, Index::ROOT, self.rect, 0);
+
+        buffers.outside_start = to_u32(buffers.entries.len());
+        let mut outside: Vec<_> = self.outside_of_range.iter().collect();
+        outside.sort_by_key(|(_, (_, point))| morton_code(self.rect, *point));
+        for (identity, (value, point)) in outside {
+            buffers.entries.push(GpuEntry {
+                point: [point.x.raw(), point.y.raw()],
+            });
+            buffers.values.push((identity, value));
+        }
+        buffers
+    }
+
+    fn write_gpu_node<'a>(
+        &'a self,
+        buffers: &mut GpuBuffers<'a, T, ID>,
+        index: Index,
+        rect: Rect,
+        slot: usize,
+    ) {
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => {
+                let first = buffers.nodes.len();
+                buffers.nodes.resize(first + 4, GpuNode::default());
+                buffers.nodes[slot] = GpuNode {
+                    rect: gpu_rect(rect),
+                    kind: FLAT_NODE_NESTED,
+                    first: to_u32(first),
+                    count: 4,
+                    padding: 0,
+                };
+                for (offset, quadrant) in Quadrant::all().into_iter().enumerate() {
+                    self.write_gpu_node(
+                        buffers,
+                        index.child_at(quadrant),
+                        rect.get_child_at(quadrant),
+                        first + offset,
+                    );
+                }
+            }
+            bucket => {
+                let first = buffers.entries.len();
+                if let Some(Bucket::Owned(bucket)) = bucket {
+                    let mut entries: Vec<_> = bucket.iter().collect();
+                    entries.sort_by_key(|(ip, _)| morton_code(self.rect, ip.point));
+                    for (ip, value) in entries {
+                        buffers.entries.push(GpuEntry {
+                            point: [ip.point.x.raw(), ip.point.y.raw()],
+                        });
+                        buffers.values.push((&ip.identity, value));
+                    }
+                }
+                buffers.nodes[slot] = GpuNode {
+                    rect: gpu_rect(rect),
+                    kind: FLAT_NODE_LEAF,
+                    first: to_u32(first),
+                    count: to_u32(buffers.entries.len() - first),
+                    padding: 0,
+                };
+            }
+        }
+    }
+}
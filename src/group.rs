@@ -0,0 +1,61 @@
+//! Moving a group of entries by the same offset, e.g. a formation of units.
+
+use crate::{bucket::Bucket, expect_consistent, CorruptionError, Point, QuadTree};
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Move every entry in `identities` by `delta`. Identities that are not in this tree are skipped. Returns the amount of moved entries.
+    ///
+    /// A group usually moves a short distance, so most entries stay in the same leaf. Those are moved in place, without searching the tree for their new leaf. The other entries are moved with [`QuadTree::update`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a new position does not fit in a float.
+    pub fn translate_group(
+        &mut self,
+        identities: impl IntoIterator<Item = ID>,
+        delta: Point,
+    ) -> usize {
+        let mut moved = 0;
+        for identity in identities {
+            if expect_consistent(self.translate_entry(identity, delta)) {
+                moved += 1;
+            }
+        }
+        moved
+    }
+
+    fn translate_entry(&mut self, identity: ID, delta: Point) -> Result<bool, CorruptionError> {
+        let filter = self.position_filter;
+        let Some((point, index, generation)) = self.identity_to_point.get_mut(&identity) else {
+            return Ok(false);
+        };
+        let new_point = *point + delta;
+        let new_point = filter.map_or(new_point, |filter| filter(new_point));
+        let Some(index) = *index else {
+            return self.move_entry(identity, new_point, |_| {});
+        };
+        if !self
+            .rect
+            .get_index_rect(index)
+            .contains_as_leaf(self.rect, new_point)
+        {
+            return self.move_entry(identity, new_point, |_| {});
+        }
+        *point = new_point;
+        self.next_generation += 1;
+        *generation = self.next_generation;
+        let Some(Bucket::Owned(entries)) = self.items.get_mut(index.to_idx()) else {
+            return Err(CorruptionError::new("entry is missing from its bucket"));
+        };
+        let (ip, _) = entries
+            .iter_mut()
+            .find(|(ip, _)| ip.identity == identity)
+            .ok_or(CorruptionError::new("entry is missing from its bucket"))?;
+        ip.point = new_point;
+        self.dirty.touch(index);
+        Ok(true)
+    }
+}
@@ -0,0 +1,113 @@
+//! [`HandleQuadTree`], a tree that gives out its own dense identities, for callers that have no identity of their own that is `Ord`, `Clone` and `Debug`.
+
+use crate::{IdentityPoint, Point, QuadTree, R32};
+use std::fmt;
+
+/// The identity of an entry of a [`HandleQuadTree`], which is returned by [`HandleQuadTree::insert`].
+///
+/// Handles are dense: they count up from 0, and the handle of a removed entry is given to the next inserted entry. A handle can be used as an index into a `Vec` that the caller keeps next to the tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntryId(pub usize);
+
+impl fmt::Display for EntryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A [`QuadTree`] whose identities are [`EntryId`] handles that the tree gives out itself, so `T` is the only type parameter and there are no bounds on it.
+///
+/// Because handles are reused, a handle that is kept after its entry is removed can find an entry that is inserted later. Use [`QuadTree::weak_ref`] on [`HandleQuadTree::tree`] if a reference has to notice that.
+#[derive(Clone, Debug)]
+pub struct HandleQuadTree<T, const N: usize> {
+    tree: QuadTree<T, EntryId, N>,
+    /// The handles of removed entries, which are given out again before any new handle
+    free: Vec<EntryId>,
+    /// The next handle that was never given out
+    next: usize,
+}
+
+impl<T, const N: usize> HandleQuadTree<T, N> {
+    /// Create a new tree which covers the area between `top_left` and `bottom_right`, see [`QuadTree::new`]
+    #[must_use]
+    pub fn new(top_left: Point, bottom_right: Point) -> Self {
+        Self {
+            tree: QuadTree::new(top_left, bottom_right),
+            free: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// The tree with its [`EntryId`] identities, for the queries that are not wrapped
+    #[must_use]
+    pub fn tree(&self) -> &QuadTree<T, EntryId, N> {
+        &self.tree
+    }
+
+    /// The amount of entries in this tree
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.next - self.free.len()
+    }
+
+    /// Returns `true` if this tree has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert a value `value` at the given `point`, and return the handle of the new entry. This is the most recently freed handle, or the next unused handle if no handle is free.
+    pub fn insert(&mut self, point: Point, value: T) -> EntryId {
+        let identity = self.free.pop().unwrap_or_else(|| {
+            self.next += 1;
+            EntryId(self.next - 1)
+        });
+        self.tree.insert(IdentityPoint { identity, point }, value);
+        identity
+    }
+
+    /// Update the entry `identity` to the new point, see [`QuadTree::update`].
+    ///
+    /// Will return `true` if the entry was found and updated, `false` otherwise
+    pub fn update(&mut self, identity: EntryId, point: Point) -> bool {
+        self.tree.update(identity, point)
+    }
+
+    /// Update the entry `identity` to the new point, with the opportunity to update the value, see [`QuadTree::update_point_and_value`].
+    ///
+    /// Will return `true` if the entry was found and updated, `false` otherwise
+    pub fn update_point_and_value(
+        &mut self,
+        identity: EntryId,
+        point: Point,
+        callback: impl FnOnce(&mut T),
+    ) -> bool {
+        self.tree.update_point_and_value(identity, point, callback)
+    }
+
+    /// Try to remove the entry `identity` from this tree. Will return the entry and its position if it's found, `None` otherwise. Its handle is given to the next inserted entry.
+    pub fn try_remove(&mut self, identity: EntryId) -> Option<(T, Point)> {
+        let removed = self.tree.try_remove(&identity)?;
+        self.free.push(identity);
+        Some(removed)
+    }
+
+    /// The position and value of the entry `identity`, or `None` if it is not in this tree
+    #[must_use]
+    pub fn get(&self, identity: EntryId) -> Option<(Point, &T)> {
+        self.tree.get(&identity)
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, see [`QuadTree::find_range`]
+    pub fn find_range<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        mut callback: impl FnMut(EntryId, Point, &'a T),
+    ) {
+        self.tree
+            .find_range(center, range, |identity, point, value| {
+                callback(*identity, point, value);
+            });
+    }
+}
@@ -0,0 +1,72 @@
+//! Finding the parts of a [`QuadTree`] that change the most.
+
+use crate::{index::Index, QuadTree, Rect};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Counts how often every node was rewritten during the last `window` changes of a tree
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Churn {
+    window: usize,
+    /// The nodes of the last `window` changes, oldest first
+    recent: VecDeque<Index>,
+    counts: BTreeMap<Index, u64>,
+}
+
+impl Churn {
+    /// Count a change to the node at `index`, and forget the oldest change if the window is full
+    pub(crate) fn record(&mut self, index: Index) {
+        if self.window == 0 {
+            return;
+        }
+        self.recent.push_back(index);
+        *self.counts.entry(index).or_default() += 1;
+        if self.recent.len() > self.window {
+            self.forget_oldest();
+        }
+    }
+
+    fn forget_oldest(&mut self) {
+        let Some(index) = self.recent.pop_front() else {
+            return;
+        };
+        if let Some(count) = self.counts.get_mut(&index) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&index);
+            }
+        }
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N> {
+    /// Start counting how often every node changes during the last `window` changes of this tree, see [`QuadTree::hotspots`]. A `window` of `0` stops counting and forgets all counts, which is the default.
+    ///
+    /// Every insert, update and removal inside of this tree is counted on the node that it changed, which is usually a leaf. Changes to the entries outside of this tree are not counted. Counting takes memory for `window` changes.
+    pub fn track_hotspots(&mut self, window: usize) {
+        let churn = &mut self.dirty.churn;
+        churn.window = window;
+        while churn.recent.len() > window {
+            churn.forget_oldest();
+        }
+    }
+
+    /// The `top_k` nodes that changed most often during the window of [`QuadTree::track_hotspots`], with the amount of changes to them, busiest first.
+    ///
+    /// Nodes that changed equally often are sorted from the largest node to the smallest one. A node that was split or merged since it changed is still reported with its own rect. Nothing is found if hotspots are not tracked.
+    #[must_use]
+    pub fn hotspots(&self, top_k: usize) -> Vec<(Rect, u64)> {
+        let mut hotspots = self
+            .dirty
+            .churn
+            .counts
+            .iter()
+            .map(|(index, count)| (*index, *count))
+            .collect::<Vec<_>>();
+        hotspots.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hotspots
+            .into_iter()
+            .take(top_k)
+            .map(|(index, count)| (self.rect.get_index_rect(index), count))
+            .collect()
+    }
+}
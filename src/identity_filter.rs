@@ -0,0 +1,132 @@
+//! [`IdentityFilter`], bloom filters of the identities in a [`QuadTree`] and in each of its nodes, to rule out identities without searching the tree.
+
+use crate::{bucket::Bucket, index::Index, point::Quadrant, NodeId, QuadTree, YAxis};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// The amount of bits of the filter of a whole tree for every entry, which gives about 1.4% false positives with 2 bits per identity
+const BITS_PER_ENTRY: usize = 16;
+
+/// Bloom filters of the identities in a [`QuadTree`], created by [`QuadTree::identity_filter`].
+///
+/// The filter answers whether an identity is definitely not in the tree, or maybe is. It is a copy of the identities at the time it was created, so it has to be created again after the tree changes.
+///
+/// Every node also gets a 64-bit fingerprint of the identities in it and its descendants. Two nodes whose fingerprints share no bits have no identities in common, which lets a join of two trees skip those nodes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentityFilter {
+    /// The filter of all identities, also of the entries outside of the tree. The length is a power of two.
+    words: Vec<u64>,
+    /// The fingerprints of the nodes, in the same order as the buckets of the tree
+    nodes: Vec<u64>,
+    flip_vertical: bool,
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone + Hash,
+{
+    /// Create an [`IdentityFilter`] of the identities that are in this tree now, e.g. before checking a large batch of identities that are mostly absent with [`IdentityFilter::may_contain`]. This visits every entry.
+    #[must_use]
+    pub fn identity_filter(&self) -> IdentityFilter {
+        let words = (self.identity_to_point.len() * BITS_PER_ENTRY)
+            .div_ceil(64)
+            .next_power_of_two();
+        let mut filter = IdentityFilter {
+            words: vec![0; words],
+            nodes: vec![0; self.items.len()],
+            flip_vertical: self.y_axis == YAxis::Up,
+        };
+        for identity in self.identity_to_point.keys() {
+            filter.insert(hash(identity));
+        }
+        filter.fill_node(&self.items, Index::ROOT);
+        filter
+    }
+}
+
+impl IdentityFilter {
+    /// Returns `false` if `identity` is definitely not in the tree, `true` if it may be. Only then the tree has to be searched.
+    #[must_use]
+    pub fn may_contain<ID: Hash>(&self, identity: &ID) -> bool {
+        let (first, second) = self.bits(hash(identity));
+        self.is_set(first) && self.is_set(second)
+    }
+
+    /// The fingerprint of the identities in `node` and its descendants, which is `0` if the node has no entries or does not exist
+    #[must_use]
+    pub fn node_fingerprint(&self, node: NodeId) -> u64 {
+        let index = if self.flip_vertical {
+            node.0.flip_vertical()
+        } else {
+            node.0
+        };
+        self.nodes.get(index.to_idx()).copied().unwrap_or(0)
+    }
+
+    /// Returns `false` if `identity` is definitely not in `node` or its descendants, `true` if it may be
+    #[must_use]
+    pub fn node_may_contain<ID: Hash>(&self, node: NodeId, identity: &ID) -> bool {
+        let fingerprint = node_fingerprint(hash(identity));
+        self.node_fingerprint(node) & fingerprint == fingerprint
+    }
+
+    /// Returns `false` if `node` of this filter and `other_node` of `other` definitely have no identities in common, `true` if they may have
+    #[must_use]
+    pub fn may_share(&self, node: NodeId, other: &IdentityFilter, other_node: NodeId) -> bool {
+        self.node_fingerprint(node) & other.node_fingerprint(other_node) != 0
+    }
+
+    fn insert(&mut self, hash: u64) {
+        let (first, second) = self.bits(hash);
+        for bit in [first, second] {
+            self.words[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    fn is_set(&self, bit: usize) -> bool {
+        self.words[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    /// The two bits of the filter of the whole tree for `hash`
+    fn bits(&self, hash: u64) -> (usize, usize) {
+        let mask = self.words.len() * 64 - 1;
+        // the mask is smaller than the length of the filter, which is a usize
+        #[allow(clippy::cast_possible_truncation)]
+        ((hash as usize) & mask, ((hash >> 32) as usize) & mask)
+    }
+
+    /// Set the fingerprint of the node at `index` and its descendants, and return it
+    fn fill_node<T, ID: Hash, const N: usize>(
+        &mut self,
+        items: &[Bucket<T, ID, N>],
+        index: Index,
+    ) -> u64 {
+        let fingerprint = match items.get(index.to_idx()) {
+            Some(Bucket::Nested) => Quadrant::all()
+                .into_iter()
+                .filter_map(|quadrant| index.checked_child_at(quadrant))
+                .fold(0, |fingerprint, child| {
+                    fingerprint | self.fill_node(items, child)
+                }),
+            Some(Bucket::Owned(entries)) => entries.iter().fold(0, |fingerprint, (ip, _)| {
+                fingerprint | node_fingerprint(hash(&ip.identity))
+            }),
+            None => return 0,
+        };
+        self.nodes[index.to_idx()] = fingerprint;
+        fingerprint
+    }
+}
+
+fn hash<ID: Hash>(identity: &ID) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    identity.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The two bits of a node fingerprint for `hash`, which uses other bits of the hash than the filter of the whole tree
+fn node_fingerprint(hash: u64) -> u64 {
+    (1 << ((hash >> 16) % 64)) | (1 << ((hash >> 48) % 64))
+}
@@ -0,0 +1,393 @@
+//! Incremental serialization of a [`QuadTree`], which only writes the nodes that changed since an earlier save.
+//!
+//! Every change to a tree bumps its generation, and stores that generation on the node that was rewritten and on all of its ancestors. [`QuadTree::save_incremental`] skips every subtree that did not change since the given generation, so the size of a delta depends on the amount of changed nodes instead of the size of the tree.
+
+use crate::{
+    bucket::Bucket, ensure_index_valid, flat::to_u32, hotspots::Churn, index::Index,
+    point::Quadrant, CorruptionError, IdentityPoint, Point, QuadTree, Rect,
+};
+use noisy_float::types::R32;
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 4] = b"WHQD";
+
+const RECORD_END: u8 = 0;
+const RECORD_SUBTREE: u8 = 1;
+const RECORD_OUTSIDE: u8 = 2;
+
+const NODE_NESTED: u8 = 1;
+const NODE_LEAF: u8 = 2;
+
+/// Keeps track of the generation in which every node of a [`QuadTree`] last changed
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Dirty {
+    generation: u64,
+    nodes: Vec<NodeGeneration>,
+    outside: u64,
+    pub(crate) churn: Churn,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct NodeGeneration {
+    /// The last generation in which this node was rewritten. If this node is nested, its whole subtree was rewritten.
+    pub(crate) rewritten: u64,
+    /// The last generation in which this node or one of its descendants was rewritten
+    pub(crate) subtree: u64,
+}
+
+// The generations are bookkeeping, two trees with the same entries are equal regardless of their history
+impl PartialEq for Dirty {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+impl Eq for Dirty {}
+
+impl Dirty {
+    /// Mark the node at `index` as rewritten in a new generation
+    pub(crate) fn touch(&mut self, index: Index) {
+        self.generation += 1;
+        let idx = index.to_idx();
+        if self.nodes.len() <= idx {
+            self.nodes.resize(idx + 1, NodeGeneration::default());
+        }
+        self.nodes[idx].rewritten = self.generation;
+        self.churn.record(index);
+        // parents always have a lower idx than their children, so these are in range
+        let mut current = Some(index);
+        while let Some(index) = current {
+            self.nodes[index.to_idx()].subtree = self.generation;
+            current = index.parent();
+        }
+    }
+
+    /// Mark the entries outside of the root rect as changed in a new generation
+    pub(crate) fn touch_outside(&mut self) {
+        self.generation += 1;
+        self.outside = self.generation;
+    }
+
+    /// The last generation in which the entries outside of the root rect changed
+    pub(crate) fn outside(&self) -> u64 {
+        self.outside
+    }
+
+    pub(crate) fn node(&self, index: Index) -> NodeGeneration {
+        self.nodes.get(index.to_idx()).copied().unwrap_or_default()
+    }
+}
+
+/// The nodes of a subtree that was read from a delta, in depth-first quadrant order. Nested nodes have no entries.
+type ReadNodes<T, ID> = Vec<(Index, Option<Vec<(IdentityPoint<ID>, T)>>)>;
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N> {
+    /// The generation of the last change to this tree. A new tree starts at generation `0`, and every change increases it.
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.dirty.generation
+    }
+
+    /// Write every node that changed after `since_generation` to `writer`, and return the current [`generation`] of this tree. This should be passed as `since_generation` on the next save.
+    ///
+    /// A generation of `0` writes the whole tree. `encode` is called for every written entry, and should write its identity and value.
+    ///
+    /// [`generation`]: #method.generation
+    ///
+    /// # Format
+    ///
+    /// All numbers are little endian.
+    ///
+    /// ```text
+    /// header:  b"WHQD", generation: u64, rect: left: f32, top: f32, right: f32, bottom: f32
+    /// record:  tag: u8, followed by
+    ///          0: nothing, this is the end of the delta
+    ///          1: a rewritten subtree: index: u32, followed by its nodes in depth-first quadrant order
+    ///          2: all entries outside of the root rect: count: u32, followed by the entries
+    /// node:    1: a nested node
+    ///          2: a leaf: count: u32, followed by the entries
+    /// entry:   x: f32, y: f32, followed by the bytes written by `encode`
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `writer` or `encode` fail.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a single bucket contains more than `u32::MAX` entries.
+    pub fn save_incremental<W: Write>(
+        &self,
+        writer: &mut W,
+        since_generation: u64,
+        mut encode: impl FnMut(&mut W, &ID, &T) -> io::Result<()>,
+    ) -> io::Result<u64> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&self.dirty.generation.to_le_bytes())?;
+        write_point(writer, self.rect.top_left())?;
+        write_point(writer, self.rect.bottom_right())?;
+
+        self.save_changed(writer, Index::ROOT, since_generation, &mut encode)?;
+
+        if self.dirty.outside > since_generation {
+            writer.write_all(&[RECORD_OUTSIDE])?;
+            writer.write_all(&to_u32(self.outside_of_range.len()).to_le_bytes())?;
+            for (identity, (value, point)) in &self.outside_of_range {
+                write_point(writer, *point)?;
+                encode(writer, identity, value)?;
+            }
+        }
+        writer.write_all(&[RECORD_END])?;
+        Ok(self.dirty.generation)
+    }
+
+    fn save_changed<W: Write>(
+        &self,
+        writer: &mut W,
+        index: Index,
+        since_generation: u64,
+        encode: &mut impl FnMut(&mut W, &ID, &T) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let generation = self.dirty.node(index);
+        if generation.subtree <= since_generation {
+            return Ok(());
+        }
+        if generation.rewritten > since_generation {
+            writer.write_all(&[RECORD_SUBTREE])?;
+            writer.write_all(&index.to_raw().to_le_bytes())?;
+            return self.save_subtree(writer, index, encode);
+        }
+        if let Some(Bucket::Nested) = self.items.get(index.to_idx()) {
+            for quadrant in Quadrant::all() {
+                self.save_changed(writer, index.child_at(quadrant), since_generation, encode)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn save_subtree<W: Write>(
+        &self,
+        writer: &mut W,
+        index: Index,
+        encode: &mut impl FnMut(&mut W, &ID, &T) -> io::Result<()>,
+    ) -> io::Result<()> {
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => {
+                writer.write_all(&[NODE_NESTED])?;
+                for quadrant in Quadrant::all() {
+                    self.save_subtree(writer, index.child_at(quadrant), encode)?;
+                }
+            }
+            bucket => {
+                let entries = match bucket {
+                    Some(Bucket::Owned(entries)) => entries.as_slice(),
+                    _ => &[],
+                };
+                writer.write_all(&[NODE_LEAF])?;
+                writer.write_all(&to_u32(entries.len()).to_le_bytes())?;
+                for (ip, value) in entries {
+                    write_point(writer, ip.point)?;
+                    encode(writer, &ip.identity, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + Clone,
+{
+    /// Apply a delta that was written by [`QuadTree::save_incremental`], and return the generation of the tree it was saved from.
+    ///
+    /// Deltas should be applied in the order they were saved, starting with a new tree that covers the same area. `decode` should read an identity and value that were written by `encode`.
+    ///
+    /// The whole delta is read before this tree is changed, so if an error is returned this tree is left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `reader` or `decode` fail, or an error of kind [`io::ErrorKind::InvalidData`] if the delta is malformed or was saved from a tree that covers a different area.
+    pub fn apply_incremental<R: Read>(
+        &mut self,
+        reader: &mut R,
+        mut decode: impl FnMut(&mut R) -> io::Result<(ID, T)>,
+    ) -> io::Result<u64> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a quad tree delta"));
+        }
+        let generation = u64::from_le_bytes(read_bytes(reader)?);
+        let rect = Rect::new(read_point(reader)?, read_point(reader)?);
+        if rect != self.rect {
+            return Err(invalid_data(
+                "delta was saved from a tree with a different area",
+            ));
+        }
+
+        let mut subtrees = Vec::new();
+        let mut outside = None;
+        loop {
+            match read_bytes(reader)? {
+                [RECORD_END] => break,
+                [RECORD_SUBTREE] => {
+                    let index = Index::from_raw(u32::from_le_bytes(read_bytes(reader)?))
+                        .ok_or_else(|| invalid_data("invalid node index"))?;
+                    let mut nodes = Vec::new();
+                    self.read_subtree(reader, index, &mut nodes, &mut decode)?;
+                    subtrees.push((index, nodes));
+                }
+                [RECORD_OUTSIDE] => {
+                    let entries = read_entries(reader, &mut decode)?;
+                    if entries.iter().any(|(ip, _)| self.rect.contains(ip.point)) {
+                        return Err(invalid_data("outside entry is inside of the tree"));
+                    }
+                    outside = Some(entries);
+                }
+                _ => return Err(invalid_data("unknown record")),
+            }
+        }
+
+        for (index, nodes) in subtrees {
+            self.replace_subtree(index, nodes)
+                .map_err(io::Error::other)?;
+        }
+        if let Some(entries) = outside {
+            self.replace_outside(entries);
+        }
+        Ok(generation)
+    }
+
+    fn read_subtree<R: Read>(
+        &self,
+        reader: &mut R,
+        index: Index,
+        nodes: &mut ReadNodes<T, ID>,
+        decode: &mut impl FnMut(&mut R) -> io::Result<(ID, T)>,
+    ) -> io::Result<()> {
+        match read_bytes(reader)? {
+            [NODE_NESTED] => {
+                let children = index
+                    .children()
+                    .ok_or_else(|| invalid_data("nested node is too deep"))?;
+                nodes.push((index, None));
+                for child in children {
+                    self.read_subtree(reader, child, nodes, decode)?;
+                }
+            }
+            [NODE_LEAF] => {
+                let entries = read_entries(reader, decode)?;
+                let rect = self.rect.get_index_rect(index);
+                if !entries.iter().all(|(ip, _)| rect.contains(ip.point)) {
+                    return Err(invalid_data("entry is outside of its node"));
+                }
+                nodes.push((index, Some(entries)));
+            }
+            _ => return Err(invalid_data("unknown node kind")),
+        }
+        Ok(())
+    }
+
+    fn replace_subtree(
+        &mut self,
+        index: Index,
+        nodes: ReadNodes<T, ID>,
+    ) -> Result<(), CorruptionError> {
+        self.clear_subtree(index);
+        for (node_index, entries) in nodes {
+            let bucket = ensure_index_valid(&mut self.items, node_index)?;
+            *bucket = if let Some(entries) = entries {
+                for (ip, _) in &entries {
+                    self.next_generation += 1;
+                    self.identity_to_point.insert(
+                        ip.identity.clone(),
+                        (ip.point, Some(node_index), self.next_generation),
+                    );
+                }
+                Bucket::Owned(entries.into_iter().collect())
+            } else {
+                Bucket::Nested
+            };
+        }
+        while self.items.len() > 1 && matches!(self.items.last(), Some(Bucket::Nested)) {
+            self.items.pop();
+        }
+        self.dirty.touch(index);
+        Ok(())
+    }
+
+    /// Mark every node in the subtree at `index` as stale, and forget the entries in it.
+    fn clear_subtree(&mut self, index: Index) {
+        let Some(bucket) = self.items.get_mut(index.to_idx()) else {
+            return;
+        };
+        if let Bucket::Owned(entries) = std::mem::replace(bucket, Bucket::Nested) {
+            for (ip, _) in entries {
+                // entries that moved to a node that was already replaced should be kept
+                if let Some((_, Some(current), _)) = self.identity_to_point.get(&ip.identity) {
+                    if *current == index {
+                        self.identity_to_point.remove(&ip.identity);
+                    }
+                }
+            }
+        }
+        if let Some(children) = index.children() {
+            for child in children {
+                self.clear_subtree(child);
+            }
+        }
+    }
+
+    fn replace_outside(&mut self, entries: Vec<(IdentityPoint<ID>, T)>) {
+        for identity in self.outside_of_range.take().into_keys() {
+            if let Some((_, None, _)) = self.identity_to_point.get(&identity) {
+                self.identity_to_point.remove(&identity);
+            }
+        }
+        for (ip, value) in entries {
+            let generation = self.new_generation();
+            self.identity_to_point
+                .insert(ip.identity.clone(), (ip.point, None, generation));
+            self.outside_of_range.insert(ip.identity, (value, ip.point));
+        }
+        self.dirty.touch_outside();
+    }
+}
+
+pub(crate) fn write_point(writer: &mut impl Write, point: Point) -> io::Result<()> {
+    writer.write_all(&point.x.raw().to_le_bytes())?;
+    writer.write_all(&point.y.raw().to_le_bytes())
+}
+
+pub(crate) fn read_bytes<const LEN: usize>(reader: &mut impl Read) -> io::Result<[u8; LEN]> {
+    let mut bytes = [0; LEN];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub(crate) fn read_point(reader: &mut impl Read) -> io::Result<Point> {
+    let mut read = || {
+        R32::try_new(f32::from_le_bytes(read_bytes(reader)?))
+            .ok_or_else(|| invalid_data("coordinate is not a real number"))
+    };
+    Ok(Point::new_noisy_float(read()?, read()?))
+}
+
+fn read_entries<R: Read, T, ID>(
+    reader: &mut R,
+    decode: &mut impl FnMut(&mut R) -> io::Result<(ID, T)>,
+) -> io::Result<Vec<(IdentityPoint<ID>, T)>> {
+    let count = u32::from_le_bytes(read_bytes(reader)?);
+    // `count` is not trusted, so don't preallocate with it
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let point = read_point(reader)?;
+        let (identity, value) = decode(reader)?;
+        entries.push((IdentityPoint { identity, point }, value));
+    }
+    Ok(entries)
+}
+
+pub(crate) fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
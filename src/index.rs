@@ -38,6 +38,7 @@ impl fmt::Debug for PrintIndex<'_> {
 }
 
 impl Index {
+    #[allow(clippy::useless_nonzero_new_unchecked)] // `Option::unwrap` is not const on older compilers
     pub const ROOT: Index = Index(unsafe { NonZeroU32::new_unchecked(1) });
 
     ///
@@ -68,6 +69,17 @@ impl Index {
     //     (((total_bits - leading_zeroes) - 1) / 2) as usize
     // }
 
+    /// The index of the node on the other side of every horizontal middle line, so every quadrant in the path is flipped vertically
+    pub(crate) const fn flip_vertical(self) -> Index {
+        let n = self.0.get();
+        // every pair of bits below the leading 1 is a quadrant with the vertical bit first, the leading 1 itself is on an even bit
+        let vertical_bits = (u32::MAX >> n.leading_zeros()) & 0xAAAA_AAAA;
+        match NonZeroU32::new(n ^ vertical_bits) {
+            Some(n) => Index(n),
+            None => self,
+        }
+    }
+
     pub const fn iter_from_root(self) -> FromRootIterator {
         FromRootIterator {
             idx: self.0.leading_zeros() + 1,
@@ -124,9 +136,26 @@ impl Index {
         (self.0.get() - offset - 1) as usize
     }
 
+    /// The raw value of this index, see the module documentation for the layout
+    pub(crate) const fn to_raw(self) -> u32 {
+        self.0.get()
+    }
+
+    /// Create an index from a value returned by [`Index::to_raw`]. Returns `None` if `n` is not a valid index.
+    pub(crate) fn from_raw(n: u32) -> Option<Index> {
+        // every valid index has an odd amount of significant bits: 1 for the root, and 2 for every layer below that
+        let n = NonZeroU32::new(n)?;
+        (n.leading_zeros() % 2 == 1).then_some(Index(n))
+    }
+
     pub(crate) fn child_at(self, quadrant: crate::point::Quadrant) -> Index {
         Index(NonZeroU32::new((self.0.get() << 2) | quadrant as u32).unwrap())
     }
+
+    /// The same as [`Index::child_at`], but returns `None` instead of panicking if this index is at the maximum depth
+    pub(crate) fn checked_child_at(self, quadrant: crate::point::Quadrant) -> Option<Index> {
+        (self.0.leading_zeros() >= 2).then(|| self.child_at(quadrant))
+    }
 }
 
 pub struct FromRootIterator {
@@ -227,6 +256,19 @@ fn validate_quadrants() {
     });
 }
 
+#[test]
+fn flip_vertical() {
+    permutations(6, |index, quadrants| {
+        let flipped = index.flip_vertical().iter_from_root().collect::<Vec<_>>();
+        let expected = quadrants
+            .iter()
+            .map(|quadrant| Quadrant::from_bits(*quadrant as u8 ^ 0b10))
+            .collect::<Vec<_>>();
+        assert_eq!(flipped, expected);
+        assert_eq!(index.flip_vertical().flip_vertical(), index);
+    });
+}
+
 #[test]
 fn validate_index() {
     let total_len: u32 = if cfg!(feature = "slow-tests") { 15 } else { 10 };
@@ -247,3 +289,58 @@ fn validate_index() {
         "{map:?} is not completely filled"
     );
 }
+
+/// Check every raw value for consistency between `from_raw`, `to_idx`, `parent`, `children` and `child_at`. `to_idx` is the position of the node in breadth-first order, so two nodes never share a bucket, and the children of a node are the 4 buckets at `4 * idx + 1`, which `ensure_index_valid` and the merges rely on. With the `slow-tests` feature this checks every `u32`, which includes every node of all 15 levels.
+#[test]
+#[allow(clippy::cast_possible_truncation)]
+fn index_arithmetic() {
+    let end = if cfg!(feature = "slow-tests") {
+        u32::MAX
+    } else {
+        1 << 19
+    };
+    for raw in 0..=end {
+        let Some(index) = Index::from_raw(raw) else {
+            // the leading 1 of a valid index is at an even bit
+            assert!(
+                raw == 0 || raw.ilog2() % 2 == 1,
+                "{raw:#b} should be a valid index"
+            );
+            continue;
+        };
+        assert_eq!(index.to_raw(), raw);
+        let depth = raw.ilog2() / 2;
+        assert_eq!(index.iter_from_root().len(), depth as usize);
+
+        // the nodes above this one, then the position in its own level
+        let above = (0..depth).map(|depth| 4usize.pow(depth)).sum::<usize>();
+        let idx = above + (raw - 4u32.pow(depth)) as usize;
+        assert_eq!(index.to_idx(), idx, "{index:?}");
+
+        match index.parent() {
+            Some(parent) => {
+                assert_eq!(parent.to_idx(), (idx - 1) / 4);
+                assert_eq!(
+                    parent.child_at(Quadrant::from_bits((raw & 0b11) as u8)),
+                    index
+                );
+            }
+            None => assert_eq!(index, Index::ROOT),
+        }
+
+        if let Some(children) = index.children() {
+            assert!(depth < 15);
+            for ((quadrant, child), n) in Quadrant::all().into_iter().zip(children).zip(1..) {
+                assert_eq!(index.child_at(quadrant), child);
+                assert_eq!(index.checked_child_at(quadrant), Some(child));
+                assert_eq!(child.to_idx(), 4 * idx + n);
+                assert_eq!(child.parent(), Some(index));
+            }
+        } else {
+            assert_eq!(depth, 15);
+            for quadrant in Quadrant::all() {
+                assert_eq!(index.checked_child_at(quadrant), None);
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! [`IntQuadTree`], a tree with `i32` positions, e.g. for entities on a tile grid, whose queries compare the exact integer positions.
+//!
+//! The nodes are the same as those of a [`QuadTree`], like for a [`QuadTree64`](crate::QuadTree64): every position is stored relative to the middle of the tree as a [`Point`], and the exact position is stored next to its value. A query collects the entries that may match with the `f32` positions, and then checks their exact positions with integer math, so an entry on the edge of a range or a rect is never lost or added by rounding.
+
+use crate::{IdentityPoint, Point, QuadTree, R32};
+
+/// A position of an [`IntQuadTree`]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IntPoint {
+    /// The horizontal coordinate
+    pub x: i32,
+    /// The vertical coordinate
+    pub y: i32,
+}
+
+impl IntPoint {
+    /// Create a point at `x, y`
+    #[must_use]
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// The squared distance between this point and `other`, which can not overflow
+    #[must_use]
+    pub fn distance_squared(self, other: Self) -> u128 {
+        let dx = u128::from((i64::from(self.x) - i64::from(other.x)).unsigned_abs());
+        let dy = u128::from((i64::from(self.y) - i64::from(other.y)).unsigned_abs());
+        dx * dx + dy * dy
+    }
+}
+
+/// A [`QuadTree`] with `i32` positions, see the [module documentation](self).
+///
+/// Only the most common methods are wrapped, because every method of a [`QuadTree`] that takes or returns a position needs an integer version here.
+#[derive(Clone, Debug)]
+pub struct IntQuadTree<T, ID, const N: usize> {
+    tree: QuadTree<(T, IntPoint), ID, N>,
+    /// The middle of the tree, which is `0, 0` for the inner tree
+    origin: (i64, i64),
+}
+
+impl<T, ID, const N: usize> IntQuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Create a new tree which covers the area between `top_left` and `bottom_right`, including its edges, see [`QuadTree::new`]
+    #[must_use]
+    #[allow(clippy::manual_midpoint)] // the sum of two `i32` can not overflow an `i64`
+    pub fn new(top_left: IntPoint, bottom_right: IntPoint) -> Self {
+        let origin = (
+            (i64::from(top_left.x) + i64::from(bottom_right.x)) / 2,
+            (i64::from(top_left.y) + i64::from(bottom_right.y)) / 2,
+        );
+        Self {
+            tree: QuadTree::new(to_local(origin, top_left), to_local(origin, bottom_right)),
+            origin,
+        }
+    }
+
+    /// The tree with the positions relative to the middle of this tree, for the queries that are not wrapped. The values are stored with their exact position.
+    #[must_use]
+    pub fn inner(&self) -> &QuadTree<(T, IntPoint), ID, N> {
+        &self.tree
+    }
+
+    /// The amount of entries in this tree, including the entries outside of its range
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns `true` if this tree has no entries, also none outside of its range
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Insert a new entry, see [`QuadTree::insert`]
+    pub fn insert(&mut self, identity: ID, point: IntPoint, value: T) {
+        self.tree.insert(
+            IdentityPoint {
+                identity,
+                point: to_local(self.origin, point),
+            },
+            (value, point),
+        );
+    }
+
+    /// Move an entry to `point`, see [`QuadTree::update`]
+    pub fn update(&mut self, identity: ID, point: IntPoint) -> bool {
+        self.tree
+            .update_point_and_value(identity, to_local(self.origin, point), |(_, exact)| {
+                *exact = point;
+            })
+    }
+
+    /// Remove an entry, see [`QuadTree::try_remove`]
+    pub fn try_remove(&mut self, identity: &ID) -> Option<(T, IntPoint)> {
+        self.tree.try_remove(identity).map(|(value, _)| value)
+    }
+
+    /// The position and value of the entry with the given identity, see [`QuadTree::get`]
+    #[must_use]
+    pub fn get(&self, identity: &ID) -> Option<(IntPoint, &T)> {
+        self.tree
+            .get(identity)
+            .map(|(_, (value, point))| (*point, value))
+    }
+
+    /// Find all entries with a distance of at most `range` away from point `center`, see [`QuadTree::find_range`]. The distances are computed with the exact positions.
+    #[allow(clippy::cast_precision_loss)] // the range is widened by more than its rounding
+    pub fn find_range<'a>(
+        &'a self,
+        center: IntPoint,
+        range: u32,
+        mut callback: impl FnMut(&ID, IntPoint, &'a T),
+    ) {
+        let range_squared = u128::from(range) * u128::from(range);
+        let local_center = to_local(self.origin, center);
+        // every position in range has coordinates of at most `reach`, so it is rounded by less than `EPSILON * reach`, and so is the center
+        let reach = local_center.x.raw().abs().max(local_center.y.raw().abs()) + range as f32;
+        let local_range = range as f32 * (1. + 4. * f32::EPSILON) + 4. * f32::EPSILON * reach;
+        self.tree.find_range(
+            local_center,
+            R32::new(local_range),
+            |identity, _, (value, point)| {
+                if point.distance_squared(center) <= range_squared {
+                    callback(identity, *point, value);
+                }
+            },
+        );
+    }
+
+    /// Find all entries inside of the rect between the opposite corners `a` and `b`, including the entries on its edges, see [`QuadTree::find_rect`]
+    pub fn find_rect<'a>(
+        &'a self,
+        a: IntPoint,
+        b: IntPoint,
+        mut callback: impl FnMut(&ID, IntPoint, &'a T),
+    ) {
+        let (left, right) = (a.x.min(b.x), a.x.max(b.x));
+        let (top, bottom) = (a.y.min(b.y), a.y.max(b.y));
+        let (top_left, bottom_right) = (
+            to_local(self.origin, IntPoint::new(left, top)),
+            to_local(self.origin, IntPoint::new(right, bottom)),
+        );
+        // the positions in the rect and its corners are rounded by at most half an ULP of its largest coordinate
+        let largest = [top_left.x, top_left.y, bottom_right.x, bottom_right.y]
+            .into_iter()
+            .map(|n| n.raw().abs())
+            .fold(0., f32::max);
+        let slack = R32::new(2. * f32::EPSILON * largest);
+        self.tree.find_rect(
+            top_left - slack,
+            bottom_right + slack,
+            |identity, _, (value, point)| {
+                if (left..=right).contains(&point.x) && (top..=bottom).contains(&point.y) {
+                    callback(identity, *point, value);
+                }
+            },
+        );
+    }
+}
+
+/// `point` relative to `origin` as a [`Point`] of the inner tree
+#[allow(clippy::cast_precision_loss)] // the queries are widened by more than the rounding
+fn to_local(origin: (i64, i64), point: IntPoint) -> Point {
+    Point::new(
+        (i64::from(point.x) - origin.0) as f32,
+        (i64::from(point.y) - origin.1) as f32,
+    )
+}
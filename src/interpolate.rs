@@ -0,0 +1,44 @@
+//! [`QuadTree::sample_idw`], which interpolates the values of the entries around a point, e.g. for sparse sensor readings.
+
+use crate::{Point, QuadTree, R32};
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// The inverse distance weighted average of the values of the entries within `radius` of `point`, where `value_of` gives the number that is averaged for a value.
+    ///
+    /// Every entry is weighted by `1 / distance²`, so closer entries count more. If entries are exactly at `point`, the result is the average of only those entries. Returns `None` if no entry is within `radius`.
+    ///
+    /// This is a single [`QuadTree::find_range`], and does not allocate.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn sample_idw(
+        &self,
+        point: Point,
+        radius: R32,
+        value_of: impl Fn(&T) -> f32,
+    ) -> Option<f32> {
+        // the sums are kept as f64, so many small weights are not lost next to a large one
+        let (mut weighted, mut weights) = (0.0_f64, 0.0_f64);
+        let (mut exact, mut exact_count) = (0.0_f64, 0_u32);
+        self.find_range(point, radius, |_, other, value| {
+            let value = f64::from(value_of(value));
+            let distance_squared = f64::from(point.distance_squared_to(other).raw());
+            if distance_squared == 0.0 {
+                exact += value;
+                exact_count += 1;
+            } else {
+                weighted += value / distance_squared;
+                weights += 1.0 / distance_squared;
+            }
+        });
+        if exact_count > 0 {
+            Some((exact / f64::from(exact_count)) as f32)
+        } else if weights > 0.0 {
+            Some((weighted / weights) as f32)
+        } else {
+            None
+        }
+    }
+}
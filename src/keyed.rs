@@ -0,0 +1,147 @@
+//! A secondary index over the values of a tree, which is kept up to date by every change to the tree.
+
+use crate::{FindRangeCtx, IdentityPoint, Point, QuadTree, R32};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A [`QuadTree`] with a secondary index on a key of its values, e.g. the kind of an entity.
+///
+/// The key of every value is extracted with the function that is passed to [`KeyedQuadTree::new`], and is extracted again whenever a value changes. Only the most common methods are wrapped, [`KeyedQuadTree::tree`] can be used for every other query.
+#[derive(Clone, Debug)]
+pub struct KeyedQuadTree<T, ID, K, const N: usize> {
+    tree: QuadTree<T, ID, N>,
+    key: fn(&T) -> K,
+    by_key: BTreeMap<K, BTreeSet<ID>>,
+}
+
+impl<T, ID, K, const N: usize> KeyedQuadTree<T, ID, K, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+    K: Ord,
+{
+    /// Create a new tree which covers the area between `top_left` and `bottom_right`, see [`QuadTree::new`], which indexes its values by `key`
+    #[must_use]
+    pub fn new(top_left: Point, bottom_right: Point, key: fn(&T) -> K) -> Self {
+        Self {
+            tree: QuadTree::new(top_left, bottom_right),
+            key,
+            by_key: BTreeMap::new(),
+        }
+    }
+
+    /// The tree without the index, for the queries that are not wrapped
+    #[must_use]
+    pub fn tree(&self) -> &QuadTree<T, ID, N> {
+        &self.tree
+    }
+
+    /// Insert a value `value` at the given `point`, see [`QuadTree::insert`]
+    pub fn insert(&mut self, point: IdentityPoint<ID>, value: T) {
+        if let Some(old_key) = self.key_of(&point.identity) {
+            self.unindex(&point.identity, &old_key);
+        }
+        self.index(point.identity.clone(), (self.key)(&value));
+        self.tree.insert(point, value);
+    }
+
+    /// Update the given identity to the new point, see [`QuadTree::update`].
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise
+    pub fn update(&mut self, identity: ID, point: Point) -> bool {
+        self.tree.update(identity, point)
+    }
+
+    /// Update the given identity to the new point, with the opportunity to update the value, see [`QuadTree::update_point_and_value`]. The key of the value is extracted again after `callback` returns.
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise
+    pub fn update_point_and_value(
+        &mut self,
+        identity: ID,
+        point: Point,
+        callback: impl FnOnce(&mut T),
+    ) -> bool {
+        let Some(old_key) = self.key_of(&identity) else {
+            return false;
+        };
+        let key = self.key;
+        let mut new_key = None;
+        let updated = self
+            .tree
+            .update_point_and_value(identity.clone(), point, |value| {
+                callback(value);
+                new_key = Some(key(value));
+            });
+        if let Some(new_key) = new_key.filter(|new_key| *new_key != old_key) {
+            self.unindex(&identity, &old_key);
+            self.index(identity, new_key);
+        }
+        updated
+    }
+
+    /// Try to remove the entry with the given identity from this tree. Will return the entry and its position if it's found, `None` otherwise.
+    pub fn try_remove(&mut self, identity: &ID) -> Option<(T, Point)> {
+        let (value, point) = self.tree.try_remove(identity)?;
+        self.unindex(identity, &(self.key)(&value));
+        Some((value, point))
+    }
+
+    /// All entries whose value has key `key`, ordered by their identity
+    pub fn find_by_key<'a>(&'a self, key: &K) -> impl Iterator<Item = (&'a ID, Point, &'a T)> {
+        self.by_key
+            .get(key)
+            .into_iter()
+            .flatten()
+            .filter_map(|identity| {
+                let (point, value) = self.tree.get(identity)?;
+                Some((identity, point, value))
+            })
+    }
+
+    /// Find all entries with key `key` and a distance less than `range` away from point `center`, see [`QuadTree::find_range`].
+    ///
+    /// If there are fewer entries with `key` than [`QuadTree::estimate_range_cost`] expects the query to compare, only the entries with `key` are compared. Otherwise the range is queried, and the entries with other keys are skipped.
+    pub fn find_range_with_key<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        key: &K,
+        mut callback: impl FnMut(&ID, Point, &'a T),
+    ) {
+        let Some(identities) = self.by_key.get(key) else {
+            return;
+        };
+        if identities.len() <= self.tree.estimate_range_cost(center, range).entries {
+            let ctx = FindRangeCtx::new(center, range);
+            for (identity, point, value) in self.find_by_key(key) {
+                if ctx.point_in_range(point) {
+                    callback(identity, point, value);
+                }
+            }
+        } else {
+            self.tree
+                .find_range(center, range, |identity, point, value| {
+                    if (self.key)(value) == *key {
+                        callback(identity, point, value);
+                    }
+                });
+        }
+    }
+
+    fn index(&mut self, identity: ID, key: K) {
+        self.by_key.entry(key).or_default().insert(identity);
+    }
+
+    fn key_of(&self, identity: &ID) -> Option<K> {
+        self.tree
+            .get(identity)
+            .map(|(_, value)| (self.key)(value))
+    }
+
+    fn unindex(&mut self, identity: &ID, key: &K) {
+        if let Some(identities) = self.by_key.get_mut(key) {
+            identities.remove(identity);
+            if identities.is_empty() {
+                self.by_key.remove(key);
+            }
+        }
+    }
+}
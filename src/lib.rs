@@ -4,50 +4,334 @@
 
 #![warn(clippy::pedantic, missing_docs)]
 
+mod aggregate;
+mod approx;
 mod bucket;
+mod bulk;
+mod coarse;
+mod coincident;
+mod compact;
+#[cfg(feature = "forest")]
+mod concurrent_forest;
+mod connected_regions;
+#[cfg(feature = "async")]
+mod cooperative;
+mod cost;
+mod curve;
+mod dedup;
+mod depth;
+mod entry;
+mod error;
+mod eviction;
+mod explain;
+mod farthest;
+mod flat;
+#[cfg(feature = "forest")]
+mod forest;
+mod free_space;
+#[cfg(feature = "frozen")]
+mod frozen;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod group;
+mod handles;
+mod hotspots;
+mod identity_filter;
+mod incremental;
+mod interpolate;
 mod index;
+mod integer;
+mod keyed;
+mod maintenance;
+mod migrate;
+mod nearest;
+mod neighbors;
+mod node;
+mod order;
+mod outside;
+mod parallel;
+#[cfg(feature = "pathfinding")]
+mod pathfinding;
 mod point;
+mod position_only;
+pub mod prelude;
+mod range_iter;
+mod recorder;
+#[cfg(feature = "region")]
+mod region;
+#[cfg(feature = "replication")]
+mod replication;
+mod retain;
+mod snapshot;
+mod space;
+mod spanning_tree;
 mod tests;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+#[cfg(feature = "tiles")]
+mod tiles;
+mod tombstone;
+mod transform;
+#[cfg(feature = "paranoid")]
+mod validate;
+mod weak;
+mod wide;
+mod zipped;
 
-use bucket::Bucket;
+use bucket::{Bucket, Entries};
+#[cfg(not(feature = "paranoid"))]
+use error::CorruptionError;
+use incremental::Dirty;
 use index::Index;
-use point::Rect;
+use outside::Outside;
+use point::clamped_r32;
 use smallvec::SmallVec;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::Infallible;
+use std::ops::ControlFlow;
 
+pub use aggregate::AggregateQuadTree;
 pub use bucket::IdentityPoint;
-pub use noisy_float::types::R32;
-pub use point::Point;
+pub use bulk::BulkBuilder;
+pub use coarse::CoarseHit;
+pub use compact::CompactQuadTree;
+#[cfg(feature = "forest")]
+pub use concurrent_forest::ConcurrentQuadForest;
+#[cfg(feature = "async")]
+pub use cooperative::{yield_now, YieldNow};
+pub use cost::CostEstimate;
+pub use dedup::dedup_by_identity;
+pub use depth::DepthUsage;
+pub use entry::EntryMut;
+pub use error::BoundsError;
+#[cfg(feature = "paranoid")]
+pub use error::CorruptionError;
+pub use eviction::EvictionPolicy;
+pub use explain::{NodeVisit, PruneReason, QueryTrace, TracedNode};
+#[cfg(feature = "ffi")]
+pub use flat::c_header;
+pub use flat::{FlatNode, FlatPoint, FlatRect, FlatTree, FLAT_NODE_LEAF, FLAT_NODE_NESTED};
+#[cfg(feature = "forest")]
+pub use forest::QuadForest;
+#[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+pub use frozen::Mmap;
+#[cfg(feature = "frozen")]
+pub use frozen::{FrozenQuadTree, Pod};
+#[cfg(feature = "gpu")]
+pub use gpu::{GpuBuffers, GpuEntry, GpuNode};
+pub use handles::{EntryId, HandleQuadTree};
+pub use identity_filter::IdentityFilter;
+pub use integer::{IntPoint, IntQuadTree};
+pub use keyed::KeyedQuadTree;
+pub use maintenance::MaintenanceBudget;
+pub use node::NodeId;
+pub use noisy_float::types::{r32, r64, R32, R64};
+pub use order::{PageEntries, SpatialCursor};
+pub use parallel::SubtreeView;
+pub use point::{Point, Quadrant, Rect, YAxis};
+pub use position_only::PointIndex;
+pub use range_iter::RangeIter;
+pub use recorder::{Operation, Recorder, Script};
+#[cfg(feature = "region")]
+pub use region::RegionQuadTree;
+#[cfg(feature = "replication")]
+pub use replication::EntryChange;
+pub use snapshot::{SnapshotView, TreeSnapshot};
+pub use space::{TypedPoint, TypedQuadTree};
+#[cfg(feature = "tiles")]
+pub use tiles::{quadkey_to_tile, tile_to_quadkey, TileCoord, TileEntries};
+pub use tombstone::TombstoneQuadTree;
+pub use weak::WeakEntryRef;
+pub use wide::{Point64, QuadTree64};
+pub use zipped::{ZippedNode, ZippedQuery};
 
 /// The quad tree implementation. This is generic over value `T`, with bucket size of `N`. Each item should have unique identity `ID`
 ///
 /// This tree will split when more than `n` items are inserted. Each split will have its own capacity of `N` items.
 ///
-/// `N` should be a value of 1 or larger. A good value will depend on the size of `T` and how evenly distributed the data points are, and will only matter in how much memory is allocated.
+/// `N` should be a value of 1 or larger, a tree with `N = 0` fails to compile. A good value will depend on the size of `T` and how evenly distributed the data points are, and will only matter in how much memory is allocated.
 ///
 /// A good starting value for `N` is 4.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 pub struct QuadTree<T, ID, const N: usize> {
     rect: point::Rect,
     items: Vec<Bucket<T, ID, N>>,
-    outside_of_range: BTreeMap<ID, (T, Point)>,
-    identity_to_point: BTreeMap<ID, (Point, Option<Index>)>,
+    outside_of_range: Outside<T, ID>,
+    /// The position, the leaf and the generation of every entry
+    identity_to_point: BTreeMap<ID, (Point, Option<Index>, u64)>,
+    dirty: Dirty,
+    y_axis: YAxis,
+    /// The nodes that could be merged, if merges are deferred to [`QuadTree::maintenance`]
+    pending_merges: Option<BTreeSet<Index>>,
+    /// The last generation that an inserted or moved entry got, see [`WeakEntryRef`]
+    next_generation: u64,
+    /// Nodes whose width and height are both smaller than this are not split, see [`QuadTree::set_min_cell_extent`]
+    min_cell_extent: R32,
+    /// Applied to every position that is inserted or updated, see [`QuadTree::set_position_filter`]
+    position_filter: Option<fn(Point) -> Point>,
 }
 
+// the generations of the entries are only compared with the tree that handed them out, so they do not make two trees different
+// the position filter is not compared either, function pointers are not reliably equal even to themselves
+impl<T: PartialEq, ID: PartialEq, const N: usize> PartialEq for QuadTree<T, ID, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rect == other.rect
+            && self.items == other.items
+            && self.outside_of_range == other.outside_of_range
+            && self.identity_to_point.len() == other.identity_to_point.len()
+            && self
+                .identity_to_point
+                .iter()
+                .zip(&other.identity_to_point)
+                .all(|((a, (a_point, a_index, _)), (b, (b_point, b_index, _)))| {
+                    a == b && a_point == b_point && a_index == b_index
+                })
+            && self.dirty == other.dirty
+            && self.y_axis == other.y_axis
+            && self.pending_merges == other.pending_merges
+            && self.min_cell_extent == other.min_cell_extent
+    }
+}
+
+impl<T: Eq, ID: Eq, const N: usize> Eq for QuadTree<T, ID, N> {}
+
 impl<T, ID, const N: usize> QuadTree<T, ID, N>
 where
-    ID: std::cmp::Ord + std::fmt::Display + Clone + std::cmp::PartialEq<ID>,
+    ID: std::cmp::Ord + std::fmt::Debug + Clone + std::cmp::PartialEq<ID>,
 {
+    /// Evaluated by [`QuadTree::new`], which every tree is created with, so a tree with a bucket size of 0 is a compile error instead of a tree that splits forever
+    const VALID_BUCKET_SIZE: () = assert!(N >= 1, "The bucket size N should be 1 or larger");
+
+    /// The default [`QuadTree::min_cell_extent`], in ULPs of the largest coordinate of the tree
+    const DEFAULT_MIN_CELL_ULPS: f32 = 4.;
+
     /// Create a new [`QuadTree`] which covers the area between `top_left` and `bottom_right`. Points outside of this range will be inserted in a slow [`BTreeMap`], so choose this value carefully.
     ///
-    /// When dealing with a perfect rectangle around point `0, 0`, you can use `sized_around_origin` instead
+    /// When dealing with a perfect rectangle around point `0, 0`, you can use `sized_around_origin` instead. Use [`QuadTree::try_new`] to check that `top_left` is above and to the left of `bottom_right`.
+    ///
+    /// `top_left` and `bottom_right` can be the same point, the tree then holds every entry at that point in a single bucket that is never split. A tree with a width or height of 0 is only split along its other axis. A node is never split below the maximum depth of 15 levels, when it is smaller than [`QuadTree::min_cell_extent`], or when one of its children would cover the same area as the node itself because its middle is rounded onto one of its edges; such a node holds more than `N` entries instead.
     #[must_use]
     pub fn new(top_left: Point, bottom_right: Point) -> Self {
+        let () = Self::VALID_BUCKET_SIZE;
+        let rect = point::Rect::new(top_left, bottom_right);
         Self {
-            rect: point::Rect::new(top_left, bottom_right),
+            rect,
             items: vec![Bucket::Owned(SmallVec::new_const())],
-            outside_of_range: BTreeMap::new(),
+            outside_of_range: Outside::default(),
             identity_to_point: BTreeMap::new(),
+            dirty: Dirty::default(),
+            y_axis: YAxis::Down,
+            pending_merges: None,
+            next_generation: 0,
+            min_cell_extent: rect.ulps(Self::DEFAULT_MIN_CELL_ULPS),
+            position_filter: None,
+        }
+    }
+
+    /// Create a new [`QuadTree`] which covers the area between `top_left` and `bottom_right` in a coordinate system where the y axis points in the direction of `y_axis`, see [`QuadTree::new`].
+    ///
+    /// With [`YAxis::Up`], `top_left` has the highest y value, and the [`Quadrant`]s returned by [`QuadTree::path_of`] and [`QuadTree::node_of`] are named the same way: [`Quadrant::TopLeft`] is the child with the highest y values. The [`NodeId`]s of such a tree should only be passed to a tree with the same y axis.
+    #[must_use]
+    pub fn with_y_axis(top_left: Point, bottom_right: Point, y_axis: YAxis) -> Self {
+        let mut tree = match y_axis {
+            YAxis::Down => Self::new(top_left, bottom_right),
+            YAxis::Up => Self::new(
+                Point::new_noisy_float(top_left.x, bottom_right.y),
+                Point::new_noisy_float(bottom_right.x, top_left.y),
+            ),
+        };
+        tree.y_axis = y_axis;
+        tree
+    }
+
+    /// Stop splitting nodes whose width and height are both smaller than `extent`, a node that reaches this size holds more than `N` entries instead. Nodes that are already split are not merged by this.
+    ///
+    /// By default this is 4 ULPs of the largest coordinate of the tree, close to the size at which the middle of a node is rounded onto one of its edges. A larger value keeps deep clusters of entries, e.g. many entries that are a millimeter apart in a tree that covers kilometers, in a few large leaves.
+    pub fn set_min_cell_extent(&mut self, extent: R32) {
+        self.min_cell_extent = extent;
+    }
+
+    /// The size below which nodes are not split, see [`QuadTree::set_min_cell_extent`]
+    #[must_use]
+    pub fn min_cell_extent(&self) -> R32 {
+        self.min_cell_extent
+    }
+
+    /// Pass every position that is inserted or updated through `filter` first, e.g. to snap entries to a grid, to clamp them to the bounds of this tree, or to round them to centimeters. The filtered position is the one that is stored, returned by [`QuadTree::get`] and compared by every query, so callers do not have to round the same way everywhere.
+    ///
+    /// This applies to [`QuadTree::insert`], [`QuadTree::update`], [`QuadTree::upsert`], [`QuadTree::translate_group`], [`QuadTree::entry_mut`] and everything that is built on them, but not to [`QuadTree::translate_all`], [`QuadTree::scale_all`] and [`QuadTree::recenter`], which move every entry at once. Entries that are already in this tree are not moved when the filter is set. `None` removes the filter.
+    pub fn set_position_filter(&mut self, filter: Option<fn(Point) -> Point>) {
+        self.position_filter = filter;
+    }
+
+    /// The filter for inserted and updated positions, see [`QuadTree::set_position_filter`]
+    #[must_use]
+    pub fn position_filter(&self) -> Option<fn(Point) -> Point> {
+        self.position_filter
+    }
+
+    /// `point` after the position filter of this tree, see [`QuadTree::set_position_filter`]
+    pub(crate) fn filter_position(&self, point: Point) -> Point {
+        self.position_filter.map_or(point, |filter| filter(point))
+    }
+
+    /// The direction of the y axis of this tree, see [`QuadTree::with_y_axis`]
+    #[must_use]
+    pub fn y_axis(&self) -> YAxis {
+        self.y_axis
+    }
+
+    /// The top left and bottom right corners of the area that this tree covers, in the same order as they were given to [`QuadTree::new`] or [`QuadTree::with_y_axis`]
+    #[must_use]
+    pub fn bounds(&self) -> (Point, Point) {
+        let (top_left, bottom_right) = (self.rect.top_left(), self.rect.bottom_right());
+        match self.y_axis {
+            YAxis::Down => (top_left, bottom_right),
+            YAxis::Up => (
+                Point::new_noisy_float(top_left.x, bottom_right.y),
+                Point::new_noisy_float(bottom_right.x, top_left.y),
+            ),
+        }
+    }
+
+    /// Returns `true` if `point` lies inside of the area that this tree covers, including its edges. An entry at any other point is stored in a slower [`BTreeMap`] outside of the tree.
+    #[must_use]
+    pub fn covers(&self, point: Point) -> bool {
+        self.rect.contains(point)
+    }
+
+    /// The amount of entries in this tree, including the entries outside of its range
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.identity_to_point.len()
+    }
+
+    /// Returns `true` if this tree has no entries, also none outside of its range
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.identity_to_point.is_empty()
+    }
+
+    /// Returns `true` if this tree has an entry with the given identity, also if the entry is outside of the range of this tree. See [`QuadTree::identity_filter`] to check many identities that are mostly absent.
+    #[doc(alias = "contains")]
+    #[must_use]
+    pub fn contains_identity(&self, identity: &ID) -> bool {
+        self.identity_to_point.contains_key(identity)
+    }
+
+    /// Create a new [`QuadTree`] which covers the area between `top_left` and `bottom_right`, see [`QuadTree::new`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`BoundsError::NotOrdered`] if `top_left` is not strictly above and to the left of `bottom_right`. [`QuadTree::new`] would create a tree that puts every entry in the wrong quadrant.
+    pub fn try_new(top_left: Point, bottom_right: Point) -> Result<Self, BoundsError> {
+        if top_left.x < bottom_right.x && top_left.y < bottom_right.y {
+            Ok(Self::new(top_left, bottom_right))
+        } else {
+            Err(BoundsError::NotOrdered {
+                top_left,
+                bottom_right,
+            })
         }
     }
 
@@ -61,52 +345,14 @@ where
 
     /// Insert a value `value` at the given `point`. If the existing `point.identity` already exists, it will be updated instead.
     pub fn insert(&mut self, point: IdentityPoint<ID>, value: T) {
-        if let Some((_, old_index)) = self.identity_to_point.remove(&point.identity) {
-            let mut value = Some(value);
-            let new_index =
-                self.update_inner(&point.identity, point.point, old_index, |old_value, idx| {
-                    if let Some(value) = value.take() {
-                        *old_value = value;
-                    }
-                    idx
-                });
-            self.identity_to_point
-                .insert(point.identity, (point.point, new_index));
-            return;
-        }
-        if !self.rect.contains(point.point) {
-            self.outside_of_range
-                .insert(point.identity.clone(), (value, point.point));
-            self.identity_to_point
-                .insert(point.identity, (point.point, None));
-            return;
-        }
-        let index = Self::find_bucket_mut(
-            &mut self.items,
-            &mut self.identity_to_point,
-            self.rect,
-            point.point,
-            true,
-            |bucket, index| {
-                bucket.push((point.clone(), value));
-                index
-            },
-        );
-        self.identity_to_point
-            .insert(point.identity, (point.point, Some(index)));
+        expect_consistent(self.insert_entry(point, value));
     }
 
     /// Update the given identity to the new point.
     ///
     /// Will return `true` if the identity was found and updated, `false` otherwise
     pub fn update(&mut self, identity: ID, point: Point) -> bool {
-        if let Some((_, maybe_index)) = self.identity_to_point.remove(&identity) {
-            let new_idx = self.update_inner(&identity, point, maybe_index, |_, new_idx| new_idx);
-            self.identity_to_point.insert(identity, (point, new_idx));
-            true
-        } else {
-            false
-        }
+        expect_consistent(self.update_entry(identity, point, |_| {}))
     }
 
     /// Update the given identity to the new point, with the opportunity to update the value
@@ -118,15 +364,25 @@ where
         point: Point,
         callback: impl FnOnce(&mut T),
     ) -> bool {
-        if let Some((_, maybe_index)) = self.identity_to_point.remove(&identity) {
-            let new_idx = self.update_inner(&identity, point, maybe_index, |val, new_idx| {
-                callback(val);
-                new_idx
-            });
-            self.identity_to_point.insert(identity, (point, new_idx));
-            true
+        expect_consistent(self.update_entry(identity, point, callback))
+    }
+
+    /// Insert or update the entry with the given identity at `point`.
+    ///
+    /// If the identity does not exist yet, `make` is called to create its value. Otherwise the entry is moved to `point`, and `on_existing` is called with its value and its old position.
+    pub fn upsert(
+        &mut self,
+        identity: ID,
+        point: Point,
+        make: impl FnOnce() -> T,
+        on_existing: impl FnOnce(&mut T, Point),
+    ) {
+        if let Some(&(old_point, _, _)) = self.identity_to_point.get(&identity) {
+            expect_consistent(self.update_entry(identity, point, |value| {
+                on_existing(value, old_point);
+            }));
         } else {
-            false
+            expect_consistent(self.insert_entry(IdentityPoint { identity, point }, make()));
         }
     }
 
@@ -137,68 +393,570 @@ where
     /// Will panic if the identity is not found in this quad tree.
     pub fn remove(&mut self, identity: &ID) -> (T, Point) {
         self.try_remove(identity)
-            .unwrap_or_else(|| panic!("Identity {identity} not found"))
+            .unwrap_or_else(|| panic!("Identity {identity:?} not found"))
     }
 
     /// Try to remove the entry with the given identity from this quad tree. Will return the entry and the last know position if it's found, `None` otherwise.
-    #[allow(clippy::missing_panics_doc)] // should not panic unless the internal state is wrong
     pub fn try_remove(&mut self, identity: &ID) -> Option<(T, Point)> {
-        let (point, index) = self.identity_to_point.remove(identity)?;
-        if let Some(index) = index {
-            let result = self.items[index.to_idx()]
-                .as_owned_mut()
-                .remove_by_identity(identity);
+        expect_consistent(self.remove_entry(identity))
+    }
 
-            if let Some(parent) = index.parent() {
-                self.try_merge(parent);
-            }
-            Some((result, point))
-        } else {
-            self.outside_of_range.remove(identity)
-        }
+    /// The same as [`insert`], but returns an error instead of panicking when the internal state of this tree is inconsistent.
+    ///
+    /// [`insert`]: #method.insert
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if this tree is corrupted.
+    #[cfg(feature = "paranoid")]
+    pub fn checked_insert(
+        &mut self,
+        point: IdentityPoint<ID>,
+        value: T,
+    ) -> Result<(), CorruptionError> {
+        self.insert_entry(point, value)
+    }
+
+    /// The same as [`update_point_and_value`], but returns an error instead of panicking when the internal state of this tree is inconsistent.
+    ///
+    /// [`update_point_and_value`]: #method.update_point_and_value
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if this tree is corrupted.
+    #[cfg(feature = "paranoid")]
+    pub fn checked_update(
+        &mut self,
+        identity: ID,
+        point: Point,
+        callback: impl FnOnce(&mut T),
+    ) -> Result<bool, CorruptionError> {
+        self.update_entry(identity, point, callback)
+    }
+
+    /// The same as [`try_remove`], but returns an error instead of panicking when the internal state of this tree is inconsistent.
+    ///
+    /// [`try_remove`]: #method.try_remove
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if this tree is corrupted.
+    #[cfg(feature = "paranoid")]
+    pub fn checked_remove(&mut self, identity: &ID) -> Result<Option<(T, Point)>, CorruptionError> {
+        self.remove_entry(identity)
     }
 
     /// Find all entries with a distance less than `range` away from point `center`. Each entry found will be passed to `callback`.
     ///
-    /// `point` can be a point outside of this [`QuadTree`].
+    /// `point` can be a point outside of this [`QuadTree`]. Entries that are exactly `range` away are included, see [`find_range_with_boundary`] to change this.
+    ///
+    /// This never panics by itself, also not for ranges and positions close to the largest float, only `callback` can panic. It also never allocates: the nodes are visited recursively, which is at most 16 calls deep.
+    ///
+    /// [`find_range_with_boundary`]: #method.find_range_with_boundary
     pub fn find_range<'a>(
         &'a self,
         center: Point,
         range: R32,
+        callback: impl FnMut(&ID, Point, &'a T),
+    ) {
+        self.find_range_with_boundary(center, range, RangeBoundary::Inclusive, callback);
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, like [`find_range`], but only pass their identity and position to `callback`.
+    ///
+    /// The values are never read, e.g. for a broad phase that only needs the identities, and looks up the values of the few entries that are left later.
+    ///
+    /// [`find_range`]: #method.find_range
+    pub fn find_range_ids(&self, center: Point, range: R32, mut callback: impl FnMut(&ID, Point)) {
+        self.find_range(center, range, |identity, point, _| callback(identity, point));
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, like [`find_range`], and append their identities, positions and values to three parallel buffers, e.g. for SIMD processing of the results.
+    ///
+    /// The buffers are not cleared first, so they can be reused every frame by clearing them, which keeps their memory. `ids`, `points` and `values` get the same amount of new items, in the same order.
+    ///
+    /// [`find_range`]: #method.find_range
+    pub fn find_range_soa<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        ids: &mut Vec<ID>,
+        points: &mut Vec<Point>,
+        values: &mut Vec<&'a T>,
+    ) {
+        self.find_range(center, range, |identity, point, value| {
+            ids.push(identity.clone());
+            points.push(point);
+            values.push(value);
+        });
+    }
+
+    /// Find all entries within `range` of point `center`, where `boundary` decides what happens to entries on the edge of the range. Each entry found will be passed to `callback`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `boundary` is a negative [`RangeBoundary::Tolerance`].
+    pub fn find_range_with_boundary<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        boundary: RangeBoundary,
+        callback: impl FnMut(&ID, Point, &'a T),
+    ) {
+        self.find_range_with_options(
+            center,
+            range,
+            RangeOptions::new().boundary(boundary),
+            callback,
+        );
+    }
+
+    /// Find all entries within `range` of point `center`, with `options` to change which entries are found. Each entry found will be passed to `callback`. This does not allocate, see [`QuadTree::find_range`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the boundary of `options` is a negative [`RangeBoundary::Tolerance`].
+    pub fn find_range_with_options<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        options: RangeOptions,
         mut callback: impl FnMut(&ID, Point, &'a T),
     ) {
-        let ctx = FindRangeCtx::new(center, range);
+        let ctx = FindRangeCtx::with_boundary(center, range, options.boundary);
+        let _: ControlFlow<Infallible> = self.find_range_ctx(
+            &ctx,
+            options.include_out_of_range,
+            |identity, point, value, _| {
+                callback(identity, point, value);
+                ControlFlow::Continue(())
+            },
+        );
+    }
 
-        self.find_range_inner(self.rect, Index::ROOT, &ctx, &mut callback);
+    /// Find all entries with a distance less than `range` away from point `center`, like [`find_range`], until `callback` returns [`ControlFlow::Break`]. The rest of the entries are skipped then, e.g. to check if there is any obstacle in range.
+    ///
+    /// Returns the break value of `callback`, or [`ControlFlow::Continue`] if `callback` never broke.
+    ///
+    /// [`find_range`]: #method.find_range
+    pub fn find_range_until<'a, B>(
+        &'a self,
+        center: Point,
+        range: R32,
+        mut callback: impl FnMut(&ID, Point, &'a T) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        self.find_range_ctx(
+            &FindRangeCtx::new(center, range),
+            true,
+            |identity, point, value, _| callback(identity, point, value),
+        )
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, like [`find_range`], and pass each of them to `callback` with the rect of the leaf that it is in, e.g. to key a cache on the cell that an entry is in. The rect is `None` for the entries outside of this tree.
+    ///
+    /// The rect is the same as [`QuadTree::node_rect`] of the leaf, and changes when the leaf is split or merged.
+    ///
+    /// [`find_range`]: #method.find_range
+    pub fn find_range_with_leaf<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        mut callback: impl FnMut(&ID, Point, &'a T, Option<Rect>),
+    ) {
+        let _: ControlFlow<Infallible> = self.find_range_ctx(
+            &FindRangeCtx::new(center, range),
+            true,
+            |identity, point, value, leaf| {
+                callback(identity, point, value, leaf);
+                ControlFlow::Continue(())
+            },
+        );
+    }
+
+    /// Pass every entry in the range of `ctx` and the rect of its leaf to `callback` until it breaks, including the entries outside of this tree if `include_out_of_range` is set
+    fn find_range_ctx<'a, B>(
+        &'a self,
+        ctx: &FindRangeCtx,
+        include_out_of_range: bool,
+        mut callback: impl FnMut(&ID, Point, &'a T, Option<Rect>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        self.find_range_inner(self.rect, Index::ROOT, ctx, &mut callback)?;
 
+        // most queries are far away from every entry outside of this tree
+        if !include_out_of_range || !self.outside_of_range.touches(ctx.full_rect) {
+            return ControlFlow::Continue(());
+        }
         for (ip, (value, point)) in &self.outside_of_range {
             if ctx.point_in_range(*point) {
-                callback(ip, *point, value);
+                callback(ip, *point, value, None)?;
             }
         }
+        ControlFlow::Continue(())
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, like [`find_range`], and pass each of them to `callback` with mutable access to its value, e.g. to damage every unit in an explosion without looking each of them up again.
+    ///
+    /// Positions can not be changed by `callback`, so the layout of the tree stays the same. Every leaf with an entry in range is marked as changed for [`QuadTree::save_incremental`], whether or not `callback` changed a value.
+    ///
+    /// [`find_range`]: #method.find_range
+    pub fn find_range_mut(
+        &mut self,
+        center: Point,
+        range: R32,
+        mut callback: impl FnMut(&ID, Point, &mut T),
+    ) {
+        let ctx = FindRangeCtx::new(center, range);
+        self.find_range_mut_inner(self.rect, Index::ROOT, &ctx, &mut callback);
+        if !self.outside_of_range.touches(ctx.full_rect) {
+            return;
+        }
+        let mut found = false;
+        for (ip, point, value) in self.outside_of_range.iter_mut() {
+            if ctx.point_in_range(point) {
+                callback(ip, point, value);
+                found = true;
+            }
+        }
+        if found {
+            self.dirty.touch_outside();
+        }
+    }
+
+    /// Find all entries inside of the rect between `top_left` and `bottom_right`, including the entries on its edges and the entries outside of this tree. Each entry found will be passed to `callback`.
+    ///
+    /// The corners can be any two opposite corners of the rect, see [`Rect::from_corners`], so the same call works for a tree with [`YAxis::Up`]. Like [`find_range`], this never allocates.
+    ///
+    /// [`find_range`]: #method.find_range
+    pub fn find_rect<'a>(
+        &'a self,
+        top_left: Point,
+        bottom_right: Point,
+        mut callback: impl FnMut(&ID, Point, &'a T),
+    ) {
+        self.find_rect_with_leaf(top_left, bottom_right, |identity, point, value, _| {
+            callback(identity, point, value);
+        });
+    }
+
+    /// Find all entries inside of the rect between `top_left` and `bottom_right`, like [`find_rect`], and pass each of them to `callback` with the rect of the leaf that it is in. The rect is `None` for the entries outside of this tree.
+    ///
+    /// [`find_rect`]: #method.find_rect
+    pub fn find_rect_with_leaf<'a>(
+        &'a self,
+        top_left: Point,
+        bottom_right: Point,
+        mut callback: impl FnMut(&ID, Point, &'a T, Option<Rect>),
+    ) {
+        let area = Rect::from_corners(top_left, bottom_right);
+        self.find_rect_inner(self.rect, Index::ROOT, area, &mut callback);
+        if !self.outside_of_range.touches(area) {
+            return;
+        }
+        for (ip, (value, point)) in &self.outside_of_range {
+            if area.contains(*point) {
+                callback(ip, *point, value, None);
+            }
+        }
+    }
+
+    /// Iterate over all entries that are outside of the rect of this tree, sorted by identity
+    #[must_use]
+    pub fn iter_out_of_range(&self) -> impl ExactSizeIterator<Item = (&ID, Point, &T)> {
+        self.outside_of_range
+            .iter()
+            .map(|(identity, (value, point))| (identity, *point, value))
+    }
+
+    /// Iterate over all entries, leaf by leaf, followed by the entries outside of this tree sorted by identity. See [`QuadTree::iter_morton`] for an order that keeps entries that are close together close together.
+    pub fn iter(&self) -> impl Iterator<Item = (&ID, Point, &T)> {
+        // the nodes below a leaf are stale and never have entries, so the buckets can be walked in the order of the vec
+        self.items
+            .iter()
+            .filter_map(|bucket| match bucket {
+                Bucket::Owned(entries) => Some(entries),
+                Bucket::Nested => None,
+            })
+            .flatten()
+            .map(|(ip, value)| (&ip.identity, ip.point, value))
+            .chain(self.iter_out_of_range())
+    }
+
+    /// Iterate over all entries in the same order as [`QuadTree::iter`], with mutable access to their values.
+    ///
+    /// Positions can not be changed here, see [`QuadTree::update`]. Every leaf with entries is marked as changed for [`QuadTree::save_incremental`], whether or not a value is changed.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&ID, Point, &mut T)> {
+        self.touch_entries_below(Index::ROOT);
+        if !self.outside_of_range.is_empty() {
+            self.dirty.touch_outside();
+        }
+        self.items
+            .iter_mut()
+            .filter_map(|bucket| match bucket {
+                Bucket::Owned(entries) => Some(entries),
+                Bucket::Nested => None,
+            })
+            .flatten()
+            .map(|(ip, value)| (&ip.identity, ip.point, value))
+            .chain(self.outside_of_range.iter_mut())
+    }
+
+    /// Mark every leaf with entries below the node at `index` as changed
+    fn touch_entries_below(&mut self, index: Index) {
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => {
+                for quadrant in Quadrant::all() {
+                    if let Some(child) = index.checked_child_at(quadrant) {
+                        self.touch_entries_below(child);
+                    }
+                }
+            }
+            Some(Bucket::Owned(entries)) if !entries.is_empty() => self.dirty.touch(index),
+            _ => {}
+        }
+    }
+}
+
+impl<T, ID, const N: usize> IntoIterator for QuadTree<T, ID, N> {
+    type Item = (ID, Point, T);
+    type IntoIter = std::vec::IntoIter<(ID, Point, T)>;
+
+    /// The entries in the same order as [`QuadTree::iter`]
+    fn into_iter(self) -> Self::IntoIter {
+        let mut entries = Vec::with_capacity(self.identity_to_point.len());
+        for bucket in self.items {
+            if let Bucket::Owned(bucket) = bucket {
+                entries.extend(
+                    bucket
+                        .into_iter()
+                        .map(|(ip, value)| (ip.identity, ip.point, value)),
+                );
+            }
+        }
+        entries.extend(
+            self.outside_of_range
+                .into_iter()
+                .map(|(identity, (value, point))| (identity, point, value)),
+        );
+        entries.into_iter()
     }
 }
 
 impl<T, ID, const N: usize> QuadTree<T, ID, N>
 where
-    ID: std::cmp::Ord + std::fmt::Display + Clone,
+    ID: std::cmp::Ord + std::fmt::Debug + Clone,
 {
+    /// The depth of the leaf that contains the entry with the given identity, where the root has depth 0.
+    ///
+    /// Returns `None` if the identity is not found, or if the entry is outside of the range of this tree.
+    #[must_use]
+    pub fn depth_of(&self, identity: &ID) -> Option<usize> {
+        self.path_of(identity).map(|path| path.len())
+    }
+
+    /// The quadrants that lead from the root to the leaf that contains the entry with the given identity.
+    ///
+    /// Entries with the same path are in the same leaf, so the path can be used as a key for the area that an entry is in. The path changes when the tree is split or merged.
+    ///
+    /// Returns `None` if the identity is not found, or if the entry is outside of the range of this tree.
+    #[must_use]
+    pub fn path_of(&self, identity: &ID) -> Option<impl ExactSizeIterator<Item = Quadrant>> {
+        let (_, index, _) = self.identity_to_point.get(identity)?;
+        index.map(|index| self.public_index(index).iter_from_root())
+    }
+
+    /// The position and value of the entry with the given identity, also if the entry is outside of the range of this tree. Returns `None` if the identity is not found.
+    #[must_use]
+    pub fn get(&self, identity: &ID) -> Option<(Point, &T)> {
+        let (point, index, _) = self.identity_to_point.get(identity)?;
+        if let Some(index) = index {
+            let Some(Bucket::Owned(entries)) = self.items.get(index.to_idx()) else { return None };
+            entries
+                .iter()
+                .find(|(ip, _)| &ip.identity == identity)
+                .map(|(_, value)| (*point, value))
+        } else {
+            self.outside_of_range
+                .get(identity)
+                .map(|(value, point)| (*point, value))
+        }
+    }
+
+    /// The position of the entry with the given identity, and mutable access to its value. Returns `None` if the identity is not found.
+    ///
+    /// The position can not be changed here, see [`QuadTree::update`]. The leaf of the entry is marked as changed for [`QuadTree::save_incremental`], whether or not the value is changed.
+    pub fn get_mut(&mut self, identity: &ID) -> Option<(Point, &mut T)> {
+        let (point, index, _) = *self.identity_to_point.get(identity)?;
+        let value = if let Some(index) = index {
+            let Some(Bucket::Owned(entries)) = self.items.get_mut(index.to_idx()) else { return None };
+            let (_, value) = entries
+                .iter_mut()
+                .find(|(ip, _)| &ip.identity == identity)?;
+            self.dirty.touch(index);
+            value
+        } else {
+            let value = self.outside_of_range.value_mut(identity)?;
+            self.dirty.touch_outside();
+            value
+        };
+        Some((point, value))
+    }
+
+    fn insert_entry(
+        &mut self,
+        mut point: IdentityPoint<ID>,
+        value: T,
+    ) -> Result<(), CorruptionError> {
+        point.point = self.filter_position(point.point);
+        let generation = self.new_generation();
+        // an identity that is inserted again at the same point only replaces its value, without touching the map twice
+        if let Some((old_point, index, old_generation)) =
+            self.identity_to_point.get_mut(&point.identity)
+        {
+            if *old_point == point.point {
+                *old_generation = generation;
+                if let Some(index) = *index {
+                    *bucket_mut(&mut self.items, index)?
+                        .as_owned_mut()?
+                        .value_by_identity(&point.identity)? = value;
+                    self.dirty.touch(index);
+                } else {
+                    let old_value = self.outside_of_range.value_mut(&point.identity).ok_or(
+                        CorruptionError::new("entry is missing from outside of range"),
+                    )?;
+                    *old_value = value;
+                    self.dirty.touch_outside();
+                }
+                return Ok(());
+            }
+        }
+        if let Some((_, old_index, _)) = self.identity_to_point.remove(&point.identity) {
+            let mut value = Some(value);
+            let new_index =
+                self.update_inner(&point.identity, point.point, old_index, |old_value, idx| {
+                    if let Some(value) = value.take() {
+                        *old_value = value;
+                    }
+                    idx
+                })?;
+            self.identity_to_point
+                .insert(point.identity, (point.point, new_index, generation));
+            return Ok(());
+        }
+        if !self.rect.contains(point.point) {
+            self.outside_of_range
+                .insert(point.identity.clone(), (value, point.point));
+            self.identity_to_point
+                .insert(point.identity, (point.point, None, generation));
+            self.dirty.touch_outside();
+            return Ok(());
+        }
+        let index = Self::find_bucket_mut(
+            &mut self.items,
+            &mut self.identity_to_point,
+            &mut self.dirty,
+            self.rect,
+            point.point,
+            Some(self.min_cell_extent),
+            |bucket, index| {
+                bucket.push((point.clone(), value));
+                index
+            },
+        )?;
+        self.identity_to_point
+            .insert(point.identity, (point.point, Some(index), generation));
+        self.dirty.touch(index);
+        Ok(())
+    }
+
+    fn update_entry(
+        &mut self,
+        identity: ID,
+        point: Point,
+        callback: impl FnOnce(&mut T),
+    ) -> Result<bool, CorruptionError> {
+        let point = self.filter_position(point);
+        self.move_entry(identity, point, callback)
+    }
+
+    /// Move the entry with `identity` to `point`, which is already filtered, see [`QuadTree::set_position_filter`]
+    fn move_entry(
+        &mut self,
+        identity: ID,
+        point: Point,
+        callback: impl FnOnce(&mut T),
+    ) -> Result<bool, CorruptionError> {
+        if let Some((old_point, maybe_index, mut generation)) =
+            self.identity_to_point.remove(&identity)
+        {
+            let new_idx = self.update_inner(&identity, point, maybe_index, |val, new_idx| {
+                callback(val);
+                new_idx
+            })?;
+            if point != old_point {
+                generation = self.new_generation();
+            }
+            self.identity_to_point
+                .insert(identity, (point, new_idx, generation));
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn remove_entry(&mut self, identity: &ID) -> Result<Option<(T, Point)>, CorruptionError> {
+        let Some((point, index, _)) = self.identity_to_point.remove(identity) else { return Ok(None) };
+        if let Some(index) = index {
+            let result = bucket_mut(&mut self.items, index)?
+                .as_owned_mut()?
+                .remove_by_identity(identity)?;
+            self.dirty.touch(index);
+
+            if let Some(parent) = index.parent() {
+                if let Some(pending) = &mut self.pending_merges {
+                    pending.insert(parent);
+                } else {
+                    self.try_merge(parent)?;
+                }
+            }
+            Ok(Some((result, point)))
+        } else {
+            self.dirty.touch_outside();
+            Ok(self.outside_of_range.remove(identity))
+        }
+    }
+
     fn update_inner<R>(
         &mut self,
         identity: &ID,
         new_point: Point,
         old_index: Option<Index>,
         callback: impl FnOnce(&mut T, Option<Index>) -> R,
-    ) -> R {
+    ) -> Result<R, CorruptionError> {
+        // an entry usually stays in its leaf, which is found with its old index instead of descending the tree again
+        if let Some(idx) = old_index {
+            if self.rect.get_index_rect(idx).contains_as_leaf(self.rect, new_point) {
+                if let Bucket::Owned(entries) = bucket_mut(&mut self.items, idx)? {
+                    if let Some((ip, value)) =
+                        entries.iter_mut().find(|(ip, _)| &ip.identity == identity)
+                    {
+                        let result = callback(value, Some(idx));
+                        ip.point = new_point;
+                        self.dirty.touch(idx);
+                        return Ok(result);
+                    }
+                }
+            }
+        }
         let mut callback = Some(callback);
         // get the new index first
         let new_index = if self.rect.contains(new_point) {
             let (result, idx) = Self::find_bucket_mut(
                 &mut self.items,
                 &mut self.identity_to_point,
+                &mut self.dirty,
                 self.rect,
                 new_point,
-                false,
+                None,
                 |bucket, idx| {
                     // if the new index is the same as the old index, we just update it in-place and early return
                     if Some(idx) == old_index {
@@ -212,9 +970,10 @@ where
                     }
                     (None, idx)
                 },
-            );
+            )?;
             if let Some(result) = result {
-                return result;
+                self.dirty.touch(idx);
+                return Ok(result);
             }
             Some(idx)
         } else {
@@ -223,28 +982,40 @@ where
 
         // We cannot update in-place, remove the old value and re-insert it
         let mut value = if let Some(idx) = old_index {
-            self.items[idx.to_idx()]
-                .as_owned_mut()
-                .remove_by_identity(identity)
+            self.dirty.touch(idx);
+            bucket_mut(&mut self.items, idx)?
+                .as_owned_mut()?
+                .remove_by_identity(identity)?
         } else {
-            self.outside_of_range.remove(identity).unwrap().0
+            self.dirty.touch_outside();
+            self.outside_of_range
+                .remove(identity)
+                .ok_or(CorruptionError::new(
+                    "entry is missing from outside of range",
+                ))?
+                .0
         };
         if let Some(index) = new_index {
             // new point is in this quad tree, quick insert it
-            let bucket = self.items[index.to_idx()].as_owned_mut();
+            let bucket = bucket_mut(&mut self.items, index)?.as_owned_mut()?;
             let (smallvec, new_index) =
                 if bucket.requires_split(self.rect.get_index_rect(index), Some(new_point)) {
                     let (new_vec, new_index) = Self::split(
                         &mut self.items,
                         &mut self.identity_to_point,
-                        self.rect,
+                        &mut self.dirty,
+                        self.rect.get_index_rect(index),
                         index,
                         new_point,
-                    );
+                        self.min_cell_extent,
+                    )?;
                     (new_vec, Some(new_index))
                 } else {
                     (bucket.into_inner(), new_index)
                 };
+            if let Some(new_index) = new_index {
+                self.dirty.touch(new_index);
+            }
             let result = (callback.take().unwrap())(&mut value, new_index);
             smallvec.push((
                 IdentityPoint::<ID> {
@@ -253,27 +1024,30 @@ where
                 },
                 value,
             ));
-            result
+            Ok(result)
         } else {
             // new point is out of range of this quad tree, simply insert it into `out_of_range`
             let result = (callback.take().unwrap())(&mut value, None);
+            self.dirty.touch_outside();
             self.outside_of_range
                 .insert(identity.clone(), (value, new_point));
-            result
+            Ok(result)
         }
     }
 
     fn find_bucket_mut<R>(
         items: &mut Vec<Bucket<T, ID, N>>,
-        identity_to_point: &mut BTreeMap<ID, (Point, Option<Index>)>,
+        identity_to_point: &mut BTreeMap<ID, (Point, Option<Index>, u64)>,
+        dirty: &mut Dirty,
         mut rect: point::Rect,
         point: Point,
-        require_resize: bool,
-        cb: impl FnOnce(&mut SmallVec<[(IdentityPoint<ID>, T); N]>, Index) -> R,
-    ) -> R {
+        // the `min_cell_extent` of the tree if a full leaf should be split, or `None` if it should not be split
+        split: Option<R32>,
+        cb: impl FnOnce(&mut Entries<T, ID, N>, Index) -> R,
+    ) -> Result<R, CorruptionError> {
         let mut index = Index::ROOT;
         loop {
-            let bucket = ensure_index_valid(items, index);
+            let bucket = ensure_index_valid(items, index)?;
             match bucket {
                 Bucket::Nested => {
                     let (new_rect, quadrant) = rect.get_quadrant(point);
@@ -281,16 +1055,24 @@ where
                     rect = new_rect;
                 }
                 Bucket::Owned(smallvec) => {
-                    let (smallvec, index) = if require_resize {
+                    let (smallvec, index) = if let Some(min_cell_extent) = split {
                         if smallvec.len() < N {
-                            return cb(smallvec, index);
+                            return Ok(cb(smallvec, index));
                         }
 
-                        Self::split(items, identity_to_point, rect, index, point)
+                        Self::split(
+                            items,
+                            identity_to_point,
+                            dirty,
+                            rect,
+                            index,
+                            point,
+                            min_cell_extent,
+                        )?
                     } else {
                         (smallvec, index)
                     };
-                    return cb(smallvec, index);
+                    return Ok(cb(smallvec, index));
                 }
             }
         }
@@ -298,43 +1080,51 @@ where
 
     fn split<'a>(
         items: &'a mut Vec<Bucket<T, ID, N>>,
-        identity_to_point: &mut BTreeMap<ID, (Point, Option<Index>)>,
+        identity_to_point: &mut BTreeMap<ID, (Point, Option<Index>, u64)>,
+        dirty: &mut Dirty,
         rect: point::Rect,
         index: Index,
         point: Point,
-    ) -> (&'a mut SmallVec<[(IdentityPoint<ID>, T); N]>, Index) {
+        min_cell_extent: R32,
+    ) -> Result<(&'a mut Entries<T, ID, N>, Index), CorruptionError> {
         let new_item_quadrant = rect.get_quadrant(point).1;
 
         if let Some(Bucket::Owned(smallvec)) = items.get_mut(index.to_idx()) {
-            if smallvec
-                .iter()
-                .all(|(ip, _)| rect.get_quadrant(ip.point).1 == new_item_quadrant)
+            // a node at the maximum depth can not be split, and neither can a node whose children would cover the same area as itself, e.g. because its middle is rounded onto one of its edges
+            if index.children().is_none()
+                || rect.extent() < min_cell_extent.raw()
+                || point::Quadrant::all()
+                    .into_iter()
+                    .any(|quadrant| rect.get_child_at(quadrant) == rect)
+                || smallvec
+                    .iter()
+                    .all(|(ip, _)| rect.get_quadrant(ip.point).1 == new_item_quadrant)
             {
                 // special case: all of these positions are on the same quadrant, so we cannot split
                 // therefor we must overflow the smallvec
 
                 // safety: rust lifetimes are jank and the compiler thinks this is still borrowed below even though we clearly return
                 // so this breaks that issue
-                return (unsafe { &mut *(smallvec as *mut _) }, index);
+                return Ok((unsafe { &mut *std::ptr::from_mut(smallvec) }, index));
             }
         } else {
-            panic!(
-                "Item at index {index:?} ({}) is nested, but we tried to split it",
-                index.to_idx()
-            );
+            return Err(CorruptionError::new("tried to split a nested node"));
         }
         // debug_tree(items, Index::ROOT);
+        dirty.touch(index);
 
         // we should be able to safely split
-        ensure_index_valid(items, index.child_at(new_item_quadrant));
-        let Bucket::Owned(smallvec) = std::mem::replace(&mut items[index.to_idx()], Bucket::Nested) else { unreachable!() };
+        ensure_index_valid(items, index.child_at(new_item_quadrant))?;
+        let bucket = std::mem::replace(bucket_mut(items, index)?, Bucket::Nested);
+        let Bucket::Owned(smallvec) = bucket else {
+            return Err(CorruptionError::new("tried to split a nested node"));
+        };
 
         for (point, value) in smallvec {
             let (mut rect, quadrant) = rect.get_quadrant(point.point);
             let mut index = index.child_at(quadrant);
             let (smallvec, index) = loop {
-                ensure_index_valid(items, index);
-                match &mut items[index.to_idx()] {
+                match ensure_index_valid(items, index)? {
                     Bucket::Owned(smallvec) => break (smallvec, index),
                     Bucket::Nested => {
                         let (new_rect, quadrant) = rect.get_quadrant(point.point);
@@ -345,122 +1135,302 @@ where
             };
             smallvec.push((point.clone(), value));
 
-            identity_to_point.insert(point.identity, (point.point, Some(index)));
+            if let Some((_, entry_index, _)) = identity_to_point.get_mut(&point.identity) {
+                *entry_index = Some(index);
+            }
         }
         let mut index = index.child_at(new_item_quadrant);
-        let mut rect = rect;
+        let mut rect = rect.get_child_at(new_item_quadrant);
         loop {
-            match ensure_index_valid(items, index) {
+            match ensure_index_valid(items, index)? {
                 Bucket::Owned(smallvec) => {
                     // rust lifetimes again, we're `break`ing here but it keeps the lifetime for the next iteration
-                    let smallvec = unsafe { &mut *(smallvec as *mut _) };
-                    break (smallvec, index);
+                    let smallvec = unsafe { &mut *std::ptr::from_mut(smallvec) };
+                    break Ok((smallvec, index));
                 }
                 Bucket::Nested => {
                     let (new_rect, quadrant) = rect.get_quadrant(point);
                     rect = new_rect;
                     index = index.child_at(quadrant);
                 }
-            };
+            }
         }
     }
 
-    fn try_merge(&mut self, index: Index) {
-        let Some(children) = index.children() else { return };
-        let sum = children.iter().fold(0, |acc, idx| {
-            acc + if let Bucket::Owned(n) = &self.items[idx.to_idx()] {
-                n.len()
-            } else {
-                N + 1
-            }
-        });
+    fn try_merge(&mut self, index: Index) -> Result<(), CorruptionError> {
+        let Some(children) = index.children() else { return Ok(()) };
+        let mut sum = 0;
+        for child_idx in children {
+            sum += match self.items.get(child_idx.to_idx()) {
+                Some(Bucket::Owned(n)) => n.len(),
+                Some(Bucket::Nested) => N + 1,
+                None => return Err(CorruptionError::new("child of a nested node is missing")),
+            };
+        }
         if sum <= N {
             let mut parent = SmallVec::new();
             for child_idx in children {
-                let Bucket::Owned(n) = std::mem::replace(&mut self.items[child_idx.to_idx()], Bucket::Nested) else { unreachable!() };
+                let bucket =
+                    std::mem::replace(bucket_mut(&mut self.items, child_idx)?, Bucket::Nested);
+                let Bucket::Owned(n) = bucket else {
+                    return Err(CorruptionError::new("merged a nested node"));
+                };
                 for (ip, value) in n {
-                    self.identity_to_point
-                        .insert(ip.identity.clone(), (ip.point, Some(index)));
+                    if let Some((_, entry_index, _)) = self.identity_to_point.get_mut(&ip.identity)
+                    {
+                        *entry_index = Some(index);
+                    }
                     parent.push((ip, value));
                 }
             }
-            debug_assert!(matches!(self.items[index.to_idx()], Bucket::Nested));
-            self.items[index.to_idx()] = Bucket::Owned(parent);
+            let bucket = bucket_mut(&mut self.items, index)?;
+            if !matches!(bucket, Bucket::Nested) {
+                return Err(CorruptionError::new(
+                    "parent of a merged node is not nested",
+                ));
+            }
+            *bucket = Bucket::Owned(parent);
+            self.dirty.touch(index);
 
             while self.items.len() > 1 && matches!(self.items.last(), Some(Bucket::Nested)) {
                 self.items.pop();
             }
         }
+        Ok(())
     }
 
-    fn find_range_inner<'a>(
+    fn find_range_inner<'a, B>(
         &'a self,
         rect: Rect,
         index: Index,
         ctx: &FindRangeCtx,
-        callback: &mut impl FnMut(&ID, Point, &'a T),
-    ) {
+        callback: &mut impl FnMut(&ID, Point, &'a T, Option<Rect>) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
         if !ctx.contains_rect(rect) {
-            return;
+            return ControlFlow::Continue(());
         }
         match self.items.get(index.to_idx()) {
             Some(Bucket::Owned(items)) => {
                 for (ident, val) in items {
+                    if ctx.point_in_range(ident.point) {
+                        callback(&ident.identity, ident.point, val, Some(rect))?;
+                    }
+                }
+            }
+            Some(Bucket::Nested) => {
+                for child in point::Quadrant::all() {
+                    let rect = rect.get_child_at(child);
+                    // a nested node at the maximum depth is corrupt, and is skipped instead of panicking
+                    let Some(index) = index.checked_child_at(child) else {
+                        break;
+                    };
+                    self.find_range_inner(rect, index, ctx, callback)?;
+                }
+            }
+            None => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn find_range_mut_inner(
+        &mut self,
+        rect: Rect,
+        index: Index,
+        ctx: &FindRangeCtx,
+        callback: &mut impl FnMut(&ID, Point, &mut T),
+    ) {
+        if !ctx.contains_rect(rect) {
+            return;
+        }
+        match self.items.get_mut(index.to_idx()) {
+            Some(Bucket::Owned(items)) => {
+                let mut found = false;
+                for (ident, val) in items.iter_mut() {
                     if ctx.point_in_range(ident.point) {
                         callback(&ident.identity, ident.point, val);
+                        found = true;
                     }
                 }
+                if found {
+                    self.dirty.touch(index);
+                }
             }
             Some(Bucket::Nested) => {
                 for child in point::Quadrant::all() {
                     let rect = rect.get_child_at(child);
-                    let index = index.child_at(child);
-                    self.find_range_inner(rect, index, ctx, callback);
+                    // a nested node at the maximum depth is corrupt, and is skipped instead of panicking
+                    let Some(index) = index.checked_child_at(child) else {
+                        return;
+                    };
+                    self.find_range_mut_inner(rect, index, ctx, callback);
                 }
             }
             None => {}
         }
     }
+
+    fn find_rect_inner<'a>(
+        &'a self,
+        rect: Rect,
+        index: Index,
+        area: Rect,
+        callback: &mut impl FnMut(&ID, Point, &'a T, Option<Rect>),
+    ) {
+        if !area.touches(rect) {
+            return;
+        }
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Owned(items)) => {
+                for (ident, val) in items {
+                    if area.contains(ident.point) {
+                        callback(&ident.identity, ident.point, val, Some(rect));
+                    }
+                }
+            }
+            Some(Bucket::Nested) => {
+                for child in point::Quadrant::all() {
+                    let rect = rect.get_child_at(child);
+                    // a nested node at the maximum depth is corrupt, and is skipped instead of panicking
+                    let Some(index) = index.checked_child_at(child) else {
+                        return;
+                    };
+                    self.find_rect_inner(rect, index, area, callback);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// Decides if entries that are exactly on the edge of a range are included in [`QuadTree::find_range_with_boundary`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RangeBoundary {
+    /// Entries at exactly `range` are included. This is the behavior of [`QuadTree::find_range`].
+    Inclusive,
+    /// Only entries that are closer than `range` are included
+    Exclusive,
+    /// Entries up to `range + tolerance` are included, to allow for floating point drift between the positions and the range.
+    Tolerance(R32),
+}
+
+/// Options for [`QuadTree::find_range_with_options`]. The default options find the same entries as [`QuadTree::find_range`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RangeOptions {
+    boundary: RangeBoundary,
+    include_out_of_range: bool,
+}
+
+impl RangeOptions {
+    /// The default options, which include the entries on the boundary and the entries outside of the tree
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            boundary: RangeBoundary::Inclusive,
+            include_out_of_range: true,
+        }
+    }
+
+    /// Decide what happens to entries on the edge of the range, see [`QuadTree::find_range_with_boundary`]
+    #[must_use]
+    pub const fn boundary(mut self, boundary: RangeBoundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Decide if entries outside of the rect of the tree are found. Skipping them saves a scan over all of them, which matters for trees that park many entries outside of their rect.
+    #[must_use]
+    pub const fn include_out_of_range(mut self, include: bool) -> Self {
+        self.include_out_of_range = include;
+        self
+    }
+}
+
+impl Default for RangeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 struct FindRangeCtx {
     center: Point,
     range_squared: R32,
+    inclusive: bool,
     full_rect: Rect,
 }
 impl FindRangeCtx {
     fn new(center: Point, range: R32) -> Self {
+        Self::with_boundary(center, range, RangeBoundary::Inclusive)
+    }
+
+    fn with_boundary(center: Point, range: R32, boundary: RangeBoundary) -> Self {
+        let range = match boundary {
+            RangeBoundary::Inclusive | RangeBoundary::Exclusive => range,
+            RangeBoundary::Tolerance(tolerance) => {
+                assert!(
+                    tolerance >= 0.0,
+                    "Tolerance {tolerance} should not be negative"
+                );
+                clamped_r32(range.raw() + tolerance.raw())
+            }
+        };
+        // the query path should never panic, so everything that can overflow is clamped to the largest float
+        let offset = |value: R32, offset: f32| clamped_r32(value.raw() + offset);
         Self {
             center,
-            range_squared: range * range,
-            full_rect: Rect::new(center - range, center + range),
+            range_squared: clamped_r32(range.raw() * range.raw()),
+            inclusive: boundary != RangeBoundary::Exclusive,
+            full_rect: Rect::new(
+                Point::new_noisy_float(
+                    offset(center.x, -range.raw()),
+                    offset(center.y, -range.raw()),
+                ),
+                Point::new_noisy_float(
+                    offset(center.x, range.raw()),
+                    offset(center.y, range.raw()),
+                ),
+            ),
         }
     }
     fn contains_rect(&self, rect: Rect) -> bool {
-        self.full_rect.intersects(rect)
+        self.full_rect.touches(rect)
     }
 
     fn point_in_range(&self, point: Point) -> bool {
-        self.center.distance_squared_to(point) <= self.range_squared
+        let distance_squared = self.center.distance_squared_to(point);
+        if self.inclusive {
+            distance_squared <= self.range_squared
+        } else {
+            distance_squared < self.range_squared
+        }
     }
 }
 
 fn ensure_index_valid<T, ID, const N: usize>(
     items: &mut Vec<Bucket<T, ID, N>>,
     index: Index,
-) -> &mut Bucket<T, ID, N> {
+) -> Result<&mut Bucket<T, ID, N>, CorruptionError> {
     if let Some(index) = index.parent() {
         let index = index.child_at(point::Quadrant::BottomRight);
         let new_max_item_count = index.to_idx();
         if items.len() <= new_max_item_count {
             items.resize_with(new_max_item_count + 1, || Bucket::Owned(SmallVec::new()));
         }
-    } else {
-        debug_assert!(!items.is_empty());
-    };
+    }
+    bucket_mut(items, index)
+}
+
+fn bucket_mut<T, ID, const N: usize>(
+    items: &mut [Bucket<T, ID, N>],
+    index: Index,
+) -> Result<&mut Bucket<T, ID, N>, CorruptionError> {
+    items
+        .get_mut(index.to_idx())
+        .ok_or(CorruptionError::new("node is missing"))
+}
 
-    // Safety: We just asserted that this vec has this index
-    unsafe { items.get_unchecked_mut(index.to_idx()) }
+/// Unwrap the result of an internal operation, which only fails if the tree is corrupted
+fn expect_consistent<R>(result: Result<R, CorruptionError>) -> R {
+    result.unwrap_or_else(|error| panic!("{error}"))
 }
 
 // fn debug_tree<T: std::fmt::Debug, const N: usize>(items: &[Bucket<T, ID, N>], index: Index) {
@@ -0,0 +1,89 @@
+//! Deferred merges, for soft real-time workloads that would rather spend leftover frame time on keeping the tree small than merge nodes in every removal.
+
+use crate::{bucket::Bucket, expect_consistent, index::Index, CorruptionError, QuadTree};
+use std::collections::BTreeSet;
+
+/// The amount of work that one call to [`QuadTree::maintenance`] may do
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MaintenanceBudget {
+    max_nodes: usize,
+}
+
+impl MaintenanceBudget {
+    /// A budget that checks at most `max_nodes` nodes for a merge
+    #[must_use]
+    pub const fn nodes(max_nodes: usize) -> Self {
+        Self { max_nodes }
+    }
+
+    /// A budget that does all of the pending work
+    #[must_use]
+    pub const fn unlimited() -> Self {
+        Self::nodes(usize::MAX)
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Decide if nodes are merged by the removal that made them small enough, or later by [`QuadTree::maintenance`]. Merges are not deferred by default.
+    ///
+    /// A merge moves the entries of 4 leaves into their parent, which makes the time of a removal hard to predict. With deferred merges, a removal only remembers the parent of its leaf. Queries are not affected by the nodes that are not merged yet, other than visiting a few more of them.
+    ///
+    /// When merges stop being deferred, the pending merges are done right away.
+    pub fn set_deferred_merges(&mut self, deferred: bool) {
+        if deferred {
+            self.pending_merges.get_or_insert_with(BTreeSet::new);
+        } else if self.pending_merges.is_some() {
+            self.maintenance(MaintenanceBudget::unlimited());
+            self.pending_merges = None;
+        }
+    }
+
+    /// The amount of nodes that [`QuadTree::maintenance`] still has to check for a merge
+    #[must_use]
+    pub fn pending_maintenance(&self) -> usize {
+        self.pending_merges.as_ref().map_or(0, BTreeSet::len)
+    }
+
+    /// Do the merges that were deferred by [`QuadTree::set_deferred_merges`], checking at most as many nodes as `budget` allows. Returns the amount of nodes that still have to be checked.
+    ///
+    /// The deepest nodes are checked first. When a node is merged, its parent is checked as well, so a tree that lost most of its entries shrinks over multiple calls.
+    pub fn maintenance(&mut self, budget: MaintenanceBudget) -> usize {
+        expect_consistent(self.maintenance_inner(budget))
+    }
+
+    fn maintenance_inner(&mut self, budget: MaintenanceBudget) -> Result<usize, CorruptionError> {
+        for _ in 0..budget.max_nodes {
+            let Some(pending) = &mut self.pending_merges else {
+                return Ok(0);
+            };
+            let Some(index) = pending.iter().next_back().copied() else {
+                break;
+            };
+            pending.remove(&index);
+            if !self.is_nested(index) {
+                // the node was merged or removed after it became pending
+                continue;
+            }
+            self.try_merge(index)?;
+            if !self.is_nested(index) {
+                if let (Some(parent), Some(pending)) = (index.parent(), &mut self.pending_merges) {
+                    pending.insert(parent);
+                }
+            }
+        }
+        Ok(self.pending_maintenance())
+    }
+
+    /// Returns `true` if the node at `index` is nested and has all of its children
+    pub(crate) fn is_nested(&self, index: Index) -> bool {
+        matches!(self.items.get(index.to_idx()), Some(Bucket::Nested))
+            && index.children().is_some_and(|children| {
+                children
+                    .iter()
+                    .all(|child| child.to_idx() < self.items.len())
+            })
+    }
+}
@@ -0,0 +1,78 @@
+//! Choosing new bounds for a tree from its entries, and moving the entries into them, for trees whose initial bounds turned out to be wrong.
+
+use crate::{
+    bucket::Bucket, curve::morton_code, index::Index, IdentityPoint, Point, QuadTree, Rect, R32,
+};
+use smallvec::SmallVec;
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// The smallest rect around every entry, also the entries outside of this tree, with a margin of 1/16th of its longest side on every side. The margin is 1 if all entries are at the same point. Returns the current rect if this tree is empty.
+    ///
+    /// The rect can be passed to [`QuadTree::migrate_to_bounds`], so no entry is stored outside of the tree anymore.
+    #[must_use]
+    pub fn suggest_bounds(&self) -> Rect {
+        let mut points = self.identity_to_point.values().map(|(point, _, _)| *point);
+        let Some(first) = points.next() else {
+            return self.rect;
+        };
+        let (mut left, mut top, mut right, mut bottom) = (first.x, first.y, first.x, first.y);
+        for point in points {
+            left = left.min(point.x);
+            top = top.min(point.y);
+            right = right.max(point.x);
+            bottom = bottom.max(point.y);
+        }
+        let longest = (right - left).max(bottom - top);
+        let margin = if longest > 0.0 {
+            longest / 16.0
+        } else {
+            R32::new(1.0)
+        };
+        Rect::new(
+            Point::new_noisy_float(left - margin, top - margin),
+            Point::new_noisy_float(right + margin, bottom + margin),
+        )
+    }
+
+    /// Rebuild this tree to cover `rect` instead, and move every entry into it. Entries outside of `rect` are stored outside of the tree.
+    ///
+    /// The entries are inserted in Z-order, like [`BulkBuilder`](crate::BulkBuilder) does, so the new tree is built from entries that are close together. The y axis of this tree and whether its merges are deferred stay the same. A delta that is saved by [`QuadTree::save_incremental`] after this can only be applied to a tree that covers the new area.
+    pub fn migrate_to_bounds(&mut self, rect: Rect) {
+        let mut entries = Vec::with_capacity(self.identity_to_point.len());
+        for bucket in std::mem::take(&mut self.items) {
+            if let Bucket::Owned(bucket) = bucket {
+                entries.extend(bucket);
+            }
+        }
+        entries.extend(
+            self.outside_of_range
+                .take()
+                .into_iter()
+                .map(|(identity, (value, point))| (IdentityPoint { identity, point }, value)),
+        );
+        entries.sort_by_key(|(ip, _)| morton_code(rect, ip.point));
+
+        self.rect = rect;
+        self.items = vec![Bucket::Owned(SmallVec::new_const())];
+        let generations = std::mem::take(&mut self.identity_to_point);
+        if let Some(pending) = &mut self.pending_merges {
+            pending.clear();
+        }
+        self.dirty.touch(Index::ROOT);
+        self.dirty.touch_outside();
+        for (point, value) in entries {
+            let identity = point.identity.clone();
+            self.insert(point, value);
+            // the entries keep their generation, they are only stored somewhere else
+            if let (Some((_, _, generation)), Some((_, _, old))) = (
+                self.identity_to_point.get_mut(&identity),
+                generations.get(&identity),
+            ) {
+                *generation = *old;
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Nearest neighbor queries that are answered for many entries at once.
+//!
+//! The entries of a leaf are close together, so they are looked up as one group: a node of the tree is only visited if it could contain a closer neighbor for at least one of the entries in the group.
+
+use crate::{
+    bucket::Bucket, index::Index, neighbors::for_each_leaf, point::Quadrant, Point, QuadTree, Rect,
+    R32,
+};
+use noisy_float::prelude::Float;
+use smallvec::SmallVec;
+use std::collections::BinaryHeap;
+
+/// An entry that is looking for its nearest neighbors
+struct Query<'a, ID> {
+    identity: &'a ID,
+    point: Point,
+    /// The nearest entries that were found so far with their squared distance, with the farthest on top
+    nearest: BinaryHeap<(R32, &'a ID)>,
+}
+
+impl<'a, ID: Ord> Query<'a, ID> {
+    fn new(identity: &'a ID, point: Point, k: usize) -> Self {
+        Self {
+            identity,
+            point,
+            nearest: BinaryHeap::with_capacity(k),
+        }
+    }
+
+    fn offer(&mut self, k: usize, identity: &'a ID, point: Point) {
+        if identity == self.identity {
+            return;
+        }
+        let candidate = (self.point.distance_squared_to(point), identity);
+        if self.nearest.len() < k {
+            self.nearest.push(candidate);
+        } else if let Some(mut farthest) = self.nearest.peek_mut() {
+            if candidate < *farthest {
+                *farthest = candidate;
+            }
+        }
+    }
+}
+
+/// The entries that are looked up together, and the bounding box of their points
+struct QueryGroup<'a, ID> {
+    rect: Rect,
+    queries: Vec<Query<'a, ID>>,
+}
+
+impl<'a, ID: Ord> QueryGroup<'a, ID> {
+    /// The squared distance that a node must be within to contain a closer neighbor for any of the queries, or `None` if a query has less than `k` neighbors
+    fn bound(&self, k: usize) -> Option<R32> {
+        let mut bound = R32::new(0.0);
+        for query in &self.queries {
+            if query.nearest.len() < k {
+                return None;
+            }
+            bound = bound.max(query.nearest.peek()?.0);
+        }
+        Some(bound)
+    }
+
+    fn offer(&mut self, k: usize, identity: &'a ID, point: Point) {
+        for query in &mut self.queries {
+            query.offer(k, identity, point);
+        }
+    }
+}
+
+impl<T, ID: Ord, const N: usize> QuadTree<T, ID, N> {
+    /// Find the `k` nearest other entries of every entry in this tree.
+    ///
+    /// `callback` is called once for every entry, with the neighbors of that entry and their distance to it. The neighbors are sorted by distance, and then by identity. An entry has less than `k` neighbors if this tree contains `k` entries or less.
+    ///
+    /// The entries are visited one leaf at a time, and entries that are outside of the range of this tree are visited last.
+    pub fn all_k_nearest<'a>(
+        &'a self,
+        k: usize,
+        mut callback: impl FnMut(&'a ID, &[(&'a ID, R32)]),
+    ) {
+        let mut neighbors = Vec::with_capacity(k);
+        let mut report = |group: QueryGroup<'a, ID>| {
+            for query in group.queries {
+                neighbors.clear();
+                neighbors.extend(
+                    query
+                        .nearest
+                        .into_sorted_vec()
+                        .into_iter()
+                        .map(|(distance, identity)| (identity, distance.sqrt())),
+                );
+                callback(query.identity, &neighbors);
+            }
+        };
+
+        for_each_leaf(self.rect, &|index| self.node_kind(index), |index, _| {
+            let Some(Bucket::Owned(entries)) = self.items.get(index.to_idx()) else {
+                return;
+            };
+            let Some((first, _)) = entries.first() else {
+                return;
+            };
+            let mut rect = Rect::new(first.point, first.point);
+            let queries = entries
+                .iter()
+                .map(|(ip, _)| {
+                    rect = rect.expand_to(ip.point);
+                    Query::new(&ip.identity, ip.point, k)
+                })
+                .collect();
+            report(self.search_group(QueryGroup { rect, queries }, k));
+        });
+        for (identity, (_, point)) in &self.outside_of_range {
+            let group = QueryGroup {
+                rect: Rect::new(*point, *point),
+                queries: vec![Query::new(identity, *point, k)],
+            };
+            report(self.search_group(group, k));
+        }
+    }
+
+    /// Build a graph that connects every entry to its `K` nearest other entries, see [`QuadTree::all_k_nearest`].
+    ///
+    /// The graph is sorted by identity, and the neighbors of every entry are sorted by distance, and then by identity.
+    #[must_use]
+    pub fn knn_graph<const K: usize>(&self) -> Vec<(ID, SmallVec<[ID; K]>)>
+    where
+        ID: Clone,
+    {
+        let mut graph = Vec::with_capacity(self.identity_to_point.len());
+        self.all_k_nearest(K, |identity, neighbors| {
+            let neighbors = neighbors
+                .iter()
+                .map(|(neighbor, _)| (*neighbor).clone())
+                .collect();
+            graph.push((identity.clone(), neighbors));
+        });
+        graph.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        graph
+    }
+
+    /// Find the two entries that are closest to each other, and the distance between them.
+    ///
+    /// If multiple pairs have the same distance, the pair with the lowest identities is returned. The first identity of the pair is always lower than the second. Returns `None` if this tree contains less than 2 entries.
+    #[must_use]
+    pub fn closest_pair(&self) -> Option<(&ID, &ID, R32)> {
+        let mut closest: Option<(R32, &ID, &ID)> = None;
+        self.all_k_nearest(1, |identity, neighbors| {
+            let Some((neighbor, distance)) = neighbors.first() else {
+                return;
+            };
+            let pair = (*distance, identity.min(*neighbor), identity.max(*neighbor));
+            closest = Some(closest.map_or(pair, |closest| closest.min(pair)));
+        });
+        closest.map(|(distance, a, b)| (a, b, distance))
+    }
+
+    fn search_group<'a>(&'a self, mut group: QueryGroup<'a, ID>, k: usize) -> QueryGroup<'a, ID> {
+        if k == 0 {
+            return group;
+        }
+        for (identity, (_, point)) in &self.outside_of_range {
+            group.offer(k, identity, *point);
+        }
+        self.search_node(Index::ROOT, self.rect, &mut group, k);
+        group
+    }
+
+    fn search_node<'a>(
+        &'a self,
+        index: Index,
+        rect: Rect,
+        group: &mut QueryGroup<'a, ID>,
+        k: usize,
+    ) {
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => {
+                let mut children = Quadrant::all().map(|quadrant| {
+                    let child_rect = rect.get_child_at(quadrant);
+                    let distance = group.rect.min_distance_squared(child_rect);
+                    (distance, quadrant, child_rect)
+                });
+                // visit the closest children first, so the bound shrinks as fast as possible
+                children.sort_unstable_by_key(|(distance, _, _)| *distance);
+                for (distance, quadrant, child_rect) in children {
+                    if matches!(group.bound(k), Some(bound) if distance > bound) {
+                        break;
+                    }
+                    self.search_node(index.child_at(quadrant), child_rect, group, k);
+                }
+            }
+            Some(Bucket::Owned(entries)) => {
+                for (ip, _) in entries {
+                    group.offer(k, &ip.identity, ip.point);
+                }
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Leaf adjacency for trees that are stored in the flat [`Index`] layout.
+//!
+//! Two leaves are neighbors when their rects share (a part of) an edge. Because every child rect is derived from the midpoint of its parent, edges that are shared between leaves have exactly the same coordinates, so no epsilon is needed.
+
+use crate::{
+    index::Index,
+    point::{Quadrant, Rect},
+};
+
+/// What a node in the flat layout contains
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum NodeKind {
+    /// This node is split, its children should be scanned
+    Nested,
+    /// This node is a leaf
+    Leaf,
+    /// This node does not exist in the layout
+    Missing,
+}
+
+/// Call `callback` for every leaf that shares an edge with the leaf at `leaf`.
+///
+/// `node_kind` should report what is stored at the given [`Index`].
+pub(crate) fn for_each_leaf_neighbor(
+    root: Rect,
+    leaf: Index,
+    node_kind: &impl Fn(Index) -> NodeKind,
+    mut callback: impl FnMut(Index, Rect),
+) {
+    let leaf_rect = root.get_index_rect(leaf);
+    visit(root, Index::ROOT, leaf, leaf_rect, node_kind, &mut callback);
+}
+
+fn visit(
+    rect: Rect,
+    index: Index,
+    leaf: Index,
+    leaf_rect: Rect,
+    node_kind: &impl Fn(Index) -> NodeKind,
+    callback: &mut impl FnMut(Index, Rect),
+) {
+    if index == leaf || !rect.touches(leaf_rect) {
+        return;
+    }
+    match node_kind(index) {
+        NodeKind::Nested => {
+            for quadrant in Quadrant::all() {
+                visit(
+                    rect.get_child_at(quadrant),
+                    index.child_at(quadrant),
+                    leaf,
+                    leaf_rect,
+                    node_kind,
+                    callback,
+                );
+            }
+        }
+        NodeKind::Leaf => {
+            if rect.shares_edge(leaf_rect) {
+                callback(index, rect);
+            }
+        }
+        NodeKind::Missing => {}
+    }
+}
+
+/// Call `callback` for every leaf in the tree, in depth-first quadrant order.
+pub(crate) fn for_each_leaf(
+    root: Rect,
+    node_kind: &impl Fn(Index) -> NodeKind,
+    mut callback: impl FnMut(Index, Rect),
+) {
+    fn inner(
+        rect: Rect,
+        index: Index,
+        node_kind: &impl Fn(Index) -> NodeKind,
+        callback: &mut impl FnMut(Index, Rect),
+    ) {
+        match node_kind(index) {
+            NodeKind::Nested => {
+                for quadrant in Quadrant::all() {
+                    inner(
+                        rect.get_child_at(quadrant),
+                        index.child_at(quadrant),
+                        node_kind,
+                        callback,
+                    );
+                }
+            }
+            NodeKind::Leaf => callback(index, rect),
+            NodeKind::Missing => {}
+        }
+    }
+    inner(root, Index::ROOT, node_kind, &mut callback);
+}
@@ -0,0 +1,81 @@
+//! [`NodeId`], the public name of a node in a [`QuadTree`].
+
+use crate::{index::Index, point::Quadrant, QuadTree, Rect, YAxis};
+use std::fmt;
+
+/// Identifies a node of a [`QuadTree`] by its position: the quadrants that lead from the root to it.
+///
+/// A `NodeId` does not depend on the contents of a tree, so the same id refers to the same area in every tree with the same rect, also after the node is split, merged or removed. The encoding is internal to this crate, use [`NodeId::path`] and [`NodeId::from_path`] to store an id elsewhere.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub(crate) Index);
+
+impl NodeId {
+    /// The root node, which covers the whole rect of a tree
+    pub const ROOT: NodeId = NodeId(Index::ROOT);
+
+    /// Create an id from the quadrants that lead from the root to the node, or `None` if the path is deeper than 15 quadrants
+    #[must_use]
+    pub fn from_path(path: impl IntoIterator<Item = Quadrant>) -> Option<NodeId> {
+        path.into_iter()
+            .try_fold(Index::ROOT, Index::checked_child_at)
+            .map(NodeId)
+    }
+
+    /// The quadrants that lead from the root to this node
+    #[must_use]
+    pub fn path(self) -> impl ExactSizeIterator<Item = Quadrant> {
+        self.0.iter_from_root()
+    }
+
+    /// The amount of splits between the root and this node, which is `0` for the root itself
+    #[must_use]
+    pub fn depth(self) -> usize {
+        self.path().len()
+    }
+
+    /// The parent of this node, or `None` for the root
+    #[must_use]
+    pub fn parent(self) -> Option<NodeId> {
+        self.0.parent().map(NodeId)
+    }
+
+    /// The child of this node in `quadrant`, or `None` if this node is at the maximum depth of 15
+    #[must_use]
+    pub fn child(self, quadrant: Quadrant) -> Option<NodeId> {
+        self.0.checked_child_at(quadrant).map(NodeId)
+    }
+}
+
+impl fmt::Debug for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.path()).finish()
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord,
+{
+    /// The leaf that contains the entry with the given identity, see [`QuadTree::path_of`].
+    ///
+    /// Returns `None` if the identity is not found, or if the entry is outside of the range of this tree.
+    #[must_use]
+    pub fn node_of(&self, identity: &ID) -> Option<NodeId> {
+        let (_, index, _) = self.identity_to_point.get(identity)?;
+        index.map(|index| NodeId(self.public_index(index)))
+    }
+
+    /// The area that `node` covers in this tree, whether or not this tree has that node
+    #[must_use]
+    pub fn node_rect(&self, node: NodeId) -> Rect {
+        self.rect.get_index_rect(self.public_index(node.0))
+    }
+
+    /// Convert between the index of a node in `items` and the index of its [`NodeId`], which names the quadrants in the direction of the y axis of this tree. This is its own inverse.
+    pub(crate) fn public_index(&self, index: Index) -> Index {
+        match self.y_axis {
+            YAxis::Down => index,
+            YAxis::Up => index.flip_vertical(),
+        }
+    }
+}
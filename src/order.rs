@@ -0,0 +1,149 @@
+//! Iterating over the entries of a [`QuadTree`] in the order of a space-filling curve, all at once or page by page.
+
+use crate::{
+    bucket::Bucket,
+    curve::{hilbert_code, morton_code},
+    index::Index,
+    point::Quadrant,
+    Point, QuadTree, Rect,
+};
+
+/// The position in the order of [`QuadTree::iter_morton`] after which [`QuadTree::page_after`] continues.
+///
+/// A cursor only refers to a position on the curve and an identity, not to the tree itself, so it stays valid while the tree changes and can be stored outside of the program, e.g. in the URL of the next page.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpatialCursor<ID> {
+    position: u32,
+    identity: ID,
+}
+
+impl<ID> SpatialCursor<ID> {
+    /// Create a cursor from the parts that [`SpatialCursor::position`] and [`SpatialCursor::identity`] return
+    #[must_use]
+    pub fn from_parts(position: u32, identity: ID) -> Self {
+        Self { position, identity }
+    }
+
+    /// The position on the Morton curve of the last entry of the page
+    #[must_use]
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    /// The identity of the last entry of the page
+    #[must_use]
+    pub fn identity(&self) -> &ID {
+        &self.identity
+    }
+}
+
+/// The entries of a page with their position, as found by [`QuadTree::page_after`]
+pub type PageEntries<'a, T, ID> = Vec<(&'a ID, Point, &'a T)>;
+
+/// An entry with its position on a curve, see [`QuadTree::sorted_entries`]
+type CurveEntry<'a, T, ID> = (u32, &'a ID, Point, &'a T);
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord,
+{
+    /// Iterate over all entries in the order of the Morton (Z-order) curve, which is the order of the quadrants of this tree.
+    ///
+    /// Entries that are outside of this tree are sorted as if they were on the closest edge of the tree. Entries that are this close together that they are at the same position on the curve are sorted by identity.
+    pub fn iter_morton(&self) -> impl ExactSizeIterator<Item = (&ID, Point, &T)> {
+        self.sorted_entries(morton_code)
+            .into_iter()
+            .map(|(_, identity, point, value)| (identity, point, value))
+    }
+
+    /// Iterate over all entries in the order of the Hilbert curve, which starts in the top-left corner and ends in the top-right corner of this tree.
+    ///
+    /// Consecutive entries are close together more often than for [`QuadTree::iter_morton`], because the Hilbert curve never jumps. Entries outside of this tree and entries at the same position on the curve are sorted the same as for [`QuadTree::iter_morton`].
+    pub fn iter_hilbert(&self) -> impl ExactSizeIterator<Item = (&ID, Point, &T)> {
+        self.sorted_entries(hilbert_code)
+            .into_iter()
+            .map(|(_, identity, point, value)| (identity, point, value))
+    }
+
+    /// Get up to `limit` entries that come after `cursor` in the order of [`QuadTree::iter_morton`], starting at the first entry if `cursor` is `None`.
+    ///
+    /// Also returns the cursor for the next page, which is `None` after the last page. Entries that are inserted or removed between two pages don't change which other entries are on the next pages, but an entry that is moved between two pages can be skipped or found twice.
+    pub fn page_after(
+        &self,
+        cursor: Option<SpatialCursor<ID>>,
+        limit: usize,
+    ) -> (PageEntries<'_, T, ID>, Option<SpatialCursor<ID>>)
+    where
+        ID: Clone,
+    {
+        if limit == 0 {
+            return (Vec::new(), cursor);
+        }
+        let mut entries = self.curve_entries(morton_code);
+        if let Some(cursor) = &cursor {
+            entries.retain(|(position, identity, _, _)| {
+                (*position, *identity) > (cursor.position, &cursor.identity)
+            });
+        }
+        let has_next_page = entries.len() > limit;
+        if has_next_page {
+            // only the entries of this page have to be sorted
+            entries.select_nth_unstable_by(limit - 1, |a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+            entries.truncate(limit);
+        }
+        entries.sort_unstable_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        let next = entries
+            .last()
+            .filter(|_| has_next_page)
+            .map(|(position, identity, _, _)| {
+                SpatialCursor::from_parts(*position, (*identity).clone())
+            });
+        let page = entries
+            .into_iter()
+            .map(|(_, identity, point, value)| (identity, point, value))
+            .collect();
+        (page, next)
+    }
+
+    /// All entries with their position on the curve of `code`, sorted by that position and then by identity
+    fn sorted_entries(&self, code: fn(Rect, Point) -> u32) -> Vec<CurveEntry<'_, T, ID>> {
+        let mut entries = self.curve_entries(code);
+        entries.sort_unstable_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        entries
+    }
+
+    /// All entries with their position on the curve of `code`, in no particular order
+    fn curve_entries(&self, code: fn(Rect, Point) -> u32) -> Vec<CurveEntry<'_, T, ID>> {
+        let mut entries = Vec::new();
+        self.for_each_entry_below(Index::ROOT, &mut |identity, point, value| {
+            entries.push((code(self.rect, point), identity, point, value));
+        });
+        for (identity, (value, point)) in &self.outside_of_range {
+            entries.push((code(self.rect, *point), identity, *point, value));
+        }
+        entries
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N> {
+    /// Pass every entry in the node at `index` and its descendants to `callback`, in Z-order
+    pub(crate) fn for_each_entry_below<'a>(
+        &'a self,
+        index: Index,
+        callback: &mut impl FnMut(&'a ID, Point, &'a T),
+    ) {
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => {
+                for quadrant in Quadrant::all() {
+                    self.for_each_entry_below(index.child_at(quadrant), callback);
+                }
+            }
+            Some(Bucket::Owned(entries)) => {
+                for (ip, value) in entries {
+                    callback(&ip.identity, ip.point, value);
+                }
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! [`Outside`], the entries of a [`QuadTree`](crate::QuadTree) outside of its root rect, with a rect around them so queries that are far away from them can skip all of them.
+
+use crate::{Point, Rect};
+use std::{
+    collections::{btree_map, BTreeMap},
+    ops::Deref,
+};
+
+/// The entries outside of the root rect of a tree, by identity. Reading goes through the map, every change goes through the methods here, which keep [`Outside::touches`] up to date.
+#[derive(Clone, Debug)]
+pub(crate) struct Outside<T, ID> {
+    entries: BTreeMap<ID, (T, Point)>,
+    /// A rect around every entry, or `None` if there are none. This only shrinks when an entry on its edge is removed.
+    bounds: Option<Rect>,
+}
+
+// the bounds follow from the entries
+impl<T: PartialEq, ID: PartialEq> PartialEq for Outside<T, ID> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<T, ID> Default for Outside<T, ID> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T, ID> Deref for Outside<T, ID> {
+    type Target = BTreeMap<ID, (T, Point)>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+impl<T, ID: Ord> FromIterator<(ID, (T, Point))> for Outside<T, ID> {
+    fn from_iter<I: IntoIterator<Item = (ID, (T, Point))>>(entries: I) -> Self {
+        let mut outside = Self::default();
+        for (identity, entry) in entries {
+            outside.insert(identity, entry);
+        }
+        outside
+    }
+}
+
+impl<T, ID> IntoIterator for Outside<T, ID> {
+    type Item = (ID, (T, Point));
+    type IntoIter = btree_map::IntoIter<ID, (T, Point)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, T, ID> IntoIterator for &'a Outside<T, ID> {
+    type Item = (&'a ID, &'a (T, Point));
+    type IntoIter = btree_map::Iter<'a, ID, (T, Point)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl<T, ID: Ord> Outside<T, ID> {
+    /// Returns `false` if no entry can be inside of `rect`, so a query of `rect` can skip every entry
+    pub(crate) fn touches(&self, rect: Rect) -> bool {
+        self.bounds.is_some_and(|bounds| bounds.touches(rect))
+    }
+
+    pub(crate) fn insert(&mut self, identity: ID, entry: (T, Point)) -> Option<(T, Point)> {
+        let point = entry.1;
+        let old = self.entries.insert(identity, entry);
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => bounds.expand_to(point),
+            None => Rect::new(point, point),
+        });
+        if let Some((_, old_point)) = &old {
+            self.shrink_from(*old_point);
+        }
+        old
+    }
+
+    pub(crate) fn remove(&mut self, identity: &ID) -> Option<(T, Point)> {
+        let old = self.entries.remove(identity);
+        if let Some((_, point)) = &old {
+            self.shrink_from(*point);
+        }
+        old
+    }
+
+    /// The value of the entry with `identity`. Its position can only be changed with [`Outside::insert`].
+    pub(crate) fn value_mut(&mut self, identity: &ID) -> Option<&mut T> {
+        self.entries.get_mut(identity).map(|(value, _)| value)
+    }
+
+    /// Every entry, with mutable access to its value
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = (&ID, Point, &mut T)> {
+        self.entries
+            .iter_mut()
+            .map(|(identity, (value, point))| (identity, *point, value))
+    }
+
+    /// Change the position of every entry with `transform`
+    pub(crate) fn transform_points(&mut self, mut transform: impl FnMut(Point) -> Point) {
+        for (_, point) in self.entries.values_mut() {
+            *point = transform(*point);
+        }
+        self.bounds = self.compute_bounds();
+    }
+
+    /// Remove every entry, and return them
+    pub(crate) fn take(&mut self) -> BTreeMap<ID, (T, Point)> {
+        self.bounds = None;
+        std::mem::take(&mut self.entries)
+    }
+
+    /// Compute the bounds again if `point` of a removed entry was on their edge, so they do not stay larger than needed
+    fn shrink_from(&mut self, point: Point) {
+        let Some(bounds) = self.bounds else { return };
+        let (top_left, bottom_right) = (bounds.top_left(), bounds.bottom_right());
+        if point.x == top_left.x
+            || point.y == top_left.y
+            || point.x == bottom_right.x
+            || point.y == bottom_right.y
+        {
+            self.bounds = self.compute_bounds();
+        }
+    }
+
+    fn compute_bounds(&self) -> Option<Rect> {
+        let mut points = self.entries.values().map(|(_, point)| *point);
+        let first = points.next()?;
+        Some(points.fold(Rect::new(first, first), Rect::expand_to))
+    }
+}
@@ -0,0 +1,227 @@
+//! [`QuadTree::split_for_parallel`], which splits a tree into read-only views of disjoint subtrees, e.g. to hand a part of the map to every worker of a job system.
+
+use crate::{
+    bucket::Bucket, index::Index, point::Quadrant, FindRangeCtx, NodeId, Point, QuadTree, Rect, R32,
+};
+use std::convert::Infallible;
+use std::ops::ControlFlow;
+
+/// A read-only view of some subtrees of a [`QuadTree`], created by [`QuadTree::split_for_parallel`].
+///
+/// The queries of a view only find the entries below its nodes, and the entries outside of the range of the tree if [`SubtreeView::includes_out_of_range`] returns `true`. The views of a single split never share an entry, so the results of the same query on all of them together are the same as the results of that query on the tree.
+#[derive(Clone, Debug)]
+pub struct SubtreeView<'a, T, ID, const N: usize> {
+    tree: &'a QuadTree<T, ID, N>,
+    /// The roots of the subtrees of this view, in depth-first order
+    nodes: Vec<Index>,
+    outside: bool,
+    len: usize,
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Split this tree into `parts` views of disjoint subtrees with about as many entries each, which can be queried from different threads at the same time.
+    ///
+    /// The subtrees of a view are next to each other in depth-first order, so a view covers a compact area if the entries are spread evenly. There are always `parts` views, but a view can be empty if this tree has fewer subtrees than `parts`, and a single leaf is never split over several views. The entries outside of the range of this tree are part of the last view.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parts` is `0`.
+    #[must_use]
+    pub fn split_for_parallel(&self, parts: usize) -> Vec<SubtreeView<'_, T, ID, N>> {
+        assert!(parts > 0, "A tree can not be split into 0 parts");
+        let total = self.count_entries(Index::ROOT);
+        let mut nodes = Vec::new();
+        self.collect_subtrees(Index::ROOT, total.div_ceil(parts), &mut nodes);
+
+        let mut views = (0..parts)
+            .map(|part| SubtreeView {
+                tree: self,
+                nodes: Vec::new(),
+                outside: part == parts - 1,
+                len: 0,
+            })
+            .collect::<Vec<_>>();
+        let mut before = 0;
+        for (index, count) in nodes {
+            // a subtree goes to the view whose share of the entries it starts in
+            let part = (before * parts)
+                .checked_div(total)
+                .unwrap_or(0)
+                .min(parts - 1);
+            views[part].nodes.push(index);
+            views[part].len += count;
+            before += count;
+        }
+        views[parts - 1].len += self.outside_of_range.len();
+        views
+    }
+
+    /// Push the largest subtrees below `index` with at most `target` entries to `nodes` with their amount of entries, or the leaves that are larger than that
+    fn collect_subtrees(&self, index: Index, target: usize, nodes: &mut Vec<(Index, usize)>) {
+        let count = match self.items.get(index.to_idx()) {
+            None => return,
+            Some(_) => self.count_entries(index),
+        };
+        if count <= target || !self.is_nested(index) {
+            nodes.push((index, count));
+            return;
+        }
+        for quadrant in Quadrant::all() {
+            // a nested node at the maximum depth is corrupt, and is skipped instead of panicking
+            let Some(child) = index.checked_child_at(quadrant) else {
+                break;
+            };
+            self.collect_subtrees(child, target, nodes);
+        }
+    }
+}
+
+impl<'a, T, ID, const N: usize> SubtreeView<'a, T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// The tree that this view is a part of
+    #[must_use]
+    pub fn tree(&self) -> &'a QuadTree<T, ID, N> {
+        self.tree
+    }
+
+    /// The roots of the subtrees of this view, in depth-first order. Use [`QuadTree::node_rect`] for the area that they cover.
+    #[must_use]
+    pub fn nodes(&self) -> impl ExactSizeIterator<Item = NodeId> + '_ {
+        self.nodes
+            .iter()
+            .map(|index| NodeId(self.tree.public_index(*index)))
+    }
+
+    /// Returns `true` if this view has the entries outside of the range of the tree
+    #[must_use]
+    pub fn includes_out_of_range(&self) -> bool {
+        self.outside
+    }
+
+    /// The amount of entries in this view
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if this view has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The position and value of the entry with the given identity, or `None` if it is not found or not part of this view
+    #[must_use]
+    pub fn get(&self, identity: &ID) -> Option<(Point, &'a T)> {
+        let (_, index, _) = self.tree.identity_to_point.get(identity)?;
+        let included = match index {
+            Some(index) => self.contains_node(*index),
+            None => self.outside,
+        };
+        if included {
+            self.tree.get(identity)
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over all entries of this view, subtree by subtree, followed by the entries outside of the range of the tree if this view has them
+    pub fn iter(&self) -> impl Iterator<Item = (&'a ID, Point, &'a T)> + 'a {
+        let tree = self.tree;
+        let mut stack = self.nodes.iter().rev().copied().collect::<Vec<_>>();
+        std::iter::from_fn(move || loop {
+            let index = stack.pop()?;
+            match tree.items.get(index.to_idx()) {
+                Some(Bucket::Owned(entries)) => return Some(entries),
+                Some(Bucket::Nested) => stack.extend(
+                    Quadrant::all()
+                        .into_iter()
+                        .rev()
+                        .filter_map(|quadrant| index.checked_child_at(quadrant)),
+                ),
+                None => {}
+            }
+        })
+        .flatten()
+        .map(|(ip, value)| (&ip.identity, ip.point, value))
+        .chain(
+            self.outside
+                .then(|| tree.iter_out_of_range())
+                .into_iter()
+                .flatten(),
+        )
+    }
+
+    /// Find all entries of this view with a distance less than `range` away from point `center`, see [`QuadTree::find_range`]
+    pub fn find_range(
+        &self,
+        center: Point,
+        range: R32,
+        mut callback: impl FnMut(&ID, Point, &'a T),
+    ) {
+        let ctx = FindRangeCtx::new(center, range);
+        for index in &self.nodes {
+            let _: ControlFlow<Infallible> = self.tree.find_range_inner(
+                self.tree.rect.get_index_rect(*index),
+                *index,
+                &ctx,
+                &mut |identity, point, value, _| {
+                    callback(identity, point, value);
+                    ControlFlow::Continue(())
+                },
+            );
+        }
+        if !self.outside || !self.tree.outside_of_range.touches(ctx.full_rect) {
+            return;
+        }
+        for (ip, (value, point)) in &self.tree.outside_of_range {
+            if ctx.point_in_range(*point) {
+                callback(ip, *point, value);
+            }
+        }
+    }
+
+    /// Find all entries of this view inside of the rect between `top_left` and `bottom_right`, see [`QuadTree::find_rect`]
+    pub fn find_rect(
+        &self,
+        top_left: Point,
+        bottom_right: Point,
+        mut callback: impl FnMut(&ID, Point, &'a T),
+    ) {
+        let area = Rect::from_corners(top_left, bottom_right);
+        for index in &self.nodes {
+            self.tree.find_rect_inner(
+                self.tree.rect.get_index_rect(*index),
+                *index,
+                area,
+                &mut |identity, point, value, _| callback(identity, point, value),
+            );
+        }
+        if !self.outside || !self.tree.outside_of_range.touches(area) {
+            return;
+        }
+        for (ip, (value, point)) in &self.tree.outside_of_range {
+            if area.contains(*point) {
+                callback(ip, *point, value);
+            }
+        }
+    }
+
+    /// Returns `true` if the node at `index` is one of the nodes of this view or below one of them
+    fn contains_node(&self, mut index: Index) -> bool {
+        loop {
+            if self.nodes.contains(&index) {
+                return true;
+            }
+            let Some(parent) = index.parent() else {
+                return false;
+            };
+            index = parent;
+        }
+    }
+}
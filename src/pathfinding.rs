@@ -0,0 +1,91 @@
+//! A* path planning over the empty leaves of a [`RegionQuadTree`].
+//!
+//! Each empty leaf is a node in the graph, and leaves that share an edge are connected. The cost of moving between two leaves is the distance between their centers. Large open areas are a single leaf, so this searches far fewer nodes than a uniform grid would.
+
+use crate::{
+    index::Index, neighbors::for_each_leaf_neighbor, point::Rect, region::RegionNode, Point,
+    RegionQuadTree, R32,
+};
+use noisy_float::prelude::Float;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+};
+
+impl RegionQuadTree {
+    /// Find the shortest path through the empty area of this tree from `start` to `goal`, using A* over the empty leaves.
+    ///
+    /// The path is returned as the centers of every leaf that is passed through, starting with the leaf of `start` and ending with the leaf of `goal`.
+    ///
+    /// Returns `None` if `start` or `goal` are outside of this tree or in a filled area, or if there is no path between them.
+    #[must_use]
+    pub fn find_path(&self, start: Point, goal: Point) -> Option<Vec<Point>> {
+        let (start_index, start_rect, false) = self.leaf_at(start)? else {
+            return None;
+        };
+        let (goal_index, _, false) = self.leaf_at(goal)? else {
+            return None;
+        };
+        let distance = |a: Point, b: Point| a.distance_squared_to(b).sqrt();
+        let node_kind = |index: Index| self.node_kind(index);
+
+        // for every leaf that has been reached: the cost to get there, and the leaf we came from
+        let mut visited = BTreeMap::<Index, (R32, Option<Index>, Rect)>::new();
+        let mut open = BinaryHeap::new();
+        visited.insert(start_index, (R32::default(), None, start_rect));
+        open.push(Reverse((
+            distance(start_rect.middle(), goal),
+            R32::default(),
+            start_index,
+        )));
+
+        while let Some(Reverse((_, cost, index))) = open.pop() {
+            let (best_cost, _, rect) = visited[&index];
+            if cost > best_cost {
+                // we already found a shorter route to this leaf
+                continue;
+            }
+            if index == goal_index {
+                return Some(Self::reconstruct_path(&visited, index));
+            }
+            let center = rect.middle();
+            for_each_leaf_neighbor(self.rect(), index, &node_kind, |neighbor, neighbor_rect| {
+                if self.node(neighbor) != RegionNode::Empty {
+                    return;
+                }
+                let neighbor_center = neighbor_rect.middle();
+                let neighbor_cost = cost + distance(center, neighbor_center);
+                if visited
+                    .get(&neighbor)
+                    .is_some_and(|(existing, _, _)| *existing <= neighbor_cost)
+                {
+                    return;
+                }
+                visited.insert(neighbor, (neighbor_cost, Some(index), neighbor_rect));
+                open.push(Reverse((
+                    neighbor_cost + distance(neighbor_center, goal),
+                    neighbor_cost,
+                    neighbor,
+                )));
+            });
+        }
+        None
+    }
+
+    fn reconstruct_path(
+        visited: &BTreeMap<Index, (R32, Option<Index>, Rect)>,
+        mut index: Index,
+    ) -> Vec<Point> {
+        let mut path = Vec::new();
+        loop {
+            let (_, previous, rect) = visited[&index];
+            path.push(rect.middle());
+            match previous {
+                Some(previous) => index = previous,
+                None => break,
+            }
+        }
+        path.reverse();
+        path
+    }
+}
@@ -1,3 +1,4 @@
+use crate::BoundsError;
 use noisy_float::{
     prelude::Float,
     types::{r32, R32},
@@ -11,9 +12,9 @@ use std::ops;
 /// [`QuadTree`]: struct.QuadTree.html
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Point {
-    ///
+    /// The horizontal coordinate
     pub x: R32,
-    ///
+    /// The vertical coordinate
     pub y: R32,
 }
 
@@ -35,6 +36,29 @@ impl Point {
         }
     }
 
+    /// Create a point at `x, y`. Unlike [`Point::new`], this also checks in release mode.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`BoundsError::NotFinite`] if `x` or `y` are `NaN` or `Infinity`.
+    pub fn try_new(x: f32, y: f32) -> Result<Self, BoundsError> {
+        match (R32::try_new(x), R32::try_new(y)) {
+            (Some(x), Some(y)) => Ok(Self { x, y }),
+            _ => Err(BoundsError::NotFinite { x, y }),
+        }
+    }
+
+    /// Create a point at `x, y`, where `NaN` is replaced with `0.0`, `Infinity` with [`f32::MAX`] and negative `Infinity` with [`f32::MIN`].
+    ///
+    /// This is meant for input that is almost always valid, e.g. the output of a physics simulation, where a bad value should not panic.
+    #[must_use]
+    pub fn new_clamped(x: f32, y: f32) -> Self {
+        Self {
+            x: clamped_r32(x),
+            y: clamped_r32(y),
+        }
+    }
+
     /// Create a point at `x, y` with `noisy_float`'s [`R32`]
     #[must_use]
     pub const fn new_noisy_float(x: R32, y: R32) -> Self {
@@ -54,6 +78,15 @@ impl Point {
     }
 }
 
+/// Turn `value` into an [`R32`] without panicking, see [`Point::new_clamped`]
+pub(crate) fn clamped_r32(value: f32) -> R32 {
+    if value.is_nan() {
+        r32(0.0)
+    } else {
+        r32(value.clamp(f32::MIN, f32::MAX))
+    }
+}
+
 impl ops::Add<R32> for Point {
     type Output = Point;
 
@@ -64,6 +97,16 @@ impl ops::Add<R32> for Point {
         }
     }
 }
+impl ops::Add for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Point) -> Self {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
 impl ops::Sub<R32> for Point {
     type Output = Point;
 
@@ -85,8 +128,11 @@ impl ops::Neg for Point {
     }
 }
 
+/// An axis-aligned rectangle in the [`QuadTree`] graph, spanning from a top-left to a bottom-right [`Point`].
+///
+/// [`QuadTree`]: struct.QuadTree.html
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) struct Rect {
+pub struct Rect {
     top: R32,
     left: R32,
     bottom: R32,
@@ -94,6 +140,8 @@ pub(crate) struct Rect {
 }
 
 impl Rect {
+    /// Create a new [`Rect`] that spans from `top_left` to `bottom_right`
+    #[must_use]
     pub const fn new(top_left: Point, bottom_right: Point) -> Self {
         Self {
             top: top_left.y,
@@ -103,13 +151,41 @@ impl Rect {
         }
     }
 
+    /// Create a new [`Rect`] from any two opposite corners, e.g. a top-right and a bottom-left corner
+    #[must_use]
+    pub fn from_corners(a: Point, b: Point) -> Self {
+        Self {
+            top: a.y.min(b.y),
+            left: a.x.min(b.x),
+            bottom: a.y.max(b.y),
+            right: a.x.max(b.x),
+        }
+    }
+
+    /// The top-left corner of this rect
+    #[must_use]
+    pub const fn top_left(self) -> Point {
+        Point::new_noisy_float(self.left, self.top)
+    }
+
+    /// The bottom-right corner of this rect
+    #[must_use]
+    pub const fn bottom_right(self) -> Point {
+        Point::new_noisy_float(self.right, self.bottom)
+    }
+
+    /// The center of this rect
+    #[must_use]
     pub fn middle(self) -> Point {
+        // halving first can not overflow, and gives the same result as halving the sum
         Point::new_noisy_float(
-            (self.left + self.right) / 2.0,
-            (self.top + self.bottom) / 2.0,
+            self.left / 2.0 + self.right / 2.0,
+            self.top / 2.0 + self.bottom / 2.0,
         )
     }
 
+    /// Returns `true` if `point` lies inside of this rect. Points on the edge of the rect are considered to be inside.
+    #[must_use]
     pub fn contains(self, point: Point) -> bool {
         !(self.left > point.x
             || self.right < point.x
@@ -117,7 +193,21 @@ impl Rect {
             || self.bottom < point.y)
     }
 
-    pub fn get_child_at(self, quadrant: Quadrant) -> Rect {
+    /// The largest of the width and the height of this rect, which is infinite if it does not fit in a float
+    pub(crate) fn extent(self) -> f32 {
+        (self.right.raw() - self.left.raw()).max(self.bottom.raw() - self.top.raw())
+    }
+
+    /// The distance between the largest coordinate of this rect and the next larger float, times `ulps`
+    pub(crate) fn ulps(self, ulps: f32) -> R32 {
+        let largest = [self.top, self.left, self.bottom, self.right]
+            .into_iter()
+            .map(Float::abs)
+            .fold(R32::new(0.), R32::max);
+        largest * f32::EPSILON * ulps
+    }
+
+    pub(crate) fn get_child_at(self, quadrant: Quadrant) -> Rect {
         let middle = self.middle();
         match quadrant {
             Quadrant::TopLeft => Rect {
@@ -147,7 +237,7 @@ impl Rect {
         }
     }
 
-    pub fn get_quadrant(self, point: Point) -> (Rect, Quadrant) {
+    pub(crate) fn get_quadrant(self, point: Point) -> (Rect, Quadrant) {
         let middle = self.middle();
         let quadrant = if point.x < middle.x {
             if point.y < middle.y {
@@ -167,24 +257,117 @@ impl Rect {
         index.iter_from_root().fold(self, Rect::get_child_at)
     }
 
-    pub(crate) fn intersects(&self, rect: Rect) -> bool {
-        !(self.left < rect.right
+    /// Returns `true` if this rect and `rect` share an area that is larger than zero.
+    #[cfg(feature = "region")]
+    pub(crate) fn overlaps(self, rect: Rect) -> bool {
+        self.left < rect.right
             && self.right > rect.left
-            && self.top > rect.bottom
-            && self.bottom < rect.top)
+            && self.top < rect.bottom
+            && self.bottom > rect.top
+    }
+
+    /// Returns `true` if `point` lies inside of this rect, including the top and left edge but excluding the bottom and right edge.
+    ///
+    /// Unlike [`contains`], a point on an edge shared by two neighboring rects is only inside of one of them.
+    ///
+    /// [`contains`]: #method.contains
+    #[cfg(feature = "region")]
+    pub(crate) fn contains_half_open(self, point: Point) -> bool {
+        self.left <= point.x && point.x < self.right && self.top <= point.y && point.y < self.bottom
+    }
+
+    /// Returns `true` if `point` is in this rect when it is a node of a tree with `tree_rect`. This is the node that an insert of `point` would descend to: a point on the edge between two nodes is in the right or bottom one, and only the edges of the tree itself are inclusive.
+    pub(crate) fn contains_as_leaf(self, tree_rect: Rect, point: Point) -> bool {
+        tree_rect.contains(point)
+            && self.left <= point.x
+            && (point.x < self.right || self.right == tree_rect.right)
+            && self.top <= point.y
+            && (point.y < self.bottom || self.bottom == tree_rect.bottom)
+    }
+
+    /// Returns `true` if `rect` lies completely inside of this rect.
+    #[cfg(feature = "region")]
+    pub(crate) fn contains_rect(self, rect: Rect) -> bool {
+        self.left <= rect.left
+            && self.right >= rect.right
+            && self.top <= rect.top
+            && self.bottom >= rect.bottom
+    }
+
+    /// Returns `true` if this rect and `rect` overlap or touch, including touching only at a corner.
+    pub(crate) fn touches(self, rect: Rect) -> bool {
+        self.left <= rect.right
+            && self.right >= rect.left
+            && self.top <= rect.bottom
+            && self.bottom >= rect.top
+    }
+
+    /// The smallest squared distance between a point in this rect and a point in `rect`, which is `0` if they touch.
+    pub(crate) fn min_distance_squared(self, rect: Rect) -> R32 {
+        let axis = |min: R32, max: R32, other_min: R32, other_max: R32| {
+            (other_min.raw() - max.raw())
+                .max(min.raw() - other_max.raw())
+                .max(0.0)
+        };
+        let dx = axis(self.left, self.right, rect.left, rect.right);
+        let dy = axis(self.top, self.bottom, rect.top, rect.bottom);
+        R32::try_new(dx * dx + dy * dy).unwrap_or_else(R32::max_value)
+    }
+
+    /// The smallest rect that contains both this rect and `point`
+    pub(crate) fn expand_to(self, point: Point) -> Rect {
+        Rect {
+            top: self.top.min(point.y),
+            left: self.left.min(point.x),
+            bottom: self.bottom.max(point.y),
+            right: self.right.max(point.x),
+        }
+    }
+
+    /// The point in this rect that is closest to `point`, which is `point` itself if it is in this rect
+    pub(crate) fn clamp(self, point: Point) -> Point {
+        Point::new_noisy_float(
+            point.x.max(self.left).min(self.right),
+            point.y.max(self.top).min(self.bottom),
+        )
+    }
+
+    /// The largest squared distance between `point` and a point in this rect
+    pub(crate) fn max_distance_squared(self, point: Point) -> R32 {
+        let axis = |min: R32, max: R32, value: R32| (value - min).abs().max((max - value).abs());
+        let dx = axis(self.left, self.right, point.x).raw();
+        let dy = axis(self.top, self.bottom, point.y).raw();
+        R32::try_new(dx * dx + dy * dy).unwrap_or_else(R32::max_value)
+    }
+
+    /// Returns `true` if this rect and `rect` lie next to each other and share (a part of) an edge.
+    ///
+    /// Rects that only touch at a corner do not share an edge.
+    pub(crate) fn shares_edge(self, rect: Rect) -> bool {
+        let overlaps_horizontally = self.left < rect.right && self.right > rect.left;
+        let overlaps_vertically = self.top < rect.bottom && self.bottom > rect.top;
+        ((self.right == rect.left || self.left == rect.right) && overlaps_vertically)
+            || ((self.bottom == rect.top || self.top == rect.bottom) && overlaps_horizontally)
     }
 }
 
+/// One of the 4 children of a node. The y axis grows downwards, so the top children have the lowest y values, unless the tree is created with [`YAxis::Up`].
 #[repr(u8)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Quadrant {
+    /// The child with the lowest x and y values
     TopLeft = 0b00,
+    /// The child with the highest x and lowest y values
     TopRight = 0b01,
+    /// The child with the lowest x and highest y values
     BottomLeft = 0b10,
+    /// The child with the highest x and y values
     BottomRight = 0b11,
 }
 
 impl Quadrant {
+    /// All quadrants, in the order that children are stored in
+    #[must_use]
     pub const fn all() -> [Quadrant; 4] {
         [
             Self::TopLeft,
@@ -194,7 +377,7 @@ impl Quadrant {
         ]
     }
 
-    pub const fn from_bits(bits: u8) -> Self {
+    pub(crate) const fn from_bits(bits: u8) -> Self {
         match bits {
             0b00 => Self::TopLeft,
             0b01 => Self::TopRight,
@@ -204,3 +387,15 @@ impl Quadrant {
         }
     }
 }
+
+/// The direction of the y axis of a [`QuadTree`](crate::QuadTree), which decides which children are the top ones of a [`Quadrant`], see [`QuadTree::with_y_axis`](crate::QuadTree::with_y_axis).
+///
+/// The rects of nodes are not affected: [`Rect::top_left`] is always the corner with the lowest coordinates.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum YAxis {
+    /// The y axis grows downwards, like in screen coordinates, so [`Quadrant::TopLeft`] has the lowest y values
+    #[default]
+    Down,
+    /// The y axis grows upwards, like in OpenGL, so [`Quadrant::TopLeft`] has the highest y values
+    Up,
+}
@@ -0,0 +1,23 @@
+//! Trees with `T = ()`, which only store positions, e.g. when the values live in an ECS.
+//!
+//! `()` takes no memory, so an entry of such a tree takes exactly as much room as its [`IdentityPoint`], and moving an entry only moves its identity and position.
+
+use crate::{IdentityPoint, Point, QuadTree, R32};
+
+/// A [`QuadTree`] that only stores positions, see the [module documentation](self)
+pub type PointIndex<ID, const N: usize> = QuadTree<(), ID, N>;
+
+impl<ID, const N: usize> QuadTree<(), ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Insert `identity` at `point`, see [`QuadTree::insert`]
+    pub fn insert_point(&mut self, identity: ID, point: Point) {
+        self.insert(IdentityPoint { identity, point }, ());
+    }
+
+    /// Find all identities with a distance less than `range` away from point `center`, see [`QuadTree::find_range_ids`]
+    pub fn find_points(&self, center: Point, range: R32, callback: impl FnMut(&ID, Point)) {
+        self.find_range_ids(center, range, callback);
+    }
+}
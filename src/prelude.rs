@@ -0,0 +1,5 @@
+//! The types that most users of a [`QuadTree`] need, for a single `use whquadtree::prelude::*;`.
+//!
+//! The optional parts of this crate, like [`QuadForest`](crate::QuadForest) or [`RegionQuadTree`](crate::RegionQuadTree), are enabled by cargo features, and are imported from the crate root. Every feature except `testkit` and the ones for `async`, `ffi`, `mmap`, `paranoid` and `pathfinding` is enabled by default, so users that only need a [`QuadTree`] can disable the default features to compile less.
+
+pub use crate::{r32, IdentityPoint, Point, QuadTree, RangeBoundary, RangeOptions, Rect, R32};
@@ -0,0 +1,84 @@
+//! [`RangeIter`], an iterator over the entries in range of a point, for code where a callback is hard to borrow-check.
+
+use crate::{
+    bucket::Bucket, index::Index, point::Quadrant, FindRangeCtx, IdentityPoint, Point, QuadTree,
+    Rect, R32,
+};
+use smallvec::SmallVec;
+use std::{collections::btree_map, slice};
+
+/// The nodes that still have to be visited. A node is only pushed with its 3 siblings, so even the deepest tree needs at most `15 * 3 + 4` nodes, and this never allocates.
+type Stack = SmallVec<[(Rect, Index); 64]>;
+
+/// An iterator over the entries within range of a point, created by [`QuadTree::iter_range`]
+pub struct RangeIter<'a, T, ID, const N: usize> {
+    tree: &'a QuadTree<T, ID, N>,
+    ctx: FindRangeCtx,
+    stack: Stack,
+    leaf: slice::Iter<'a, (IdentityPoint<ID>, T)>,
+    outside: btree_map::Iter<'a, ID, (T, Point)>,
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Iterate over all entries with a distance less than `range` away from point `center`. This finds the same entries as [`QuadTree::find_range`], in the same order, and also does not allocate.
+    ///
+    /// Unlike the callback of [`QuadTree::find_range`], the iterator can be returned from a function or stored in a struct, which can be easier to fit into generic code. It is also lazy, so a query that stops early with `?`, `break` or an adapter like [`Iterator::find`] does not visit the rest of the nodes.
+    #[doc(alias = "find_range_iter")]
+    #[must_use]
+    pub fn iter_range(&self, center: Point, range: R32) -> RangeIter<'_, T, ID, N> {
+        let mut stack = Stack::new();
+        stack.push((self.rect, Index::ROOT));
+        let ctx = FindRangeCtx::new(center, range);
+        let outside = if self.outside_of_range.touches(ctx.full_rect) {
+            self.outside_of_range.iter()
+        } else {
+            btree_map::Iter::default()
+        };
+        RangeIter {
+            tree: self,
+            ctx,
+            stack,
+            leaf: [].iter(),
+            outside,
+        }
+    }
+}
+
+impl<'a, T, ID, const N: usize> Iterator for RangeIter<'a, T, ID, N> {
+    type Item = (&'a ID, Point, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            for (ip, value) in self.leaf.by_ref() {
+                if self.ctx.point_in_range(ip.point) {
+                    return Some((&ip.identity, ip.point, value));
+                }
+            }
+            let Some((rect, index)) = self.stack.pop() else {
+                break;
+            };
+            if !self.ctx.contains_rect(rect) {
+                continue;
+            }
+            match self.tree.items.get(index.to_idx()) {
+                Some(Bucket::Owned(entries)) => self.leaf = entries.iter(),
+                Some(Bucket::Nested) => {
+                    // the children are pushed in reverse, so they are visited in the same order as `find_range`
+                    for child in Quadrant::all().into_iter().rev() {
+                        if let Some(child_index) = index.checked_child_at(child) {
+                            self.stack.push((rect.get_child_at(child), child_index));
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
+        self.outside
+            .by_ref()
+            .find(|(_, (_, point))| self.ctx.point_in_range(*point))
+            .map(|(identity, (value, point))| (identity, *point, value))
+    }
+}
@@ -0,0 +1,253 @@
+//! [`Recorder`], which logs the changes to a tree into a [`Script`] that can be replayed and shrunk, to turn a bug report into a small reproducer.
+
+use crate::{IdentityPoint, MaintenanceBudget, Point, QuadTree, YAxis};
+
+/// A change to a tree, which is logged by a [`Recorder`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation<T, ID> {
+    /// [`QuadTree::insert`]
+    Insert {
+        /// The identity of the entry
+        identity: ID,
+        /// The position of the entry
+        point: Point,
+        /// The value of the entry
+        value: T,
+    },
+    /// [`QuadTree::update`]
+    Update {
+        /// The identity of the entry
+        identity: ID,
+        /// The new position of the entry
+        point: Point,
+    },
+    /// [`QuadTree::update_point_and_value`], with the value that the callback left behind
+    UpdateValue {
+        /// The identity of the entry
+        identity: ID,
+        /// The new position of the entry
+        point: Point,
+        /// The new value of the entry
+        value: T,
+    },
+    /// [`QuadTree::try_remove`]
+    Remove {
+        /// The identity of the entry
+        identity: ID,
+    },
+    /// [`QuadTree::set_deferred_merges`]
+    SetDeferredMerges(bool),
+    /// [`QuadTree::maintenance`]
+    Maintenance(MaintenanceBudget),
+}
+
+/// The changes to a tree that were logged by a [`Recorder`], which can be replayed on a new tree with [`Script::replay`].
+///
+/// A script can also be written by hand, e.g. from the log of a bug report.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Script<T, ID> {
+    /// The top-left corner of the tree, see [`QuadTree::with_y_axis`]
+    pub top_left: Point,
+    /// The bottom-right corner of the tree, see [`QuadTree::with_y_axis`]
+    pub bottom_right: Point,
+    /// The direction of the y axis of the tree
+    pub y_axis: YAxis,
+    /// The changes to the tree, in order
+    pub operations: Vec<Operation<T, ID>>,
+}
+
+/// Logs every change that is made through it to a tree into a [`Script`], created by [`Recorder::attach`].
+///
+/// Only the changes are wrapped, [`Recorder::tree`] can be used for every query.
+#[derive(Debug)]
+pub struct Recorder<'a, T, ID, const N: usize> {
+    tree: &'a mut QuadTree<T, ID, N>,
+    script: Script<T, ID>,
+}
+
+impl<'a, T, ID, const N: usize> Recorder<'a, T, ID, N>
+where
+    T: Clone,
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Start recording the changes to `tree`.
+    ///
+    /// The script starts with an insert of every entry that is already in `tree`, in the order of their identities. The tree that is replayed from it has the same entries, but can be split differently than `tree` if `tree` was split by entries that were removed since.
+    pub fn attach(tree: &'a mut QuadTree<T, ID, N>) -> Self {
+        let (top_left, bottom_right) = tree.bounds();
+        let mut operations = Vec::new();
+        if tree.pending_merges.is_some() {
+            operations.push(Operation::SetDeferredMerges(true));
+        }
+        for identity in tree.identity_to_point.keys() {
+            if let Some((point, value)) = tree.get(identity) {
+                operations.push(Operation::Insert {
+                    identity: identity.clone(),
+                    point,
+                    value: value.clone(),
+                });
+            }
+        }
+        Self {
+            script: Script {
+                top_left,
+                bottom_right,
+                y_axis: tree.y_axis,
+                operations,
+            },
+            tree,
+        }
+    }
+
+    /// The tree that is recorded
+    #[must_use]
+    pub fn tree(&self) -> &QuadTree<T, ID, N> {
+        self.tree
+    }
+
+    /// The changes that were recorded so far
+    #[must_use]
+    pub fn script(&self) -> &Script<T, ID> {
+        &self.script
+    }
+
+    /// Stop recording, and return the changes that were recorded
+    #[must_use]
+    pub fn detach(self) -> Script<T, ID> {
+        self.script
+    }
+
+    /// Insert a value `value` at the given `point`, see [`QuadTree::insert`]
+    pub fn insert(&mut self, point: IdentityPoint<ID>, value: T) {
+        self.script.operations.push(Operation::Insert {
+            identity: point.identity.clone(),
+            point: point.point,
+            value: value.clone(),
+        });
+        self.tree.insert(point, value);
+    }
+
+    /// Update the given identity to the new point, see [`QuadTree::update`].
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise
+    pub fn update(&mut self, identity: ID, point: Point) -> bool {
+        self.script.operations.push(Operation::Update {
+            identity: identity.clone(),
+            point,
+        });
+        self.tree.update(identity, point)
+    }
+
+    /// Update the given identity to the new point, with the opportunity to update the value, see [`QuadTree::update_point_and_value`]. The value is recorded after `callback` returns.
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise
+    pub fn update_point_and_value(
+        &mut self,
+        identity: ID,
+        point: Point,
+        callback: impl FnOnce(&mut T),
+    ) -> bool {
+        let mut new_value = None;
+        let updated = self
+            .tree
+            .update_point_and_value(identity.clone(), point, |value| {
+                callback(value);
+                new_value = Some(value.clone());
+            });
+        if let Some(value) = new_value {
+            self.script.operations.push(Operation::UpdateValue {
+                identity,
+                point,
+                value,
+            });
+        }
+        updated
+    }
+
+    /// Try to remove the entry with the given identity, see [`QuadTree::try_remove`]
+    pub fn try_remove(&mut self, identity: &ID) -> Option<(T, Point)> {
+        self.script.operations.push(Operation::Remove {
+            identity: identity.clone(),
+        });
+        self.tree.try_remove(identity)
+    }
+
+    /// Decide if nodes are merged by removals or by [`Recorder::maintenance`], see [`QuadTree::set_deferred_merges`]
+    pub fn set_deferred_merges(&mut self, deferred: bool) {
+        self.script
+            .operations
+            .push(Operation::SetDeferredMerges(deferred));
+        self.tree.set_deferred_merges(deferred);
+    }
+
+    /// Do the pending merges within `budget`, see [`QuadTree::maintenance`]
+    pub fn maintenance(&mut self, budget: MaintenanceBudget) -> usize {
+        self.script.operations.push(Operation::Maintenance(budget));
+        self.tree.maintenance(budget)
+    }
+}
+
+impl<T, ID> Script<T, ID>
+where
+    T: Clone,
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Build a new tree with bucket size `N`, and apply every operation of this script to it
+    #[must_use]
+    pub fn replay<const N: usize>(&self) -> QuadTree<T, ID, N> {
+        let mut tree = QuadTree::with_y_axis(self.top_left, self.bottom_right, self.y_axis);
+        for operation in &self.operations {
+            match operation.clone() {
+                Operation::Insert {
+                    identity,
+                    point,
+                    value,
+                } => tree.insert(IdentityPoint { identity, point }, value),
+                Operation::Update { identity, point } => {
+                    tree.update(identity, point);
+                }
+                Operation::UpdateValue {
+                    identity,
+                    point,
+                    value,
+                } => {
+                    tree.update_point_and_value(identity, point, |old| *old = value);
+                }
+                Operation::Remove { identity } => {
+                    tree.try_remove(&identity);
+                }
+                Operation::SetDeferredMerges(deferred) => tree.set_deferred_merges(deferred),
+                Operation::Maintenance(budget) => {
+                    tree.maintenance(budget);
+                }
+            }
+        }
+        tree
+    }
+
+    /// Find a smaller script for which `fails` still returns `true`, e.g. a script whose replay still panics or breaks an assertion. `fails` should return `true` for this script.
+    ///
+    /// Operations are removed in chunks, starting with halves of the script, and every removal after which `fails` still returns `true` is kept. The result is only locally minimal: removing any single operation from it makes `fails` return `false`, but a smaller script may exist.
+    #[must_use]
+    pub fn shrink(&self, mut fails: impl FnMut(&Self) -> bool) -> Self {
+        let mut script = self.clone();
+        let mut chunk = script.operations.len().max(1).div_ceil(2);
+        loop {
+            let mut start = 0;
+            while start < script.operations.len() {
+                let mut candidate = script.clone();
+                let end = (start + chunk).min(candidate.operations.len());
+                candidate.operations.drain(start..end);
+                if fails(&candidate) {
+                    script = candidate;
+                } else {
+                    start += chunk;
+                }
+            }
+            if chunk == 1 {
+                return script;
+            }
+            chunk = chunk.div_ceil(2);
+        }
+    }
+}
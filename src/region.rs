@@ -0,0 +1,482 @@
+//! Contains a [`RegionQuadTree`], which stores filled and empty area instead of a set of points.
+//!
+//! This uses the same flat [`Index`] layout as the [`QuadTree`], but every leaf is either completely filled or completely empty. Whenever all four children of a node end up with the same state, they are merged back into their parent.
+//!
+//! [`QuadTree`]: ../struct.QuadTree.html
+
+use crate::{
+    index::Index,
+    neighbors::{for_each_leaf, NodeKind},
+    point::{Quadrant, Rect},
+    Point,
+};
+
+/// The maximum depth that can be encoded in an [`Index`]
+const MAX_DEPTH: usize = 15;
+
+/// A single slot in the flat node list of a [`RegionQuadTree`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum RegionNode {
+    /// This node is split, its children should be scanned
+    Nested,
+    /// This node is a leaf without any filled area
+    Empty,
+    /// This node is a leaf that is completely filled
+    Filled,
+    /// This slot is not part of the tree, e.g. because its parent is a leaf or because it was merged away
+    Unused,
+}
+
+impl RegionNode {
+    const fn leaf(filled: bool) -> Self {
+        if filled {
+            Self::Filled
+        } else {
+            Self::Empty
+        }
+    }
+
+    pub(crate) const fn filled(self) -> Option<bool> {
+        match self {
+            Self::Nested => None,
+            // unused slots can never be reached from the root, but if one is somehow read it has no filled area
+            Self::Empty | Self::Unused => Some(false),
+            Self::Filled => Some(true),
+        }
+    }
+}
+
+/// A quad tree where each node represents an area that is either filled or empty, e.g. an occupancy map or fog of war.
+///
+/// Area is tracked in cells that are at most `max_depth` splits deep. Cells at that depth that are only partially covered by [`fill_rect`] or [`clear_rect`] are updated when the given rect contains the center of the cell. The top and left edges of the given rect are inclusive and the bottom and right edges are exclusive, so two rects that share an edge never both claim the same cell.
+///
+/// [`fill_rect`]: #method.fill_rect
+/// [`clear_rect`]: #method.clear_rect
+#[derive(Clone, Debug)]
+pub struct RegionQuadTree {
+    rect: Rect,
+    max_depth: usize,
+    pub(crate) nodes: Vec<RegionNode>,
+}
+
+impl RegionQuadTree {
+    /// Create a new, completely empty [`RegionQuadTree`] which covers the area between `top_left` and `bottom_right`.
+    ///
+    /// The smallest cell in this tree will be `max_depth` splits deep.
+    ///
+    /// Nodes are stored in a flat list where every depth is laid out after the previous one, so touching a cell at depth `d` can grow that list to `(4^(d + 1) - 1) / 3` nodes. A `max_depth` of 10 needs at most about 1.4 million nodes (1.4 MB), but a `max_depth` of 15 can allocate about 1.4 billion nodes (1.4 GB) for a single cell near the bottom-right corner.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `max_depth` is larger than 15.
+    #[must_use]
+    pub fn new(top_left: Point, bottom_right: Point, max_depth: usize) -> Self {
+        assert!(
+            max_depth <= MAX_DEPTH,
+            "max_depth can be at most {MAX_DEPTH}, got {max_depth}"
+        );
+        Self {
+            rect: Rect::new(top_left, bottom_right),
+            max_depth,
+            nodes: vec![RegionNode::Empty],
+        }
+    }
+
+    /// The area that this tree covers
+    #[must_use]
+    pub const fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// The maximum depth of the cells in this tree
+    #[must_use]
+    pub const fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Mark the area of `rect` as filled. Area outside of this tree is ignored.
+    pub fn fill_rect(&mut self, rect: Rect) {
+        self.set_area(Index::ROOT, self.rect, 0, rect, true);
+        self.shrink();
+    }
+
+    /// Mark the area of `rect` as empty. Area outside of this tree is ignored.
+    pub fn clear_rect(&mut self, rect: Rect) {
+        self.set_area(Index::ROOT, self.rect, 0, rect, false);
+        self.shrink();
+    }
+
+    /// Returns `true` if `point` lies in a filled area. Points outside of this tree are never occupied.
+    #[must_use]
+    pub fn is_occupied(&self, point: Point) -> bool {
+        self.leaf_at(point).is_some_and(|(_, _, filled)| filled)
+    }
+
+    /// Find the leaf that contains `point`, with its rect and whether it is filled.
+    pub(crate) fn leaf_at(&self, point: Point) -> Option<(Index, Rect, bool)> {
+        if !self.rect.contains(point) {
+            return None;
+        }
+        let mut rect = self.rect;
+        let mut index = Index::ROOT;
+        loop {
+            match self.node(index).filled() {
+                None => {
+                    let (new_rect, quadrant) = rect.get_quadrant(point);
+                    rect = new_rect;
+                    index = index.child_at(quadrant);
+                }
+                Some(filled) => return Some((index, rect, filled)),
+            }
+        }
+    }
+
+    /// Get all the leaves of this tree in depth-first quadrant order, with `true` for filled leaves and `false` for empty leaves.
+    #[must_use]
+    pub fn leaves(&self) -> Vec<(Rect, bool)> {
+        let mut result = Vec::new();
+        for_each_leaf(self.rect, &|index| self.node_kind(index), |index, rect| {
+            if let Some(filled) = self.node(index).filled() {
+                result.push((rect, filled));
+            }
+        });
+        result
+    }
+
+    /// Create a new [`RegionQuadTree`] from a `width` by `height` bitmap, where every pixel that is at least `threshold` is filled.
+    ///
+    /// `pixels` are stored row by row, starting at the top-left. Each pixel covers a 1 by 1 area, so the tree spans from `0, 0` to the smallest power of two that fits both `width` and `height`. The area outside of the bitmap is empty.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `pixels` does not contain exactly `width * height` pixels, or if the bitmap is larger than `32768` pixels in either direction.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // the size is at most 2^15, which fits in an f32 exactly
+    pub fn from_bitmap(width: usize, height: usize, pixels: &[u8], threshold: u8) -> Self {
+        let pixel_count = width
+            .checked_mul(height)
+            .unwrap_or_else(|| panic!("Expected {width} * {height} pixels, but that overflows"));
+        assert_eq!(
+            pixels.len(),
+            pixel_count,
+            "Expected {width} * {height} pixels, got {}",
+            pixels.len()
+        );
+        let size = width.max(height).max(1).next_power_of_two();
+        let max_depth = size.trailing_zeros() as usize;
+        assert!(
+            max_depth <= MAX_DEPTH,
+            "Bitmaps can be at most {} pixels wide or high",
+            1 << MAX_DEPTH
+        );
+
+        let mut tree = Self::new(
+            Point::zero(),
+            Point::new(size as f32, size as f32),
+            max_depth,
+        );
+        let bitmap = Bitmap {
+            width,
+            height,
+            pixels,
+            threshold,
+        };
+        tree.nodes[0] = tree.build_from_bitmap(Index::ROOT, 0, 0, size, &bitmap);
+        tree.shrink();
+        tree
+    }
+
+    /// Render the area of this tree into a `width` by `height` bitmap, row by row starting at the top-left.
+    ///
+    /// Each pixel covers a 1 by 1 area starting at the top-left of this tree, the same as [`from_bitmap`]. A pixel is set to `filled` when its center is occupied, and to `empty` otherwise.
+    ///
+    /// [`from_bitmap`]: #method.from_bitmap
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `width * height` overflows.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn to_bitmap(&self, width: usize, height: usize, filled: u8, empty: u8) -> Vec<u8> {
+        let top_left = self.rect.top_left();
+        let pixel_count = width
+            .checked_mul(height)
+            .unwrap_or_else(|| panic!("A bitmap of {width} * {height} pixels overflows"));
+        let mut pixels = Vec::with_capacity(pixel_count);
+        for y in 0..height {
+            for x in 0..width {
+                let center = Point::new(
+                    top_left.x.raw() + x as f32 + 0.5,
+                    top_left.y.raw() + y as f32 + 0.5,
+                );
+                pixels.push(if self.is_occupied(center) {
+                    filled
+                } else {
+                    empty
+                });
+            }
+        }
+        pixels
+    }
+
+    /// Build the node at `index`, which covers the `size` by `size` pixels starting at `x, y`.
+    ///
+    /// The node itself is returned. If it is nested, all of its descendants will have been written to `self.nodes`.
+    fn build_from_bitmap(
+        &mut self,
+        index: Index,
+        x: usize,
+        y: usize,
+        size: usize,
+        bitmap: &Bitmap,
+    ) -> RegionNode {
+        if size == 1 {
+            return RegionNode::leaf(bitmap.is_filled(x, y));
+        }
+        if x >= bitmap.width || y >= bitmap.height {
+            return RegionNode::Empty;
+        }
+        let half = size / 2;
+        let children = Quadrant::all().map(|quadrant| {
+            let (x, y) = match quadrant {
+                Quadrant::TopLeft => (x, y),
+                Quadrant::TopRight => (x + half, y),
+                Quadrant::BottomLeft => (x, y + half),
+                Quadrant::BottomRight => (x + half, y + half),
+            };
+            self.build_from_bitmap(index.child_at(quadrant), x, y, half, bitmap)
+        });
+        if children[0] != RegionNode::Nested && children.iter().all(|c| *c == children[0]) {
+            return children[0];
+        }
+        let child_indexes = index
+            .children()
+            .expect("Bitmap is deeper than the maximum depth");
+        let required_len = child_indexes[3].to_idx() + 1;
+        if self.nodes.len() < required_len {
+            self.nodes.resize(required_len, RegionNode::Unused);
+        }
+        for (child_index, child) in child_indexes.into_iter().zip(children) {
+            self.nodes[child_index.to_idx()] = child;
+        }
+        RegionNode::Nested
+    }
+
+    /// Create a new tree that is filled where either this tree or `other` is filled.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `other` does not cover the same area with the same `max_depth`.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a || b)
+    }
+
+    /// Create a new tree that is filled where both this tree and `other` are filled.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `other` does not cover the same area with the same `max_depth`.
+    #[must_use]
+    pub fn intersect(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a && b)
+    }
+
+    /// Create a new tree that is filled where this tree is filled, but `other` is not.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `other` does not cover the same area with the same `max_depth`.
+    #[must_use]
+    pub fn subtract(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a && !b)
+    }
+
+    fn combine(&self, other: &Self, op: fn(bool, bool) -> bool) -> Self {
+        assert!(
+            self.rect == other.rect && self.max_depth == other.max_depth,
+            "Region trees must cover the same area with the same max_depth to be combined"
+        );
+        let mut result = Self {
+            rect: self.rect,
+            max_depth: self.max_depth,
+            nodes: vec![RegionNode::Empty],
+        };
+        result.combine_inner(
+            Index::ROOT,
+            Side::Node(self, Index::ROOT),
+            Side::Node(other, Index::ROOT),
+            op,
+        );
+        result.shrink();
+        result
+    }
+
+    fn combine_inner(&mut self, index: Index, a: Side, b: Side, op: fn(bool, bool) -> bool) {
+        let value = match (a.filled(), b.filled()) {
+            (Some(a), Some(b)) => Some(op(a, b)),
+            (Some(a), None) if op(a, false) == op(a, true) => Some(op(a, false)),
+            (None, Some(b)) if op(false, b) == op(true, b) => Some(op(false, b)),
+            _ => None,
+        };
+        if let Some(filled) = value {
+            self.nodes[index.to_idx()] = RegionNode::leaf(filled);
+            return;
+        }
+        self.split(index, RegionNode::Empty);
+        for quadrant in Quadrant::all() {
+            self.combine_inner(
+                index.child_at(quadrant),
+                a.child_at(quadrant),
+                b.child_at(quadrant),
+                op,
+            );
+        }
+        self.try_merge(index);
+    }
+
+    fn set_area(&mut self, index: Index, rect: Rect, depth: usize, area: Rect, filled: bool) {
+        if !rect.overlaps(area) {
+            return;
+        }
+        let node = self.node(index);
+        if node == RegionNode::leaf(filled) {
+            return;
+        }
+        if area.contains_rect(rect)
+            || (depth == self.max_depth && area.contains_half_open(rect.middle()))
+        {
+            self.discard_children(index);
+            self.nodes[index.to_idx()] = RegionNode::leaf(filled);
+            return;
+        }
+        if depth == self.max_depth {
+            return;
+        }
+        if node != RegionNode::Nested {
+            self.split(index, node);
+        }
+        for quadrant in Quadrant::all() {
+            self.set_area(
+                index.child_at(quadrant),
+                rect.get_child_at(quadrant),
+                depth + 1,
+                area,
+                filled,
+            );
+        }
+        self.try_merge(index);
+    }
+
+    pub(crate) fn node(&self, index: Index) -> RegionNode {
+        self.nodes
+            .get(index.to_idx())
+            .copied()
+            .unwrap_or(RegionNode::Unused)
+    }
+
+    pub(crate) fn node_kind(&self, index: Index) -> NodeKind {
+        match self.node(index) {
+            RegionNode::Nested => NodeKind::Nested,
+            RegionNode::Empty | RegionNode::Filled => NodeKind::Leaf,
+            RegionNode::Unused => NodeKind::Missing,
+        }
+    }
+
+    /// Turn the node at `index` into a nested node, with all of its children set to `child`
+    fn split(&mut self, index: Index, child: RegionNode) {
+        let children = index
+            .children()
+            .expect("Tried to split a region node at the maximum depth");
+        let required_len = children[3].to_idx() + 1;
+        if self.nodes.len() < required_len {
+            self.nodes.resize(required_len, RegionNode::Unused);
+        }
+        for child_index in children {
+            self.nodes[child_index.to_idx()] = child;
+        }
+        self.nodes[index.to_idx()] = RegionNode::Nested;
+    }
+
+    /// If all children of `index` are leaves with the same state, replace `index` with that leaf
+    fn try_merge(&mut self, index: Index) {
+        let Some(children) = index.children() else {
+            return;
+        };
+        let first = self.node(children[0]);
+        if !matches!(first, RegionNode::Empty | RegionNode::Filled)
+            || children.iter().any(|c| self.node(*c) != first)
+        {
+            return;
+        }
+        for child_index in children {
+            self.nodes[child_index.to_idx()] = RegionNode::Unused;
+        }
+        self.nodes[index.to_idx()] = first;
+    }
+
+    /// Mark all descendants of `index` as unused, so they can be trimmed by [`shrink`]
+    ///
+    /// [`shrink`]: #method.shrink
+    fn discard_children(&mut self, index: Index) {
+        if self.node(index) != RegionNode::Nested {
+            return;
+        }
+        let Some(children) = index.children() else {
+            return;
+        };
+        for child_index in children {
+            self.discard_children(child_index);
+            if let Some(node) = self.nodes.get_mut(child_index.to_idx()) {
+                *node = RegionNode::Unused;
+            }
+        }
+    }
+
+    /// Remove unused nodes from the end of the node list.
+    fn shrink(&mut self) {
+        while self.nodes.len() > 1 && matches!(self.nodes.last(), Some(RegionNode::Unused)) {
+            self.nodes.pop();
+        }
+    }
+}
+
+/// The input of [`RegionQuadTree::from_bitmap`]
+struct Bitmap<'a> {
+    width: usize,
+    height: usize,
+    pixels: &'a [u8],
+    threshold: u8,
+}
+
+impl Bitmap<'_> {
+    fn is_filled(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y * self.width + x] >= self.threshold
+    }
+}
+
+/// One side of a boolean operation between two [`RegionQuadTree`]s
+#[derive(Copy, Clone)]
+enum Side<'a> {
+    Node(&'a RegionQuadTree, Index),
+    Uniform(bool),
+}
+
+impl Side<'_> {
+    fn filled(self) -> Option<bool> {
+        match self {
+            Self::Node(tree, index) => tree.node(index).filled(),
+            Self::Uniform(filled) => Some(filled),
+        }
+    }
+
+    fn child_at(self, quadrant: Quadrant) -> Self {
+        match self {
+            Self::Node(tree, index) => match tree.node(index).filled() {
+                Some(filled) => Self::Uniform(filled),
+                None => Self::Node(tree, index.child_at(quadrant)),
+            },
+            Self::Uniform(filled) => Self::Uniform(filled),
+        }
+    }
+}
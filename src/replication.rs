@@ -0,0 +1,253 @@
+//! A compact wire codec for changes to the entries of a [`QuadTree`], to replicate a tree to other machines.
+//!
+//! Positions are quantized relative to the leaf that contains them, so dense areas of the tree get more precision.
+
+use crate::{
+    curve::{dequantize, quantize},
+    incremental::{invalid_data, read_bytes, read_point, write_point},
+    index::Index,
+    Bucket, IdentityPoint, Point, QuadTree, Rect,
+};
+use std::io::{self, Read, Write};
+
+const CHANGE_INSERT: u8 = 1;
+const CHANGE_MOVE: u8 = 2;
+const CHANGE_REMOVE: u8 = 3;
+
+/// A single change to an entry of a [`QuadTree`], created by [`QuadTree::diff`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryChange<'a, T, ID> {
+    /// The entry was added, or its value changed
+    Insert {
+        /// The identity of the entry
+        identity: &'a ID,
+        /// The new position of the entry
+        point: Point,
+        /// The new value of the entry
+        value: &'a T,
+    },
+    /// The entry moved, but its value did not change
+    Move {
+        /// The identity of the entry
+        identity: &'a ID,
+        /// The new position of the entry
+        point: Point,
+    },
+    /// The entry was removed
+    Remove {
+        /// The identity of the entry
+        identity: &'a ID,
+    },
+}
+
+/// A change that was read by [`QuadTree::apply_delta`]
+enum ReadChange<T, ID> {
+    Insert(IdentityPoint<ID>, T),
+    Move(ID, Point),
+    Remove(ID),
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Get the changes that turn this tree into `newer`, ordered by identity.
+    #[must_use]
+    pub fn diff<'a>(&'a self, newer: &'a Self) -> Vec<EntryChange<'a, T, ID>>
+    where
+        T: PartialEq,
+    {
+        let mut changes = Vec::new();
+        for identity in newer.identity_to_point.keys() {
+            let Some((point, value)) = newer.get(identity) else {
+                continue;
+            };
+            match self.get(identity) {
+                Some((_, old_value)) if old_value != value => {}
+                Some((old_point, _)) => {
+                    if old_point != point {
+                        changes.push(EntryChange::Move { identity, point });
+                    }
+                    continue;
+                }
+                None => {}
+            }
+            changes.push(EntryChange::Insert {
+                identity,
+                point,
+                value,
+            });
+        }
+        for identity in self.identity_to_point.keys() {
+            if !newer.identity_to_point.contains_key(identity) {
+                changes.push(EntryChange::Remove { identity });
+            }
+        }
+        changes
+    }
+
+    /// Write `changes` to `writer` in a compact format, which can be read back with [`QuadTree::apply_delta`].
+    ///
+    /// This tree should contain the changes already, because positions are stored relative to the leaf of this tree that contains them. The decoded position is at most `leaf size / 65535` away from the original position on each axis. Positions outside of this tree are stored exactly.
+    ///
+    /// `encode_identity` and `encode_value` are called to write the identities and values of the changes.
+    ///
+    /// # Format
+    ///
+    /// All numbers are little endian. A varint is an unsigned LEB128 number.
+    ///
+    /// ```text
+    /// delta:    count: varint, followed by the changes
+    /// change:   1: insert, followed by the identity, position and value
+    ///           2: move, followed by the identity and position
+    ///           3: remove, followed by the identity
+    /// position: 0: outside of the tree, followed by x: f32, y: f32
+    ///           otherwise the index of the leaf as varint, followed by x: u16, y: u16 within that leaf
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `writer`, `encode_identity` or `encode_value` fail.
+    pub fn encode_delta<W: Write>(
+        &self,
+        changes: &[EntryChange<'_, T, ID>],
+        writer: &mut W,
+        mut encode_identity: impl FnMut(&mut W, &ID) -> io::Result<()>,
+        mut encode_value: impl FnMut(&mut W, &T) -> io::Result<()>,
+    ) -> io::Result<()> {
+        write_varint(writer, changes.len() as u64)?;
+        for change in changes {
+            match change {
+                EntryChange::Insert {
+                    identity,
+                    point,
+                    value,
+                } => {
+                    writer.write_all(&[CHANGE_INSERT])?;
+                    encode_identity(writer, identity)?;
+                    self.write_position(writer, *point)?;
+                    encode_value(writer, value)?;
+                }
+                EntryChange::Move { identity, point } => {
+                    writer.write_all(&[CHANGE_MOVE])?;
+                    encode_identity(writer, identity)?;
+                    self.write_position(writer, *point)?;
+                }
+                EntryChange::Remove { identity } => {
+                    writer.write_all(&[CHANGE_REMOVE])?;
+                    encode_identity(writer, identity)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply a delta that was written by [`QuadTree::encode_delta`]. This tree should cover the same area as the tree that wrote the delta.
+    ///
+    /// The whole delta is read before this tree is changed, so if an error is returned this tree is left untouched. Moves and removes of identities that are not in this tree are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `reader`, `decode_identity` or `decode_value` fail, or an error of kind [`io::ErrorKind::InvalidData`] if the delta is malformed.
+    pub fn apply_delta<R: Read>(
+        &mut self,
+        reader: &mut R,
+        mut decode_identity: impl FnMut(&mut R) -> io::Result<ID>,
+        mut decode_value: impl FnMut(&mut R) -> io::Result<T>,
+    ) -> io::Result<()> {
+        let count = read_varint(reader)?;
+        // `count` is not trusted, so don't preallocate with it
+        let mut changes = Vec::new();
+        for _ in 0..count {
+            let change = match read_bytes(reader)? {
+                [CHANGE_INSERT] => {
+                    let identity = decode_identity(reader)?;
+                    let point = self.read_position(reader)?;
+                    ReadChange::Insert(IdentityPoint { identity, point }, decode_value(reader)?)
+                }
+                [CHANGE_MOVE] => {
+                    let identity = decode_identity(reader)?;
+                    ReadChange::Move(identity, self.read_position(reader)?)
+                }
+                [CHANGE_REMOVE] => ReadChange::Remove(decode_identity(reader)?),
+                _ => return Err(invalid_data("unknown change")),
+            };
+            changes.push(change);
+        }
+
+        for change in changes {
+            match change {
+                ReadChange::Insert(point, value) => self.insert(point, value),
+                ReadChange::Move(identity, point) => {
+                    self.update(identity, point);
+                }
+                ReadChange::Remove(identity) => {
+                    self.try_remove(&identity);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_position(&self, writer: &mut impl Write, point: Point) -> io::Result<()> {
+        if !self.rect.contains(point) {
+            writer.write_all(&[0])?;
+            return write_point(writer, point);
+        }
+        let (index, rect) = self.leaf_for(point);
+        write_varint(writer, u64::from(index.to_raw()))?;
+        let (x, y) = quantize(rect, point);
+        writer.write_all(&x.to_le_bytes())?;
+        writer.write_all(&y.to_le_bytes())
+    }
+
+    fn read_position(&self, reader: &mut impl Read) -> io::Result<Point> {
+        let raw = read_varint(reader)?;
+        if raw == 0 {
+            return read_point(reader);
+        }
+        let index = u32::try_from(raw)
+            .ok()
+            .and_then(Index::from_raw)
+            .ok_or_else(|| invalid_data("invalid leaf index"))?;
+        let x = u16::from_le_bytes(read_bytes(reader)?);
+        let y = u16::from_le_bytes(read_bytes(reader)?);
+        Ok(dequantize(self.rect.get_index_rect(index), (x, y)))
+    }
+
+    /// Get the leaf that `point` would be stored in
+    fn leaf_for(&self, point: Point) -> (Index, Rect) {
+        let mut index = Index::ROOT;
+        let mut rect = self.rect;
+        while let Some(Bucket::Nested) = self.items.get(index.to_idx()) {
+            let (child_rect, quadrant) = rect.get_quadrant(point);
+            index = index.child_at(quadrant);
+            rect = child_rect;
+        }
+        (index, rect)
+    }
+}
+
+fn write_varint(writer: &mut impl Write, mut n: u64) -> io::Result<()> {
+    loop {
+        // the lower 7 bits, with the high bit set if more bytes follow
+        let byte = (n & 0x7F) as u8;
+        n >>= 7;
+        if n == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint(reader: &mut impl Read) -> io::Result<u64> {
+    let mut n = 0;
+    for shift in (0..64).step_by(7) {
+        let [byte] = read_bytes(reader)?;
+        n |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err(invalid_data("varint is too long"))
+}
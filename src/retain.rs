@@ -0,0 +1,129 @@
+//! [`QuadTree::retain`] and [`QuadTree::drain_range`], which remove many entries in a single walk over the tree.
+
+use crate::{
+    bucket::Bucket, expect_consistent, index::Index, point::Quadrant, CorruptionError,
+    FindRangeCtx, Point, QuadTree, Rect, R32,
+};
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Remove every entry for which `keep` returns `false`, also the entries outside of the range of this tree.
+    ///
+    /// This visits every node once, and only tries to merge the nodes that lost an entry after all of their children are done, which is much cheaper than a [`QuadTree::try_remove`] for every entry. Merges that are deferred with [`QuadTree::set_deferred_merges`] stay deferred.
+    pub fn retain(&mut self, mut keep: impl FnMut(&ID, Point, &T) -> bool) {
+        expect_consistent(self.retain_inner(
+            Index::ROOT,
+            self.rect,
+            None,
+            &mut keep,
+            &mut |_, _, _| {},
+        ));
+        self.retain_outside(keep, |_, _, _| {});
+    }
+
+    /// Remove every entry with a distance less than `range` away from point `center`, like [`QuadTree::find_range`] finds them, and return them.
+    ///
+    /// The entries are removed in a single walk over the nodes in range, see [`QuadTree::retain`].
+    pub fn drain_range(&mut self, center: Point, range: R32) -> Vec<(ID, Point, T)> {
+        let ctx = FindRangeCtx::new(center, range);
+        let mut drained = Vec::new();
+        expect_consistent(self.retain_inner(
+            Index::ROOT,
+            self.rect,
+            Some(&ctx),
+            &mut |_, point, _| !ctx.point_in_range(point),
+            &mut |identity, point, value| drained.push((identity, point, value)),
+        ));
+        if self.outside_of_range.touches(ctx.full_rect) {
+            self.retain_outside(
+                |_, point, _| !ctx.point_in_range(point),
+                |identity, point, value| drained.push((identity, point, value)),
+            );
+        }
+        drained
+    }
+
+    /// Remove the entries in the subtree at `index` for which `keep` returns `false`, skipping the nodes that are out of `range`, and pass them to `removed`. Returns `true` if an entry was removed.
+    fn retain_inner(
+        &mut self,
+        index: Index,
+        rect: Rect,
+        range: Option<&FindRangeCtx>,
+        keep: &mut impl FnMut(&ID, Point, &T) -> bool,
+        removed: &mut impl FnMut(ID, Point, T),
+    ) -> Result<bool, CorruptionError> {
+        if range.is_some_and(|ctx| !ctx.contains_rect(rect)) {
+            return Ok(false);
+        }
+        match self.items.get_mut(index.to_idx()) {
+            Some(Bucket::Owned(entries)) => {
+                let count = entries.len();
+                for (ip, value) in std::mem::take(entries) {
+                    if keep(&ip.identity, ip.point, &value) {
+                        entries.push((ip, value));
+                    } else {
+                        self.identity_to_point.remove(&ip.identity);
+                        removed(ip.identity, ip.point, value);
+                    }
+                }
+                let changed = entries.len() != count;
+                if changed {
+                    self.dirty.touch(index);
+                }
+                Ok(changed)
+            }
+            Some(Bucket::Nested) => {
+                let mut changed = false;
+                for quadrant in Quadrant::all() {
+                    // a nested node at the maximum depth is corrupt, and is skipped instead of panicking
+                    let Some(child) = index.checked_child_at(quadrant) else {
+                        break;
+                    };
+                    changed |= self.retain_inner(
+                        child,
+                        rect.get_child_at(quadrant),
+                        range,
+                        keep,
+                        removed,
+                    )?;
+                }
+                // the children are done, so a merge here can not be undone by a merge below it
+                if changed {
+                    if let Some(pending) = &mut self.pending_merges {
+                        pending.insert(index);
+                    } else if self.is_nested(index) {
+                        self.try_merge(index)?;
+                    }
+                }
+                Ok(changed)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Remove the entries outside of the range of this tree for which `keep` returns `false`, and pass them to `removed`
+    fn retain_outside(
+        &mut self,
+        mut keep: impl FnMut(&ID, Point, &T) -> bool,
+        mut removed: impl FnMut(ID, Point, T),
+    ) {
+        let count = self.outside_of_range.len();
+        let entries = self.outside_of_range.take();
+        self.outside_of_range = entries
+            .into_iter()
+            .filter_map(|(identity, (value, point))| {
+                if keep(&identity, point, &value) {
+                    return Some((identity, (value, point)));
+                }
+                self.identity_to_point.remove(&identity);
+                removed(identity, point, value);
+                None
+            })
+            .collect();
+        if self.outside_of_range.len() != count {
+            self.dirty.touch_outside();
+        }
+    }
+}
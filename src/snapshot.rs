@@ -0,0 +1,308 @@
+//! Owned copies of query results and of whole trees, which don't borrow the [`QuadTree`] they came from.
+
+use crate::{
+    bucket::Bucket, index::Index, point::Quadrant, FindRangeCtx, IdentityPoint, Point, QuadTree,
+    Rect, R32,
+};
+use std::sync::Arc;
+
+/// The entries found by [`QuadTree::snapshot_range`], cloned out of the tree so they can outlive later changes to it and be sent to other threads.
+///
+/// The entries are sorted by identity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotView<T, ID> {
+    center: Point,
+    range: R32,
+    entries: Vec<(ID, Point, T)>,
+}
+
+impl<T, ID> SnapshotView<T, ID>
+where
+    ID: Ord,
+{
+    /// The center of the query that found these entries
+    #[must_use]
+    pub fn center(&self) -> Point {
+        self.center
+    }
+
+    /// The range of the query that found these entries
+    #[must_use]
+    pub fn range(&self) -> R32 {
+        self.range
+    }
+
+    /// The amount of entries in this snapshot
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the query found nothing
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the position and value of `identity` at the time of the snapshot, or `None` if it was not found by the query
+    #[must_use]
+    pub fn get(&self, identity: &ID) -> Option<(Point, &T)> {
+        let index = self
+            .entries
+            .binary_search_by(|(other, _, _)| other.cmp(identity))
+            .ok()?;
+        let (_, point, value) = &self.entries[index];
+        Some((*point, value))
+    }
+
+    /// Iterate over the entries of this snapshot, sorted by identity
+    #[must_use]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&ID, Point, &T)> {
+        self.entries
+            .iter()
+            .map(|(identity, point, value)| (identity, *point, value))
+    }
+}
+
+impl<T, ID> IntoIterator for SnapshotView<T, ID> {
+    type Item = (ID, Point, T);
+    type IntoIter = std::vec::IntoIter<(ID, Point, T)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+    T: Clone,
+{
+    /// Find all entries with a distance less than `range` away from point `center`, see [`QuadTree::find_range`], and return clones of them.
+    ///
+    /// The entries are in the order in which [`QuadTree::find_range`] finds them.
+    #[must_use]
+    pub fn find_range_owned(&self, center: Point, range: R32) -> Vec<(ID, Point, T)> {
+        let mut entries = Vec::new();
+        self.find_range(center, range, |identity, point, value| {
+            entries.push((identity.clone(), point, value.clone()));
+        });
+        entries
+    }
+
+    /// The same as [`QuadTree::find_range_owned`], but returns the entries as a [`SnapshotView`] which can look up entries by identity.
+    #[must_use]
+    pub fn snapshot_range(&self, center: Point, range: R32) -> SnapshotView<T, ID> {
+        let mut entries = self.find_range_owned(center, range);
+        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        SnapshotView {
+            center,
+            range,
+            entries,
+        }
+    }
+}
+
+/// A frozen copy of a whole [`QuadTree`], created by [`QuadTree::snapshot`], which can be queried from other threads while the tree keeps changing.
+///
+/// The nodes of a snapshot are stored in [`Arc`]s. [`QuadTree::snapshot_since`] shares every subtree that did not change since an earlier snapshot with it, so only the leaves that changed are cloned again. Cloning a snapshot only clones an [`Arc`].
+#[derive(Clone, Debug)]
+pub struct TreeSnapshot<T, ID> {
+    generation: u64,
+    rect: Rect,
+    len: usize,
+    pub(crate) root: Arc<SnapshotNode<T, ID>>,
+    pub(crate) outside_of_range: Arc<Vec<(ID, Point, T)>>,
+}
+
+#[derive(Debug)]
+pub(crate) enum SnapshotNode<T, ID> {
+    Leaf(Vec<(IdentityPoint<ID>, T)>),
+    /// The children, in quadrant order
+    Nested([Arc<SnapshotNode<T, ID>>; 4]),
+}
+
+impl<T, ID> TreeSnapshot<T, ID> {
+    /// The [`generation`](QuadTree::generation) of the tree when this snapshot was taken
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The amount of entries in this snapshot, also the entries outside of the tree
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree had no entries when this snapshot was taken
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, see [`QuadTree::find_range`]. The entries are found in the same order as the tree would have found them.
+    pub fn find_range<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        mut callback: impl FnMut(&'a ID, Point, &'a T),
+    ) {
+        let ctx = FindRangeCtx::new(center, range);
+        find_range_inner(&self.root, self.rect, &ctx, &mut callback);
+        for (identity, point, value) in self.outside_of_range.iter() {
+            if ctx.point_in_range(*point) {
+                callback(identity, *point, value);
+            }
+        }
+    }
+
+    /// Find all entries inside of the rect between `top_left` and `bottom_right`, see [`QuadTree::find_rect`]
+    pub fn find_rect<'a>(
+        &'a self,
+        top_left: Point,
+        bottom_right: Point,
+        mut callback: impl FnMut(&'a ID, Point, &'a T),
+    ) {
+        let area = Rect::from_corners(top_left, bottom_right);
+        find_rect_inner(&self.root, self.rect, area, &mut callback);
+        for (identity, point, value) in self.outside_of_range.iter() {
+            if area.contains(*point) {
+                callback(identity, *point, value);
+            }
+        }
+    }
+}
+
+fn find_range_inner<'a, T, ID>(
+    node: &'a SnapshotNode<T, ID>,
+    rect: Rect,
+    ctx: &FindRangeCtx,
+    callback: &mut impl FnMut(&'a ID, Point, &'a T),
+) {
+    if !ctx.contains_rect(rect) {
+        return;
+    }
+    match node {
+        SnapshotNode::Leaf(entries) => {
+            for (ip, value) in entries {
+                if ctx.point_in_range(ip.point) {
+                    callback(&ip.identity, ip.point, value);
+                }
+            }
+        }
+        SnapshotNode::Nested(children) => {
+            for (child, quadrant) in children.iter().zip(Quadrant::all()) {
+                find_range_inner(child, rect.get_child_at(quadrant), ctx, callback);
+            }
+        }
+    }
+}
+
+fn find_rect_inner<'a, T, ID>(
+    node: &'a SnapshotNode<T, ID>,
+    rect: Rect,
+    area: Rect,
+    callback: &mut impl FnMut(&'a ID, Point, &'a T),
+) {
+    if !area.touches(rect) {
+        return;
+    }
+    match node {
+        SnapshotNode::Leaf(entries) => {
+            for (ip, value) in entries {
+                if area.contains(ip.point) {
+                    callback(&ip.identity, ip.point, value);
+                }
+            }
+        }
+        SnapshotNode::Nested(children) => {
+            for (child, quadrant) in children.iter().zip(Quadrant::all()) {
+                find_rect_inner(child, rect.get_child_at(quadrant), area, callback);
+            }
+        }
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+    T: Clone,
+{
+    /// Take a [`TreeSnapshot`] of this whole tree, which clones every entry.
+    ///
+    /// Use [`QuadTree::snapshot_since`] to take the next snapshot, which only clones the entries that changed.
+    #[must_use]
+    pub fn snapshot(&self) -> TreeSnapshot<T, ID> {
+        self.take_snapshot(None)
+    }
+
+    /// Take a [`TreeSnapshot`] of this whole tree that shares every subtree which did not change since `previous` with it. Only the leaves that changed since `previous` are cloned, and `previous` itself is not changed.
+    ///
+    /// `previous` should be a snapshot of this tree, otherwise the new snapshot can contain the entries of another tree.
+    #[must_use]
+    pub fn snapshot_since(&self, previous: &TreeSnapshot<T, ID>) -> TreeSnapshot<T, ID> {
+        self.take_snapshot(Some(previous))
+    }
+
+    fn take_snapshot(&self, previous: Option<&TreeSnapshot<T, ID>>) -> TreeSnapshot<T, ID> {
+        // after a change to the rect of the tree every node is rewritten, so nothing can be shared
+        let previous = previous.filter(|previous| previous.rect == self.rect);
+        let outside_of_range = match previous {
+            Some(previous) if self.dirty.outside() <= previous.generation => {
+                Arc::clone(&previous.outside_of_range)
+            }
+            _ => Arc::new(
+                self.outside_of_range
+                    .iter()
+                    .map(|(identity, (value, point))| (identity.clone(), *point, value.clone()))
+                    .collect(),
+            ),
+        };
+        TreeSnapshot {
+            generation: self.generation(),
+            rect: self.rect,
+            len: self.identity_to_point.len(),
+            root: self.snapshot_node(
+                Index::ROOT,
+                previous.map(|previous| (&previous.root, previous.generation)),
+            ),
+            outside_of_range,
+        }
+    }
+
+    /// The snapshot of the node at `index`, which shares the node of the previous snapshot if it did not change since the generation of that snapshot
+    fn snapshot_node(
+        &self,
+        index: Index,
+        previous: Option<(&Arc<SnapshotNode<T, ID>>, u64)>,
+    ) -> Arc<SnapshotNode<T, ID>> {
+        let changed = self.dirty.node(index);
+        if let Some((node, generation)) = previous {
+            if changed.subtree <= generation {
+                return Arc::clone(node);
+            }
+        }
+        // the children of a rewritten node were created or moved without being marked themselves
+        let previous = previous.filter(|(_, generation)| changed.rewritten <= *generation);
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => {
+                let children = Quadrant::all().map(|quadrant| {
+                    let previous = match previous {
+                        Some((node, generation)) => match &**node {
+                            SnapshotNode::Nested(children) => {
+                                Some((&children[quadrant as usize], generation))
+                            }
+                            SnapshotNode::Leaf(_) => None,
+                        },
+                        None => None,
+                    };
+                    self.snapshot_node(index.child_at(quadrant), previous)
+                });
+                Arc::new(SnapshotNode::Nested(children))
+            }
+            Some(Bucket::Owned(entries)) => Arc::new(SnapshotNode::Leaf(entries.to_vec())),
+            None => Arc::new(SnapshotNode::Leaf(Vec::new())),
+        }
+    }
+}
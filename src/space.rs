@@ -0,0 +1,163 @@
+//! An opt-in layer that tags points with the coordinate space they are in, so points of different spaces can not be passed to the same tree by accident.
+//!
+//! A space is any type, usually an empty enum or a unit struct, e.g. `enum World {}` and `enum Screen {}`. A [`TypedQuadTree<T, ID, World, N>`] only accepts and returns [`TypedPoint<World>`], so a query with a [`TypedPoint<Screen>`] does not compile.
+
+use crate::{IdentityPoint, Point, QuadTree, R32};
+use std::{fmt, marker::PhantomData};
+
+/// A [`Point`] in the coordinate space `S`
+pub struct TypedPoint<S> {
+    point: Point,
+    space: PhantomData<fn() -> S>,
+}
+
+impl<S> TypedPoint<S> {
+    /// Create a new point in space `S`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `x` or `y` are NaN, see [`Point::new`].
+    #[must_use]
+    pub fn new(x: f32, y: f32) -> Self {
+        Self::from_point(Point::new(x, y))
+    }
+
+    /// Mark `point` as being in space `S`
+    #[must_use]
+    pub const fn from_point(point: Point) -> Self {
+        Self {
+            point,
+            space: PhantomData,
+        }
+    }
+
+    /// The untyped point
+    #[must_use]
+    pub const fn point(self) -> Point {
+        self.point
+    }
+
+    /// Move this point to space `O`, with `convert` turning the untyped point of space `S` into a point of space `O`
+    #[must_use]
+    pub fn convert<O>(self, convert: impl FnOnce(Point) -> Point) -> TypedPoint<O> {
+        TypedPoint::from_point(convert(self.point))
+    }
+}
+
+// these are implemented by hand, because deriving them would require `S` to implement them too
+
+impl<S> Clone for TypedPoint<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for TypedPoint<S> {}
+
+impl<S> PartialEq for TypedPoint<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.point == other.point
+    }
+}
+
+impl<S> Eq for TypedPoint<S> {}
+
+impl<S> fmt::Debug for TypedPoint<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} in {}", self.point, std::any::type_name::<S>())
+    }
+}
+
+/// A [`QuadTree`] that only accepts and returns points in the coordinate space `S`, see [`TypedPoint`].
+///
+/// Only the most common methods are wrapped. Everything else is available on the untyped tree through [`TypedQuadTree::untyped`], which is an explicit step out of the typed layer.
+pub struct TypedQuadTree<T, ID, S, const N: usize> {
+    tree: QuadTree<T, ID, N>,
+    space: PhantomData<fn() -> S>,
+}
+
+impl<T, ID, S, const N: usize> TypedQuadTree<T, ID, S, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Create a new tree that spans from `top_left` to `bottom_right`, see [`QuadTree::new`]
+    #[must_use]
+    pub fn new(top_left: TypedPoint<S>, bottom_right: TypedPoint<S>) -> Self {
+        Self::from_untyped(QuadTree::new(top_left.point, bottom_right.point))
+    }
+
+    /// Mark the points of `tree` as being in space `S`
+    #[must_use]
+    pub const fn from_untyped(tree: QuadTree<T, ID, N>) -> Self {
+        Self {
+            tree,
+            space: PhantomData,
+        }
+    }
+
+    /// The untyped tree
+    #[must_use]
+    pub const fn untyped(&self) -> &QuadTree<T, ID, N> {
+        &self.tree
+    }
+
+    /// Turn this into the untyped tree
+    #[must_use]
+    pub fn into_untyped(self) -> QuadTree<T, ID, N> {
+        self.tree
+    }
+
+    /// Insert a new entry, see [`QuadTree::insert`]
+    pub fn insert(&mut self, identity: ID, point: TypedPoint<S>, value: T) {
+        self.tree.insert(
+            IdentityPoint {
+                identity,
+                point: point.point,
+            },
+            value,
+        );
+    }
+
+    /// Move an entry to `point`, see [`QuadTree::update`]
+    pub fn update(&mut self, identity: ID, point: TypedPoint<S>) -> bool {
+        self.tree.update(identity, point.point)
+    }
+
+    /// Remove an entry, see [`QuadTree::try_remove`]
+    pub fn try_remove(&mut self, identity: &ID) -> Option<(T, TypedPoint<S>)> {
+        self.tree
+            .try_remove(identity)
+            .map(|(value, point)| (value, TypedPoint::from_point(point)))
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, see [`QuadTree::find_range`]
+    pub fn find_range<'a>(
+        &'a self,
+        center: TypedPoint<S>,
+        range: R32,
+        mut callback: impl FnMut(&ID, TypedPoint<S>, &'a T),
+    ) {
+        self.tree
+            .find_range(center.point, range, |identity, point, value| {
+                callback(identity, TypedPoint::from_point(point), value);
+            });
+    }
+}
+
+impl<T: Clone, ID: Clone, S, const N: usize> Clone for TypedQuadTree<T, ID, S, N> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            space: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, ID: fmt::Debug, S, const N: usize> fmt::Debug for TypedQuadTree<T, ID, S, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedQuadTree")
+            .field("space", &std::any::type_name::<S>())
+            .field("tree", &self.tree)
+            .finish()
+    }
+}
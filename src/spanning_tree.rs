@@ -0,0 +1,215 @@
+//! A Euclidean minimum spanning tree over the entries of a [`QuadTree`], built with Borůvka's algorithm.
+//!
+//! Every round, each component of the forest is connected to its nearest other component. The nearest other component of an entry is found with the tree, which skips every node that only contains entries of the same component.
+
+use crate::{
+    bucket::Bucket, index::Index, neighbors::for_each_leaf, point::Quadrant, Point, QuadTree, Rect,
+    R32,
+};
+use noisy_float::prelude::Float;
+
+/// Which components the entries of a node belong to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Label {
+    Empty,
+    Component(usize),
+    Mixed,
+}
+
+impl Label {
+    fn merge(self, other: Label) -> Label {
+        match (self, other) {
+            (Label::Empty, label) | (label, Label::Empty) => label,
+            (a, b) if a == b => a,
+            _ => Label::Mixed,
+        }
+    }
+}
+
+/// An edge between two entries with its squared length, ordered by length and then by the entries so edges of the same length are always picked consistently
+type Edge = (R32, usize, usize);
+
+struct Forest<'a, ID> {
+    entries: Vec<(&'a ID, Point)>,
+    /// The index in `entries` of the first entry of every leaf, indexed by [`Index::to_idx`]
+    leaf_first: Vec<usize>,
+    /// The index in `entries` of the first entry that is outside of the tree
+    outside_first: usize,
+    /// The union-find parent of every entry
+    parents: Vec<usize>,
+    /// The component of every entry in the current round
+    components: Vec<usize>,
+    /// The label of every node in the current round, indexed by [`Index::to_idx`]
+    labels: Vec<Label>,
+}
+
+impl<ID> Forest<'_, ID> {
+    fn find(&mut self, mut entry: usize) -> usize {
+        while self.parents[entry] != entry {
+            self.parents[entry] = self.parents[self.parents[entry]];
+            entry = self.parents[entry];
+        }
+        entry
+    }
+
+    /// Connect the components of `a` and `b`, returns `false` if they were connected already
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a.max(b)] = a.min(b);
+        a != b
+    }
+
+    fn offer(&self, entry: usize, other: usize, nearest: &mut Option<Edge>) {
+        if self.components[entry] == self.components[other] {
+            return;
+        }
+        let distance = self.entries[entry]
+            .1
+            .distance_squared_to(self.entries[other].1);
+        let edge = (distance, entry.min(other), entry.max(other));
+        if let Some(nearest) = nearest {
+            if *nearest <= edge {
+                return;
+            }
+        }
+        *nearest = Some(edge);
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N> {
+    /// Build a minimum spanning tree that connects all entries of this tree, where the weight of an edge is the distance between its entries.
+    ///
+    /// Returns the edges of the spanning tree with their length, which is one less than the amount of entries unless this tree is empty. Entries that are outside of the range of this tree are connected as well.
+    #[must_use]
+    pub fn euclidean_mst(&self) -> Vec<(ID, ID, R32)>
+    where
+        ID: Clone,
+    {
+        let mut forest = self.forest();
+        let mut edges = Vec::with_capacity(forest.entries.len().saturating_sub(1));
+        loop {
+            for entry in 0..forest.entries.len() {
+                forest.components[entry] = forest.find(entry);
+            }
+            self.label_node(&mut forest, Index::ROOT);
+
+            // the shortest edge that leaves every component, indexed by the component
+            let mut shortest = vec![None; forest.entries.len()];
+            for entry in 0..forest.entries.len() {
+                let component = forest.components[entry];
+                let mut nearest = shortest[component];
+                self.nearest_other(&forest, Index::ROOT, self.rect, entry, &mut nearest);
+                for other in forest.outside_first..forest.entries.len() {
+                    forest.offer(entry, other, &mut nearest);
+                }
+                shortest[component] = nearest;
+            }
+
+            let mut merged = false;
+            for (distance, a, b) in shortest.into_iter().flatten() {
+                if forest.union(a, b) {
+                    merged = true;
+                    edges.push((
+                        forest.entries[a].0.clone(),
+                        forest.entries[b].0.clone(),
+                        distance.sqrt(),
+                    ));
+                }
+            }
+            if !merged {
+                return edges;
+            }
+        }
+    }
+
+    fn forest(&self) -> Forest<'_, ID> {
+        let mut entries = Vec::with_capacity(self.identity_to_point.len());
+        let mut leaf_first = vec![0; self.items.len()];
+        for_each_leaf(self.rect, &|index| self.node_kind(index), |index, _| {
+            if let Some(Bucket::Owned(leaf)) = self.items.get(index.to_idx()) {
+                leaf_first[index.to_idx()] = entries.len();
+                entries.extend(leaf.iter().map(|(ip, _)| (&ip.identity, ip.point)));
+            }
+        });
+        let outside_first = entries.len();
+        entries.extend(
+            self.outside_of_range
+                .iter()
+                .map(|(identity, (_, point))| (identity, *point)),
+        );
+        Forest {
+            parents: (0..entries.len()).collect(),
+            components: vec![0; entries.len()],
+            labels: vec![Label::Empty; self.items.len()],
+            entries,
+            leaf_first,
+            outside_first,
+        }
+    }
+
+    fn label_node(&self, forest: &mut Forest<'_, ID>, index: Index) -> Label {
+        let label = match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => Quadrant::all()
+                .into_iter()
+                .fold(Label::Empty, |label, quadrant| {
+                    label.merge(self.label_node(forest, index.child_at(quadrant)))
+                }),
+            Some(Bucket::Owned(entries)) => {
+                let first = forest.leaf_first[index.to_idx()];
+                (first..first + entries.len()).fold(Label::Empty, |label, entry| {
+                    label.merge(Label::Component(forest.components[entry]))
+                })
+            }
+            None => return Label::Empty,
+        };
+        forest.labels[index.to_idx()] = label;
+        label
+    }
+
+    /// Find the shortest edge from `entry` to an entry in another component that is shorter than `nearest`
+    fn nearest_other(
+        &self,
+        forest: &Forest<'_, ID>,
+        index: Index,
+        rect: Rect,
+        entry: usize,
+        nearest: &mut Option<Edge>,
+    ) {
+        let Some(bucket) = self.items.get(index.to_idx()) else {
+            return;
+        };
+        let label = forest.labels[index.to_idx()];
+        if label == Label::Empty || label == Label::Component(forest.components[entry]) {
+            return;
+        }
+        let point = forest.entries[entry].1;
+        let distance = |rect: Rect| Rect::new(point, point).min_distance_squared(rect);
+        if matches!(nearest, Some((shortest, _, _)) if distance(rect) > *shortest) {
+            return;
+        }
+        match bucket {
+            Bucket::Nested => {
+                let mut children = Quadrant::all().map(|quadrant| {
+                    let child_rect = rect.get_child_at(quadrant);
+                    (distance(child_rect), quadrant, child_rect)
+                });
+                children.sort_unstable_by_key(|(distance, _, _)| *distance);
+                for (_, quadrant, child_rect) in children {
+                    self.nearest_other(
+                        forest,
+                        index.child_at(quadrant),
+                        child_rect,
+                        entry,
+                        nearest,
+                    );
+                }
+            }
+            Bucket::Owned(entries) => {
+                let first = forest.leaf_first[index.to_idx()];
+                for other in first..first + entries.len() {
+                    forest.offer(entry, other, nearest);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Building trees with an exact layout in tests, without depending on the order in which a tree splits.
+//!
+//! Every leaf is given by its path of quadrants from the root, with the entries that it contains. The nodes on the path to a leaf are nested, and every other node is an empty leaf. For example, `quadtree_layout!(Point::zero(), Point::new(8., 8.), { [TopLeft] => [(1, 1., 1., 10)], [BottomRight, TopLeft] => [(2, 5., 5., 20)] })` builds a tree with entry 1 at depth 1 and entry 2 at depth 2.
+
+use crate::{
+    bucket::Bucket, ensure_index_valid, expect_consistent, index::Index, IdentityPoint, Point,
+    QuadTree, Quadrant, Rect,
+};
+
+/// The leaves and their entries of a tree that is built by [`LayoutBuilder::build`]
+type Leaf<T, ID> = (Vec<Quadrant>, Vec<(ID, Point, T)>);
+
+/// Builds a [`QuadTree`] with an exact layout, see the [module documentation](self)
+#[derive(Clone, Debug)]
+pub struct LayoutBuilder<T, ID> {
+    rect: Rect,
+    leaves: Vec<Leaf<T, ID>>,
+    outside: Vec<(ID, Point, T)>,
+}
+
+impl<T, ID> LayoutBuilder<T, ID>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Start a tree that covers the area between `top_left` and `bottom_right`, see [`QuadTree::new`]
+    #[must_use]
+    pub fn new(top_left: Point, bottom_right: Point) -> Self {
+        Self {
+            rect: Rect::new(top_left, bottom_right),
+            leaves: Vec::new(),
+            outside: Vec::new(),
+        }
+    }
+
+    /// Add the leaf at `path` from the root with `entries`, in this order. An empty path is the root itself.
+    #[must_use]
+    pub fn leaf(mut self, path: &[Quadrant], entries: Vec<(ID, Point, T)>) -> Self {
+        self.leaves.push((path.to_vec(), entries));
+        self
+    }
+
+    /// Add an entry that is outside of the rect of the tree
+    #[must_use]
+    pub fn outside(mut self, identity: ID, point: Point, value: T) -> Self {
+        self.outside.push((identity, point, value));
+        self
+    }
+
+    /// Build the tree. Leaves may have more than `N` entries, and siblings that could be merged are not merged, so the tree is exactly as described.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the layout is not valid: if a leaf is on the path to another leaf, if a leaf is given twice or is deeper than 15, if an entry is not in the area of its leaf or not outside of the tree, or if an identity is used twice.
+    #[must_use]
+    pub fn build<const N: usize>(self) -> QuadTree<T, ID, N> {
+        let mut tree = QuadTree::new(self.rect.top_left(), self.rect.bottom_right());
+        let mut declared = Vec::new();
+        for (path, entries) in self.leaves {
+            let index = path
+                .iter()
+                .try_fold(Index::ROOT, |index, quadrant| {
+                    nest(&mut tree, index, &declared, &path);
+                    index.checked_child_at(*quadrant)
+                })
+                .unwrap_or_else(|| panic!("leaf {path:?} is deeper than 15"));
+            assert!(
+                !declared.contains(&index),
+                "leaf {path:?} is given more than once"
+            );
+            let bucket = expect_consistent(ensure_index_valid(&mut tree.items, index));
+            assert!(
+                !matches!(bucket, Bucket::Nested),
+                "leaf {path:?} is on the path to another leaf"
+            );
+            let Bucket::Owned(bucket) = bucket else {
+                unreachable!("checked above");
+            };
+            for (identity, point, value) in entries {
+                let mut rect = self.rect;
+                let in_leaf = rect.contains(point)
+                    && path.iter().all(|quadrant| {
+                        let (child, found) = rect.get_quadrant(point);
+                        rect = child;
+                        found == *quadrant
+                    });
+                assert!(
+                    in_leaf,
+                    "entry {identity:?} at {point:?} is not in leaf {path:?}"
+                );
+                tree.next_generation += 1;
+                let generation = tree.next_generation;
+                let previous = tree
+                    .identity_to_point
+                    .insert(identity.clone(), (point, Some(index), generation));
+                assert!(previous.is_none(), "identity {identity:?} is used twice");
+                bucket.push((IdentityPoint { identity, point }, value));
+            }
+            declared.push(index);
+        }
+        for (identity, point, value) in self.outside {
+            assert!(
+                !self.rect.contains(point),
+                "entry {identity:?} at {point:?} is not outside of the tree"
+            );
+            tree.next_generation += 1;
+            let generation = tree.next_generation;
+            let previous = tree
+                .identity_to_point
+                .insert(identity.clone(), (point, None, generation));
+            assert!(previous.is_none(), "identity {identity:?} is used twice");
+            tree.outside_of_range.insert(identity, (value, point));
+        }
+        tree
+    }
+}
+
+/// Make the node at `index`, which is on the path to leaf `path`, nested
+fn nest<T, ID, const N: usize>(
+    tree: &mut QuadTree<T, ID, N>,
+    index: Index,
+    declared: &[Index],
+    path: &[Quadrant],
+) {
+    assert!(
+        !declared.contains(&index),
+        "leaf {path:?} is inside of another leaf"
+    );
+    let bucket = expect_consistent(ensure_index_valid(&mut tree.items, index));
+    *bucket = Bucket::Nested;
+    if let Some(child) = index.checked_child_at(Quadrant::TopLeft) {
+        // this adds all four children as empty leaves
+        expect_consistent(ensure_index_valid(&mut tree.items, child));
+    }
+}
+
+/// Build a [`QuadTree`] with an exact layout, see the [module documentation](crate::testkit).
+///
+/// This is a shorthand for a [`LayoutBuilder`]: every leaf is a list of [`Quadrant`] names, followed by its entries as `(identity, x, y, value)`.
+#[macro_export]
+macro_rules! quadtree_layout {
+    ($top_left:expr, $bottom_right:expr, {
+        $([$($quadrant:ident),* $(,)?] => [$(($identity:expr, $x:expr, $y:expr, $value:expr)),* $(,)?]),* $(,)?
+    }) => {{
+        let builder = $crate::testkit::LayoutBuilder::new($top_left, $bottom_right);
+        $(
+            let builder = builder.leaf(
+                &[$($crate::Quadrant::$quadrant),*],
+                vec![$(($identity, $crate::Point::new($x, $y), $value)),*],
+            );
+        )*
+        builder.build()
+    }};
+}
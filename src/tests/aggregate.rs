@@ -0,0 +1,139 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, AggregateQuadTree, Point, R32};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Enemy {
+    strength: u32,
+}
+
+fn strength(enemy: &Enemy) -> u32 {
+    enemy.strength
+}
+
+type Tree = AggregateQuadTree<Enemy, u32, u32, 2>;
+
+/// The strongest and weakest enemy within `range`, by comparing every entry
+fn brute_force(tree: &Tree, center: Point, range: f32) -> Option<(u32, u32)> {
+    let mut found = Vec::new();
+    tree.tree()
+        .find_range(center, R32::new(range), |identity, _, enemy| {
+            found.push((enemy.strength, *identity));
+        });
+    let max = found
+        .iter()
+        .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))?;
+    let min = found.iter().min()?;
+    Some((max.1, min.1))
+}
+
+fn queried(tree: &Tree, center: Point, range: f32) -> Option<(u32, u32)> {
+    let max = tree.max_in_range(center, R32::new(range));
+    let min = tree.min_in_range(center, R32::new(range));
+    assert_eq!(max.is_some(), min.is_some());
+    Some((*max?.0, *min?.0))
+}
+
+#[test]
+fn follows_changes() {
+    let mut tree = Tree::new(Point::zero(), Point::new(64., 64.), strength);
+    assert_eq!(tree.max_in_range(Point::zero(), R32::new(100.)), None);
+    let mut seed = 7_u32;
+    let mut next = move |limit: u32| {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (seed >> 8) % limit
+    };
+    let centers = [
+        (Point::new(10., 10.), 8.),
+        (Point::new(32., 32.), 20.),
+        (Point::new(60., 5.), 12.),
+        (Point::new(-3., 30.), 6.),
+        (Point::new(32., 32.), 100.),
+    ];
+    for step in 0..600 {
+        let identity = next(120);
+        let point = Point::new(next(70) as f32 - 3., next(64) as f32);
+        match step % 5 {
+            0 | 1 => tree.insert(
+                ip(identity, point.x.raw(), point.y.raw()),
+                Enemy { strength: next(20) },
+            ),
+            2 => {
+                tree.update(identity, point);
+            }
+            3 => {
+                let strength = next(20);
+                tree.update_point_and_value(identity, point, |enemy| enemy.strength = strength);
+            }
+            _ => {
+                tree.try_remove(&identity);
+            }
+        }
+        for (center, range) in centers {
+            assert_eq!(
+                queried(&tree, center, range),
+                brute_force(&tree, center, range),
+                "step {step}, center {center:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn ties_pick_the_lowest_identity() {
+    let mut tree = Tree::new(Point::zero(), Point::new(64., 64.), strength);
+    for n in (0..20).rev() {
+        tree.insert(
+            ip(n, (n % 5) as f32 * 12. + 1., (n / 5) as f32 * 12. + 1.),
+            Enemy { strength: n % 3 },
+        );
+    }
+    let (identity, point, enemy) = tree
+        .max_in_range(Point::new(30., 30.), R32::new(100.))
+        .unwrap();
+    assert_eq!(
+        (*identity, point, enemy.strength),
+        (2, Point::new(25., 1.), 2)
+    );
+    let (identity, _, _) = tree
+        .min_in_range(Point::new(49., 37.), R32::new(13.))
+        .unwrap();
+    assert_eq!(*identity, 18);
+}
+
+#[test]
+fn independent_of_order() {
+    let entries = (0..60)
+        .map(|n: u32| (n, (n * 7 % 61) as f32, (n * 13 % 59) as f32, n % 4))
+        .collect::<Vec<_>>();
+    let build = |entries: &mut dyn Iterator<Item = &(u32, f32, f32, u32)>| {
+        let mut tree = Tree::new(Point::zero(), Point::new(64., 64.), strength);
+        for &(identity, x, y, strength) in entries {
+            tree.insert(ip(identity, x, y), Enemy { strength });
+        }
+        tree
+    };
+    let forward = build(&mut entries.iter());
+    let backward = build(&mut entries.iter().rev());
+    // the same entries can be in different leaves, but every query still finds the same entry
+    for center in [Point::new(10., 10.), Point::new(40., 20.), Point::zero()] {
+        for range in [3., 10., 30., 100.] {
+            let range = R32::new(range);
+            assert_eq!(
+                forward.max_in_range(center, range),
+                backward.max_in_range(center, range)
+            );
+            assert_eq!(
+                forward.min_in_range(center, range),
+                backward.min_in_range(center, range)
+            );
+        }
+    }
+    // ties are broken by the lowest identity, so the first of the entries with the largest strength is found
+    assert_eq!(
+        forward
+            .max_in_range(Point::new(32., 32.), R32::new(100.))
+            .map(|(identity, _, _)| *identity),
+        Some(3)
+    );
+}
@@ -0,0 +1,99 @@
+//! Checks that the queries that promise not to allocate don't, with an allocator that counts the allocations of every thread.
+
+use crate::{tests::ip, Point, QuadTree, RangeBoundary, RangeOptions, R32};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+// SAFETY: every call is forwarded to the system allocator
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // the counter is gone while the thread shuts down, those allocations don't matter
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The amount of allocations that `f` makes on this thread
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn tree() -> QuadTree<u32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..1000 {
+        tree.insert(ip(n, (n % 37) as f32 * 1.7, (n / 37) as f32 * 2.3), n);
+    }
+    // a deep branch, to check that the traversal does not need more room for deeper trees
+    for n in 0..20 {
+        tree.insert(ip(2000 + n, 0.001 * n as f32, 0.001), n);
+    }
+    tree.insert(ip(5000, -3., 4.), 5000);
+    tree
+}
+
+#[test]
+fn counts_allocations() {
+    assert_eq!(allocations(|| {}), 0);
+    assert_eq!(allocations(|| drop(Box::new(1))), 1);
+}
+
+#[test]
+fn find_range() {
+    let tree = tree();
+    for (center, range) in [
+        (Point::new(10., 10.), 5.),
+        (Point::zero(), 0.01),
+        (Point::new(32., 32.), 1000.),
+        (Point::new(f32::MAX, f32::MIN), f32::MAX),
+    ] {
+        let mut found = 0;
+        let count = allocations(|| {
+            tree.find_range(center, R32::new(range), |_, _, _| found += 1);
+            tree.find_range_with_options(
+                center,
+                R32::new(range),
+                RangeOptions::new()
+                    .boundary(RangeBoundary::Tolerance(R32::new(0.5)))
+                    .include_out_of_range(false),
+                |_, _, _| {},
+            );
+        });
+        assert_eq!(count, 0, "around {center:?} with range {range}");
+    }
+}
+
+#[test]
+fn other_queries() {
+    let tree = tree();
+    let count = allocations(|| {
+        assert!(tree.farthest_from(Point::new(3., 3.)).is_some());
+        let cost = tree.estimate_range_cost(Point::new(3., 3.), R32::new(10.));
+        assert!(cost.entries > 0);
+        tree.find_range_coarse(Point::new(3., 3.), R32::new(10.), R32::new(4.), |_| {});
+        assert!(tree.iter_range(Point::new(32., 32.), R32::new(1000.)).count() > 0);
+    });
+    assert_eq!(count, 0);
+}
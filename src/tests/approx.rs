@@ -0,0 +1,71 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, R32};
+use std::collections::BTreeSet;
+
+fn tree() -> QuadTree<u32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..1024 {
+        tree.insert(
+            ip(n, (n % 32) as f32 * 2. + 0.5, (n / 32) as f32 * 2. + 0.5),
+            n,
+        );
+    }
+    tree.insert(ip(2000, -1., 20.), 2000);
+    tree.insert(ip(2001, -20., 20.), 2001);
+    tree
+}
+
+fn within(tree: &QuadTree<u32, u32, 4>, center: Point, range: f32) -> BTreeSet<u32> {
+    let mut found = BTreeSet::new();
+    tree.find_range(center, R32::new(range), |identity, _, _| {
+        found.insert(*identity);
+    });
+    found
+}
+
+#[test]
+fn error_bound() {
+    let tree = tree();
+    let center = Point::new(3., 20.);
+    for epsilon in [0., 0.5, 2., 5.] {
+        let mut found = BTreeSet::new();
+        tree.find_range_approx(
+            center,
+            R32::new(12.),
+            R32::new(epsilon),
+            |identity, point, value| {
+                assert_eq!(identity, value);
+                assert_eq!(tree.get(identity), Some((point, value)));
+                assert!(found.insert(*identity), "{identity} was found twice");
+            },
+        );
+        let inner = within(&tree, center, 12. - epsilon - 0.001);
+        let outer = within(&tree, center, 12. + epsilon);
+        assert!(found.is_superset(&inner), "epsilon {epsilon}");
+        assert!(found.is_subset(&outer), "epsilon {epsilon}");
+        if epsilon == 0. {
+            assert_eq!(found, within(&tree, center, 12.));
+        }
+        // entries outside of the tree are exact
+        assert!(found.contains(&2000));
+        assert!(!found.contains(&2001));
+    }
+}
+
+#[test]
+fn epsilon_larger_than_range() {
+    let tree = tree();
+    let center = Point::new(31., 31.);
+    let mut found = BTreeSet::new();
+    tree.find_range_approx(center, R32::new(1.), R32::new(4.), |identity, _, _| {
+        found.insert(*identity);
+    });
+    assert!(found.is_subset(&within(&tree, center, 5.)));
+}
+
+#[test]
+#[should_panic = "Epsilon -1 should not be negative"]
+fn negative_epsilon() {
+    tree().find_range_approx(Point::zero(), R32::new(1.), R32::new(-1.), |_, _, _| {});
+}
@@ -0,0 +1,252 @@
+#![allow(clippy::cast_precision_loss, clippy::trivially_copy_pass_by_ref)] // the callbacks must match the signature of `from_reader`
+
+use crate::{
+    bucket::Bucket,
+    tests::{ip, ipv},
+    BulkBuilder, IdentityPoint, Point, QuadTree, Rect, R32,
+};
+use smallvec::smallvec;
+use std::io::{self, Read, Write};
+
+fn write_entry(writer: &mut dyn Write, point: &IdentityPoint<u32>, value: &u32) -> io::Result<()> {
+    writer.write_all(&point.identity.to_le_bytes())?;
+    writer.write_all(&point.point.x.raw().to_le_bytes())?;
+    writer.write_all(&point.point.y.raw().to_le_bytes())?;
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn read_entry(reader: &mut dyn Read) -> io::Result<Option<(IdentityPoint<u32>, u32)>> {
+    let mut bytes = [0; 16];
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let number = |n: usize| [bytes[n], bytes[n + 1], bytes[n + 2], bytes[n + 3]];
+    let point = IdentityPoint {
+        identity: u32::from_le_bytes(number(0)),
+        point: Point::new(f32::from_le_bytes(number(4)), f32::from_le_bytes(number(8))),
+    };
+    Ok(Some((point, u32::from_le_bytes(number(12)))))
+}
+
+fn spill_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("whquadtree-test-{}-{name}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn from_reader() {
+    let mut input = Vec::new();
+    let mut expected = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(100., 100.));
+    for n in 0..1000 {
+        let x = (n * 7919 % 1000) as f32 / 10. + if n % 100 == 0 { 200. } else { 0. };
+        let y = (n * 104_729 % 997) as f32 / 10.;
+        let point = IdentityPoint {
+            identity: n,
+            point: Point::new(x, y),
+        };
+        write_entry(&mut input, &point, &(n * 2)).unwrap();
+        expected.insert(point, n * 2);
+    }
+
+    for depth in [0, 2, 4] {
+        let dir = spill_dir(&format!("depth-{depth}"));
+        let tree: QuadTree<u32, u32, 4> = BulkBuilder::new(Point::zero(), Point::new(100., 100.))
+            .spill_dir(&dir)
+            .partition_depth(depth)
+            .from_reader(&input[..], read_entry, write_entry)
+            .unwrap();
+        for n in 0..1000 {
+            assert_eq!(tree.get(&n), expected.get(&n));
+        }
+        let mut found = 0;
+        tree.find_range(Point::new(50., 50.), R32::new(1000.), |_, _, _| found += 1);
+        assert_eq!(found, 1000);
+        // the spill files are removed again
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
+
+#[test]
+fn errors_remove_spill_files() {
+    let dir = spill_dir("errors");
+    let mut input = Vec::new();
+    for n in 0..10 {
+        let point = IdentityPoint {
+            identity: n,
+            point: Point::new(n as f32, 1.),
+        };
+        write_entry(&mut input, &point, &n).unwrap();
+    }
+    let mut writes = 0;
+    let result = BulkBuilder::new(Point::zero(), Point::new(10., 10.))
+        .spill_dir(&dir)
+        .from_reader::<u32, u32, 4>(&input[..], read_entry, |writer, point, value| {
+            writes += 1;
+            if writes == 5 {
+                return Err(io::Error::other("disk is full"));
+            }
+            write_entry(writer, point, value)
+        });
+    assert_eq!(result.unwrap_err().to_string(), "disk is full");
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    std::fs::remove_dir(&dir).unwrap();
+}
+
+/// The same entries as in `from_reader`, with every 100th entry outside of the tree
+fn entries() -> Vec<(IdentityPoint<u32>, u32)> {
+    (0..1000)
+        .map(|n| {
+            let x = (n * 7919 % 1000) as f32 / 10. + if n % 100 == 0 { 200. } else { 0. };
+            let y = (n * 104_729 % 997) as f32 / 10.;
+            (ip(n, x, y), n * 2)
+        })
+        .collect()
+}
+
+#[test]
+fn from_iter_bulk() {
+    let mut expected = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(100., 100.));
+    for (point, value) in entries() {
+        expected.insert(point, value);
+    }
+    let tree =
+        QuadTree::<u32, u32, 4>::from_iter_bulk(Point::zero(), Point::new(100., 100.), entries());
+    assert_eq!(tree.len(), 1000);
+    assert_eq!(tree.iter_out_of_range().count(), 10);
+    for n in 0..1000 {
+        assert_eq!(tree.get(&n), expected.get(&n));
+    }
+    for (center, range) in [
+        (Point::new(50., 50.), 10.),
+        (Point::new(3., 97.), 25.),
+        (Point::new(200., 50.), 60.),
+    ] {
+        let find = |tree: &QuadTree<u32, u32, 4>| {
+            let mut found = Vec::new();
+            tree.find_range(center, R32::new(range), |identity, _, _| {
+                found.push(*identity);
+            });
+            found.sort_unstable();
+            found
+        };
+        assert_eq!(find(&tree), find(&expected));
+    }
+    // every leaf could be split further by an insert, the same as for the inserted tree
+    assert!(tree.items.iter().all(|bucket| match bucket {
+        Bucket::Owned(entries) => entries.len() <= 4,
+        Bucket::Nested => true,
+    }));
+}
+
+#[test]
+fn from_iter_bulk_layout() {
+    let top_left = Rect::new(Point::zero(), Point::new(4., 4.));
+    let tree = QuadTree::<u32, u32, 2>::from_iter_bulk(
+        Point::zero(),
+        Point::new(8., 8.),
+        [
+            (ip(1, 1., 1.), 1),
+            (ip(2, 3., 1.), 2),
+            (ip(3, 1., 3.), 3),
+            (ip(4, 6., 6.), 4),
+            // a later entry replaces an earlier one with the same identity
+            (ip(1, 0.5, 0.5), 10),
+            (ip(5, 9., 9.), 5),
+        ],
+    );
+    assert_eq!(
+        tree.items,
+        vec![
+            Bucket::Nested,
+            Bucket::Nested,
+            Bucket::Owned(smallvec![]),
+            Bucket::Owned(smallvec![]),
+            Bucket::Owned(smallvec![ipv(4, 6., 6., 4)]),
+            Bucket::Owned(smallvec![ipv(1, 0.5, 0.5, 10)]),
+            Bucket::Owned(smallvec![ipv(2, 3., 1., 2)]),
+            Bucket::Owned(smallvec![ipv(3, 1., 3., 3)]),
+            Bucket::Owned(smallvec![]),
+        ]
+    );
+    assert_eq!(
+        tree.node_rect(tree.node_of(&1).unwrap()),
+        Rect::new(Point::zero(), Point::new(2., 2.))
+    );
+    assert_eq!(
+        tree.node_rect(tree.node_of(&1).unwrap().parent().unwrap()),
+        top_left
+    );
+    assert_eq!(tree.get(&5), Some((Point::new(9., 9.), &5)));
+    assert_eq!(tree.len(), 5);
+
+    // entries in the same child are not split, like after an insert
+    let clustered = QuadTree::<u32, u32, 2>::from_iter_bulk(
+        Point::zero(),
+        Point::new(8., 8.),
+        (0..5).map(|n| (ip(n, 1. + n as f32 / 10., 1.), n)),
+    );
+    assert_eq!(clustered.items.len(), 1);
+    assert_eq!(clustered.effective_max_depth(), 0);
+}
+
+#[test]
+fn extend() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(100., 100.));
+    tree.extend(entries());
+    let bulk =
+        QuadTree::<u32, u32, 4>::from_iter_bulk(Point::zero(), Point::new(100., 100.), entries());
+    assert_eq!(tree, bulk);
+
+    // a tree with entries inserts the new entries one by one
+    tree.extend([(ip(5000, 1., 1.), 1), (ip(5001, 300., 1.), 2)]);
+    assert_eq!(tree.len(), 1002);
+    assert_eq!(tree.get(&5001), Some((Point::new(300., 1.), &2)));
+}
+
+#[test]
+fn collect() {
+    let entries = entries();
+    let tree = entries.iter().copied().collect::<QuadTree<u32, u32, 4>>();
+    // the entry at `0, 0` is moved out to `200, 0`
+    let bounds = (Point::new(0.1, 0.), Point::new(290., 99.6));
+    assert_eq!(tree.bounds(), bounds);
+    assert_eq!(tree.len(), 1000);
+    assert_eq!(tree.iter_out_of_range().count(), 0);
+    assert_eq!(tree, QuadTree::from_iter_bulk(bounds.0, bounds.1, entries));
+
+    let empty = std::iter::empty().collect::<QuadTree<u32, u32, 4>>();
+    assert_eq!(empty.bounds(), (Point::new(0., 0.), Point::new(0., 0.)));
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn optimize() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::new(0., 0.), Point::new(100., 100.));
+    tree.set_deferred_merges(true);
+    for (point, value) in entries() {
+        tree.insert(point, value);
+    }
+    for identity in 0..900 {
+        tree.try_remove(&identity);
+    }
+    let weak = tree.weak_ref(&950).unwrap();
+    let depth = tree.effective_max_depth();
+
+    tree.optimize();
+    let mut expected = QuadTree::from_iter_bulk(
+        Point::new(0., 0.),
+        Point::new(100., 100.),
+        entries().into_iter().skip(900),
+    );
+    expected.set_deferred_merges(true);
+    assert_eq!(tree, expected);
+    assert_eq!(tree.pending_maintenance(), 0);
+    assert!(tree.effective_max_depth() < depth);
+    assert_eq!(tree.iter_out_of_range().count(), 1);
+    // the entries are not moved, so references to them stay valid
+    assert_eq!(weak.upgrade(&tree), Some((entries()[950].0.point, &1900)));
+}
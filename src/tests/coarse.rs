@@ -0,0 +1,63 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, CoarseHit, Point, QuadTree, R32};
+
+fn tree() -> QuadTree<u32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..256 {
+        tree.insert(
+            ip(n, (n % 16) as f32 * 4. + 1., (n / 16) as f32 * 4. + 1.),
+            n,
+        );
+    }
+    tree.insert(ip(1000, -2., 1.), 1000);
+    tree
+}
+
+#[test]
+fn without_cells() {
+    let tree = tree();
+    let mut expected = Vec::new();
+    tree.find_range(Point::new(20., 20.), R32::new(10.), |identity, _, _| {
+        expected.push(*identity);
+    });
+    let mut found = Vec::new();
+    tree.find_range_coarse(
+        Point::new(20., 20.),
+        R32::new(10.),
+        R32::new(0.),
+        |hit| match hit {
+            CoarseHit::Entry { identity, .. } => found.push(*identity),
+            CoarseHit::Cell { .. } => panic!("no node is smaller than 0"),
+        },
+    );
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn cells() {
+    let tree = tree();
+    let mut entries = Vec::new();
+    let mut cells = Vec::new();
+    tree.find_range_coarse(
+        Point::new(-3., 1.),
+        R32::new(30.),
+        R32::new(20.),
+        |hit| match hit {
+            CoarseHit::Entry { identity, .. } => entries.push(*identity),
+            CoarseHit::Cell { node, rect, count } => cells.push((node.depth(), rect, count)),
+        },
+    );
+    // only the entry outside of the tree is passed by itself
+    assert_eq!(entries, vec![1000]);
+    // the cells of 16 × 16 near the top-left corner, with 16 entries each
+    assert!(!cells.is_empty());
+    for (depth, rect, count) in &cells {
+        assert_eq!(*depth, 2);
+        assert_eq!(*count, 16);
+        let (top_left, bottom_right) = (rect.top_left(), rect.bottom_right());
+        assert!(top_left.x < 27. && top_left.y < 31.);
+        assert_eq!(bottom_right.x - top_left.x, 16.);
+    }
+    assert!(cells.len() < 16);
+}
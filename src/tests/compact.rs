@@ -0,0 +1,58 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, R32};
+
+fn tree() -> QuadTree<u32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(100., 100.));
+    for n in 0..200 {
+        let x = (n * 37 % 100) as f32 + 0.123;
+        let y = (n * 61 % 100) as f32 + 0.456;
+        tree.insert(ip(n, x, y), n * 2);
+    }
+    tree.insert(ip(200, -10.5, 250.25), 400);
+    tree
+}
+
+#[test]
+fn approximate_positions() {
+    let original = tree();
+    let compact = tree().into_compact();
+    assert_eq!(compact.len(), 201);
+    assert!(!compact.is_empty());
+
+    let mut seen = 0;
+    compact.for_each(|identity, point, value| {
+        seen += 1;
+        let (original_point, original_value) = original.get(identity).unwrap();
+        assert_eq!(value, original_value);
+        if *identity == 200 {
+            assert_eq!(point, original_point);
+        } else {
+            // no leaf is larger than the root
+            let max_error = 100. / 65535.;
+            assert!((point.x.raw() - original_point.x.raw()).abs() <= max_error);
+            assert!((point.y.raw() - original_point.y.raw()).abs() <= max_error);
+        }
+    });
+    assert_eq!(seen, 201);
+}
+
+#[test]
+fn find_range() {
+    let original = tree();
+    let compact = tree().into_compact();
+    for (center, range) in [
+        (Point::new(50., 50.), 20.),
+        (Point::new(0., 0.), 35.),
+        (Point::new(-10., 250.), 1.),
+    ] {
+        let range = R32::new(range);
+        let mut expected = Vec::new();
+        original.find_range(center, range, |identity, _, _| expected.push(*identity));
+        let mut found = Vec::new();
+        compact.find_range(center, range, |identity, _, _| found.push(*identity));
+        expected.sort_unstable();
+        found.sort_unstable();
+        assert_eq!(found, expected);
+    }
+}
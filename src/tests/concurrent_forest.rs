@@ -0,0 +1,92 @@
+#![cfg(feature = "forest")]
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, ConcurrentQuadForest, Point, QuadTree, R32};
+
+fn entries_in_range(
+    find_range: impl FnOnce(&mut dyn FnMut(&u32, Point, &u32)),
+) -> Vec<(u32, Point, u32)> {
+    let mut entries = Vec::new();
+    find_range(&mut |identity, point, value| entries.push((*identity, point, *value)));
+    entries.sort_unstable_by_key(|(identity, _, _)| *identity);
+    entries
+}
+
+/// The position of entry `n` after `step` moves, which is sometimes outside of the forest
+fn position(n: u32, step: u32) -> Point {
+    let x = ((n * 37 + step * 11) % 110) as f32 - 5.;
+    let y = ((n * 23 + step * 7) % 104) as f32 - 2.;
+    Point::new(x, y)
+}
+
+#[test]
+fn matches_single_tree() {
+    const THREADS: u32 = 4;
+    const ENTRIES: u32 = 100;
+    const STEPS: u32 = 20;
+
+    let forest = ConcurrentQuadForest::<u32, u32, 4>::new(Point::zero(), Point::new(100., 100.), 4);
+    std::thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let forest = &forest;
+            scope.spawn(move || {
+                let identities = (0..ENTRIES).map(|n| n * THREADS + thread);
+                for n in identities.clone() {
+                    forest.insert(ip(n, 0., 0.), 0);
+                }
+                for step in 0..STEPS {
+                    for n in identities.clone() {
+                        assert!(
+                            forest.update_point_and_value(n, position(n, step), |value| {
+                                *value += 1;
+                            })
+                        );
+                    }
+                }
+                for n in identities.filter(|n| n % 7 == 0) {
+                    assert_eq!(forest.try_remove(&n), Some((STEPS, position(n, STEPS - 1))));
+                }
+            });
+        }
+    });
+
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(100., 100.));
+    for n in (0..ENTRIES * THREADS).filter(|n| n % 7 != 0) {
+        tree.insert(ip(n, 0., 0.), STEPS);
+        tree.update(n, position(n, STEPS - 1));
+    }
+    let mut count = 0;
+    tree.find_range(Point::zero(), R32::new(1000.), |_, _, _| count += 1);
+    assert_eq!(forest.len(), count);
+
+    for (center, range) in [
+        (Point::new(50., 50.), 1000.),
+        (Point::new(25., 25.), 25.),
+        (Point::new(-5., 100.), 30.),
+        (Point::new(75., 10.), 0.),
+    ] {
+        let range = R32::new(range);
+        let expected = entries_in_range(|callback| tree.find_range(center, range, callback));
+        let found = entries_in_range(|callback| forest.find_range(center, range, callback));
+        assert_eq!(found, expected, "find_range({center:?}, {range})");
+    }
+}
+
+#[test]
+fn with_shard() {
+    let forest = ConcurrentQuadForest::<(), u32, 4>::new(Point::zero(), Point::new(10., 10.), 2);
+    forest.insert(ip(1, 6., 1.), ());
+    forest.insert(ip(2, 20., 1.), ());
+    assert_eq!(forest.shard_of(Point::new(6., 1.)), Some(1));
+    assert_eq!(forest.shard_of(Point::new(20., 1.)), None);
+
+    let mut found = Vec::new();
+    forest.with_shard(1, |shard| {
+        shard.find_range(Point::zero(), R32::new(100.), |identity, _, ()| {
+            found.push(*identity);
+        });
+    });
+    assert_eq!(found, [1]);
+    assert_eq!(forest.len(), 2);
+    assert!(!forest.is_empty());
+}
@@ -0,0 +1,49 @@
+use crate::{
+    tests::{ip, rect},
+    Point, QuadTree,
+};
+
+#[test]
+fn corners_are_not_connected() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(8., 8.));
+    tree.insert(ip(1, 1., 1.), 1);
+    tree.insert(ip(2, 5., 5.), 2);
+
+    let regions = tree.connected_regions(|_, entries| entries.is_empty());
+    assert_eq!(
+        regions,
+        vec![vec![rect(4., 0., 8., 4.)], vec![rect(0., 4., 4., 8.)]]
+    );
+
+    let regions = tree.connected_regions(|_, entries| entries.len() <= 1);
+    assert_eq!(
+        regions,
+        vec![vec![
+            rect(0., 0., 4., 4.),
+            rect(4., 0., 8., 4.),
+            rect(0., 4., 4., 8.),
+            rect(4., 4., 8., 8.),
+        ]]
+    );
+
+    assert!(tree.connected_regions(|_, _| false).is_empty());
+}
+
+#[test]
+fn leaves_of_different_sizes() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(8., 8.));
+    // split the top-left quadrant a second time
+    tree.insert(ip(1, 1., 1.), 1);
+    tree.insert(ip(2, 5., 5.), 2);
+    tree.insert(ip(3, 3., 3.), 3);
+
+    // the empty leaves inside of the top-left quadrant connect to the larger empty top-right and bottom-left quadrants
+    let regions = tree.connected_regions(|_, entries| entries.is_empty());
+    assert_eq!(
+        regions,
+        vec![
+            vec![rect(2., 0., 4., 2.), rect(4., 0., 8., 4.)],
+            vec![rect(0., 2., 2., 4.), rect(0., 4., 4., 8.)],
+        ]
+    );
+}
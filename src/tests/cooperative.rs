@@ -0,0 +1,93 @@
+#![cfg(feature = "async")]
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, yield_now, IdentityPoint, Point, QuadTree, R32};
+use std::{
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+};
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Run `future` to completion, and return its output with the amount of times it yielded
+fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    let mut yields = 0;
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return (output, yields),
+            Poll::Pending => yields += 1,
+        }
+    }
+}
+
+fn entries() -> Vec<(IdentityPoint<u32>, u32)> {
+    (0..300)
+        .map(|n| {
+            (
+                ip(n, (n * 37 % 70) as f32 - 3., (n * 23 % 66) as f32 - 1.),
+                n,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn build_async() {
+    let (tree, yields) = block_on(QuadTree::<u32, u32, 4>::build_async(
+        Point::zero(),
+        Point::new(64., 64.),
+        entries(),
+        100,
+    ));
+    assert_eq!(yields, 3);
+
+    let mut expected = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for (point, value) in entries() {
+        expected.insert(point, value);
+    }
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn find_range_async() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    for (point, value) in entries() {
+        tree.insert(point, value);
+    }
+    for (center, range) in [(Point::new(20., 30.), 15.), (Point::new(32., 32.), 100.)] {
+        let range = R32::new(range);
+        let mut expected = Vec::new();
+        tree.find_range(center, range, |identity, _, _| expected.push(*identity));
+
+        let mut found = Vec::new();
+        let ((), yields) = block_on(tree.find_range_async(center, range, 1, |identity, _, _| {
+            found.push(*identity);
+        }));
+        assert_eq!(found, expected);
+        assert!(yields > 1);
+
+        let mut hooks = 0;
+        let mut found = Vec::new();
+        let ((), yields) = block_on(tree.find_range_yielding(
+            center,
+            range,
+            4,
+            || {
+                hooks += 1;
+                yield_now()
+            },
+            |identity, _, _| found.push(*identity),
+        ));
+        assert_eq!(found, expected);
+        assert_eq!(hooks, yields);
+    }
+}
@@ -0,0 +1,152 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, R32};
+
+fn all_found(tree: &QuadTree<u32, u32, 1>, center: Point, range: f32) -> Vec<u32> {
+    let mut found = Vec::new();
+    tree.find_range(center, R32::new(range), |identity, _, _| {
+        found.push(*identity);
+    });
+    found.sort_unstable();
+    found
+}
+
+#[test]
+fn zero_area_root() {
+    let point = Point::new(3., 4.);
+    let mut tree = QuadTree::<u32, u32, 1>::new(point, point);
+    for n in 0..10 {
+        tree.insert(ip(n, 3., 4.), n);
+    }
+    tree.insert(ip(10, 3., 4.5), 10);
+    // the entries at the point can never be separated, so the root overflows instead of splitting
+    assert_eq!(tree.items.len(), 1);
+    assert_eq!(tree.depth_of(&0), Some(0));
+    assert_eq!(tree.depth_of(&10), None);
+    assert_eq!(tree.outside_of_range.len(), 1);
+    assert_eq!(all_found(&tree, point, 0.), (0..10).collect::<Vec<_>>());
+    assert_eq!(all_found(&tree, point, 1.), (0..11).collect::<Vec<_>>());
+    for n in 0..11 {
+        assert_eq!(
+            tree.remove(&n),
+            (n, if n == 10 { Point::new(3., 4.5) } else { point })
+        );
+    }
+    assert_eq!(tree, QuadTree::new(point, point));
+}
+
+#[test]
+fn thin_root() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(0., 64.));
+    for n in 0..64 {
+        tree.insert(ip(n, 0., n as f32), n);
+    }
+    assert!(tree.items.len() > 1);
+    for n in 0..64 {
+        assert!(tree.depth_of(&n).is_some_and(|depth| depth <= 15));
+    }
+    assert_eq!(all_found(&tree, Point::new(0., 10.), 1.), vec![9, 10, 11]);
+    for n in 0..64 {
+        assert_eq!(tree.remove(&n), (n, Point::new(0., n as f32)));
+    }
+    assert_eq!(all_found(&tree, Point::zero(), 100.), vec![]);
+}
+
+#[test]
+fn splits_one_level_per_insert() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(64., 64.));
+    // every point is in the top-right child of the leaf that holds entry 0, so every insert splits that leaf one level deeper
+    let point = |n: u32| match n {
+        0 => Point::new(1e-9, 1e-9),
+        n => Point::new(48. / (1 << (n - 1)) as f32, 1e-9),
+    };
+    for n in 0..8 {
+        let point = point(n);
+        tree.insert(ip(n, point.x.raw(), point.y.raw()), n);
+        assert_eq!(tree.depth_of(&0), Some(n as usize), "{n}");
+    }
+    for n in 0..8 {
+        assert_eq!(tree.get(&n), Some((point(n), &n)));
+        assert_eq!(all_found(&tree, point(n), 0.), vec![n]);
+    }
+    for n in 0..8 {
+        assert_eq!(tree.remove(&n), (n, point(n)));
+    }
+    assert_eq!(all_found(&tree, Point::zero(), 100.), vec![]);
+}
+
+#[test]
+fn does_not_split_cells_that_can_not_be_halved() {
+    // the middle of two neighbouring floats is rounded onto one of them, so one child would cover the whole root
+    let left = f32::from_bits(1_f32.to_bits() + 1);
+    let right = f32::from_bits(1_f32.to_bits() + 2);
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::new(left, left), Point::new(right, right));
+    assert_eq!(tree.rect.middle(), Point::new(right, right));
+    tree.insert(ip(1, left, left), 1);
+    tree.insert(ip(2, right, right), 2);
+    tree.insert(ip(3, left, right), 3);
+    assert_eq!(tree.items.len(), 1);
+    assert_eq!(all_found(&tree, Point::new(left, left), 1.), vec![1, 2, 3]);
+    assert!(tree.update(1, Point::new(right, left)));
+    assert_eq!(tree.items.len(), 1);
+    assert_eq!(tree.get(&1), Some((Point::new(right, left), &1)));
+}
+
+#[test]
+fn update_splits_the_leaf_it_moves_into() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(64., 64.));
+    tree.insert(ip(1, 1., 1.), 1);
+    tree.insert(ip(2, 40., 40.), 2);
+    tree.insert(ip(3, 1., 20.), 3);
+    tree.insert(ip(4, 100., 100.), 4);
+    assert_eq!(tree.depth_of(&1), Some(2));
+    // the leaf of entry 1 covers `(0, 0)` to `(16, 16)`, whose middle separates both entries
+    assert!(tree.update(4, Point::new(10., 1.)));
+    assert_eq!(tree.depth_of(&1), Some(3));
+    assert_eq!(tree.depth_of(&4), Some(3));
+    assert_eq!(all_found(&tree, Point::new(10., 1.), 0.), vec![4]);
+}
+
+#[test]
+fn denormal_coordinates() {
+    let size = f32::MIN_POSITIVE / 1024.;
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(size, size));
+    for n in 0..40 {
+        let offset = size * (n % 8) as f32 / 8.;
+        tree.insert(ip(n, offset, size * (n / 8) as f32 / 8.), n);
+    }
+    for n in 0..40 {
+        assert!(tree.depth_of(&n).is_some_and(|depth| depth <= 15), "{n}");
+    }
+    assert_eq!(all_found(&tree, Point::zero(), size * 4.).len(), 40);
+}
+
+#[test]
+fn min_cell_extent() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(16., 16.));
+    assert_eq!(tree.min_cell_extent(), R32::new(16. * f32::EPSILON * 4.));
+    assert_ne!(tree, {
+        let mut other = tree.clone();
+        other.set_min_cell_extent(R32::new(10.));
+        other
+    });
+    tree.set_min_cell_extent(R32::new(10.));
+    tree.insert(ip(1, 1., 1.), 1);
+    tree.insert(ip(2, 9., 9.), 2);
+    tree.insert(ip(3, 5., 1.), 3);
+    tree.insert(ip(4, 20., 20.), 4);
+    // the children of the root are 8 wide, so they hold every entry instead of splitting
+    assert_eq!(tree.items.len(), 5);
+    assert_eq!(tree.depth_of(&1), Some(1));
+    assert_eq!(tree.depth_of(&3), Some(1));
+    assert!(tree.update(4, Point::new(1., 5.)));
+    assert_eq!(tree.items.len(), 5);
+    assert_eq!(tree.depth_of(&4), Some(1));
+    assert_eq!(all_found(&tree, Point::new(3., 3.), 3.), vec![1, 3, 4]);
+
+    // a smaller extent lets the next insert split the leaf
+    tree.set_min_cell_extent(R32::new(1.));
+    tree.insert(ip(5, 7., 7.), 5);
+    assert_eq!(tree.depth_of(&1), Some(2));
+    assert_eq!(tree.depth_of(&5), Some(2));
+}
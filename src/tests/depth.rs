@@ -0,0 +1,49 @@
+use crate::{tests::ip, DepthUsage, NodeId, Point, QuadTree, Quadrant};
+
+#[test]
+fn depth_usage() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(64., 64.));
+    assert_eq!(tree.effective_max_depth(), 0);
+    assert_eq!(tree.depth_usage(NodeId::ROOT), DepthUsage::default());
+    // every point splits the leaf of entry 0 one level deeper
+    tree.insert(ip(0, 1e-9, 1e-9), 0);
+    for n in 1..6 {
+        tree.insert(ip(n, 48. / f32::from(1_u16 << (n - 1)), 1e-9), n);
+    }
+    tree.insert(ip(10, 60., 60.), 10);
+    tree.insert(ip(11, 60., 60.), 11);
+    assert_eq!(tree.effective_max_depth(), 5);
+
+    let usage = tree.depth_usage(NodeId::ROOT);
+    assert_eq!(
+        usage,
+        DepthUsage {
+            deepest: 5,
+            overflowing_leaves: 1,
+            entries_at_max_depth: 0,
+        }
+    );
+    assert_eq!(usage.headroom(), 10);
+    assert!(usage.is_near_limit(10));
+    assert!(!usage.is_near_limit(9));
+
+    let top_left = NodeId::ROOT.child(Quadrant::TopLeft).unwrap();
+    let bottom_right = NodeId::ROOT.child(Quadrant::BottomRight).unwrap();
+    let subtrees = tree.subtree_depth_usage(1);
+    assert_eq!(subtrees.len(), 4);
+    assert_eq!(subtrees[0], (top_left, tree.depth_usage(top_left)));
+    assert_eq!(tree.depth_usage(top_left).deepest, 5);
+    assert_eq!(
+        tree.depth_usage(bottom_right),
+        DepthUsage {
+            deepest: 1,
+            overflowing_leaves: 1,
+            entries_at_max_depth: 0,
+        }
+    );
+    // below a leaf, and below the deepest leaf
+    let below_leaf = bottom_right.child(Quadrant::TopLeft).unwrap();
+    assert_eq!(tree.depth_usage(below_leaf).deepest, 2);
+    assert_eq!(tree.subtree_depth_usage(6), vec![]);
+    assert_eq!(tree.subtree_depth_usage(5).len(), 4);
+}
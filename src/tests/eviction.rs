@@ -0,0 +1,79 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, EvictionPolicy, Point, QuadTree, R32};
+
+fn found(tree: &QuadTree<u32, u32, 4>) -> Vec<u32> {
+    let mut found = Vec::new();
+    tree.find_range(Point::zero(), R32::new(1000.), |identity, _, _| {
+        found.push(*identity);
+    });
+    found.sort_unstable();
+    found
+}
+
+#[test]
+fn oldest() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    let mut evicted = Vec::new();
+    for n in 0..20 {
+        tree.insert_bounded(
+            ip(n, 10., 10.),
+            n,
+            8,
+            EvictionPolicy::Oldest,
+            |identity, point, value| {
+                assert_eq!(identity, value);
+                assert_eq!(point, Point::new(10., 10.));
+                evicted.push(identity);
+            },
+        );
+    }
+    assert_eq!(evicted, (0..12).collect::<Vec<_>>());
+    assert_eq!(found(&tree), (12..20).collect::<Vec<_>>());
+
+    // entries that can be split into other leaves are never evicted
+    for n in 20..40 {
+        tree.insert_bounded(
+            ip(n, (n % 8) as f32 * 8. + 20., (n / 8) as f32 * 8.),
+            n,
+            1,
+            EvictionPolicy::Oldest,
+            |_, _, _| {
+                panic!("nothing should be evicted");
+            },
+        );
+    }
+    assert_eq!(found(&tree).len(), 28);
+}
+
+#[test]
+fn farthest_from_center() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    let mut evicted = Vec::new();
+    // all of these are in the top-left quadrant of the root, which can not split, so the farthest from (32, 32) are evicted
+    let points = [
+        (0., 0.),
+        (0.001, 0.),
+        (0., 0.002),
+        (0.003, 0.),
+        (0., 0.0005),
+        (0.004, 0.004),
+    ];
+    for (n, (x, y)) in points.into_iter().enumerate() {
+        let n = u32::try_from(n).unwrap();
+        tree.insert_bounded(
+            ip(n, x, y),
+            n,
+            4,
+            EvictionPolicy::FarthestFromCenter,
+            |identity, _, _| {
+                evicted.push(identity);
+            },
+        );
+    }
+    assert_eq!(found(&tree).len(), 4);
+    assert_eq!(evicted.len(), 2);
+    assert!(evicted
+        .iter()
+        .all(|identity| !found(&tree).contains(identity)));
+}
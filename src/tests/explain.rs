@@ -0,0 +1,78 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, CostEstimate, NodeId, NodeVisit, Point, QuadTree, Quadrant, R32};
+
+#[test]
+fn matches_find_range() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..40 {
+        tree.insert(ip(n, (n % 8) as f32 * 8. + 1., (n / 8) as f32 * 8. + 1.), n);
+    }
+    tree.insert(ip(100, -5., 2.), 100);
+
+    for (center, range) in [
+        (Point::new(10., 10.), 12.),
+        (Point::new(0., 0.), 8.),
+        (Point::new(60., 60.), 3.),
+    ] {
+        let mut found = 0;
+        tree.find_range(center, R32::new(range), |_, _, _| found += 1);
+        let trace = tree.explain_find_range(center, R32::new(range));
+        assert_eq!(trace.matches(), found);
+        assert_eq!(trace.center(), center);
+        assert!(trace.comparisons() >= found);
+        assert_eq!(trace.nodes()[0].node, NodeId::ROOT);
+        assert_eq!(trace.nodes()[0].visit, NodeVisit::Nested);
+        assert!(trace
+            .to_string()
+            .starts_with(&format!("find_range around ({}, {})", center.x, center.y)));
+    }
+}
+
+#[test]
+fn display() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(8., 8.));
+    tree.insert(ip(1, 1., 1.), 10);
+    tree.insert(ip(2, 5., 1.), 20);
+    tree.insert(ip(3, -3., 1.), 30);
+
+    let trace = tree.explain_find_range(Point::new(1., 1.), R32::new(2.));
+    assert_eq!(
+        trace.nodes()[1].node,
+        NodeId::from_path([Quadrant::TopLeft]).unwrap()
+    );
+    assert_eq!(
+        trace.nodes()[1].visit,
+        NodeVisit::Leaf {
+            compared: 1,
+            matched: 1
+        }
+    );
+    let rendered = trace.to_string();
+    assert!(rendered.contains("\n[] (0, 0)..(8, 8): nested\n"));
+    assert!(rendered.contains("\n  [TopLeft] (0, 0)..(4, 4): leaf, 1 of 1 entries in range\n"));
+    assert!(rendered.ends_with("outside of the tree: 0 of 1 entries in range\n"));
+}
+
+#[test]
+fn cost_estimate() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    assert_eq!(
+        tree.estimate_range_cost(Point::zero(), R32::new(10.)),
+        CostEstimate {
+            nodes: 1,
+            entries: 0
+        }
+    );
+    for n in 0..40 {
+        tree.insert(ip(n, (n % 8) as f32 * 8. + 1., (n / 8) as f32 * 8. + 1.), n);
+    }
+    tree.insert(ip(100, -5., 2.), 100);
+
+    for (center, range) in [(Point::new(10., 10.), 12.), (Point::new(60., 60.), 3.)] {
+        let cost = tree.estimate_range_cost(center, R32::new(range));
+        let trace = tree.explain_find_range(center, R32::new(range));
+        assert_eq!(cost.nodes, trace.nodes().len());
+        assert_eq!(cost.entries, trace.comparisons());
+    }
+}
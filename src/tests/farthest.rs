@@ -0,0 +1,97 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, R32};
+
+fn tree() -> QuadTree<(), u32, 2> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..150 {
+        let x = (n * 37 % 64) as f32 + 0.25;
+        let y = (n * 23 % 61) as f32 + 0.5;
+        tree.insert(ip(n, x, y), ());
+    }
+    tree
+}
+
+fn entries(tree: &QuadTree<(), u32, 2>) -> Vec<(u32, Point)> {
+    let mut entries = Vec::new();
+    tree.find_range(
+        Point::new(32., 32.),
+        R32::new(1000.),
+        |identity, point, ()| {
+            entries.push((*identity, point));
+        },
+    );
+    entries.sort_unstable_by_key(|(identity, _)| *identity);
+    entries
+}
+
+fn brute_force_farthest(tree: &QuadTree<(), u32, 2>, point: Point) -> Option<(u32, Point, R32)> {
+    entries(tree)
+        .into_iter()
+        .map(|(identity, other)| {
+            (
+                point.distance_squared_to(other),
+                std::cmp::Reverse(identity),
+                other,
+            )
+        })
+        .max()
+        .map(|(distance, identity, other)| (identity.0, other, R32::new(distance.raw().sqrt())))
+}
+
+#[test]
+fn farthest_from() {
+    let mut tree = tree();
+    tree.insert(ip(150, -20., 3.), ());
+    for point in [
+        Point::new(0., 0.),
+        Point::new(32., 32.),
+        Point::new(63., 10.),
+        Point::new(-100., 200.),
+    ] {
+        let farthest = tree
+            .farthest_from(point)
+            .map(|(identity, point, distance)| (*identity, point, distance));
+        assert_eq!(farthest, brute_force_farthest(&tree, point));
+    }
+
+    // ties are broken by identity
+    let mut tree = QuadTree::<(), u32, 2>::new(Point::zero(), Point::new(10., 10.));
+    assert_eq!(tree.farthest_from(Point::zero()), None);
+    tree.insert(ip(2, 6., 8.), ());
+    tree.insert(ip(1, 8., 6.), ());
+    assert_eq!(
+        tree.farthest_from(Point::zero()),
+        Some((&1, Point::new(8., 6.), R32::new(10.)))
+    );
+}
+
+#[test]
+fn diameter() {
+    let mut tree = tree();
+    tree.insert(ip(150, 70., 80.), ());
+    let entries = entries(&tree);
+    let mut expected = None;
+    for (a, point) in &entries {
+        for (b, other) in &entries {
+            if a < b {
+                let pair = (
+                    point.distance_squared_to(*other),
+                    std::cmp::Reverse((*a, *b)),
+                );
+                expected = expected.max(Some(pair));
+            }
+        }
+    }
+    let (distance, std::cmp::Reverse((a, b))) = expected.unwrap();
+    assert_eq!(
+        tree.diameter(),
+        Some((&a, &b, R32::new(distance.raw().sqrt())))
+    );
+
+    let mut tree = QuadTree::<(), u32, 2>::new(Point::zero(), Point::new(10., 10.));
+    tree.insert(ip(1, 1., 1.), ());
+    assert_eq!(tree.diameter(), None);
+    tree.insert(ip(2, 1., 1.), ());
+    assert_eq!(tree.diameter(), Some((&1, &2, R32::new(0.))));
+}
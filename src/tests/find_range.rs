@@ -1,7 +1,10 @@
 #![allow(clippy::cast_precision_loss)]
 
-use crate::{tests::ip, Point, QuadTree};
+use crate::{
+    tests::ip, NodeVisit, Point, PruneReason, QuadTree, RangeBoundary, RangeOptions, Rect, YAxis,
+};
 use noisy_float::types::r32;
+use std::ops::ControlFlow;
 
 #[test]
 pub fn in_range() {
@@ -24,7 +27,7 @@ pub fn in_range() {
 
     let mut points = Vec::new();
     tree.find_range(Point::zero(), r32(3.5), |id, point, v| {
-        points.push((*id, point.x, point.y, *v))
+        points.push((*id, point.x, point.y, *v));
     });
     assert_eq!(
         points,
@@ -43,7 +46,7 @@ pub fn in_range() {
 
     let mut points = Vec::new();
     tree.find_range(Point::new(4.5, 4.5), r32(5.5), |id, point, v| {
-        points.push((*id, point.x, point.y, *v))
+        points.push((*id, point.x, point.y, *v));
     });
     assert_eq!(
         points,
@@ -71,7 +74,7 @@ pub fn out_of_range() {
 
     let mut points = Vec::new();
     tree.find_range(Point::new(-10., -10.), r32(3.5), |id, point, v| {
-        points.push((*id, point.x, point.y, *v))
+        points.push((*id, point.x, point.y, *v));
     });
     assert_eq!(
         points,
@@ -84,3 +87,419 @@ pub fn out_of_range() {
         ]
     );
 }
+
+#[test]
+pub fn range_boundary() {
+    let mut tree = QuadTree::<u32, u32, 4>::sized_around_origin(Point::new(10., 10.));
+    tree.insert(ip(1, 2., 0.), 1);
+    tree.insert(ip(2, 0., 2.001), 2);
+    tree.insert(ip(3, 1., 1.), 3);
+    // exactly on the edge, outside of the tree
+    tree.insert(ip(4, 0., -12.), 4);
+
+    let find = |center: Point, range: f32, boundary| {
+        let mut ids = Vec::new();
+        tree.find_range_with_boundary(center, r32(range), boundary, |id, _, _| ids.push(*id));
+        ids.sort_unstable();
+        ids
+    };
+    assert_eq!(find(Point::zero(), 2., RangeBoundary::Inclusive), [1, 3]);
+    assert_eq!(find(Point::zero(), 2., RangeBoundary::Exclusive), [3]);
+    assert_eq!(
+        find(Point::zero(), 2., RangeBoundary::Tolerance(r32(0.01))),
+        [1, 2, 3]
+    );
+    assert_eq!(
+        find(Point::new(0., -10.), 2., RangeBoundary::Inclusive),
+        [4]
+    );
+    assert_eq!(find(Point::new(0., -10.), 2., RangeBoundary::Exclusive), []);
+}
+
+#[test]
+pub fn skip_out_of_range() {
+    let mut tree = QuadTree::<u32, u32, 4>::sized_around_origin(Point::new(10., 10.));
+    for (n, i) in (-15..=15).enumerate() {
+        let n = u32::try_from(n).unwrap();
+        tree.insert(ip(n, i as f32, i as f32), n);
+    }
+
+    let find = |options| {
+        let mut ids = Vec::new();
+        tree.find_range_with_options(Point::new(-10., -10.), r32(3.5), options, |id, _, _| {
+            ids.push(*id);
+        });
+        ids.sort_unstable();
+        ids
+    };
+    assert_eq!(find(RangeOptions::default()), [3, 4, 5, 6, 7]);
+    assert_eq!(
+        find(RangeOptions::new().include_out_of_range(false)),
+        [5, 6, 7]
+    );
+    assert_eq!(
+        find(
+            RangeOptions::new()
+                .include_out_of_range(false)
+                .boundary(RangeBoundary::Exclusive)
+        ),
+        [5, 6, 7]
+    );
+
+    let outside = tree
+        .iter_out_of_range()
+        .map(|(id, point, value)| {
+            assert_eq!(id, value);
+            (*id, point)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(outside.len(), 10);
+    assert_eq!(outside[0], (0, Point::new(-15., -15.)));
+    assert!(outside.iter().all(|(_, point)| point.x.raw().abs() > 10.));
+}
+
+#[test]
+pub fn extreme_values_do_not_panic() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(
+        Point::new(f32::MIN, f32::MIN),
+        Point::new(f32::MAX, f32::MAX),
+    );
+    tree.insert(ip(1, f32::MAX, f32::MAX), 1);
+    tree.insert(ip(2, f32::MIN, f32::MIN), 2);
+    tree.insert(ip(3, 0., 0.), 3);
+    tree.insert(ip(4, 1., 1.), 4);
+
+    let find = |center: Point, range: f32, boundary| {
+        let mut ids = Vec::new();
+        tree.find_range_with_boundary(center, r32(range), boundary, |id, _, _| ids.push(*id));
+        ids.sort_unstable();
+        ids
+    };
+    assert_eq!(
+        find(Point::zero(), f32::MAX, RangeBoundary::Inclusive),
+        [1, 2, 3, 4]
+    );
+    assert_eq!(
+        find(
+            Point::new(f32::MAX, f32::MAX),
+            f32::MAX,
+            RangeBoundary::Tolerance(r32(f32::MAX))
+        ),
+        [1, 2, 3, 4]
+    );
+    assert_eq!(
+        find(Point::new(f32::MIN, f32::MIN), 1., RangeBoundary::Exclusive),
+        [2]
+    );
+}
+
+#[test]
+fn ids_only() {
+    let mut tree = QuadTree::<[u64; 64], u32, 4>::new(Point::zero(), Point::new(16., 16.));
+    for n in 0..20 {
+        tree.insert(ip(n, n as f32 * 0.75, 8.), [u64::from(n); 64]);
+    }
+    tree.insert(ip(100, -1., 8.), [100; 64]);
+    let mut expected = Vec::new();
+    tree.find_range(Point::new(0., 8.), r32(3.), |identity, point, value| {
+        assert_eq!(value[63], u64::from(*identity));
+        expected.push((*identity, point));
+    });
+    let mut found = Vec::new();
+    tree.find_range_ids(Point::new(0., 8.), r32(3.), |identity, point| {
+        found.push((*identity, point));
+    });
+    assert_eq!(found, expected);
+    assert_eq!(found.len(), 6);
+}
+
+#[test]
+fn soa() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(16., 16.));
+    for n in 0..30 {
+        tree.insert(ip(n, (n % 6) as f32, (n / 6) as f32), n * 10);
+    }
+    let (mut ids, mut points, mut values) = (Vec::new(), Vec::new(), Vec::new());
+    tree.find_range_soa(
+        Point::new(2., 2.),
+        r32(1.),
+        &mut ids,
+        &mut points,
+        &mut values,
+    );
+    let mut expected = Vec::new();
+    tree.find_range(Point::new(2., 2.), r32(1.), |identity, point, value| {
+        expected.push((*identity, point, value));
+    });
+    assert_eq!(expected.len(), 5);
+    assert_eq!(
+        ids.iter()
+            .zip(&points)
+            .zip(&values)
+            .map(|((identity, point), value)| (*identity, *point, *value))
+            .collect::<Vec<_>>(),
+        expected
+    );
+    // the buffers are appended to
+    tree.find_range_soa(
+        Point::new(0., 0.),
+        r32(0.),
+        &mut ids,
+        &mut points,
+        &mut values,
+    );
+    assert_eq!((ids.len(), points.len(), values.len()), (6, 6, 6));
+    assert_eq!((ids[5], *values[5]), (0, 0));
+}
+
+#[test]
+fn prunes_nodes_outside_of_the_range() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..256 {
+        tree.insert(
+            ip(n, (n % 16) as f32 * 4. + 1., (n / 16) as f32 * 4. + 1.),
+            n,
+        );
+    }
+    let trace = tree.explain_find_range(Point::new(5., 5.), r32(4.));
+    assert_eq!(trace.matches(), 5);
+    // only the leaves around the range compare their entries
+    assert!(trace.comparisons() <= 16, "{trace}");
+    assert!(trace
+        .nodes()
+        .iter()
+        .any(|node| node.visit == NodeVisit::Pruned(PruneReason::OutOfRange)));
+}
+
+#[test]
+fn find_rect() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..256 {
+        tree.insert(ip(n, (n % 16) as f32 * 4., (n / 16) as f32 * 4.), n);
+    }
+    tree.insert(ip(1000, -2., 10.), 1000);
+    tree.insert(ip(1001, 70., 10.), 1001);
+
+    let find = |tree: &QuadTree<u32, u32, 4>, a: Point, b: Point| {
+        let mut found = Vec::new();
+        tree.find_rect(a, b, |identity, point, value| {
+            assert_eq!(identity, value);
+            found.push((*identity, point));
+        });
+        found.sort_unstable();
+        found
+    };
+    let brute_force = |tree: &QuadTree<u32, u32, 4>, rect: Rect| {
+        let mut found = Vec::new();
+        tree.find_range(Point::new(32., 32.), r32(1000.), |identity, point, _| {
+            if rect.contains(point) {
+                found.push((*identity, point));
+            }
+        });
+        found.sort_unstable();
+        found
+    };
+
+    for (a, b) in [
+        (Point::new(4., 4.), Point::new(12., 8.)),
+        (Point::new(30., 61.), Point::new(33., 2.)),
+        (Point::new(-5., 0.), Point::new(1., 64.)),
+        (Point::new(0., 0.), Point::new(80., 80.)),
+        (Point::new(1., 1.), Point::new(3., 3.)),
+    ] {
+        assert_eq!(
+            find(&tree, a, b),
+            brute_force(&tree, Rect::from_corners(a, b)),
+            "{a:?} {b:?}"
+        );
+    }
+    // the edges are inclusive, and entries outside of the tree are found
+    assert_eq!(
+        find(&tree, Point::new(-4., 8.), Point::new(0., 12.)),
+        vec![
+            (32, Point::new(0., 8.)),
+            (48, Point::new(0., 12.)),
+            (1000, Point::new(-2., 10.))
+        ]
+    );
+
+    // any two opposite corners can be used, e.g. for a tree whose y axis points up
+    let mut tree =
+        QuadTree::<u32, u32, 4>::with_y_axis(Point::new(0., 64.), Point::new(64., 0.), YAxis::Up);
+    tree.insert(ip(1, 10., 50.), 1);
+    tree.insert(ip(2, 10., 10.), 2);
+    assert_eq!(
+        find(&tree, Point::new(0., 64.), Point::new(32., 32.)),
+        vec![(1, Point::new(10., 50.))]
+    );
+}
+
+#[test]
+fn find_range_mut() {
+    let mut tree = QuadTree::<u32, u32, 2>::new(Point::zero(), Point::new(16., 16.));
+    for n in 0..16 {
+        tree.insert(ip(n, n as f32, n as f32), 100);
+    }
+    tree.insert(ip(100, -1., -1.), 100);
+    tree.insert(ip(101, -10., -10.), 100);
+    let mut found = Vec::new();
+    tree.find_range_mut(Point::new(1., 1.), r32(3.), |identity, point, value| {
+        found.push((*identity, point));
+        *value -= 10 + identity % 100;
+    });
+    found.sort_unstable();
+    assert_eq!(
+        found,
+        vec![
+            (0, Point::new(0., 0.)),
+            (1, Point::new(1., 1.)),
+            (2, Point::new(2., 2.)),
+            (3, Point::new(3., 3.)),
+            (100, Point::new(-1., -1.)),
+        ]
+    );
+    for n in 0..16 {
+        let expected = if n <= 3 { 90 - n } else { 100 };
+        assert_eq!(
+            tree.get(&n),
+            Some((Point::new(n as f32, n as f32), &expected))
+        );
+    }
+    assert_eq!(tree.get(&100), Some((Point::new(-1., -1.), &90)));
+    assert_eq!(tree.get(&101), Some((Point::new(-10., -10.), &100)));
+    // the positions did not change, so the entries are still found by position
+    assert!(tree.update(2, Point::new(15., 1.)));
+    assert_eq!(tree.remove(&3), (87, Point::new(3., 3.)));
+}
+
+#[test]
+fn find_range_until() {
+    let mut tree = QuadTree::<u32, u32, 2>::new(Point::zero(), Point::new(16., 16.));
+    for n in 0..16 {
+        tree.insert(ip(n, n as f32, 15. - n as f32), n);
+    }
+    tree.insert(ip(100, -1., 17.), 100);
+
+    let mut all = Vec::new();
+    tree.find_range(Point::new(0., 16.), r32(6.), |identity, _, _| {
+        all.push(*identity);
+    });
+    assert_eq!(all, vec![0, 1, 2, 3, 100]);
+
+    // the traversal stops at the first entry that breaks, in the same order as `find_range`
+    let mut visited = Vec::new();
+    let found = tree.find_range_until(Point::new(0., 16.), r32(6.), |identity, point, _| {
+        visited.push(*identity);
+        if point.x > 1. {
+            ControlFlow::Break(*identity)
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert_eq!(found, ControlFlow::Break(2));
+    assert_eq!(visited, vec![0, 1, 2]);
+
+    // entries outside of the tree can break too
+    let found = tree.find_range_until(Point::new(0., 16.), r32(6.), |identity, _, _| {
+        if *identity == 100 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert_eq!(found, ControlFlow::Break(()));
+    let found: ControlFlow<()> = tree.find_range_until(Point::new(0., 16.), r32(6.), |_, _, _| {
+        ControlFlow::Continue(())
+    });
+    assert_eq!(found, ControlFlow::Continue(()));
+}
+
+#[test]
+fn find_range_with_leaf() {
+    let mut tree = QuadTree::<u32, u32, 2>::new(Point::zero(), Point::new(16., 16.));
+    tree.insert(ip(0, 1., 1.), 0);
+    tree.insert(ip(1, 2., 2.), 1);
+    tree.insert(ip(2, 14., 14.), 2);
+    tree.insert(ip(100, 20., 20.), 100);
+
+    let top_left = Rect::new(Point::zero(), Point::new(8., 8.));
+    let bottom_right = Rect::new(Point::new(8., 8.), Point::new(16., 16.));
+    let expected = vec![
+        (0, Some(top_left)),
+        (1, Some(top_left)),
+        (2, Some(bottom_right)),
+        (100, None),
+    ];
+    let mut found = Vec::new();
+    tree.find_range_with_leaf(Point::new(8., 8.), r32(20.), |identity, _, value, leaf| {
+        assert_eq!(identity, value);
+        found.push((*identity, leaf));
+    });
+    found.sort_unstable();
+    assert_eq!(found, expected);
+
+    let mut found = Vec::new();
+    tree.find_rect_with_leaf(
+        Point::zero(),
+        Point::new(20., 20.),
+        |identity, _, _, leaf| {
+            found.push((*identity, leaf));
+        },
+    );
+    found.sort_unstable();
+    assert_eq!(found, expected);
+
+    // the rect is the rect of the node that the entry is in
+    for (identity, leaf) in &expected[..3] {
+        let node = tree.node_of(identity).unwrap();
+        assert_eq!(Some(tree.node_rect(node)), *leaf);
+    }
+
+    // only the entries in range are passed
+    let mut found = Vec::new();
+    tree.find_range_with_leaf(Point::new(14., 14.), r32(1.), |identity, _, _, leaf| {
+        found.push((*identity, leaf));
+    });
+    assert_eq!(found, vec![(2, Some(bottom_right))]);
+}
+
+#[test]
+fn find_range_grouped() {
+    let mut tree = QuadTree::<u32, u32, 2>::sized_around_origin(Point::new(10., 10.));
+    for (identity, x, y) in [
+        (3, 1., 1.),
+        (4, 2., 2.),
+        (1, 1., 1.),
+        (5, -5., -5.),
+        (6, 15., 15.),
+        (2, 1., 1.),
+        (7, -9., -9.),
+    ] {
+        tree.insert(ip(identity, x, y), identity * 10);
+    }
+
+    let mut found = Vec::new();
+    tree.find_range_grouped(Point::new(5., 5.), r32(15.), |point, entries| {
+        found.push((
+            point,
+            entries
+                .iter()
+                .map(|(identity, value)| (**identity, **value))
+                .collect::<Vec<_>>(),
+        ));
+    });
+    assert_eq!(
+        found,
+        vec![
+            (Point::new(-5., -5.), vec![(5, 50)]),
+            (Point::new(1., 1.), vec![(1, 10), (2, 20), (3, 30)]),
+            (Point::new(2., 2.), vec![(4, 40)]),
+            (Point::new(15., 15.), vec![(6, 60)]),
+        ]
+    );
+
+    // nothing in range
+    tree.find_range_grouped(Point::new(-9., 9.), r32(1.), |_, _| {
+        panic!("nothing is in range")
+    });
+}
@@ -0,0 +1,75 @@
+use crate::{
+    tests::ip, FlatNode, FlatPoint, FlatRect, Point, QuadTree, FLAT_NODE_LEAF, FLAT_NODE_NESTED,
+};
+
+#[test]
+fn layout() {
+    assert_eq!(std::mem::size_of::<FlatPoint>(), 8);
+    assert_eq!(std::mem::size_of::<FlatRect>(), 16);
+    assert_eq!(std::mem::size_of::<FlatNode>(), 28);
+    assert_eq!(std::mem::align_of::<FlatNode>(), 4);
+}
+
+#[test]
+fn export() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(8., 8.));
+    tree.insert(ip(5, 1., 1.), 50);
+    tree.insert(ip(2, 5., 5.), 20);
+    tree.insert(ip(9, 10., 10.), 90);
+
+    let flat = tree.export_flat();
+    let rect = |left, top, right, bottom| FlatRect {
+        left,
+        top,
+        right,
+        bottom,
+    };
+    let leaf = |rect, first, count| FlatNode {
+        rect,
+        kind: FLAT_NODE_LEAF,
+        first,
+        count,
+    };
+    assert_eq!(flat.rect, rect(0., 0., 8., 8.));
+    assert_eq!(
+        flat.nodes,
+        vec![
+            FlatNode {
+                rect: rect(0., 0., 8., 8.),
+                kind: FLAT_NODE_NESTED,
+                first: 1,
+                count: 4
+            },
+            leaf(rect(0., 0., 4., 4.), 0, 1),
+            leaf(rect(4., 0., 8., 4.), 1, 0),
+            leaf(rect(0., 4., 4., 8.), 1, 0),
+            leaf(rect(4., 4., 8., 8.), 1, 1),
+        ]
+    );
+    assert_eq!(
+        flat.points,
+        vec![
+            FlatPoint { x: 1., y: 1. },
+            FlatPoint { x: 5., y: 5. },
+            FlatPoint { x: 10., y: 10. },
+        ]
+    );
+    assert_eq!(flat.outside_start, 2);
+    assert_eq!(flat.values, vec![(&2, &20), (&5, &50), (&9, &90)]);
+    assert_eq!(flat.value_indices, vec![1, 0, 2]);
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn header() {
+    let header = crate::c_header();
+    for name in [
+        "FlatPoint",
+        "FlatRect",
+        "FlatNode",
+        "FLAT_NODE_LEAF",
+        "FLAT_NODE_NESTED",
+    ] {
+        assert!(header.contains(name), "{name} is missing from the header");
+    }
+}
@@ -0,0 +1,167 @@
+#![cfg(feature = "forest")]
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{dedup_by_identity, tests::ip, Point, QuadForest, QuadTree, R32};
+
+fn entries_in_range(
+    find_range: impl FnOnce(&mut dyn FnMut(&u32, Point, &u32)),
+) -> Vec<(u32, Point, u32)> {
+    let mut entries = Vec::new();
+    find_range(&mut |identity, point, value| entries.push((*identity, point, *value)));
+    entries.sort_unstable_by_key(|(identity, _, _)| *identity);
+    entries
+}
+
+fn count(tree: &QuadTree<u32, u32, 4>) -> usize {
+    let mut count = 0;
+    tree.find_range(Point::zero(), R32::new(1000.), |_, _, _| count += 1);
+    count
+}
+
+fn assert_same(forest: &QuadForest<u32, u32, 4>, tree: &QuadTree<u32, u32, 4>) {
+    for (center, range) in [
+        (Point::new(50., 50.), 1000.),
+        (Point::new(25., 25.), 25.),
+        (Point::new(0., 100.), 30.),
+        (Point::new(-10., 50.), 12.),
+        (Point::new(75., 10.), 0.),
+    ] {
+        let range = R32::new(range);
+        let expected = entries_in_range(|callback| tree.find_range(center, range, callback));
+        let found = entries_in_range(|callback| forest.find_range(center, range, callback));
+        assert_eq!(found, expected, "find_range({center:?}, {range})");
+    }
+    assert_eq!(forest.len(), count(tree));
+}
+
+#[test]
+fn matches_single_tree() {
+    let mut forest = QuadForest::<u32, u32, 4>::new(Point::zero(), Point::new(100., 100.), 3);
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(100., 100.));
+    for n in 0..300 {
+        // some entries are on the edges between shards, or outside of the forest
+        let x = (n * 37 % 110) as f32 - 5.;
+        let y = (n * 23 % 105) as f32 - (100. / 3.) * (n % 2) as f32;
+        forest.insert(ip(n, x, y), n);
+        tree.insert(ip(n, x, y), n);
+    }
+    assert_same(&forest, &tree);
+
+    for n in (0..300).step_by(3) {
+        let point = Point::new((n * 13 % 100) as f32, (n * 7 % 120) as f32);
+        assert!(forest.update(n, point));
+        assert!(tree.update(n, point));
+    }
+    assert!(!forest.update(1000, Point::zero()));
+    assert_same(&forest, &tree);
+
+    for n in (0..300).step_by(5) {
+        assert_eq!(forest.try_remove(&n), tree.try_remove(&n));
+    }
+    assert_eq!(forest.try_remove(&0), None);
+    assert_same(&forest, &tree);
+
+    let entries = forest.shards().iter().map(count).sum::<usize>();
+    assert!(entries <= forest.len());
+}
+
+#[test]
+fn shard_of() {
+    let forest = QuadForest::<(), u32, 4>::new(Point::zero(), Point::new(90., 90.), 3);
+    assert_eq!(forest.shard_of(Point::new(0., 0.)), Some(0));
+    assert_eq!(forest.shard_of(Point::new(30., 0.)), Some(1));
+    assert_eq!(forest.shard_of(Point::new(29.9, 60.)), Some(6));
+    assert_eq!(forest.shard_of(Point::new(90., 90.)), Some(8));
+    assert_eq!(forest.shard_of(Point::new(90.1, 90.)), None);
+    for (n, shard) in forest.shards().iter().enumerate() {
+        let rect = shard.rect;
+        assert_eq!(forest.shard_of(rect.top_left()), Some(n));
+    }
+}
+
+#[test]
+fn update_moves_between_shards() {
+    let mut forest = QuadForest::<u32, u32, 4>::new(Point::zero(), Point::new(10., 10.), 2);
+    forest.insert(ip(1, 1., 1.), 10);
+    assert_eq!(count(&forest.shards()[0]), 1);
+
+    assert!(forest.update_point_and_value(1, Point::new(9., 9.), |value| *value += 1));
+    assert_eq!(count(&forest.shards()[0]), 0);
+    assert_eq!(count(&forest.shards()[3]), 1);
+
+    assert!(forest.update(1, Point::new(20., 20.)));
+    assert_eq!(count(&forest.shards()[3]), 0);
+    assert_eq!(forest.len(), 1);
+
+    forest.insert(ip(1, 2., 8.), 20);
+    assert_eq!(count(&forest.shards()[2]), 1);
+    assert_eq!(forest.remove(&1), (20, Point::new(2., 8.)));
+    assert!(forest.is_empty());
+}
+
+#[test]
+fn seams_are_found_once() {
+    let mut forest = QuadForest::<u32, u32, 4>::new(Point::zero(), Point::new(90., 90.), 3);
+    // entries on every corner and edge between two shards, and on the edge of the forest
+    let mut n = 0;
+    for x in [0., 15., 30., 45., 60., 75., 90.] {
+        for y in [0., 30., 60., 90.] {
+            forest.insert(ip(n, x, y), n);
+            forest.insert(ip(n + 1, y, x), n + 1);
+            n += 2;
+        }
+    }
+    for center in [
+        Point::new(30., 30.),
+        Point::new(60., 45.),
+        Point::new(90., 90.),
+        Point::new(-1., 30.),
+    ] {
+        for range in [0., 1., 30., 200.] {
+            let found =
+                entries_in_range(|callback| forest.find_range(center, R32::new(range), callback));
+            let mut identities = found
+                .iter()
+                .map(|(identity, _, _)| *identity)
+                .collect::<Vec<_>>();
+            identities.dedup();
+            assert_eq!(
+                identities.len(),
+                found.len(),
+                "find_range({center:?}, {range})"
+            );
+        }
+    }
+}
+
+#[test]
+fn dedup_overlapping_queries() {
+    let mut forest = QuadForest::<u32, u32, 4>::new(Point::zero(), Point::new(90., 90.), 3);
+    for n in 0..100 {
+        forest.insert(ip(n, (n % 10) as f32 * 9., (n / 10) as f32 * 9.), n);
+    }
+    let centers = [
+        Point::new(27., 27.),
+        Point::new(36., 27.),
+        Point::new(31., 40.),
+    ];
+    let range = R32::new(15.);
+
+    let mut expected = Vec::new();
+    for center in centers {
+        forest.find_range(center, range, |identity, _, _| expected.push(*identity));
+    }
+    let total = expected.len();
+    expected.sort_unstable();
+    expected.dedup();
+    assert!(expected.len() < total, "the queries should overlap");
+
+    let mut found = Vec::new();
+    let mut callback = dedup_by_identity(|identity: &u32, _, _: &u32| found.push(*identity));
+    for center in centers {
+        forest.find_range(center, range, &mut callback);
+    }
+    drop(callback);
+    found.sort_unstable();
+    assert_eq!(found, expected);
+}
@@ -0,0 +1,56 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, R32};
+
+fn is_free(tree: &QuadTree<u32, u32, 4>, point: Point, clearance: f32) -> bool {
+    let mut free = true;
+    tree.find_range(point, R32::new(clearance), |_, other, _| {
+        free &= other.distance_squared_to(point) >= clearance * clearance;
+    });
+    free
+}
+
+#[test]
+fn nearest_empty_point() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    assert_eq!(
+        tree.nearest_empty_point(Point::new(10., 10.), R32::new(1.)),
+        Some(Point::new(10., 10.))
+    );
+    // a grid of units around the center, one unit apart
+    for x in 0..11 {
+        for y in 0..11 {
+            tree.insert(ip(x * 11 + y, 5. + x as f32, 5. + y as f32), 0);
+        }
+    }
+    let center = Point::new(10., 10.);
+    let found = tree.nearest_empty_point(center, R32::new(1.)).unwrap();
+    assert!(is_free(&tree, found, 1.), "{found:?} is not free");
+    // the closest free points are just outside of the grid, between two rows: `(15.866, 9.5)` is `5.887` away
+    let distance = found.distance_squared_to(center).raw().sqrt();
+    assert!(
+        (5.88..5.95).contains(&distance),
+        "{found:?} is {distance} away"
+    );
+
+    // a point outside of the tree is moved into it
+    assert_eq!(
+        tree.nearest_empty_point(Point::new(-10., 30.), R32::new(1.)),
+        Some(Point::new(0., 30.))
+    );
+}
+
+#[test]
+fn no_free_space() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(4., 4.));
+    for n in 0..25 {
+        tree.insert(ip(n, (n % 5) as f32, (n / 5) as f32), n);
+    }
+    assert_eq!(
+        tree.nearest_empty_point(Point::new(2., 2.), R32::new(1.)),
+        None
+    );
+    assert!(tree
+        .nearest_empty_point(Point::new(2., 2.), R32::new(0.5))
+        .is_some_and(|point| is_free(&tree, point, 0.5)));
+}
@@ -0,0 +1,193 @@
+#![cfg(feature = "frozen")]
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, FrozenQuadTree, Point, QuadTree, Rect, R32};
+use std::{collections::BTreeMap, io};
+
+fn tree() -> QuadTree<[f32; 2], u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(100., 100.));
+    for n in 0..300 {
+        let x = (n * 37 % 100) as f32 + 0.5;
+        let y = (n * 61 % 97) as f32;
+        tree.insert(ip(n, x, y), [x * 2., y * 2.]);
+    }
+    tree.insert(ip(300, -10., 250.), [1., 2.]);
+    tree
+}
+
+/// Copy `bytes` into a buffer that is aligned to 16 bytes
+fn aligned(bytes: &[u8]) -> Vec<u128> {
+    let mut buffer = vec![0u128; bytes.len().div_ceil(16)];
+    for (n, byte) in bytes.iter().enumerate() {
+        buffer[n / 16] |= u128::from(*byte) << ((n % 16) * 8);
+    }
+    if cfg!(target_endian = "big") {
+        for word in &mut buffer {
+            *word = word.swap_bytes();
+        }
+    }
+    buffer
+}
+
+fn as_bytes(buffer: &[u128]) -> &[u8] {
+    // SAFETY: `u128` has no padding, and `u8` has no alignment
+    unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast(), buffer.len() * 16) }
+}
+
+#[test]
+fn round_trip() {
+    let original = tree();
+    let mut bytes = Vec::new();
+    original.write_frozen(&mut bytes).unwrap();
+    let buffer = aligned(&bytes);
+    let frozen = FrozenQuadTree::<[f32; 2], u32, _>::from_bytes(as_bytes(&buffer)).unwrap();
+    assert_eq!(frozen.len(), 301);
+    assert_eq!(
+        frozen.rect(),
+        Rect::new(Point::zero(), Point::new(100., 100.))
+    );
+
+    let mut entries = BTreeMap::new();
+    frozen.for_each(|identity, point, value| {
+        assert!(entries.insert(*identity, (point, *value)).is_none());
+    });
+    for (identity, (point, value)) in &entries {
+        assert_eq!(original.get(identity), Some((*point, value)));
+    }
+    assert_eq!(entries.len(), 301);
+
+    for (center, range) in [
+        (Point::new(50., 50.), 20.),
+        (Point::new(0., 0.), 35.),
+        (Point::new(-10., 250.), 1.),
+    ] {
+        let range = R32::new(range);
+        let mut expected = Vec::new();
+        original.find_range(center, range, |identity, _, _| expected.push(*identity));
+        let mut found = Vec::new();
+        frozen.find_range(center, range, |identity, _, _| found.push(*identity));
+        expected.sort_unstable();
+        found.sort_unstable();
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn invalid_bytes() {
+    let mut bytes = Vec::new();
+    tree().write_frozen(&mut bytes).unwrap();
+    let error = |bytes: &[u8]| {
+        let buffer = aligned(bytes);
+        let bytes = &as_bytes(&buffer)[..bytes.len()];
+        FrozenQuadTree::<[f32; 2], u32, _>::from_bytes(bytes)
+            .err()
+            .map(|error| error.kind())
+    };
+    assert_eq!(error(&bytes), None);
+    assert_eq!(
+        error(&bytes[..bytes.len() - 1]),
+        Some(io::ErrorKind::InvalidData)
+    );
+    assert_eq!(error(b"WHQD"), Some(io::ErrorKind::InvalidData));
+
+    // a different value type
+    let buffer = aligned(&bytes);
+    assert!(FrozenQuadTree::<u32, u32, _>::from_bytes(as_bytes(&buffer)).is_err());
+
+    // the first child of the root points at the root itself
+    let mut cyclic = bytes.clone();
+    cyclic[64 + 20..64 + 24].copy_from_slice(&0u32.to_ne_bytes());
+    assert_eq!(error(&cyclic), Some(io::ErrorKind::InvalidData));
+
+    // not aligned
+    let buffer = aligned(&[&[0][..], &bytes].concat());
+    assert!(FrozenQuadTree::<[f32; 2], u32, _>::from_bytes(&as_bytes(&buffer)[1..]).is_err());
+}
+
+#[test]
+fn to_bytes() {
+    let original = tree();
+    let bytes = original.to_bytes();
+    let mut written = Vec::new();
+    original.write_frozen(&mut written).unwrap();
+    assert_eq!(bytes, written);
+
+    let loaded = QuadTree::<[f32; 2], u32, 4>::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded, original);
+    assert_eq!(loaded.len(), 301);
+    // a buffer that is not aligned is copied
+    let unaligned = [&[0][..], &bytes].concat();
+    assert_eq!(
+        QuadTree::<[f32; 2], u32, 4>::from_bytes(&unaligned[1..]).unwrap(),
+        original
+    );
+
+    let empty = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(10., 10.));
+    assert_eq!(QuadTree::from_bytes(&empty.to_bytes()).unwrap(), empty);
+    // a tree that can still be changed after loading
+    let mut loaded = QuadTree::<[f32; 2], u32, 4>::from_bytes(&bytes).unwrap();
+    loaded.remove(&0);
+    loaded.insert(ip(0, 1., 1.), [0., 0.]);
+    assert_eq!(loaded.get(&0), Some((Point::new(1., 1.), &[0., 0.])));
+}
+
+#[test]
+fn from_invalid_bytes() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(100., 100.));
+    tree.insert(ip(0xDEAD_BEEF, 3.25, 4.75), 1);
+    tree.insert(ip(0xFEED_F00D, 50.5, 60.5), 2);
+    tree.insert(ip(3, -12_345.5, 777.25), 3);
+    let bytes = tree.to_bytes();
+    let error = |from: &[u8], to: &[u8]| {
+        let start = bytes
+            .windows(from.len())
+            .position(|window| window == from)
+            .unwrap();
+        let mut bytes = bytes.clone();
+        bytes[start..start + from.len()].copy_from_slice(to);
+        QuadTree::<u32, u32, 4>::from_bytes(&bytes)
+            .err()
+            .map(|error| (error.kind(), error.to_string()))
+    };
+    let invalid = |message: &str| Some((io::ErrorKind::InvalidData, message.to_string()));
+
+    assert_eq!(
+        error(&0xFEED_F00Du32.to_ne_bytes(), &0xDEAD_BEEFu32.to_ne_bytes()),
+        invalid("identity is stored twice")
+    );
+    assert_eq!(
+        error(&3.25f32.to_ne_bytes(), &250f32.to_ne_bytes()),
+        invalid("entry is outside of its node")
+    );
+    let bytes_of = |x: f32, y: f32| [x.to_ne_bytes(), y.to_ne_bytes()].concat();
+    assert_eq!(
+        error(&bytes_of(-12_345.5, 777.25), &bytes_of(5., 5.)),
+        invalid("outside entry is inside of the tree")
+    );
+    assert_eq!(
+        QuadTree::<u64, u32, 4>::from_bytes(&bytes)
+            .err()
+            .map(|error| error.kind()),
+        Some(io::ErrorKind::InvalidData)
+    );
+}
+
+#[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+#[test]
+fn mmap() {
+    let path = std::env::temp_dir().join(format!("whquadtree-test-{}.frozen", std::process::id()));
+    let mut file = std::fs::File::create(&path).unwrap();
+    tree().write_frozen(&mut file).unwrap();
+    drop(file);
+
+    // SAFETY: nothing else changes this file
+    let frozen = unsafe { FrozenQuadTree::<[f32; 2], u32, _>::open(&path) }.unwrap();
+    let mut found = 0;
+    frozen.find_range(Point::new(50., 50.), R32::new(10.), |_, point, value| {
+        assert_eq!(value.map(R32::new), [point.x * 2., point.y * 2.]);
+        found += 1;
+    });
+    assert!(found > 0);
+    drop(frozen);
+    std::fs::remove_file(&path).unwrap();
+}
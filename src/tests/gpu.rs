@@ -0,0 +1,62 @@
+#![cfg(feature = "gpu")]
+
+use crate::{tests::ip, GpuEntry, GpuNode, Point, QuadTree, FLAT_NODE_LEAF, FLAT_NODE_NESTED};
+
+#[test]
+fn layout() {
+    assert_eq!(std::mem::size_of::<GpuNode>(), 32);
+    assert_eq!(std::mem::align_of::<GpuNode>(), 16);
+    assert_eq!(std::mem::size_of::<GpuEntry>(), 8);
+    assert_eq!(std::mem::align_of::<GpuEntry>(), 8);
+}
+
+#[test]
+fn morton_order() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(8., 8.));
+    // inserted in reverse Morton order, all in the same leaf
+    tree.insert(ip(1, 3., 3.), 1);
+    tree.insert(ip(2, 1., 3.), 2);
+    tree.insert(ip(3, 3., 1.), 3);
+    tree.insert(ip(4, 1., 1.), 4);
+    // splits the root, moving the other entries into the top-left leaf
+    tree.insert(ip(5, 7., 7.), 5);
+    tree.insert(ip(6, 9., 9.), 6);
+
+    let buffers = tree.to_gpu_buffers();
+    let leaf = |rect, first, count| GpuNode {
+        rect,
+        kind: FLAT_NODE_LEAF,
+        first,
+        count,
+        padding: 0,
+    };
+    assert_eq!(
+        buffers.nodes,
+        vec![
+            GpuNode {
+                rect: [0., 0., 8., 8.],
+                kind: FLAT_NODE_NESTED,
+                first: 1,
+                count: 4,
+                padding: 0,
+            },
+            leaf([0., 0., 4., 4.], 0, 4),
+            leaf([4., 0., 8., 4.], 4, 0),
+            leaf([0., 4., 4., 8.], 4, 0),
+            leaf([4., 4., 8., 8.], 4, 1),
+        ]
+    );
+    assert_eq!(
+        buffers.entries.iter().map(|e| e.point).collect::<Vec<_>>(),
+        vec![[1., 1.], [3., 1.], [1., 3.], [3., 3.], [7., 7.], [9., 9.]]
+    );
+    assert_eq!(
+        buffers
+            .values
+            .iter()
+            .map(|(id, _)| **id)
+            .collect::<Vec<_>>(),
+        vec![4, 3, 2, 1, 5, 6]
+    );
+    assert_eq!(buffers.outside_start, 5);
+}
@@ -0,0 +1,71 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, Quadrant, R32};
+
+fn entries(tree: &QuadTree<u32, u32, 4>) -> Vec<(u32, Point)> {
+    let mut found = Vec::new();
+    tree.find_range(Point::zero(), R32::new(1000.), |identity, point, _| {
+        found.push((*identity, point));
+    });
+    found.sort_unstable();
+    found
+}
+
+#[test]
+fn translate_group() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    let points = |n: u32| Point::new((n % 8) as f32 * 8. + 1., (n / 8) as f32 * 8. + 1.);
+    for n in 0..64 {
+        tree.insert(ip(n, points(n).x.raw(), points(n).y.raw()), n);
+    }
+    tree.insert(ip(100, -5., 1.), 100);
+
+    // a short move keeps every entry in its leaf, a long move moves some of them to other leaves or out of the tree
+    for delta in [
+        Point::new(1., 0.5),
+        Point::new(7., 9.),
+        Point::new(-20., 0.),
+    ] {
+        let before = entries(&tree);
+        let group = [3, 4, 12, 13, 100, 1000];
+        assert_eq!(tree.translate_group(group, delta), 5);
+        let expected = before
+            .into_iter()
+            .map(|(identity, point)| {
+                if group.contains(&identity) {
+                    (identity, point + delta)
+                } else {
+                    (identity, point)
+                }
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(entries(&tree), expected);
+        for (identity, point) in expected {
+            // every entry can still be found by a query around its own position
+            let mut found = false;
+            tree.find_range(point, R32::new(0.), |other, _, _| {
+                found |= *other == identity;
+            });
+            assert!(found, "{identity} at {point:?}");
+        }
+    }
+}
+
+#[test]
+fn edge_of_tree() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(8., 8.));
+    tree.insert(ip(1, 7., 7.), 1);
+    tree.insert(ip(2, 1., 1.), 2);
+    assert_eq!(tree.translate_group([1], Point::new(1., 1.)), 1);
+    assert_eq!(tree.depth_of(&1), Some(1));
+    assert_eq!(tree.translate_group([1], Point::new(-4., -4.)), 1);
+    assert_eq!(
+        tree.path_of(&1).unwrap().collect::<Vec<_>>(),
+        vec![Quadrant::BottomRight]
+    );
+    assert_eq!(tree.translate_group([1], Point::new(-0.5, 0.)), 1);
+    assert_eq!(
+        tree.path_of(&1).unwrap().collect::<Vec<_>>(),
+        vec![Quadrant::BottomLeft]
+    );
+}
@@ -0,0 +1,50 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{EntryId, HandleQuadTree, Point, R32};
+
+#[test]
+fn handles_are_dense() {
+    let mut tree = HandleQuadTree::<&str, 4>::new(Point::zero(), Point::new(64., 64.));
+    assert!(tree.is_empty());
+    let handles = (0..10)
+        .map(|n| tree.insert(Point::new(n as f32 * 6., 1.), "entry"))
+        .collect::<Vec<_>>();
+    assert_eq!(handles, (0..10).map(EntryId).collect::<Vec<_>>());
+    assert_eq!(tree.len(), 10);
+
+    assert_eq!(
+        tree.try_remove(EntryId(3)),
+        Some(("entry", Point::new(18., 1.)))
+    );
+    assert_eq!(tree.try_remove(EntryId(3)), None);
+    assert_eq!(
+        tree.try_remove(EntryId(7)),
+        Some(("entry", Point::new(42., 1.)))
+    );
+    assert_eq!(tree.len(), 8);
+    // freed handles are given out again before new ones
+    assert_eq!(tree.insert(Point::new(2., 2.), "new"), EntryId(7));
+    assert_eq!(tree.insert(Point::new(3., 3.), "new"), EntryId(3));
+    assert_eq!(tree.insert(Point::new(4., 4.), "new"), EntryId(10));
+    assert_eq!(tree.get(EntryId(3)), Some((Point::new(3., 3.), &"new")));
+    assert_eq!(tree.get(EntryId(11)), None);
+}
+
+#[test]
+fn update_and_find() {
+    let mut tree = HandleQuadTree::<u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    let a = tree.insert(Point::new(1., 1.), 1);
+    let b = tree.insert(Point::new(30., 30.), 2);
+    let c = tree.insert(Point::new(100., 1.), 3);
+    assert!(tree.update(b, Point::new(2., 2.)));
+    assert!(tree.update_point_and_value(c, Point::new(3., 1.), |value| *value += 10));
+    assert!(!tree.update(EntryId(5), Point::new(2., 2.)));
+
+    let mut found = Vec::new();
+    tree.find_range(Point::new(1., 1.), R32::new(4.), |identity, _, value| {
+        found.push((identity, *value));
+    });
+    found.sort_unstable();
+    assert_eq!(found, vec![(a, 1), (b, 2), (c, 13)]);
+    assert!(tree.tree().iter_out_of_range().next().is_none());
+}
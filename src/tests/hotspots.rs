@@ -0,0 +1,65 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree};
+
+fn tree() -> QuadTree<(), u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(100., 100.));
+    for n in 0..100 {
+        tree.insert(
+            ip(n, (n % 10) as f32 * 10. + 5., (n / 10) as f32 * 10. + 5.),
+            (),
+        );
+    }
+    tree
+}
+
+#[test]
+fn busiest_node_first() {
+    let mut tree = tree();
+    assert_eq!(tree.hotspots(10), []);
+
+    tree.track_hotspots(50);
+    for step in 0..30 {
+        // entry 0 wiggles around inside of its leaf
+        tree.update(0, Point::new(5. + (step % 3) as f32, 5.));
+    }
+    for n in 50..60 {
+        tree.update(n, Point::new((n % 10) as f32 * 10. + 6., 55.));
+    }
+
+    let hotspots = tree.hotspots(3);
+    assert_eq!(hotspots.len(), 3);
+    assert!(hotspots[0].0.contains(Point::new(5., 5.)));
+    assert!(!hotspots[0].0.contains(Point::new(50., 50.)));
+    assert_eq!(hotspots[0].1, 30);
+    assert!(hotspots.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+    let all = tree.hotspots(usize::MAX);
+    assert_eq!(all.iter().map(|(_, count)| count).sum::<u64>(), 40);
+}
+
+#[test]
+fn window_forgets_old_changes() {
+    let mut tree = tree();
+    tree.track_hotspots(20);
+    for step in 0..20 {
+        tree.update(0, Point::new(5. + (step % 3) as f32, 5.));
+    }
+    for step in 0..15 {
+        tree.update(99, Point::new(95. - (step % 3) as f32, 95.));
+    }
+    let hotspots = tree.hotspots(2);
+    assert!(hotspots[0].0.contains(Point::new(95., 95.)));
+    assert_eq!(hotspots[0].1, 15);
+    assert_eq!(hotspots[1].1, 5);
+
+    tree.track_hotspots(10);
+    assert_eq!(
+        tree.hotspots(2).iter().map(|(_, count)| count).sum::<u64>(),
+        10
+    );
+
+    tree.track_hotspots(0);
+    tree.update(0, Point::new(5., 5.));
+    assert_eq!(tree.hotspots(2), []);
+}
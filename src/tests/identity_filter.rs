@@ -0,0 +1,56 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, NodeId, Point, QuadTree, Quadrant};
+
+fn tree(identities: impl Iterator<Item = u32>) -> QuadTree<u32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for n in identities {
+        tree.insert(ip(n, (n % 64) as f32, (n / 64 % 64) as f32), n);
+    }
+    tree
+}
+
+#[test]
+fn may_contain() {
+    let mut tree = tree(0..1000);
+    tree.insert(ip(5000, -1., -1.), 5000);
+    assert!(tree.contains_identity(&5000));
+    assert!(!tree.contains_identity(&5001));
+    let filter = tree.identity_filter();
+    for n in (0..1000).chain([5000]) {
+        assert!(filter.may_contain(&n));
+    }
+    let false_positives = (10_000..20_000).filter(|n| filter.may_contain(n)).count();
+    assert!(false_positives < 500, "{false_positives} false positives");
+
+    let empty = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(1., 1.));
+    assert!(!empty.identity_filter().may_contain(&1));
+}
+
+#[test]
+fn node_fingerprints() {
+    let tree = tree(0..256);
+    let filter = tree.identity_filter();
+    let node = tree.node_of(&3).unwrap();
+    assert!(filter.node_may_contain(node, &3));
+    assert!(filter.node_may_contain(NodeId::ROOT, &3));
+    assert_eq!(
+        filter.node_fingerprint(NodeId::ROOT),
+        [
+            Quadrant::TopLeft,
+            Quadrant::TopRight,
+            Quadrant::BottomLeft,
+            Quadrant::BottomRight
+        ]
+        .into_iter()
+        .map(|quadrant| filter.node_fingerprint(NodeId::ROOT.child(quadrant).unwrap()))
+        .fold(0, |a, b| a | b)
+    );
+    // a node that was never created has no identities
+    let deepest = NodeId::from_path([Quadrant::BottomRight; 15]).unwrap();
+    assert_eq!(filter.node_fingerprint(deepest), 0);
+
+    let other = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    assert!(!filter.may_share(NodeId::ROOT, &other.identity_filter(), NodeId::ROOT));
+    assert!(filter.may_share(node, &filter, node));
+}
@@ -0,0 +1,228 @@
+#![allow(clippy::cast_precision_loss, clippy::trivially_copy_pass_by_ref)] // `encode` must match the signature of the callback
+
+use crate::{r32, tests::ip, Point, QuadTree};
+use std::io::{self, Read, Write};
+
+type Tree = QuadTree<u32, u32, 2>;
+
+fn new_tree() -> Tree {
+    QuadTree::new(Point::zero(), Point::new(16., 16.))
+}
+
+fn encode(writer: &mut Vec<u8>, identity: &u32, value: &u32) -> io::Result<()> {
+    writer.write_all(&identity.to_le_bytes())?;
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn decode(reader: &mut &[u8]) -> io::Result<(u32, u32)> {
+    let mut read = || {
+        let mut bytes = [0; 4];
+        reader
+            .read_exact(&mut bytes)
+            .map(|()| u32::from_le_bytes(bytes))
+    };
+    Ok((read()?, read()?))
+}
+
+/// Save the changes of `tree` since `since`, and apply them to `copy`
+fn sync(tree: &Tree, copy: &mut Tree, since: u64) -> (u64, usize) {
+    let mut delta = Vec::new();
+    let generation = tree.save_incremental(&mut delta, since, encode).unwrap();
+    assert_eq!(
+        copy.apply_incremental(&mut &delta[..], decode).unwrap(),
+        generation
+    );
+    assert_eq!(copy.export_flat(), tree.export_flat());
+    (generation, delta.len())
+}
+
+#[test]
+fn round_trip() {
+    let mut tree = new_tree();
+    assert_eq!(tree.generation(), 0);
+    for n in 0..10 {
+        tree.insert(ip(n, n as f32 * 1.5, 15. - n as f32), n * 10);
+    }
+    tree.insert(ip(10, 20., 20.), 100);
+
+    let mut copy = new_tree();
+    let (generation, _) = sync(&tree, &mut copy, 0);
+    assert_eq!(generation, tree.generation());
+
+    // move into and out of the tree, and remove enough entries to merge nodes
+    assert!(tree.update(10, Point::new(1., 1.)));
+    assert!(tree.update(3, Point::new(-1., 5.)));
+    tree.remove(&7);
+    tree.remove(&8);
+    tree.remove(&9);
+    assert!(tree.update_point_and_value(0, Point::new(0.5, 15.), |value| *value += 1));
+    let (generation, _) = sync(&tree, &mut copy, generation);
+
+    // nothing changed, so only the header is written
+    let (_, len) = sync(&tree, &mut copy, generation);
+    assert_eq!(len, 4 + 8 + 16 + 1);
+
+    // the copy can be used as a normal tree
+    for n in [0, 1, 2, 3, 4, 5, 6, 10] {
+        assert_eq!(copy.remove(&n), tree.remove(&n));
+    }
+    assert!(copy.try_remove(&7).is_none());
+}
+
+#[test]
+fn only_changed_leaves_are_saved() {
+    let mut tree = new_tree();
+    for x in 0..16 {
+        for y in 0..16 {
+            tree.insert(ip(x * 16 + y, x as f32 + 0.5, y as f32 + 0.5), 0);
+        }
+    }
+    let mut copy = new_tree();
+    let (generation, full_len) = sync(&tree, &mut copy, 0);
+
+    assert!(tree.update_point_and_value(8 * 16 + 8, Point::new(8.25, 8.25), |value| *value = 1));
+    let (_, len) = sync(&tree, &mut copy, generation);
+    // header, end, and a single record with one leaf of 1 entry
+    assert_eq!(len, 4 + 8 + 16 + 1 + (1 + 4) + (1 + 4) + (8 + 8));
+    assert!(len * 20 < full_len);
+}
+
+#[test]
+fn random_changes() {
+    // xorshift, so this test is deterministic
+    let mut state = 0x2545_f491_u32;
+    let mut random = move |max: u32| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state % max
+    };
+
+    let mut tree = new_tree();
+    let mut copy = new_tree();
+    let mut generation = 0;
+    for _ in 0..200 {
+        for _ in 0..random(8) {
+            let identity = random(40);
+            // a part of the points is outside of the tree
+            let point = Point::new(random(20) as f32, random(20) as f32);
+            match random(3) {
+                0 => tree.insert(ip(identity, point.x.raw(), point.y.raw()), identity),
+                1 => {
+                    tree.update(identity, point);
+                }
+                _ => {
+                    tree.try_remove(&identity);
+                }
+            }
+        }
+        generation = sync(&tree, &mut copy, generation).0;
+    }
+    for identity in 0..40 {
+        assert_eq!(copy.try_remove(&identity), tree.try_remove(&identity));
+    }
+}
+
+#[test]
+fn invalid_deltas() {
+    let mut tree = new_tree();
+    tree.insert(ip(1, 1., 1.), 1);
+    let mut delta = Vec::new();
+    tree.save_incremental(&mut delta, 0, encode).unwrap();
+
+    let mut other = QuadTree::<u32, u32, 2>::new(Point::zero(), Point::new(8., 8.));
+    let error = other
+        .apply_incremental(&mut &delta[..], decode)
+        .unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+    let mut copy = new_tree();
+    let error = copy
+        .apply_incremental(&mut &delta[..delta.len() - 1], decode)
+        .unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    // nothing is applied when the delta is incomplete
+    assert_eq!(copy, new_tree());
+    assert_eq!(copy.generation(), 0);
+}
+
+#[test]
+fn find_range_mut_is_saved() {
+    let mut tree = new_tree();
+    for n in 0..10 {
+        tree.insert(ip(n, n as f32 * 1.5, 15. - n as f32), n * 10);
+    }
+    tree.insert(ip(20, 30., 30.), 200);
+    let mut copy = new_tree();
+    let (generation, _) = sync(&tree, &mut copy, 0);
+
+    tree.find_range_mut(Point::new(16., 16.), r32(100.), |_, _, value| *value += 1);
+    assert!(tree.generation() > generation);
+    let (generation, _) = sync(&tree, &mut copy, generation);
+    assert_eq!(copy.get(&20), Some((Point::new(30., 30.), &201)));
+    assert_eq!(copy.get(&9), Some((Point::new(13.5, 6.), &91)));
+
+    // nothing in range changes nothing
+    tree.find_range_mut(Point::new(100., 0.), r32(1.), |_, _, _| unreachable!());
+    assert_eq!(tree.generation(), generation);
+}
+
+#[test]
+fn get_mut_is_saved() {
+    let mut tree = new_tree();
+    for n in 0..10 {
+        tree.insert(ip(n, n as f32 * 1.5, 15. - n as f32), n * 10);
+    }
+    tree.insert(ip(20, 30., 30.), 200);
+    let mut copy = new_tree();
+    let (generation, _) = sync(&tree, &mut copy, 0);
+
+    *tree.get_mut(&4).unwrap().1 += 1;
+    *tree.get_mut(&20).unwrap().1 += 1;
+    assert!(tree.generation() > generation);
+    let (generation, _) = sync(&tree, &mut copy, generation);
+    assert_eq!(copy.get(&4), Some((Point::new(6., 11.), &41)));
+    assert_eq!(copy.get(&20), Some((Point::new(30., 30.), &201)));
+
+    // a missing identity changes nothing
+    assert_eq!(tree.get_mut(&5000), None);
+    assert_eq!(tree.generation(), generation);
+}
+
+#[test]
+fn iter_mut_is_saved() {
+    let mut tree = new_tree();
+    for n in 0..10 {
+        tree.insert(ip(n, n as f32 * 1.5, 15. - n as f32), n * 10);
+    }
+    tree.insert(ip(20, 30., 30.), 200);
+    let mut copy = new_tree();
+    let (generation, _) = sync(&tree, &mut copy, 0);
+
+    for (_, _, value) in tree.iter_mut() {
+        *value += 1;
+    }
+    assert!(tree.generation() > generation);
+    sync(&tree, &mut copy, generation);
+    assert_eq!(copy, tree);
+    assert_eq!(copy.get(&20), Some((Point::new(30., 30.), &201)));
+}
+
+#[test]
+fn retain_is_saved() {
+    let mut tree = new_tree();
+    for n in 0..10 {
+        tree.insert(ip(n, n as f32 * 1.5, 15. - n as f32), n * 10);
+    }
+    tree.insert(ip(20, 30., 30.), 200);
+    let mut copy = new_tree();
+    let (generation, _) = sync(&tree, &mut copy, 0);
+
+    tree.retain(|identity, _, _| identity % 3 == 0);
+    let (generation, _) = sync(&tree, &mut copy, generation);
+    assert_eq!(copy, tree);
+    tree.drain_range(Point::new(0., 16.), r32(5.));
+    sync(&tree, &mut copy, generation);
+    assert_eq!(copy, tree);
+    assert_eq!(copy.len(), 3);
+}
@@ -4,7 +4,7 @@
 use crate::{
     bucket::Bucket,
     tests::{ip, ipv},
-    Point, QuadTree,
+    NodeId, Point, QuadTree, Quadrant, Rect, YAxis, R32,
 };
 use smallvec::{smallvec, SmallVec};
 
@@ -155,3 +155,204 @@ fn fuzz_results() {
         1086466304,
     );
 }
+
+#[test]
+fn path_of() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(8., 8.));
+    tree.insert(ip(1, 1., 1.), 1);
+    assert_eq!(tree.depth_of(&1), Some(0));
+    assert!(tree.path_of(&1).unwrap().eq([]));
+
+    tree.insert(ip(2, 7., 1.), 2);
+    tree.insert(ip(3, 1.5, 3.5), 3);
+    tree.insert(ip(4, 20., 20.), 4);
+    assert_eq!(tree.depth_of(&2), Some(1));
+    assert!(tree.path_of(&2).unwrap().eq([Quadrant::TopRight]));
+    assert_eq!(tree.depth_of(&3), Some(2));
+    assert!(tree
+        .path_of(&3)
+        .unwrap()
+        .eq([Quadrant::TopLeft, Quadrant::BottomLeft]));
+    assert!(tree
+        .path_of(&1)
+        .unwrap()
+        .eq([Quadrant::TopLeft, Quadrant::TopLeft]));
+
+    // outside of the tree, or not in the tree at all
+    assert_eq!(tree.depth_of(&4), None);
+    assert!(tree.path_of(&5).is_none());
+}
+
+#[test]
+fn node_of() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(8., 8.));
+    tree.insert(ip(1, 1., 1.), 1);
+    assert_eq!(tree.node_of(&1), Some(NodeId::ROOT));
+
+    tree.insert(ip(2, 7., 1.), 2);
+    tree.insert(ip(3, 20., 20.), 3);
+    let node = tree.node_of(&2).unwrap();
+    assert_eq!(node, NodeId::ROOT.child(Quadrant::TopRight).unwrap());
+    assert_eq!(node.depth(), 1);
+    assert_eq!(node.parent(), Some(NodeId::ROOT));
+    assert_eq!(NodeId::from_path(node.path()), Some(node));
+    assert_eq!(
+        tree.node_rect(node),
+        Rect::new(Point::new(4., 0.), Point::new(8., 4.))
+    );
+    assert_eq!(format!("{node:?}"), "[TopRight]");
+    assert_eq!(tree.node_of(&3), None);
+
+    let deepest = NodeId::from_path([Quadrant::BottomRight; 15]).unwrap();
+    assert_eq!(deepest.depth(), 15);
+    assert_eq!(deepest.child(Quadrant::TopLeft), None);
+    assert_eq!(NodeId::from_path([Quadrant::BottomRight; 16]), None);
+    assert_eq!(NodeId::ROOT.parent(), None);
+}
+
+#[test]
+fn y_axis_up() {
+    let mut tree =
+        QuadTree::<u32, u32, 1>::with_y_axis(Point::new(0., 8.), Point::new(8., 0.), YAxis::Up);
+    assert_eq!(tree.y_axis(), YAxis::Up);
+    assert_eq!(tree.bounds(), (Point::new(0., 8.), Point::new(8., 0.)));
+    // the highest y values are at the top
+    tree.insert(ip(1, 1., 7.), 1);
+    tree.insert(ip(2, 7., 1.), 2);
+    tree.insert(ip(3, 3., 3.), 3);
+    tree.insert(ip(4, 1., 1.), 4);
+    assert_eq!(
+        tree.path_of(&1).unwrap().collect::<Vec<_>>(),
+        vec![Quadrant::TopLeft]
+    );
+    assert_eq!(
+        tree.path_of(&2).unwrap().collect::<Vec<_>>(),
+        vec![Quadrant::BottomRight]
+    );
+    let node = tree.node_of(&3).unwrap();
+    assert_eq!(
+        node,
+        NodeId::from_path([Quadrant::BottomLeft, Quadrant::TopRight]).unwrap()
+    );
+    assert_eq!(
+        tree.node_rect(node),
+        Rect::new(Point::new(2., 2.), Point::new(4., 4.))
+    );
+    assert_eq!(
+        tree.node_rect(NodeId::ROOT.child(Quadrant::TopLeft).unwrap()),
+        Rect::new(Point::new(0., 4.), Point::new(4., 8.))
+    );
+
+    let mut found = Vec::new();
+    tree.find_range(Point::new(1., 7.), R32::new(1.), |identity, _, _| {
+        found.push(*identity);
+    });
+    assert_eq!(found, vec![1]);
+}
+
+#[test]
+fn covers() {
+    let tree = QuadTree::<u32, u32, 4>::new(Point::new(-4., 0.), Point::new(4., 8.));
+    assert_eq!(tree.bounds(), (Point::new(-4., 0.), Point::new(4., 8.)));
+    assert!(tree.covers(Point::new(0., 3.)));
+    assert!(tree.covers(Point::new(4., 8.)));
+    assert!(!tree.covers(Point::new(-4.5, 3.)));
+    assert!(!tree.covers(Point::new(0., 8.01)));
+}
+
+#[test]
+fn insert_again_at_same_point() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(8., 8.));
+    tree.insert(ip(1, 1., 1.), 1);
+    tree.insert(ip(2, 5., 5.), 2);
+    tree.insert(ip(3, 9., 9.), 3);
+    let items = tree.items.clone();
+    let weak = tree.weak_ref(&1).unwrap();
+
+    for n in 0..3 {
+        tree.insert(ip(1, 1., 1.), 10 + n);
+        tree.insert(ip(3, 9., 9.), 30 + n);
+    }
+    assert_eq!(tree.get(&1), Some((Point::new(1., 1.), &12)));
+    assert_eq!(tree.get(&3), Some((Point::new(9., 9.), &32)));
+    assert_eq!(tree.items.len(), items.len());
+    assert_eq!(
+        tree.path_of(&1).unwrap().collect::<Vec<_>>(),
+        [Quadrant::TopLeft]
+    );
+    assert_eq!(tree.outside_of_range.len(), 1);
+    // the entry was inserted again, so it has a new generation
+    assert_eq!(weak.upgrade(&tree), None);
+}
+
+#[test]
+fn get_and_get_mut() {
+    let mut tree = QuadTree::<u32, u32, 2>::new(Point::zero(), Point::new(8., 8.));
+    for n in 0..6 {
+        tree.insert(ip(n, n as f32, 7. - n as f32), n * 10);
+    }
+    tree.insert(ip(10, 20., 20.), 100);
+
+    assert_eq!(tree.get(&3), Some((Point::new(3., 4.), &30)));
+    assert_eq!(tree.get(&10), Some((Point::new(20., 20.), &100)));
+    assert_eq!(tree.get(&6), None);
+
+    let (point, value) = tree.get_mut(&3).unwrap();
+    assert_eq!(point, Point::new(3., 4.));
+    *value += 1;
+    *tree.get_mut(&10).unwrap().1 += 1;
+    assert_eq!(tree.get_mut(&6), None);
+
+    assert_eq!(tree.get(&3), Some((Point::new(3., 4.), &31)));
+    assert_eq!(tree.get(&10), Some((Point::new(20., 20.), &101)));
+    // the other entries and the positions are unchanged
+    let mut found = Vec::new();
+    tree.find_range(Point::new(4., 4.), R32::new(100.), |identity, _, value| {
+        found.push((*identity, *value));
+    });
+    found.sort_unstable();
+    assert_eq!(
+        found,
+        vec![
+            (0, 0),
+            (1, 10),
+            (2, 20),
+            (3, 31),
+            (4, 40),
+            (5, 50),
+            (10, 101)
+        ]
+    );
+    assert!(tree.update(3, Point::new(1., 1.)));
+    assert_eq!(tree.get(&3), Some((Point::new(1., 1.), &31)));
+}
+
+#[test]
+fn len() {
+    let mut tree = QuadTree::<u32, u32, 2>::new(Point::zero(), Point::new(8., 8.));
+    assert_eq!(tree.len(), 0);
+    assert!(tree.is_empty());
+
+    for n in 0..5 {
+        tree.insert(ip(n, n as f32, 1.), n);
+    }
+    tree.insert(ip(10, 20., 20.), 10);
+    assert_eq!(tree.len(), 6);
+    assert!(!tree.is_empty());
+    assert!(tree.contains_identity(&10));
+    assert!(!tree.contains_identity(&5));
+
+    // inserting an identity again replaces the entry, also when it moves outside of the tree
+    tree.insert(ip(2, 2., 2.), 2);
+    tree.insert(ip(3, -5., 1.), 3);
+    assert_eq!(tree.len(), 6);
+
+    tree.remove(&10);
+    tree.remove(&3);
+    assert_eq!(tree.len(), 4);
+    assert!(!tree.contains_identity(&3));
+    for n in [0, 1, 2, 4] {
+        tree.remove(&n);
+    }
+    assert!(tree.is_empty());
+}
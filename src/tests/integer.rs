@@ -0,0 +1,161 @@
+use crate::{IntPoint, IntQuadTree};
+
+/// A tree that covers every `i32` position, where `f32` has a precision of 512 at the edges
+fn grid() -> IntQuadTree<i32, i32, 4> {
+    IntQuadTree::new(
+        IntPoint::new(i32::MIN, i32::MIN),
+        IntPoint::new(i32::MAX, i32::MAX),
+    )
+}
+
+fn found_range(tree: &IntQuadTree<i32, i32, 4>, center: IntPoint, range: u32) -> Vec<i32> {
+    let mut found = Vec::new();
+    tree.find_range(center, range, |identity, point, value| {
+        assert_eq!(tree.get(identity), Some((point, value)));
+        found.push(*identity);
+    });
+    found.sort_unstable();
+    found
+}
+
+fn found_rect(tree: &IntQuadTree<i32, i32, 4>, a: IntPoint, b: IntPoint) -> Vec<i32> {
+    let mut found = Vec::new();
+    tree.find_rect(a, b, |identity, point, value| {
+        assert_eq!(tree.get(identity), Some((point, value)));
+        found.push(*identity);
+    });
+    found.sort_unstable();
+    found
+}
+
+#[test]
+fn exact_positions() {
+    let mut tree = grid();
+    let x = 2_000_000_001;
+    tree.insert(1, IntPoint::new(x, 7), 10);
+    tree.insert(2, IntPoint::new(x + 1, 7), 20);
+    tree.insert(3, IntPoint::new(x + 3, 7), 30);
+    tree.insert(4, IntPoint::new(x + 3, 11), 40);
+    assert_eq!(tree.len(), 4);
+    assert_eq!(tree.get(&2), Some((IntPoint::new(x + 1, 7), &20)));
+
+    let center = IntPoint::new(x, 7);
+    assert_eq!(found_range(&tree, center, 0), vec![1]);
+    // entries at exactly the range are found
+    assert_eq!(found_range(&tree, center, 1), vec![1, 2]);
+    assert_eq!(found_range(&tree, center, 4), vec![1, 2, 3]);
+    // 3² + 4² = 5²
+    assert_eq!(found_range(&tree, center, 5), vec![1, 2, 3, 4]);
+
+    assert_eq!(
+        found_rect(&tree, IntPoint::new(x + 1, 7), IntPoint::new(x + 3, 10)),
+        vec![2, 3]
+    );
+    // the corners can be in any order
+    assert_eq!(
+        found_rect(&tree, IntPoint::new(x + 3, 11), IntPoint::new(x + 2, 7)),
+        vec![3, 4]
+    );
+    assert_eq!(
+        found_rect(&tree, IntPoint::new(x + 2, 0), IntPoint::new(x + 2, 20)),
+        Vec::<i32>::new()
+    );
+
+    assert!(tree.update(2, IntPoint::new(x - 1, 7)));
+    assert!(!tree.update(5, center));
+    assert_eq!(found_range(&tree, IntPoint::new(x + 1, 7), 1), vec![1]);
+    assert_eq!(tree.try_remove(&2), Some((20, IntPoint::new(x - 1, 7))));
+    assert_eq!(tree.try_remove(&2), None);
+    assert_eq!(tree.len(), 3);
+}
+
+#[test]
+fn quadrant_boundaries() {
+    // the tree is split at 0, 0, and its children at 128
+    let mut tree =
+        IntQuadTree::<i32, i32, 4>::new(IntPoint::new(-256, -256), IntPoint::new(256, 256));
+    let mut identity = 0;
+    for x in [-129, -128, -127, -1, 0, 1, 127, 128, 129] {
+        for y in [-1, 0, 1] {
+            tree.insert(identity, IntPoint::new(x, y), identity);
+            identity += 1;
+        }
+    }
+    assert_eq!(
+        found_rect(&tree, IntPoint::new(0, 0), IntPoint::new(128, 0)),
+        vec![13, 16, 19, 22]
+    );
+    assert_eq!(
+        found_rect(&tree, IntPoint::new(-128, -1), IntPoint::new(-128, 1)),
+        vec![3, 4, 5]
+    );
+    assert_eq!(
+        found_range(&tree, IntPoint::new(128, 0), 1),
+        vec![19, 21, 22, 23, 25]
+    );
+
+    // outside of the tree
+    tree.insert(100, IntPoint::new(i32::MAX, i32::MIN), 1000);
+    tree.insert(101, IntPoint::new(i32::MAX - 1, i32::MIN), 1001);
+    assert_eq!(tree.inner().iter_out_of_range().count(), 2);
+    assert_eq!(
+        found_range(&tree, IntPoint::new(i32::MAX, i32::MIN), 0),
+        vec![100]
+    );
+    assert_eq!(
+        found_rect(
+            &tree,
+            IntPoint::new(i32::MAX - 1, i32::MIN),
+            IntPoint::new(i32::MAX - 1, 0)
+        ),
+        vec![101]
+    );
+}
+
+#[test]
+fn same_as_brute_force() {
+    let mut tree = grid();
+    let mut entries = Vec::new();
+    for n in 0..500 {
+        // clusters of entries next to each other
+        let x = (n / 10 * 7919 % 4000 - 2000) * 1_000_000 + n % 10;
+        let y = (n / 10 * 104_729 % 4000 - 1999) * 1_000_000 - n % 7;
+        let point = IntPoint::new(x, y);
+        tree.insert(n, point, n);
+        entries.push((n, point));
+    }
+    for (center, range) in [
+        (entries[0].1, 1),
+        (entries[123].1, 3),
+        (entries[456].1, 100_000_000),
+        (IntPoint::new(0, 0), 1_000_000_000),
+        (IntPoint::new(i32::MAX, i32::MAX), u32::MAX),
+    ] {
+        let expected = entries
+            .iter()
+            .filter(|(_, point)| {
+                point.distance_squared(center) <= u128::from(range) * u128::from(range)
+            })
+            .map(|(identity, _)| *identity)
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(found_range(&tree, center, range), expected);
+    }
+    for (a, b) in [
+        (entries[0].1, entries[5].1),
+        (entries[123].1, entries[456].1),
+        (IntPoint::new(i32::MIN, i32::MIN), IntPoint::new(0, 0)),
+    ] {
+        let (left, right) = (a.x.min(b.x), a.x.max(b.x));
+        let (top, bottom) = (a.y.min(b.y), a.y.max(b.y));
+        let expected = entries
+            .iter()
+            .filter(|(_, point)| {
+                (left..=right).contains(&point.x) && (top..=bottom).contains(&point.y)
+            })
+            .map(|(identity, _)| *identity)
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(found_rect(&tree, a, b), expected);
+    }
+}
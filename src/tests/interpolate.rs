@@ -0,0 +1,49 @@
+use crate::{tests::ip, Point, QuadTree, R32};
+
+fn tree() -> QuadTree<f32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    tree.insert(ip(1, 10., 10.), 10.);
+    tree.insert(ip(2, 14., 10.), 20.);
+    tree.insert(ip(3, 10., 30.), 100.);
+    tree.insert(ip(4, -2., 10.), 40.);
+    tree
+}
+
+#[test]
+fn sample_idw() {
+    let tree = tree();
+    let value = |value: &f32| *value;
+    // halfway between two entries, so both weigh the same
+    assert_eq!(
+        tree.sample_idw(Point::new(12., 10.), R32::new(3.), value),
+        Some(15.)
+    );
+    // 1 away from entry 1 and 3 away from entry 2: (10 / 1 + 20 / 9) / (1 / 1 + 1 / 9)
+    let sample = tree
+        .sample_idw(Point::new(11., 10.), R32::new(5.), value)
+        .unwrap();
+    assert!((sample - 11.).abs() < 1e-5, "{sample}");
+    // entries outside of the tree are also sampled
+    assert_eq!(
+        tree.sample_idw(Point::new(-3., 10.), R32::new(2.), value),
+        Some(40.)
+    );
+    assert_eq!(
+        tree.sample_idw(Point::new(40., 40.), R32::new(5.), value),
+        None
+    );
+}
+
+#[test]
+fn exact_points() {
+    let mut tree = tree();
+    tree.insert(ip(5, 10., 10.), 30.);
+    assert_eq!(
+        tree.sample_idw(Point::new(10., 10.), R32::new(50.), |value| *value),
+        Some(20.)
+    );
+    assert_eq!(
+        tree.sample_idw(Point::new(10., 10.), R32::new(50.), |value| value * 2.),
+        Some(40.)
+    );
+}
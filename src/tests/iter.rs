@@ -0,0 +1,101 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree};
+
+fn tree() -> QuadTree<u32, u32, 2> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(16., 16.));
+    for n in 0..12 {
+        tree.insert(ip(n, n as f32, 15. - n as f32), n * 10);
+    }
+    tree.insert(ip(20, 30., 30.), 200);
+    tree.insert(ip(21, -1., 3.), 210);
+    tree
+}
+
+fn sorted(mut entries: Vec<(u32, Point, u32)>) -> Vec<(u32, Point, u32)> {
+    entries.sort_unstable_by_key(|(identity, _, _)| *identity);
+    entries
+}
+
+fn expected() -> Vec<(u32, Point, u32)> {
+    (0..12)
+        .map(|n| (n, Point::new(n as f32, 15. - n as f32), n * 10))
+        .chain([
+            (20, Point::new(30., 30.), 200),
+            (21, Point::new(-1., 3.), 210),
+        ])
+        .collect()
+}
+
+#[test]
+fn iter() {
+    let mut tree = tree();
+    let entries = tree
+        .iter()
+        .map(|(identity, point, value)| (*identity, point, *value))
+        .collect::<Vec<_>>();
+    // the entries outside of the tree come last
+    assert_eq!(
+        entries[12..],
+        [
+            (20, Point::new(30., 30.), 200),
+            (21, Point::new(-1., 3.), 210)
+        ]
+    );
+    assert_eq!(sorted(entries), expected());
+
+    // the stale nodes of a merged subtree have no entries
+    for n in 2..12 {
+        tree.remove(&n);
+    }
+    let entries = tree
+        .iter()
+        .map(|(identity, _, _)| *identity)
+        .collect::<Vec<_>>();
+    assert_eq!(entries, vec![0, 1, 20, 21]);
+    assert_eq!(
+        QuadTree::<u32, u32, 2>::sized_around_origin(Point::new(1., 1.))
+            .iter()
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn iter_mut() {
+    let mut tree = tree();
+    for (identity, _, value) in tree.iter_mut() {
+        *value += identity;
+    }
+    let entries = tree
+        .iter()
+        .map(|(identity, point, value)| (*identity, point, *value))
+        .collect::<Vec<_>>();
+    let changed = expected()
+        .into_iter()
+        .map(|(identity, point, value)| (identity, point, value + identity))
+        .collect::<Vec<_>>();
+    assert_eq!(sorted(entries), changed);
+    // the positions did not change, so the entries are still found by position
+    assert_eq!(tree.get(&5), Some((Point::new(5., 10.), &55)));
+    assert!(tree.update(5, Point::new(1., 1.)));
+    assert_eq!(tree.remove(&21), (231, Point::new(-1., 3.)));
+}
+
+#[test]
+fn into_iter() {
+    let tree = tree();
+    let order = tree
+        .iter()
+        .map(|(identity, _, _)| *identity)
+        .collect::<Vec<_>>();
+    let entries = tree.into_iter().collect::<Vec<_>>();
+    assert_eq!(
+        entries
+            .iter()
+            .map(|(identity, _, _)| *identity)
+            .collect::<Vec<_>>(),
+        order
+    );
+    assert_eq!(sorted(entries), expected());
+}
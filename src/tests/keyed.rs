@@ -0,0 +1,92 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, KeyedQuadTree, Point, R32};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Entity {
+    kind: u8,
+}
+
+fn kind(entity: &Entity) -> u8 {
+    entity.kind
+}
+
+fn by_key(tree: &KeyedQuadTree<Entity, u32, u8, 4>, key: u8) -> Vec<u32> {
+    tree.find_by_key(&key)
+        .map(|(identity, _, _)| *identity)
+        .collect()
+}
+
+#[test]
+fn follows_changes() {
+    let mut tree =
+        KeyedQuadTree::<Entity, u32, u8, 4>::new(Point::zero(), Point::new(64., 64.), kind);
+    for n in 0..30 {
+        tree.insert(
+            ip(n, (n % 6) as f32 * 10. + 1., (n / 6) as f32 * 10. + 1.),
+            Entity {
+                kind: (n % 3) as u8,
+            },
+        );
+    }
+    tree.insert(ip(100, -5., -5.), Entity { kind: 1 });
+    assert_eq!(by_key(&tree, 0), (0..30).step_by(3).collect::<Vec<_>>());
+    assert_eq!(tree.find_by_key(&1).count(), 11);
+
+    // inserting an existing identity replaces its key
+    tree.insert(ip(0, 1., 1.), Entity { kind: 2 });
+    assert!(!by_key(&tree, 0).contains(&0));
+    assert!(by_key(&tree, 2).contains(&0));
+
+    // moves through the tree keep the key, changed values move to another key
+    assert!(tree.update(3, Point::new(60., 60.)));
+    assert!(tree.update_point_and_value(6, Point::new(-10., 30.), |entity| entity.kind = 5));
+    assert!(!tree.update_point_and_value(1000, Point::zero(), |entity| entity.kind = 5));
+    assert_eq!(
+        tree.find_by_key(&5).collect::<Vec<_>>(),
+        vec![(&6, Point::new(-10., 30.), &Entity { kind: 5 })]
+    );
+    assert_eq!(
+        by_key(&tree, 0),
+        (3..30).step_by(3).filter(|n| *n != 6).collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        tree.try_remove(&6),
+        Some((Entity { kind: 5 }, Point::new(-10., 30.)))
+    );
+    assert_eq!(tree.try_remove(&6), None);
+    assert_eq!(tree.find_by_key(&5).count(), 0);
+}
+
+#[test]
+fn range_with_key() {
+    let mut tree =
+        KeyedQuadTree::<Entity, u32, u8, 4>::new(Point::zero(), Point::new(64., 64.), kind);
+    for n in 0..64 {
+        // a single entry of kind 1, so both ways of answering the query are used
+        let kind = if n == 9 { 1 } else { u8::from(n % 2 == 0) * 2 };
+        tree.insert(
+            ip(n, (n % 8) as f32 * 8. + 1., (n / 8) as f32 * 8. + 1.),
+            Entity { kind },
+        );
+    }
+    for key in [0, 1, 2, 3] {
+        for (center, range) in [(Point::new(10., 10.), 12.), (Point::new(40., 30.), 30.)] {
+            let mut found = Vec::new();
+            tree.find_range_with_key(center, R32::new(range), &key, |identity, _, _| {
+                found.push(*identity);
+            });
+            found.sort_unstable();
+            let mut expected = Vec::new();
+            tree.tree()
+                .find_range(center, R32::new(range), |identity, _, entity| {
+                    if entity.kind == key {
+                        expected.push(*identity);
+                    }
+                });
+            expected.sort_unstable();
+            assert_eq!(found, expected);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{bucket::Bucket, tests::ip, MaintenanceBudget, Point, QuadTree, R32};
+
+fn tree() -> QuadTree<u32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..40 {
+        tree.insert(ip(n, n as f32 * 1.5, (n % 8) as f32 * 7.9), n);
+    }
+    tree
+}
+
+fn found(tree: &QuadTree<u32, u32, 4>) -> Vec<u32> {
+    let mut found = Vec::new();
+    tree.find_range(Point::zero(), R32::new(100.), |identity, _, _| {
+        found.push(*identity);
+    });
+    found.sort_unstable();
+    found
+}
+
+#[test]
+fn deferred_merges() {
+    let mut tree = tree();
+    tree.set_deferred_merges(true);
+    let nodes = tree.items.len();
+    assert!(nodes > 5);
+    for n in 2..40 {
+        tree.remove(&n);
+    }
+    assert_eq!(tree.items.len(), nodes);
+    assert_eq!(found(&tree), vec![0, 1]);
+    assert!(tree.pending_maintenance() > 0);
+
+    let pending = tree.pending_maintenance();
+    assert!(tree.maintenance(MaintenanceBudget::nodes(1)) <= pending);
+    assert_eq!(tree.maintenance(MaintenanceBudget::unlimited()), 0);
+    assert!(matches!(&tree.items[..], [Bucket::Owned(entries)] if entries.len() == 2));
+    assert_eq!(found(&tree), vec![0, 1]);
+}
+
+#[test]
+fn stop_deferring() {
+    let mut tree = tree();
+    tree.set_deferred_merges(true);
+    for n in 0..40 {
+        tree.remove(&n);
+    }
+    tree.set_deferred_merges(false);
+    assert_eq!(tree.pending_maintenance(), 0);
+    assert!(matches!(&tree.items[..], [Bucket::Owned(entries)] if entries.is_empty()));
+    // the merges happen right away again
+    tree.insert(ip(1, 1., 1.), 1);
+    assert_eq!(tree.maintenance(MaintenanceBudget::unlimited()), 0);
+}
@@ -0,0 +1,43 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, Rect, R32};
+
+#[test]
+fn suggest_bounds() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(8., 8.));
+    assert_eq!(
+        tree.suggest_bounds(),
+        Rect::new(Point::zero(), Point::new(8., 8.))
+    );
+    tree.insert(ip(1, 4., 4.), 1);
+    assert_eq!(
+        tree.suggest_bounds(),
+        Rect::new(Point::new(3., 3.), Point::new(5., 5.))
+    );
+    tree.insert(ip(2, 2., 1.), 2);
+    tree.insert(ip(3, -10., 20.), 3);
+    assert_eq!(
+        tree.suggest_bounds(),
+        Rect::new(Point::new(-11.1875, -0.1875), Point::new(5.1875, 21.1875))
+    );
+}
+
+#[test]
+fn migrate_to_bounds() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(8., 8.));
+    for n in 0..50 {
+        tree.insert(ip(n, n as f32 * 2., (n % 5) as f32), n);
+    }
+    assert!(tree.iter_out_of_range().count() > 40);
+    let bounds = tree.suggest_bounds();
+    tree.migrate_to_bounds(bounds);
+    assert_eq!(tree.rect, bounds);
+    assert_eq!(tree.iter_out_of_range().count(), 0);
+
+    let mut found = Vec::new();
+    tree.find_range(Point::new(50., 0.), R32::new(0.5), |identity, _, value| {
+        found.push((*identity, *value));
+    });
+    assert_eq!(found, vec![(25, 25)]);
+    assert_eq!(tree.depth_of(&25).map(|depth| depth > 0), Some(true));
+}
@@ -1,11 +1,64 @@
 #![cfg(test)]
 
-use crate::{IdentityPoint, Point};
+use crate::{IdentityPoint, Point, Rect};
 
+mod aggregate;
+mod alloc;
+mod approx;
+mod bulk;
+mod coarse;
+mod compact;
+mod concurrent_forest;
+mod connected_regions;
+mod cooperative;
+mod degenerate;
+mod depth;
+mod eviction;
+mod explain;
+mod farthest;
 mod find_range;
+mod flat;
+mod forest;
+mod free_space;
+mod frozen;
+mod gpu;
+mod group;
+mod handles;
+mod hotspots;
+mod identity_filter;
+mod incremental;
 mod insert;
+mod integer;
+mod interpolate;
+mod iter;
+mod keyed;
+mod maintenance;
+mod migrate;
+mod nearest;
+mod order;
+mod outside;
+mod parallel;
+mod paranoid;
+mod pathfinding;
+mod point;
+mod position_only;
+mod prelude;
+mod range_iter;
+mod recorder;
+mod region;
 mod remove;
+mod replication;
+mod snapshot;
+mod space;
+mod spanning_tree;
+mod testkit;
+mod tiles;
+mod tombstone;
+mod transform;
 mod update;
+mod weak;
+mod wide;
+mod zipped;
 
 /// Helper function to generate an [`IdentityPoint`]
 fn ip(identity: u32, x: f32, y: f32) -> IdentityPoint<u32> {
@@ -25,3 +78,8 @@ fn ipv<V>(identity: u32, x: f32, y: f32, value: V) -> (IdentityPoint<u32>, V) {
         value,
     )
 }
+
+/// Helper function to generate a [`Rect`] from its edges
+fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Rect {
+    Rect::new(Point::new(left, top), Point::new(right, bottom))
+}
@@ -0,0 +1,125 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, R32};
+use std::collections::BTreeMap;
+
+fn tree() -> QuadTree<(), u32, 2> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..150 {
+        let x = (n * 37 % 64) as f32 + 0.25;
+        let y = (n * 23 % 61) as f32 + 0.5;
+        tree.insert(ip(n, x, y), ());
+    }
+    // duplicate positions, and entries outside of the tree
+    tree.insert(ip(150, 0.25, 0.5), ());
+    tree.insert(ip(151, 0.25, 0.5), ());
+    tree.insert(ip(152, -5., 10.), ());
+    tree.insert(ip(153, 70., 70.), ());
+    tree
+}
+
+fn brute_force(tree: &QuadTree<(), u32, 2>, k: usize) -> BTreeMap<u32, Vec<(u32, R32)>> {
+    let mut points = Vec::new();
+    tree.find_range(
+        Point::new(32., 32.),
+        R32::new(1000.),
+        |identity, point, ()| {
+            points.push((*identity, point));
+        },
+    );
+    points
+        .iter()
+        .map(|(identity, point)| {
+            let mut neighbors: Vec<_> = points
+                .iter()
+                .filter(|(other, _)| other != identity)
+                .map(|(other, other_point)| (point.distance_squared_to(*other_point), *other))
+                .collect();
+            neighbors.sort_unstable();
+            neighbors.truncate(k);
+            let neighbors = neighbors
+                .into_iter()
+                .map(|(distance, other)| (other, R32::new(distance.raw().sqrt())))
+                .collect();
+            (*identity, neighbors)
+        })
+        .collect()
+}
+
+#[test]
+fn matches_brute_force() {
+    let tree = tree();
+    for k in [0, 1, 3, 8, 200] {
+        let mut found = BTreeMap::new();
+        tree.all_k_nearest(k, |identity, neighbors| {
+            let neighbors = neighbors
+                .iter()
+                .map(|(other, distance)| (**other, *distance))
+                .collect::<Vec<_>>();
+            assert!(found.insert(*identity, neighbors).is_none());
+        });
+        assert_eq!(found, brute_force(&tree, k), "k = {k}");
+    }
+}
+
+#[test]
+fn too_few_entries() {
+    let mut tree = QuadTree::<(), u32, 2>::new(Point::zero(), Point::new(10., 10.));
+    tree.all_k_nearest(3, |_, _| panic!("the tree is empty"));
+
+    tree.insert(ip(1, 1., 1.), ());
+    tree.insert(ip(2, 4., 5.), ());
+    let mut found = Vec::new();
+    tree.all_k_nearest(3, |identity, neighbors| {
+        found.push((*identity, neighbors.to_vec()));
+    });
+    assert_eq!(
+        found,
+        vec![(1, vec![(&2, R32::new(5.))]), (2, vec![(&1, R32::new(5.))])]
+    );
+}
+
+#[test]
+fn knn_graph() {
+    let tree = tree();
+    let graph = tree.knn_graph::<4>();
+    let expected = brute_force(&tree, 4);
+    assert_eq!(graph.len(), expected.len());
+    for ((identity, neighbors), (expected_identity, expected_neighbors)) in
+        graph.iter().zip(&expected)
+    {
+        assert_eq!(identity, expected_identity);
+        assert!(!neighbors.spilled());
+        assert!(neighbors
+            .iter()
+            .eq(expected_neighbors.iter().map(|(neighbor, _)| neighbor)));
+    }
+}
+
+#[test]
+fn closest_pair() {
+    let mut tree = tree();
+    // 150 and 151 have the same position as 0
+    assert_eq!(tree.closest_pair(), Some((&0, &150, R32::new(0.))));
+    tree.remove(&0);
+    tree.remove(&150);
+    tree.remove(&151);
+    let expected = brute_force(&tree, 1)
+        .into_iter()
+        .filter_map(|(identity, neighbors)| {
+            let (neighbor, distance) = *neighbors.first()?;
+            Some((distance, identity.min(neighbor), identity.max(neighbor)))
+        })
+        .min()
+        .map(|(distance, a, b)| (a, b, distance));
+    assert_eq!(
+        tree.closest_pair()
+            .map(|(a, b, distance)| (*a, *b, distance)),
+        expected
+    );
+
+    let mut tree = QuadTree::<(), u32, 2>::new(Point::zero(), Point::new(10., 10.));
+    assert_eq!(tree.closest_pair(), None);
+    tree.insert(ip(1, 1., 1.), ());
+    assert_eq!(tree.closest_pair(), None);
+}
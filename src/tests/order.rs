@@ -0,0 +1,124 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{
+    curve::{hilbert_code, morton_code},
+    tests::ip,
+    Point, QuadTree, SpatialCursor,
+};
+
+fn tree() -> QuadTree<u32, u32, 2> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..200 {
+        let x = (n * 37 % 70) as f32 - 3.;
+        let y = (n * 23 % 66) as f32 - 1.;
+        tree.insert(ip(n, x, y), n);
+    }
+    tree
+}
+
+#[test]
+fn sorted_by_code() {
+    let tree = tree();
+    for (entries, code) in [
+        (
+            tree.iter_morton().collect::<Vec<_>>(),
+            morton_code as fn(_, _) -> u32,
+        ),
+        (tree.iter_hilbert().collect(), hilbert_code),
+    ] {
+        assert_eq!(entries.len(), 200);
+        let mut identities = entries
+            .iter()
+            .map(|(identity, _, value)| {
+                assert_eq!(identity, value);
+                **identity
+            })
+            .collect::<Vec<_>>();
+        identities.sort_unstable();
+        assert_eq!(identities, (0..200).collect::<Vec<_>>());
+
+        for pair in entries.windows(2) {
+            let (a, b) = (code(tree.rect, pair[0].1), code(tree.rect, pair[1].1));
+            assert!((a, pair[0].0) < (b, pair[1].0));
+        }
+    }
+}
+
+#[test]
+fn hilbert_never_jumps() {
+    let mut tree = QuadTree::<(), u32, 4>::new(Point::zero(), Point::new(16., 16.));
+    for n in 0..256 {
+        tree.insert(ip(n, (n % 16) as f32 + 0.5, (n / 16) as f32 + 0.5), ());
+    }
+    let hilbert = tree
+        .iter_hilbert()
+        .map(|(_, point, ())| point)
+        .collect::<Vec<_>>();
+    assert_eq!(hilbert[0], Point::new(0.5, 0.5));
+    assert_eq!(hilbert[255], Point::new(15.5, 0.5));
+    for pair in hilbert.windows(2) {
+        assert_eq!(pair[0].distance_squared_to(pair[1]), Point::new(1., 0.).x);
+    }
+
+    let morton = tree
+        .iter_morton()
+        .map(|(_, point, ())| point)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        &morton[..4],
+        [
+            Point::new(0.5, 0.5),
+            Point::new(1.5, 0.5),
+            Point::new(0.5, 1.5),
+            Point::new(1.5, 1.5),
+        ]
+    );
+}
+
+#[test]
+fn page_after() {
+    let mut tree = tree();
+    let all = tree
+        .iter_morton()
+        .map(|(identity, _, _)| *identity)
+        .collect::<Vec<_>>();
+
+    let mut pages = Vec::new();
+    let mut cursor = None;
+    loop {
+        let (page, next) = tree.page_after(cursor, 30);
+        assert!(page.len() <= 30);
+        pages.extend(page.iter().map(|(identity, _, _)| **identity));
+        cursor = next;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(pages, all);
+    assert_eq!(tree.page_after(None, 0).0.len(), 0);
+    assert_eq!(tree.page_after(None, 500).1, None);
+
+    // changes before the cursor don't move the entries after it
+    let (first, cursor) = tree.page_after(None, 50);
+    let first = first
+        .iter()
+        .map(|(identity, _, _)| **identity)
+        .collect::<Vec<_>>();
+    let cursor = cursor.expect("there are more pages");
+    assert_eq!(*cursor.identity(), first[49]);
+    for identity in &first[..10] {
+        tree.remove(identity);
+    }
+    tree.insert(ip(1000, -10., -10.), 1000);
+    let (second, _) = tree.page_after(Some(cursor.clone()), 50);
+    assert_eq!(
+        second
+            .iter()
+            .map(|(identity, _, _)| **identity)
+            .collect::<Vec<_>>(),
+        all[50..100]
+    );
+
+    let cursor = SpatialCursor::from_parts(cursor.position(), *cursor.identity());
+    assert_eq!(tree.page_after(Some(cursor), 50).0, second);
+}
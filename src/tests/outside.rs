@@ -0,0 +1,51 @@
+use crate::{tests::ip, Point, QuadTree, Rect};
+use noisy_float::types::r32;
+
+fn found(tree: &QuadTree<u32, u32, 4>, center: Point, range: f32) -> Vec<u32> {
+    let mut found = Vec::new();
+    tree.find_range(center, r32(range), |identity, _, _| found.push(*identity));
+    found.sort_unstable();
+    found
+}
+
+#[test]
+pub fn bounds_follow_the_entries() {
+    let mut tree = QuadTree::<u32, u32, 4>::sized_around_origin(Point::new(10., 10.));
+    tree.insert(ip(0, 1., 1.), 0);
+    assert!(!tree.outside_of_range.touches(tree.rect));
+
+    tree.insert(ip(1, 20., 20.), 1);
+    tree.insert(ip(2, 50., 30.), 2);
+    let bounds = |tree: &QuadTree<u32, u32, 4>, left, top, right, bottom| {
+        let rect = Rect::new(Point::new(left, top), Point::new(right, bottom));
+        tree.outside_of_range.touches(rect)
+    };
+    assert!(bounds(&tree, 49., 29., 51., 31.));
+    assert!(!bounds(&tree, 51., 0., 60., 10.));
+
+    // removing an entry on the edge shrinks the bounds to the other entry
+    tree.remove(&2);
+    assert!(!bounds(&tree, 49., 29., 51., 31.));
+    assert!(bounds(&tree, 19., 19., 21., 21.));
+
+    // moving the last entry into the tree leaves no bounds
+    tree.update(1, Point::new(2., 2.));
+    assert!(!bounds(&tree, -100., -100., 100., 100.));
+}
+
+#[test]
+pub fn queries_find_the_same_entries() {
+    let mut tree = QuadTree::<u32, u32, 4>::sized_around_origin(Point::new(10., 10.));
+    tree.insert(ip(0, 1., 1.), 0);
+    tree.insert(ip(1, 20., 20.), 1);
+    tree.insert(ip(2, -30., 0.), 2);
+
+    assert_eq!(found(&tree, Point::new(0., 0.), 5.), vec![0]);
+    assert_eq!(found(&tree, Point::new(18., 18.), 5.), vec![1]);
+    assert_eq!(found(&tree, Point::new(-25., 0.), 6.), vec![2]);
+    assert_eq!(found(&tree, Point::new(0., 0.), 40.), vec![0, 1, 2]);
+
+    tree.remove(&2);
+    assert_eq!(found(&tree, Point::new(-25., 0.), 6.), Vec::<u32>::new());
+    assert_eq!(found(&tree, Point::new(0., 0.), 40.), vec![0, 1]);
+}
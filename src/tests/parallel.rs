@@ -0,0 +1,141 @@
+use crate::{r32, tests::ip, NodeId, Point, QuadTree, Quadrant, SubtreeView};
+
+/// A tree with a grid of 16 by 16 entries, and one entry outside of it
+fn grid() -> QuadTree<u32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for x in 0..16_u16 {
+        for y in 0..16_u16 {
+            let identity = u32::from(x * 16 + y);
+            tree.insert(
+                ip(identity, f32::from(x * 4 + 2), f32::from(y * 4 + 2)),
+                identity,
+            );
+        }
+    }
+    tree.insert(ip(1000, 100., 100.), 1000);
+    tree
+}
+
+#[test]
+fn split_for_parallel() {
+    let tree = grid();
+    let quadrants = Quadrant::all().map(|quadrant| NodeId::ROOT.child(quadrant).unwrap());
+
+    let views = tree.split_for_parallel(4);
+    assert_eq!(
+        views.iter().map(SubtreeView::len).collect::<Vec<_>>(),
+        vec![64, 64, 64, 65]
+    );
+    for (view, quadrant) in views.iter().zip(quadrants) {
+        assert_eq!(view.nodes().collect::<Vec<_>>(), vec![quadrant]);
+        assert_eq!(view.iter().count(), view.len());
+    }
+    assert_eq!(
+        views
+            .iter()
+            .map(SubtreeView::includes_out_of_range)
+            .collect::<Vec<_>>(),
+        vec![false, false, false, true]
+    );
+
+    // the subtrees are spread by the amount of entries before them
+    let views = tree.split_for_parallel(3);
+    assert_eq!(
+        views.iter().map(SubtreeView::len).collect::<Vec<_>>(),
+        vec![128, 64, 65]
+    );
+    assert_eq!(
+        views[0].nodes().collect::<Vec<_>>(),
+        vec![quadrants[0], quadrants[1]]
+    );
+
+    // more parts than subtrees
+    let views = tree.split_for_parallel(300);
+    assert_eq!(views.len(), 300);
+    assert_eq!(views.iter().map(SubtreeView::len).sum::<usize>(), 257);
+    assert!(views.iter().all(|view| view.len() <= 5));
+    assert_eq!(views[299].len(), 1);
+
+    let views = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.))
+        .split_for_parallel(2)
+        .iter()
+        .map(|view| (view.len(), view.nodes().collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    assert_eq!(views, vec![(0, vec![NodeId::ROOT]), (0, Vec::new())]);
+}
+
+#[test]
+fn split_for_parallel_queries() {
+    let tree = grid();
+    let views = tree.split_for_parallel(3);
+
+    let center = Point::new(30., 34.);
+    let mut expected = Vec::new();
+    tree.find_range(center, r32(100.), |identity, _, _| expected.push(*identity));
+    expected.sort_unstable();
+    let mut expected_rect = Vec::new();
+    tree.find_rect(
+        Point::new(10., 10.),
+        Point::new(200., 120.),
+        |identity, _, _| {
+            expected_rect.push(*identity);
+        },
+    );
+    expected_rect.sort_unstable();
+
+    // the views can be queried from different threads without locking
+    let (mut found, mut found_rect) = std::thread::scope(|scope| {
+        let workers = views
+            .iter()
+            .map(|view| {
+                scope.spawn(move || {
+                    let mut found = Vec::new();
+                    view.find_range(center, r32(100.), |identity, point, value| {
+                        assert_eq!(view.get(identity), Some((point, value)));
+                        found.push(*identity);
+                    });
+                    let mut found_rect = Vec::new();
+                    view.find_rect(
+                        Point::new(10., 10.),
+                        Point::new(200., 120.),
+                        |identity, _, _| {
+                            found_rect.push(*identity);
+                        },
+                    );
+                    (found, found_rect)
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .fold(
+                (Vec::new(), Vec::new()),
+                |(mut a, mut b), (found, found_rect)| {
+                    a.extend(found);
+                    b.extend(found_rect);
+                    (a, b)
+                },
+            )
+    });
+    found.sort_unstable();
+    found_rect.sort_unstable();
+    assert_eq!(found, expected);
+    assert_eq!(found.len(), 257);
+    assert_eq!(found_rect, expected_rect);
+    assert!(found_rect.contains(&1000));
+
+    let mut all = views
+        .iter()
+        .flat_map(|view| view.iter().map(|(identity, _, _)| *identity))
+        .collect::<Vec<_>>();
+    all.sort_unstable();
+    assert_eq!(all, (0..256).chain([1000]).collect::<Vec<_>>());
+
+    // an entry is only found in its own view
+    assert_eq!(views[0].get(&0), Some((Point::new(2., 2.), &0)));
+    assert_eq!(views[1].get(&0), None);
+    assert_eq!(views[0].get(&1000), None);
+    assert_eq!(views[2].get(&1000), Some((Point::new(100., 100.), &1000)));
+    assert_eq!(views[2].get(&2000), None);
+}
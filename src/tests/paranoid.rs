@@ -0,0 +1,98 @@
+#![cfg(feature = "paranoid")]
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{bucket::Bucket, tests::ip, Point, QuadTree};
+
+fn corrupted_tree() -> QuadTree<u32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(8., 8.));
+    tree.checked_insert(ip(1, 1., 1.), 10).unwrap();
+    tree.checked_insert(ip(2, 2., 2.), 20).unwrap();
+    // lose the entries, but keep their identities
+    let Bucket::Owned(entries) = &mut tree.items[0] else {
+        unreachable!()
+    };
+    entries.clear();
+    tree
+}
+
+#[test]
+fn checked_methods() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(8., 8.));
+    tree.checked_insert(ip(1, 1., 1.), 10).unwrap();
+    assert_eq!(
+        tree.checked_update(1, Point::new(20., 20.), |value| *value = 11),
+        Ok(true)
+    );
+    assert_eq!(
+        tree.checked_remove(&1),
+        Ok(Some((11, Point::new(20., 20.))))
+    );
+    assert_eq!(tree.checked_remove(&1), Ok(None));
+}
+
+#[test]
+fn corruption_is_reported() {
+    let mut tree = corrupted_tree();
+    let error = tree.checked_remove(&1).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Quad tree is corrupted: entry is missing from its bucket"
+    );
+    assert!(tree.checked_update(2, Point::new(5., 5.), |_| {}).is_err());
+}
+
+#[test]
+#[should_panic = "Quad tree is corrupted: entry is missing from its bucket"]
+fn corruption_panics_without_checks() {
+    corrupted_tree().remove(&1);
+}
+
+#[test]
+fn validate() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(8., 8.));
+    assert_eq!(tree.validate(), Ok(()));
+    for n in 0..8 {
+        tree.insert(ip(n, n as f32, 7. - n as f32), n);
+    }
+    tree.insert(ip(8, 20., 20.), 8);
+    assert!(tree.items.len() > 1);
+    assert_eq!(tree.validate(), Ok(()));
+    tree.update(3, Point::new(-1., 3.));
+    tree.update(8, Point::new(0.5, 0.5));
+    tree.remove(&0);
+    assert_eq!(tree.validate(), Ok(()));
+
+    let mut moved = tree.clone();
+    moved.identity_to_point.get_mut(&5).unwrap().0 = Point::new(1., 1.);
+    assert_eq!(
+        moved.validate().unwrap_err().to_string(),
+        "Quad tree is corrupted: entry has the wrong identity entry"
+    );
+    let mut outside = tree.clone();
+    let value = outside.outside_of_range[&3].0;
+    outside.outside_of_range.insert(3, (value, Point::new(1., 1.)));
+    assert_eq!(
+        outside.validate().unwrap_err().to_string(),
+        "Quad tree is corrupted: entry outside of range lies inside of the tree"
+    );
+    let mut missing = tree.clone();
+    missing.identity_to_point.insert(100, (Point::new(1., 1.), None, 0));
+    assert_eq!(
+        missing.validate().unwrap_err().to_string(),
+        "Quad tree is corrupted: identity has no entry"
+    );
+    let mut wrong_leaf = tree.clone();
+    for bucket in &mut wrong_leaf.items {
+        if let Bucket::Owned(entries) = bucket {
+            for (ip, _) in entries.iter_mut().filter(|(ip, _)| ip.identity == 5) {
+                ip.point = Point::new(7.5, 7.5);
+            }
+        }
+    }
+    wrong_leaf.identity_to_point.get_mut(&5).unwrap().0 = Point::new(7.5, 7.5);
+    assert_eq!(
+        wrong_leaf.validate().unwrap_err().to_string(),
+        "Quad tree is corrupted: entry lies outside of its leaf"
+    );
+    assert!(corrupted_tree().validate().is_err());
+}
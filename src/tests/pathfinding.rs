@@ -0,0 +1,146 @@
+#![cfg(feature = "pathfinding")]
+
+use crate::{tests::rect, Point, Rect, RegionQuadTree};
+
+fn distance(a: Point, b: Point) -> f32 {
+    a.distance_squared_to(b).raw().sqrt()
+}
+
+fn path_cost(path: &[Point]) -> f32 {
+    path.windows(2).map(|pair| distance(pair[0], pair[1])).sum()
+}
+
+/// Brute-force Dijkstra over every pair of empty leaves, to compare against the A* in `find_path`
+fn shortest_cost(tree: &RegionQuadTree, start: Point, goal: Point) -> Option<f32> {
+    let leaves: Vec<Rect> = tree
+        .leaves()
+        .into_iter()
+        .filter(|(_, filled)| !filled)
+        .map(|(rect, _)| rect)
+        .collect();
+    let leaf_of = |point: Point| {
+        let (_, rect, _) = tree.leaf_at(point)?;
+        leaves.iter().position(|leaf| *leaf == rect)
+    };
+    let (start, goal) = (leaf_of(start)?, leaf_of(goal)?);
+    let mut cost = vec![f32::INFINITY; leaves.len()];
+    let mut done = vec![false; leaves.len()];
+    cost[start] = 0.;
+    while let Some(current) = (0..leaves.len())
+        .filter(|i| !done[*i] && cost[*i].is_finite())
+        .min_by(|a, b| cost[*a].total_cmp(&cost[*b]))
+    {
+        done[current] = true;
+        for (other, leaf) in leaves.iter().enumerate() {
+            if leaves[current].shares_edge(*leaf) {
+                let new_cost = cost[current] + distance(leaves[current].middle(), leaf.middle());
+                if new_cost < cost[other] {
+                    cost[other] = new_cost;
+                }
+            }
+        }
+    }
+    cost[goal].is_finite().then_some(cost[goal])
+}
+
+#[test]
+fn path_around_a_wall() {
+    let mut tree = RegionQuadTree::new(Point::zero(), Point::new(8., 8.), 3);
+    // a wall in the middle, with a gap at the bottom. The wall splits the cells next to it, so the path hugs it
+    tree.fill_rect(rect(3., 0., 5., 7.));
+
+    let start = Point::new(0.5, 0.5);
+    let goal = Point::new(7.5, 0.5);
+    let path = tree.find_path(start, goal).unwrap();
+    assert_eq!(
+        path,
+        vec![
+            Point::new(1., 1.),
+            Point::new(2.5, 1.5),
+            Point::new(2.5, 2.5),
+            Point::new(2.5, 3.5),
+            Point::new(2.5, 4.5),
+            Point::new(2.5, 5.5),
+            Point::new(2.5, 6.5),
+            Point::new(2.5, 7.5),
+            Point::new(3.5, 7.5),
+            Point::new(4.5, 7.5),
+            Point::new(5.5, 7.5),
+            Point::new(5.5, 6.5),
+            Point::new(5.5, 5.5),
+            Point::new(5.5, 4.5),
+            Point::new(5.5, 3.5),
+            Point::new(7., 3.),
+            Point::new(7., 1.),
+        ]
+    );
+    let expected = shortest_cost(&tree, start, goal).unwrap();
+    assert!((path_cost(&path) - expected).abs() < 1e-4);
+
+    // the start and goal in the same leaf
+    assert_eq!(
+        tree.find_path(Point::new(0.5, 0.5), Point::new(1.5, 1.5)),
+        Some(vec![Point::new(1., 1.)])
+    );
+}
+
+#[test]
+fn matches_dijkstra() {
+    let mut tree = RegionQuadTree::new(Point::zero(), Point::new(16., 16.), 4);
+    for wall in [
+        rect(2., 0., 3., 12.),
+        rect(5., 4., 6., 16.),
+        rect(8., 0., 9., 10.),
+        rect(9., 12., 14., 13.),
+        rect(11., 3., 16., 4.),
+        rect(12., 6., 13., 11.),
+    ] {
+        tree.fill_rect(wall);
+    }
+
+    let points = [
+        Point::new(0.5, 0.5),
+        Point::new(15.5, 15.5),
+        Point::new(15.5, 0.5),
+        Point::new(7., 1.),
+        Point::new(10.5, 8.5),
+        Point::new(0.5, 15.5),
+    ];
+    for start in points {
+        for goal in points {
+            let path = tree.find_path(start, goal);
+            match shortest_cost(&tree, start, goal) {
+                Some(expected) => {
+                    let path = path.unwrap();
+                    assert!(
+                        (path_cost(&path) - expected).abs() < 1e-3,
+                        "Path from {start:?} to {goal:?} costs {}, expected {expected}",
+                        path_cost(&path)
+                    );
+                }
+                None => assert_eq!(path, None),
+            }
+        }
+    }
+}
+
+#[test]
+fn no_path() {
+    let mut tree = RegionQuadTree::new(Point::zero(), Point::new(8., 8.), 3);
+    tree.fill_rect(rect(3., 0., 5., 8.));
+
+    assert_eq!(
+        tree.find_path(Point::new(0.5, 0.5), Point::new(7.5, 0.5)),
+        None
+    );
+    // start in a filled area
+    assert_eq!(
+        tree.find_path(Point::new(4., 4.), Point::new(0.5, 0.5)),
+        None
+    );
+    // goal outside of the tree
+    assert_eq!(
+        tree.find_path(Point::new(0.5, 0.5), Point::new(-1., -1.)),
+        None
+    );
+}
@@ -0,0 +1,74 @@
+use crate::{r32, BoundsError, Point, QuadTree, Rect};
+
+#[test]
+fn try_new() {
+    assert_eq!(Point::try_new(1., -2.), Ok(Point::new(1., -2.)));
+    assert!(matches!(
+        Point::try_new(f32::NAN, 0.),
+        Err(BoundsError::NotFinite { y, .. }) if y == 0.
+    ));
+    assert_eq!(
+        Point::try_new(0., f32::INFINITY),
+        Err(BoundsError::NotFinite {
+            x: 0.,
+            y: f32::INFINITY
+        })
+    );
+    assert_eq!(
+        Point::try_new(f32::NEG_INFINITY, 0.)
+            .unwrap_err()
+            .to_string(),
+        "Point (-inf, 0) is not finite"
+    );
+}
+
+#[test]
+fn new_clamped() {
+    assert_eq!(Point::new_clamped(1., -2.), Point::new(1., -2.));
+    assert_eq!(Point::new_clamped(f32::NAN, 3.), Point::new(0., 3.));
+    let clamped = Point::new_clamped(f32::INFINITY, f32::NEG_INFINITY);
+    assert_eq!(clamped.x, r32(f32::MAX));
+    assert_eq!(clamped.y, r32(f32::MIN));
+}
+
+#[test]
+fn tree_try_new() {
+    let tree = QuadTree::<u32, u32, 4>::try_new(Point::zero(), Point::new(8., 4.)).unwrap();
+    assert_eq!(tree.rect, Rect::new(Point::zero(), Point::new(8., 4.)));
+    for (top_left, bottom_right) in [
+        (Point::new(8., 0.), Point::new(0., 4.)),
+        (Point::new(0., 4.), Point::new(8., 0.)),
+        (Point::new(0., 0.), Point::new(8., 0.)),
+    ] {
+        assert_eq!(
+            QuadTree::<u32, u32, 4>::try_new(top_left, bottom_right),
+            Err(BoundsError::NotOrdered {
+                top_left,
+                bottom_right
+            })
+        );
+    }
+    assert_eq!(
+        QuadTree::<u32, u32, 4>::try_new(Point::new(8., 0.), Point::new(0., 4.))
+            .unwrap_err()
+            .to_string(),
+        "Top-left corner (8, 0) is not above and to the left of bottom-right corner (0, 4)"
+    );
+}
+
+#[test]
+fn from_corners() {
+    let rect = Rect::new(Point::new(-1., 2.), Point::new(3., 5.));
+    assert_eq!(
+        Rect::from_corners(Point::new(3., 2.), Point::new(-1., 5.)),
+        rect
+    );
+    assert_eq!(
+        Rect::from_corners(Point::new(3., 5.), Point::new(-1., 2.)),
+        rect
+    );
+    assert_eq!(
+        Rect::from_corners(rect.top_left(), rect.bottom_right()),
+        rect
+    );
+}
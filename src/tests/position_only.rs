@@ -0,0 +1,42 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{bucket::Bucket, IdentityPoint, Point, PointIndex, R32};
+use std::mem::size_of;
+
+#[test]
+fn unit_values_take_no_room() {
+    assert_eq!(
+        size_of::<(IdentityPoint<u32>, ())>(),
+        size_of::<IdentityPoint<u32>>()
+    );
+    assert_eq!(
+        size_of::<Bucket<(), u32, 4>>(),
+        size_of::<Bucket<[u8; 0], u32, 4>>()
+    );
+}
+
+#[test]
+fn insert_and_find_points() {
+    let mut tree = PointIndex::<u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..64 {
+        tree.insert_point(
+            n,
+            Point::new((n % 8) as f32 * 8. + 1., (n / 8) as f32 * 8. + 1.),
+        );
+    }
+    tree.insert_point(100, Point::new(-1., 1.));
+    let mut found = Vec::new();
+    tree.find_points(Point::new(1., 1.), R32::new(8.), |identity, point| {
+        found.push((*identity, point));
+    });
+    found.sort_unstable();
+    assert_eq!(
+        found,
+        vec![
+            (0, Point::new(1., 1.)),
+            (1, Point::new(9., 1.)),
+            (8, Point::new(1., 9.)),
+            (100, Point::new(-1., 1.)),
+        ]
+    );
+}
@@ -0,0 +1,23 @@
+use crate::prelude::*;
+
+#[test]
+fn prelude() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(8., 8.));
+    tree.insert(
+        IdentityPoint {
+            identity: 1,
+            point: Point::new(2., 2.),
+        },
+        1,
+    );
+    let mut found = Vec::new();
+    tree.find_range_with_options(
+        Point::new(1., 2.),
+        r32(1.),
+        RangeOptions::new().boundary(RangeBoundary::Exclusive),
+        |identity, _, _| found.push(*identity),
+    );
+    assert!(found.is_empty());
+    assert!(Rect::new(Point::zero(), Point::new(8., 8.)).contains(Point::new(2., 2.)));
+    assert_eq!(R32::new(1.), r32(1.));
+}
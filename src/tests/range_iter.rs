@@ -0,0 +1,63 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, R32};
+
+/// Returns the entries near `center` from a function, which needs the iterator instead of a callback
+fn near(tree: &QuadTree<u32, u32, 4>, center: Point) -> impl Iterator<Item = &u32> {
+    tree.iter_range(center, R32::new(10.))
+        .map(|(_, _, value)| value)
+}
+
+#[test]
+fn same_as_find_range() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..200 {
+        tree.insert(ip(n, (n % 17) as f32 * 3.7, (n / 17) as f32 * 5.3), n);
+    }
+    for n in 0..30 {
+        tree.insert(ip(1000 + n, 0.01 * n as f32, 0.01), 1000 + n);
+    }
+    tree.insert(ip(5000, -3., 1.), 5000);
+
+    for (center, range) in [
+        (Point::new(0., 0.), 5.),
+        (Point::new(30., 30.), 12.),
+        (Point::new(32., 32.), 1000.),
+        (Point::new(-100., -100.), 1.),
+    ] {
+        let mut expected = Vec::new();
+        tree.find_range(center, R32::new(range), |identity, point, value| {
+            expected.push((*identity, point, *value));
+        });
+        let found = tree
+            .iter_range(center, R32::new(range))
+            .map(|(identity, point, value)| (*identity, point, *value))
+            .collect::<Vec<_>>();
+        assert_eq!(found, expected);
+    }
+    assert_eq!(near(&tree, Point::new(-3., 1.)).max(), Some(&5000));
+}
+
+#[test]
+fn stops_early() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..64 {
+        tree.insert(ip(n, n as f32, n as f32), n);
+    }
+    let mut visited = Vec::new();
+    let found = tree
+        .iter_range(Point::zero(), R32::new(1000.))
+        .inspect(|(identity, _, _)| visited.push(**identity))
+        .find(|(_, point, _)| point.x > 2.);
+    assert_eq!(found.map(|(identity, _, _)| *identity), Some(3));
+    assert_eq!(visited, vec![0, 1, 2, 3]);
+
+    let first_odd = || -> Result<u32, u32> {
+        for (identity, _, value) in tree.iter_range(Point::new(10., 10.), R32::new(3.)) {
+            let value = (*value % 2 == 0).then_some(*value).ok_or(*identity)?;
+            assert!(value < 10);
+        }
+        Ok(0)
+    };
+    assert_eq!(first_odd(), Err(9));
+}
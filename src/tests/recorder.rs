@@ -0,0 +1,87 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, MaintenanceBudget, Operation, Point, QuadTree, Recorder, YAxis};
+
+#[test]
+fn replay_builds_the_same_tree() {
+    let mut tree =
+        QuadTree::<u32, u32, 2>::with_y_axis(Point::new(0., 64.), Point::new(64., 0.), YAxis::Up);
+    let mut recorder = Recorder::attach(&mut tree);
+    recorder.set_deferred_merges(true);
+    for n in 0..40 {
+        recorder.insert(ip(n, (n % 8) as f32 * 8. + 1., (n / 8) as f32 * 8. + 1.), n);
+    }
+    assert!(recorder.update(3, Point::new(70., 3.)));
+    assert!(recorder.update_point_and_value(4, Point::new(5., 5.), |value| *value *= 10));
+    assert!(!recorder.update(100, Point::new(5., 5.)));
+    for n in 10..30 {
+        assert_eq!(recorder.try_remove(&n).map(|(value, _)| value), Some(n));
+    }
+    recorder.maintenance(MaintenanceBudget::nodes(2));
+    let script = recorder.detach();
+    assert_eq!(script.top_left, Point::new(0., 64.));
+    assert_eq!(script.y_axis, YAxis::Up);
+    assert_eq!(
+        script.operations[42],
+        Operation::UpdateValue {
+            identity: 4,
+            point: Point::new(5., 5.),
+            value: 40
+        }
+    );
+    assert_eq!(script.replay::<2>(), tree);
+
+    // a tree that already has entries starts its script with them
+    let script = Recorder::attach(&mut tree).detach();
+    assert_eq!(script.operations.len(), 1 + 20);
+    assert_eq!(script.operations[0], Operation::SetDeferredMerges(true));
+    let replayed = script.replay::<2>();
+    for n in 0..40 {
+        assert_eq!(replayed.get(&n), tree.get(&n));
+    }
+}
+
+#[test]
+fn shrink() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(64., 64.));
+    let mut recorder = Recorder::attach(&mut tree);
+    for n in 0..50 {
+        recorder.insert(ip(n % 10, n as f32, 1.), n);
+        if n % 7 == 0 {
+            recorder.try_remove(&(n % 5));
+        }
+    }
+    recorder.update(6, Point::new(100., 1.));
+    recorder.update(8, Point::new(1., 1.));
+    let script = recorder.detach();
+
+    // the "bug": entry 6 is outside of the tree while entry 8 is at (1, 1)
+    let fails = |tree: &QuadTree<u32, u32, 4>| {
+        tree.iter_out_of_range()
+            .any(|(identity, _, _)| *identity == 6)
+            && tree
+                .get(&8)
+                .is_some_and(|(point, _)| point == Point::new(1., 1.))
+    };
+    assert!(fails(&script.replay()));
+    let mut runs = 0;
+    let shrunk = script.shrink(|script| {
+        runs += 1;
+        fails(&script.replay())
+    });
+    assert!(runs > 1);
+    // both entries have to be inserted and moved
+    assert_eq!(shrunk.operations.len(), 4);
+    assert!(fails(&shrunk.replay()));
+    assert_eq!(
+        shrunk.operations[2..],
+        script.operations[script.operations.len() - 2..]
+    );
+    assert!(shrunk.operations[..2].iter().all(|operation| matches!(
+        operation,
+        Operation::Insert {
+            identity: 6 | 8,
+            ..
+        }
+    )));
+}
@@ -0,0 +1,148 @@
+#![cfg(feature = "region")]
+
+use crate::{tests::rect, Point, RegionQuadTree};
+
+fn tree() -> RegionQuadTree {
+    RegionQuadTree::new(Point::zero(), Point::new(8., 8.), 3)
+}
+
+#[test]
+fn fill_and_clear() {
+    let mut tree = tree();
+    assert!(!tree.is_occupied(Point::new(1., 1.)));
+    assert_eq!(tree.leaves(), vec![(rect(0., 0., 8., 8.), false)]);
+
+    tree.fill_rect(rect(0., 0., 4., 4.));
+    assert!(tree.is_occupied(Point::new(1., 1.)));
+    assert!(!tree.is_occupied(Point::new(5., 5.)));
+    assert!(!tree.is_occupied(Point::new(9., 1.)));
+    assert_eq!(
+        tree.leaves(),
+        vec![
+            (rect(0., 0., 4., 4.), true),
+            (rect(4., 0., 8., 4.), false),
+            (rect(0., 4., 4., 8.), false),
+            (rect(4., 4., 8., 8.), false),
+        ]
+    );
+
+    // filling the rest of the tree merges all nodes back into the root
+    tree.fill_rect(rect(-2., -2., 10., 10.));
+    assert_eq!(tree.leaves(), vec![(rect(0., 0., 8., 8.), true)]);
+    assert_eq!(tree.nodes.len(), 1);
+
+    tree.clear_rect(rect(1., 1., 2., 2.));
+    assert!(!tree.is_occupied(Point::new(1.5, 1.5)));
+    assert!(tree.is_occupied(Point::new(2.5, 2.5)));
+    assert_eq!(tree.leaves().len(), 10);
+
+    tree.fill_rect(rect(1., 1., 2., 2.));
+    assert_eq!(tree.leaves(), vec![(rect(0., 0., 8., 8.), true)]);
+    assert_eq!(tree.nodes.len(), 1);
+}
+
+#[test]
+fn partial_cells_use_the_center() {
+    let mut tree = tree();
+    // the smallest cells are 1x1, this rect covers the center of the cell at 0..1 but not the one at 1..2
+    tree.fill_rect(rect(0., 0., 1.4, 1.));
+    assert!(tree.is_occupied(Point::new(0.9, 0.5)));
+    assert!(!tree.is_occupied(Point::new(1.2, 0.5)));
+}
+
+#[test]
+fn touching_rects_do_not_share_cells() {
+    // both rects touch the center of the cell at 1..2, but only the rect that contains it on its left edge claims it
+    let mut tree = tree();
+    tree.fill_rect(rect(0., 0., 1.5, 1.));
+    assert!(!tree.is_occupied(Point::new(1.5, 0.5)));
+    tree.fill_rect(rect(1.5, 0., 3., 1.));
+    assert!(tree.is_occupied(Point::new(1.5, 0.5)));
+
+    let mut reversed = tree.clone();
+    reversed.clear_rect(rect(0., 0., 1.5, 1.));
+    assert!(reversed.is_occupied(Point::new(1.5, 0.5)));
+    tree.clear_rect(rect(1.5, 0., 3., 1.));
+    assert!(!tree.is_occupied(Point::new(1.5, 0.5)));
+}
+
+#[test]
+fn boolean_operations() {
+    let mut left = tree();
+    left.fill_rect(rect(0., 0., 4., 8.));
+    let mut top = tree();
+    top.fill_rect(rect(0., 0., 8., 4.));
+
+    let union = left.union(&top);
+    assert_eq!(
+        union.leaves(),
+        vec![
+            (rect(0., 0., 4., 4.), true),
+            (rect(4., 0., 8., 4.), true),
+            (rect(0., 4., 4., 8.), true),
+            (rect(4., 4., 8., 8.), false),
+        ]
+    );
+
+    let intersection = left.intersect(&top);
+    assert!(intersection.is_occupied(Point::new(1., 1.)));
+    assert!(!intersection.is_occupied(Point::new(5., 1.)));
+    assert!(!intersection.is_occupied(Point::new(1., 5.)));
+
+    let difference = left.subtract(&top);
+    assert!(!difference.is_occupied(Point::new(1., 1.)));
+    assert!(difference.is_occupied(Point::new(1., 5.)));
+
+    // subtracting a tree from itself results in a single empty node
+    let empty = union.subtract(&union);
+    assert_eq!(empty.leaves(), vec![(rect(0., 0., 8., 8.), false)]);
+    assert_eq!(empty.nodes.len(), 1);
+}
+
+#[test]
+#[should_panic = "Region trees must cover the same area"]
+fn combine_different_areas() {
+    let other = RegionQuadTree::new(Point::zero(), Point::new(4., 4.), 3);
+    let _ = tree().union(&other);
+}
+
+#[test]
+fn bitmap_round_trip() {
+    #[rustfmt::skip]
+    let pixels = [
+        0, 0, 9, 9, 9,
+        0, 0, 9, 9, 9,
+        0, 0, 0, 0, 5,
+    ];
+    let tree = RegionQuadTree::from_bitmap(5, 3, &pixels, 5);
+    assert_eq!(tree.rect(), rect(0., 0., 8., 8.));
+    assert_eq!(tree.max_depth(), 3);
+    assert!(tree.is_occupied(Point::new(2.5, 0.5)));
+    assert!(!tree.is_occupied(Point::new(0.5, 0.5)));
+    assert!(!tree.is_occupied(Point::new(6.5, 0.5)));
+    // the four pixels at 2..4, 0..2 are merged into a single leaf
+    assert!(tree.leaves().contains(&(rect(2., 0., 4., 2.), true)));
+
+    assert_eq!(
+        tree.to_bitmap(5, 3, 1, 0),
+        [
+            0, 0, 1, 1, 1, //
+            0, 0, 1, 1, 1, //
+            0, 0, 0, 0, 1, //
+        ]
+    );
+}
+
+#[test]
+fn empty_bitmap() {
+    let tree = RegionQuadTree::from_bitmap(4, 4, &[0; 16], 1);
+    assert_eq!(tree.leaves(), vec![(rect(0., 0., 4., 4.), false)]);
+    assert_eq!(tree.nodes.len(), 1);
+    assert_eq!(tree.to_bitmap(2, 2, 1, 0), [0; 4]);
+}
+
+#[test]
+#[should_panic = "overflows"]
+fn bitmap_size_overflow() {
+    let _ = RegionQuadTree::from_bitmap(usize::MAX, 2, &[], 1);
+}
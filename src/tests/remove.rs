@@ -4,7 +4,7 @@ use crate::{
     bucket::Bucket,
     index::Index,
     tests::{ip, ipv},
-    Point, QuadTree,
+    IdentityPoint, Point, QuadTree, R32,
 };
 use smallvec::smallvec;
 
@@ -74,12 +74,121 @@ fn out_of_range() {
     assert_eq!(
         tree.identity_to_point,
         [
-            (0, (Point::new(8., 8.), Some(Index::ROOT))),
-            (1, (Point::new(9., 9.), Some(Index::ROOT))),
-            (2, (Point::new(10., 10.), Some(Index::ROOT))),
-            (4, (Point::new(12., 12.), None))
+            (0, (Point::new(8., 8.), Some(Index::ROOT), 1)),
+            (1, (Point::new(9., 9.), Some(Index::ROOT), 2)),
+            (2, (Point::new(10., 10.), Some(Index::ROOT), 3)),
+            (4, (Point::new(12., 12.), None, 5))
         ]
         .into_iter()
         .collect()
     );
 }
+
+fn grid() -> QuadTree<u32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(16., 16.));
+    for n in 0..64 {
+        tree.insert(ip(n, (n % 8) as f32 * 2., (n / 8) as f32 * 2.), n);
+    }
+    tree.insert(ip(100, 20., 1.), 100);
+    tree.insert(ip(101, -30., -30.), 101);
+    tree
+}
+
+fn identities(tree: &QuadTree<u32, u32, 4>) -> Vec<u32> {
+    let mut identities = tree
+        .iter()
+        .map(|(identity, _, _)| *identity)
+        .collect::<Vec<_>>();
+    identities.sort_unstable();
+    identities
+}
+
+#[test]
+fn retain() {
+    let mut tree = grid();
+    let mut visited = 0;
+    tree.retain(|identity, point, value| {
+        assert_eq!(identity, value);
+        visited += 1;
+        point.x < 7. && *identity != 101
+    });
+    assert_eq!(visited, 66);
+    let kept = (0..64).filter(|n| n % 8 < 4).collect::<Vec<_>>();
+    assert_eq!(identities(&tree), kept);
+    assert_eq!(tree.len(), kept.len());
+    assert!(!tree.contains_identity(&101));
+    assert_eq!(tree.iter_out_of_range().count(), 0);
+
+    // the subtrees that lost their entries are merged all the way up
+    tree.retain(|identity, _, _| *identity < 2 || *identity == 8);
+    assert_eq!(
+        tree.items,
+        vec![Bucket::Owned(smallvec![
+            ipv(0, 0., 0., 0),
+            ipv(1, 2., 0., 1),
+            ipv(8, 0., 2., 8)
+        ])]
+    );
+    tree.retain(|_, _, _| false);
+    assert!(tree.is_empty());
+    assert_eq!(tree.items, vec![Bucket::Owned(smallvec![])]);
+}
+
+#[test]
+fn retain_deferred() {
+    let mut tree = grid();
+    tree.set_deferred_merges(true);
+    tree.retain(|identity, _, _| *identity == 0);
+    assert_eq!(identities(&tree), vec![0]);
+    assert!(tree.pending_maintenance() > 0);
+    tree.set_deferred_merges(false);
+    assert_eq!(
+        tree.items,
+        vec![Bucket::Owned(smallvec![ipv(0, 0., 0., 0)])]
+    );
+}
+
+#[test]
+fn drain_range() {
+    let mut tree = grid();
+    let center = Point::new(15., 2.);
+    let mut expected = Vec::new();
+    tree.find_range(center, R32::new(6.), |identity, point, value| {
+        expected.push((*identity, point, *value));
+    });
+    expected.sort_unstable_by_key(|(identity, _, _)| *identity);
+    assert!(expected.iter().any(|(identity, _, _)| *identity == 100));
+
+    let mut drained = tree.drain_range(center, R32::new(6.));
+    drained.sort_unstable_by_key(|(identity, _, _)| *identity);
+    assert_eq!(drained, expected);
+    let rest = (0..64)
+        .chain([101])
+        .filter(|n| expected.iter().all(|(identity, _, _)| identity != n))
+        .collect::<Vec<_>>();
+    assert_eq!(identities(&tree), rest);
+    assert_eq!(tree.len(), rest.len());
+
+    // nothing is left in range
+    assert_eq!(tree.drain_range(center, R32::new(6.)), vec![]);
+    tree.find_range(center, R32::new(6.), |_, _, _| unreachable!());
+}
+
+/// An identity without a `Display` implementation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Uuid(u128);
+
+#[test]
+#[should_panic = "Identity Uuid(7) not found"]
+fn remove_missing_debug_identity() {
+    let mut tree = QuadTree::<u32, Uuid, 4>::sized_around_origin(Point::new(10., 10.));
+    tree.insert(
+        IdentityPoint {
+            identity: Uuid(3),
+            point: Point::new(1., 1.),
+        },
+        3,
+    );
+    assert_eq!(tree.remove(&Uuid(3)), (3, Point::new(1., 1.)));
+    tree.remove(&Uuid(7));
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "replication")]
+#![allow(clippy::cast_precision_loss, clippy::trivially_copy_pass_by_ref)] // the callbacks must match the signature of `encode_delta`
+
+use crate::{tests::ip, EntryChange, Point, QuadTree};
+use std::io::{self, Read, Write};
+
+type Tree = QuadTree<u32, u32, 1>;
+
+fn new_tree() -> Tree {
+    QuadTree::new(Point::zero(), Point::new(16., 16.))
+}
+
+fn encode(writer: &mut Vec<u8>, n: &u32) -> io::Result<()> {
+    writer.write_all(&n.to_le_bytes())
+}
+
+fn decode(reader: &mut &[u8]) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn replicate(old: &Tree, new: &Tree, client: &mut Tree) -> usize {
+    let mut delta = Vec::new();
+    new.encode_delta(&old.diff(new), &mut delta, encode, encode)
+        .unwrap();
+    client.apply_delta(&mut &delta[..], decode, decode).unwrap();
+    delta.len()
+}
+
+#[test]
+fn diff() {
+    let mut old = new_tree();
+    old.insert(ip(1, 1., 1.), 10);
+    old.insert(ip(2, 2., 2.), 20);
+    old.insert(ip(3, 3., 3.), 30);
+    let mut new = old.clone();
+    new.update(1, Point::new(5., 5.));
+    new.update_point_and_value(2, Point::new(2., 2.), |value| *value = 21);
+    new.remove(&3);
+    new.insert(ip(4, 20., 20.), 40);
+
+    assert_eq!(
+        old.diff(&new),
+        vec![
+            EntryChange::Move {
+                identity: &1,
+                point: Point::new(5., 5.)
+            },
+            EntryChange::Insert {
+                identity: &2,
+                point: Point::new(2., 2.),
+                value: &21
+            },
+            EntryChange::Insert {
+                identity: &4,
+                point: Point::new(20., 20.),
+                value: &40
+            },
+            EntryChange::Remove { identity: &3 },
+        ]
+    );
+    assert_eq!(new.diff(&new), vec![]);
+}
+
+#[test]
+fn replicate_changes() {
+    let mut server = new_tree();
+    let mut client = new_tree();
+    for n in 0..20 {
+        server.insert(
+            ip(n, (n * 7 % 16) as f32 + 0.3, (n * 3 % 16) as f32 + 0.6),
+            n,
+        );
+    }
+    server.insert(ip(20, -3.25, 40.5), 20);
+    replicate(&new_tree(), &server, &mut client);
+
+    let old = server.clone();
+    server.update(0, Point::new(15.9, 0.1));
+    server.update(20, Point::new(-4.75, 41.5));
+    server.update_point_and_value(5, Point::new(1., 1.), |value| *value = 500);
+    server.remove(&6);
+    replicate(&old, &server, &mut client);
+
+    assert!(client.try_remove(&6).is_none());
+    for n in (0..21).filter(|n| *n != 6) {
+        let (value, point) = server.remove(&n);
+        let (client_value, client_point) = client.remove(&n);
+        assert_eq!(client_value, value);
+        if n == 20 {
+            // outside of the tree, so this is exact
+            assert_eq!(client_point, point);
+        } else {
+            // the root is 16 wide, so no leaf is larger than that
+            let max_error = 16. / 65535.;
+            assert!((client_point.x.raw() - point.x.raw()).abs() <= max_error);
+            assert!((client_point.y.raw() - point.y.raw()).abs() <= max_error);
+        }
+    }
+}
+
+#[test]
+fn compact_move() {
+    let mut old = new_tree();
+    old.insert(ip(1, 1., 1.), 10);
+    let mut new = old.clone();
+    new.update(1, Point::new(3., 3.));
+
+    let mut client = old.clone();
+    // count, tag, identity, a single byte leaf index and 2 offsets
+    assert_eq!(replicate(&old, &new, &mut client), 1 + 1 + 4 + 1 + 2 + 2);
+    let (_, point) = client.remove(&1);
+    assert!((point.x.raw() - 3.).abs() <= 16. / 65535.);
+}
+
+#[test]
+fn malformed_delta() {
+    let mut client = new_tree();
+    client.insert(ip(1, 1., 1.), 10);
+    let expected = client.clone();
+    // one change with an unknown tag
+    let error = client
+        .apply_delta(&mut &[1, 9][..], decode, decode)
+        .unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    // a move to an invalid leaf index
+    let error = client
+        .apply_delta(&mut &[1, 2, 1, 0, 0, 0, 2, 0, 0, 0, 0][..], decode, decode)
+        .unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(client, expected);
+}
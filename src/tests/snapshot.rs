@@ -0,0 +1,169 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{snapshot::SnapshotNode, tests::ip, Point, QuadTree, SnapshotView, TreeSnapshot, R32};
+use std::sync::Arc;
+
+fn tree() -> QuadTree<String, u32, 2> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..50 {
+        tree.insert(
+            ip(n, (n * 7 % 64) as f32, (n * 13 % 64) as f32),
+            n.to_string(),
+        );
+    }
+    tree
+}
+
+#[test]
+fn find_range_owned() {
+    let tree = tree();
+    let center = Point::new(20., 20.);
+    let range = R32::new(15.);
+    let mut borrowed = Vec::new();
+    tree.find_range(center, range, |identity, point, value| {
+        borrowed.push((*identity, point, value.clone()));
+    });
+    assert!(!borrowed.is_empty());
+    assert_eq!(tree.find_range_owned(center, range), borrowed);
+}
+
+#[test]
+fn snapshot_outlives_changes() {
+    let mut tree = tree();
+    let snapshot = tree.snapshot_range(Point::new(32., 32.), R32::new(20.));
+    let identities = snapshot
+        .iter()
+        .map(|(identity, _, _)| *identity)
+        .collect::<Vec<_>>();
+    assert!(identities.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(snapshot.len(), identities.len());
+
+    for identity in &identities {
+        tree.remove(identity);
+    }
+    let first = identities[0];
+    let (point, value) = snapshot.get(&first).expect("the snapshot still has it");
+    assert_eq!(*value, first.to_string());
+    assert!(point.distance_squared_to(snapshot.center()) <= snapshot.range() * snapshot.range());
+    assert_eq!(snapshot.get(&1000), None);
+
+    let snapshot = std::thread::spawn(move || snapshot)
+        .join()
+        .expect("the thread does not panic");
+    let owned = snapshot.into_iter().collect::<Vec<_>>();
+    assert_eq!(owned.len(), identities.len());
+
+    let empty: SnapshotView<String, u32> = tree.snapshot_range(Point::new(32., 32.), R32::new(0.));
+    assert!(empty.is_empty());
+}
+
+fn found(
+    snapshot: &TreeSnapshot<String, u32>,
+    center: Point,
+    range: f32,
+) -> Vec<(u32, Point, String)> {
+    let mut found = Vec::new();
+    snapshot.find_range(center, R32::new(range), |identity, point, value| {
+        found.push((*identity, point, value.clone()));
+    });
+    found
+}
+
+#[test]
+fn tree_snapshot() {
+    let mut tree = tree();
+    tree.insert(ip(100, -3., 10.), "outside".to_owned());
+    let snapshot = tree.snapshot();
+    assert_eq!(snapshot.len(), 51);
+    assert_eq!(snapshot.generation(), tree.generation());
+    for (center, range) in [(Point::new(20., 20.), 15.), (Point::new(0., 10.), 5.)] {
+        assert_eq!(
+            found(&snapshot, center, range),
+            tree.find_range_owned(center, R32::new(range))
+        );
+    }
+    let mut in_rect = Vec::new();
+    snapshot.find_rect(
+        Point::new(-5., 0.),
+        Point::new(10., 20.),
+        |identity, _, _| {
+            in_rect.push(*identity);
+        },
+    );
+    let mut expected = Vec::new();
+    tree.find_rect(
+        Point::new(-5., 0.),
+        Point::new(10., 20.),
+        |identity, _, _| {
+            expected.push(*identity);
+        },
+    );
+    assert_eq!(in_rect, expected);
+
+    // the snapshot does not see later changes, also not from another thread
+    let before = found(&snapshot, Point::new(32., 32.), 100.);
+    for n in 0..25 {
+        tree.remove(&n);
+    }
+    tree.insert(ip(200, 1., 1.), "new".to_owned());
+    std::thread::scope(|scope| {
+        scope.spawn(|| assert_eq!(found(&snapshot, Point::new(32., 32.), 100.), before));
+    });
+    let next = tree.snapshot_since(&snapshot);
+    assert_eq!(next.len(), 27);
+    assert_eq!(
+        found(&next, Point::new(32., 32.), 100.),
+        tree.find_range_owned(Point::new(32., 32.), R32::new(100.))
+    );
+}
+
+#[test]
+fn snapshot_since_shares_unchanged_nodes() {
+    let mut tree = QuadTree::<String, u32, 1>::new(Point::zero(), Point::new(64., 64.));
+    tree.insert(ip(1, 1., 1.), "1".to_owned());
+    tree.insert(ip(2, 60., 1.), "2".to_owned());
+    tree.insert(ip(3, 1., 60.), "3".to_owned());
+    tree.insert(ip(4, 60., 60.), "4".to_owned());
+    tree.insert(ip(5, -1., 1.), "5".to_owned());
+    let first = tree.snapshot();
+    let children = |snapshot: &TreeSnapshot<String, u32>| match &*snapshot.root {
+        SnapshotNode::Nested(children) => children.clone(),
+        SnapshotNode::Leaf(_) => panic!("the root is nested"),
+    };
+
+    // nothing changed, so everything is shared
+    let second = tree.snapshot_since(&first);
+    assert!(Arc::ptr_eq(&first.root, &second.root));
+    assert!(Arc::ptr_eq(
+        &first.outside_of_range,
+        &second.outside_of_range
+    ));
+
+    // only the top-left leaf and its parent are new
+    tree.update_point_and_value(1, Point::new(2., 2.), |value| *value = "one".to_owned());
+    let third = tree.snapshot_since(&second);
+    assert!(!Arc::ptr_eq(&second.root, &third.root));
+    assert!(Arc::ptr_eq(
+        &second.outside_of_range,
+        &third.outside_of_range
+    ));
+    let (old, new) = (children(&second), children(&third));
+    assert!(!Arc::ptr_eq(&old[0], &new[0]));
+    for quadrant in 1..4 {
+        assert!(Arc::ptr_eq(&old[quadrant], &new[quadrant]));
+    }
+    assert_eq!(found(&second, Point::new(2., 2.), 1.), vec![]);
+    assert_eq!(
+        found(&third, Point::new(2., 2.), 1.),
+        vec![(1, Point::new(2., 2.), "one".to_owned())]
+    );
+
+    // a split rewrites the leaf, and its new children are not taken from the previous snapshot
+    tree.insert(ip(6, 40., 40.), "6".to_owned());
+    let fourth = tree.snapshot_since(&third);
+    assert!(matches!(&*children(&fourth)[3], SnapshotNode::Nested(_)));
+    assert_eq!(
+        found(&fourth, Point::new(61., 61.), 3.),
+        tree.find_range_owned(Point::new(61., 61.), R32::new(3.))
+    );
+}
@@ -0,0 +1,35 @@
+use crate::{Point, TypedPoint, TypedQuadTree, R32};
+
+enum World {}
+enum Screen {}
+
+#[test]
+fn typed_tree() {
+    let mut tree = TypedQuadTree::<u32, u32, World, 4>::new(
+        TypedPoint::new(0., 0.),
+        TypedPoint::new(100., 100.),
+    );
+    tree.insert(1, TypedPoint::new(10., 10.), 100);
+    tree.insert(2, TypedPoint::new(50., 50.), 200);
+    assert!(tree.update(2, TypedPoint::new(12., 12.)));
+
+    // the camera is zoomed in 2 times, so a screen point is at half the distance in the world
+    let cursor = TypedPoint::<Screen>::new(22., 22.);
+    let cursor: TypedPoint<World> =
+        cursor.convert(|point| Point::new(point.x.raw() / 2., point.y.raw() / 2.));
+    let mut found = Vec::new();
+    tree.find_range(cursor, R32::new(2.), |identity, point, value| {
+        found.push((*identity, point, *value));
+    });
+    found.sort_unstable_by_key(|(identity, _, _)| *identity);
+    assert_eq!(
+        found,
+        vec![
+            (1, TypedPoint::new(10., 10.), 100),
+            (2, TypedPoint::new(12., 12.), 200)
+        ]
+    );
+
+    assert_eq!(tree.try_remove(&1), Some((100, TypedPoint::new(10., 10.))));
+    assert_eq!(tree.into_untyped().remove(&2), (200, Point::new(12., 12.)));
+}
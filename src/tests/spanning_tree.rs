@@ -0,0 +1,85 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, R32};
+use std::collections::BTreeMap;
+
+/// The lengths of a minimum spanning tree with Prim's algorithm, which are the same for every minimum spanning tree
+fn brute_force(points: &[Point]) -> Vec<R32> {
+    let mut connected = vec![false; points.len()];
+    // the shortest squared distance from every point to a connected point
+    let mut distances: Vec<Option<R32>> = vec![None; points.len()];
+    let mut lengths = Vec::new();
+    let mut next = 0;
+    for _ in 0..points.len() {
+        connected[next] = true;
+        if let Some(distance) = distances[next] {
+            lengths.push(R32::new(distance.raw().sqrt()));
+        }
+        for (n, point) in points.iter().enumerate() {
+            let distance = points[next].distance_squared_to(*point);
+            distances[n] = Some(distances[n].map_or(distance, |d| d.min(distance)));
+        }
+        if let Some(n) = (0..points.len())
+            .filter(|n| !connected[*n])
+            .min_by_key(|n| distances[*n])
+        {
+            next = n;
+        }
+    }
+    lengths.sort_unstable();
+    lengths
+}
+
+#[test]
+fn matches_brute_force() {
+    let mut tree = QuadTree::<(), u32, 3>::new(Point::zero(), Point::new(64., 64.));
+    let mut points = BTreeMap::new();
+    for n in 0..120 {
+        // a grid with many edges of the same length, and a few clusters
+        let point = if n < 64 {
+            Point::new((n % 8) as f32 * 4., (n / 8) as f32 * 4.)
+        } else {
+            Point::new((n * 37 % 64) as f32 + 0.25, (n * 23 % 61) as f32 + 0.5)
+        };
+        points.insert(n, point);
+        tree.insert(ip(n, point.x.raw(), point.y.raw()), ());
+    }
+    for (n, (x, y)) in [(120, (-10., 5.)), (121, (80., 90.)), (122, (0., 0.))] {
+        points.insert(n, Point::new(x, y));
+        tree.insert(ip(n, x, y), ());
+    }
+
+    let edges = tree.euclidean_mst();
+    assert_eq!(edges.len(), points.len() - 1);
+
+    // every edge connects two components, so the edges form a tree
+    let mut components: BTreeMap<u32, u32> = points.keys().map(|n| (*n, *n)).collect();
+    for (a, b, length) in &edges {
+        let distance = points[a].distance_squared_to(points[b]).raw().sqrt();
+        assert_eq!(*length, R32::new(distance));
+        let (from, to) = (components[a], components[b]);
+        assert_ne!(from, to);
+        for component in components.values_mut() {
+            if *component == from {
+                *component = to;
+            }
+        }
+    }
+
+    let mut lengths: Vec<_> = edges.iter().map(|(_, _, length)| *length).collect();
+    lengths.sort_unstable();
+    assert_eq!(
+        lengths,
+        brute_force(&points.values().copied().collect::<Vec<_>>())
+    );
+}
+
+#[test]
+fn small_trees() {
+    let mut tree = QuadTree::<(), u32, 3>::new(Point::zero(), Point::new(10., 10.));
+    assert!(tree.euclidean_mst().is_empty());
+    tree.insert(ip(1, 1., 1.), ());
+    assert!(tree.euclidean_mst().is_empty());
+    tree.insert(ip(2, 4., 5.), ());
+    assert_eq!(tree.euclidean_mst(), vec![(1, 2, R32::new(5.))]);
+}
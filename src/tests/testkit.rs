@@ -0,0 +1,73 @@
+#![cfg(feature = "testkit")]
+
+use crate::{quadtree_layout, testkit::LayoutBuilder, tests::ip, Point, QuadTree, Quadrant, R32};
+
+fn found(tree: &QuadTree<u32, u32, 4>) -> Vec<u32> {
+    let mut found = Vec::new();
+    tree.find_range(Point::zero(), R32::new(1000.), |identity, _, _| {
+        found.push(*identity);
+    });
+    found.sort_unstable();
+    found
+}
+
+#[test]
+fn exact_layout() {
+    let mut tree: QuadTree<u32, u32, 4> = quadtree_layout!(Point::zero(), Point::new(8., 8.), {
+        [TopLeft] => [(1, 1., 1., 10), (2, 2., 1., 20)],
+        [BottomRight, TopLeft] => [(3, 5., 5., 30)],
+        [BottomRight, BottomRight] => [(4, 7., 7., 40), (5, 6., 6., 50)],
+    });
+    assert_eq!(tree.depth_of(&1), Some(1));
+    assert_eq!(tree.depth_of(&3), Some(2));
+    assert_eq!(tree.depth_of(&5), Some(2));
+    assert_eq!(found(&tree), vec![1, 2, 3, 4, 5]);
+
+    // the tree is just as valid as a tree that was built by inserting
+    tree.insert(ip(6, 3., 6.), 60);
+    assert_eq!(tree.depth_of(&6), Some(1));
+    assert_eq!(tree.try_remove(&4), Some((40, Point::new(7., 7.))));
+    assert_eq!(tree.try_remove(&5), Some((50, Point::new(6., 6.))));
+    assert_eq!(tree.depth_of(&3), Some(0));
+    assert_eq!(found(&tree), vec![1, 2, 3, 6]);
+}
+
+#[test]
+fn overfull_leaf_and_outside() {
+    let tree: QuadTree<u32, u32, 4> = LayoutBuilder::new(Point::zero(), Point::new(8., 8.))
+        .leaf(
+            &[],
+            (0..6).map(|n| (n, Point::new(1., 1.), n * 10)).collect(),
+        )
+        .outside(10, Point::new(-1., 3.), 100)
+        .build();
+    assert_eq!(tree.depth_of(&5), Some(0));
+    assert_eq!(tree.iter_out_of_range().count(), 1);
+    assert_eq!(found(&tree), vec![0, 1, 2, 3, 4, 5, 10]);
+}
+
+#[test]
+#[should_panic = "entry 1 at Point { x: 5.0, y: 1.0 } is not in leaf [TopLeft]"]
+fn entry_outside_of_leaf() {
+    let _: QuadTree<u32, u32, 4> = LayoutBuilder::new(Point::zero(), Point::new(8., 8.))
+        .leaf(&[Quadrant::TopLeft], vec![(1, Point::new(5., 1.), 10)])
+        .build();
+}
+
+#[test]
+#[should_panic = "leaf [TopLeft, TopLeft] is inside of another leaf"]
+fn leaf_inside_of_leaf() {
+    let _: QuadTree<u32, u32, 4> = quadtree_layout!(Point::zero(), Point::new(8., 8.), {
+        [TopLeft] => [],
+        [TopLeft, TopLeft] => [],
+    });
+}
+
+#[test]
+#[should_panic = "identity 1 is used twice"]
+fn duplicate_identity() {
+    let _: QuadTree<u32, u32, 4> = quadtree_layout!(Point::zero(), Point::new(8., 8.), {
+        [TopLeft] => [(1, 1., 1., 10)],
+        [TopRight] => [(1, 5., 1., 10)],
+    });
+}
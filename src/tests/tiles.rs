@@ -0,0 +1,104 @@
+#![cfg(feature = "tiles")]
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{quadkey_to_tile, tests::ip, tile_to_quadkey, Point, QuadTree, Rect};
+use std::collections::BTreeMap;
+
+#[test]
+fn quadkeys() {
+    assert_eq!(tile_to_quadkey(3, 3, 5).as_deref(), Some("213"));
+    assert_eq!(quadkey_to_tile("213"), Some((3, 3, 5)));
+    assert_eq!(tile_to_quadkey(0, 0, 0).as_deref(), Some(""));
+    assert_eq!(quadkey_to_tile(""), Some((0, 0, 0)));
+    assert_eq!(tile_to_quadkey(2, 4, 0), None);
+    assert_eq!(tile_to_quadkey(32, 0, 0), None);
+    assert_eq!(quadkey_to_tile("0124"), None);
+    assert_eq!(quadkey_to_tile(&"1".repeat(32)), None);
+    for (zoom, x, y) in [(1, 1, 0), (5, 17, 30), (31, u32::MAX >> 1, 12345)] {
+        let quadkey = tile_to_quadkey(zoom, x, y).unwrap();
+        assert_eq!(quadkey_to_tile(&quadkey), Some((zoom, x, y)));
+    }
+}
+
+#[test]
+fn find_in_tile() {
+    let mut tree = QuadTree::<u32, u32, 2>::new(Point::zero(), Point::new(256., 256.));
+    for n in 0..300 {
+        let x = (n * 37 % 256) as f32 + 0.5;
+        let y = (n * 23 % 251) as f32;
+        tree.insert(ip(n, x, y), n);
+    }
+    tree.insert(ip(300, 256., 256.), 300);
+    tree.insert(ip(301, -1., 10.), 301);
+
+    assert_eq!(tree.tile_of(Point::new(128., 127.), 1), Some((1, 0)));
+    assert_eq!(tree.tile_of(Point::new(256., 256.), 8), Some((255, 255)));
+    assert_eq!(tree.tile_of(Point::new(-1., 10.), 0), None);
+
+    for zoom in [0, 1, 3, 6, 10] {
+        let tiles = 1 << zoom;
+        let mut found = 0;
+        for x in (0..tiles).step_by(tiles as usize / 4 + 1) {
+            for y in 0..tiles.min(16) {
+                tree.find_in_tile(zoom, x, y, |identity, point, _| {
+                    assert_eq!(tree.tile_of(point, zoom), Some((x, y)), "{identity}");
+                    found += 1;
+                });
+                let expected = (0..=300)
+                    .filter(|n| {
+                        tree.get(n)
+                            .and_then(|(point, _)| tree.tile_of(point, zoom))
+                            == Some((x, y))
+                    })
+                    .count();
+                found -= expected;
+            }
+        }
+        assert_eq!(found, 0);
+    }
+
+    let mut found = 0;
+    tree.find_in_tile(0, 0, 0, |_, _, _| found += 1);
+    assert_eq!(found, 301);
+    tree.find_in_tile(2, 4, 0, |_, _, _| panic!("the tile does not exist"));
+}
+
+#[test]
+fn tiles_in_rect() {
+    let mut tree = QuadTree::<u32, u32, 3>::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..200 {
+        let x = (n * 37 % 64) as f32 + 0.5;
+        let y = (n * 23 % 61) as f32;
+        tree.insert(ip(n, x, y), n);
+    }
+    tree.insert(ip(200, 16., 16.), 200);
+
+    let rect = Rect::new(Point::new(10., 16.), Point::new(40., 50.));
+    for zoom in [0, 1, 2, 4, 7] {
+        let tiles = tree.tiles_in_rect(rect, zoom);
+        let mut expected = BTreeMap::<String, Vec<u32>>::new();
+        for n in 0..=200 {
+            let (point, _) = tree.get(&n).unwrap();
+            if rect.contains(point) {
+                let (x, y) = tree.tile_of(point, zoom).unwrap();
+                let quadkey = tile_to_quadkey(zoom, x, y).unwrap();
+                expected.entry(quadkey).or_default().push(n);
+            }
+        }
+        let found: BTreeMap<_, _> = tiles
+            .iter()
+            .map(|(tile, entries)| {
+                assert_eq!(tile.zoom, zoom);
+                let mut identities: Vec<_> =
+                    entries.iter().map(|(identity, _, _)| **identity).collect();
+                identities.sort_unstable();
+                (tile.quadkey().unwrap(), identities)
+            })
+            .collect();
+        assert_eq!(found, expected, "zoom {zoom}");
+        // sorted by quadkey, so every tile is only found once
+        assert!(tiles
+            .windows(2)
+            .all(|pair| pair[0].0.quadkey() < pair[1].0.quadkey()));
+    }
+}
@@ -0,0 +1,54 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, TombstoneQuadTree, R32};
+
+fn found(tree: &TombstoneQuadTree<u32, u32, 2>) -> Vec<(u32, u32)> {
+    let mut found = Vec::new();
+    tree.find_range(Point::zero(), R32::new(1000.), |identity, _, value| {
+        found.push((*identity, *value));
+    });
+    found.sort_unstable();
+    found
+}
+
+#[test]
+fn remove_and_reinsert() {
+    let mut tree = TombstoneQuadTree::<u32, u32, 2>::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..40 {
+        tree.insert(ip(n, (n * 7 % 64) as f32, (n * 13 % 64) as f32), n);
+    }
+    let before = tree.clone();
+
+    for n in (0..40).step_by(2) {
+        assert!(tree.remove(&n));
+    }
+    assert!(!tree.remove(&0));
+    assert!(!tree.remove(&100));
+    assert!(!tree.update(0, Point::new(1., 1.)));
+    assert_eq!(tree.len(), 20);
+    assert_eq!(tree.tombstones(), 20);
+    assert_eq!(
+        found(&tree),
+        (1..40).step_by(2).map(|n| (n, n)).collect::<Vec<_>>()
+    );
+
+    // reinserting a tombstone revives it with the new value
+    tree.insert(ip(0, 2., 2.), 100);
+    assert!(tree.update(0, Point::new(3., 3.)));
+    assert_eq!(tree.len(), 21);
+    assert_eq!(found(&tree)[0], (0, 100));
+
+    assert_eq!(tree.vacuum(), 19);
+    assert_eq!(tree.tombstones(), 0);
+    assert_eq!(tree.len(), 21);
+    assert_eq!(found(&tree).len(), 21);
+
+    // vacuuming removes the entries from the tree itself
+    let mut vacuumed = before;
+    for n in (2..40).step_by(2) {
+        assert!(vacuumed.remove(&n));
+    }
+    vacuumed.insert(ip(0, 3., 3.), 100);
+    vacuumed.vacuum();
+    assert_eq!(vacuumed, tree);
+}
@@ -0,0 +1,111 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, Quadrant, R32};
+
+fn entries(tree: &QuadTree<u32, u32, 4>) -> Vec<(u32, Point, Option<usize>)> {
+    let mut found = Vec::new();
+    tree.find_range(tree.rect.middle(), R32::new(1e6), |identity, point, _| {
+        found.push((*identity, point, tree.depth_of(identity)));
+    });
+    found.sort_unstable();
+    found
+}
+
+fn tree() -> QuadTree<u32, u32, 4> {
+    let mut tree = QuadTree::new(Point::zero(), Point::new(64., 64.));
+    for n in 0..64 {
+        tree.insert(ip(n, (n % 8) as f32 * 8. + 1., (n / 8) as f32 * 8. + 1.), n);
+    }
+    tree.insert(ip(100, -5., 1.), 100);
+    tree
+}
+
+#[test]
+fn translate_all() {
+    let mut tree = tree();
+    let before = entries(&tree);
+    let delta = Point::new(1000., -250.);
+    tree.translate_all(delta);
+    assert_eq!(tree.rect.top_left(), delta);
+    assert_eq!(
+        entries(&tree),
+        before
+            .into_iter()
+            .map(|(identity, point, depth)| (identity, point + delta, depth))
+            .collect::<Vec<_>>()
+    );
+    let mut found = Vec::new();
+    tree.find_range(Point::new(1001., -249.), R32::new(0.5), |identity, _, _| {
+        found.push(*identity);
+    });
+    assert_eq!(found, vec![0]);
+}
+
+#[test]
+fn scale_all() {
+    let mut tree = tree();
+    let before = entries(&tree);
+    tree.scale_all(R32::new(0.5));
+    assert_eq!(tree.rect.bottom_right(), Point::new(32., 32.));
+    assert_eq!(
+        entries(&tree),
+        before
+            .into_iter()
+            .map(|(identity, point, depth)| {
+                (
+                    identity,
+                    Point::new(point.x.raw() / 2., point.y.raw() / 2.),
+                    depth,
+                )
+            })
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn rounding_moves_entries() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(2., 2.));
+    // 1 - 2^-24 is just left of the middle, but rounds to the middle once it is moved
+    let left = 1. - f32::EPSILON / 2.;
+    tree.insert(ip(1, left, 0.5), 1);
+    tree.insert(ip(2, 1.5, 0.5), 2);
+    tree.insert(ip(3, 2. + f32::EPSILON * 2., 0.5), 3);
+    tree.translate_all(Point::new(2., 0.));
+    assert_eq!(tree.rect.middle(), Point::new(3., 1.));
+    let mut found = Vec::new();
+    tree.find_range(Point::new(3.5, 0.5), R32::new(1.), |identity, _, _| {
+        found.push(*identity);
+    });
+    found.sort_unstable();
+    assert_eq!(found, vec![1, 2, 3]);
+    // entry 1 is now on the edge between two leaves, and entry 3 on the edge of the tree
+    assert_eq!(tree.path_of(&1).unwrap().next(), Some(Quadrant::TopRight));
+    assert_eq!(tree.iter_out_of_range().count(), 0);
+}
+
+#[test]
+#[should_panic = "Scale factor 0 should be larger than 0"]
+fn scale_by_zero() {
+    tree().scale_all(R32::new(0.));
+}
+
+#[test]
+fn recenter() {
+    let mut tree = tree();
+    tree.insert(ip(200, 5000., 1.), 200);
+    tree.recenter(Point::new(32., 32.));
+    assert_eq!(tree.rect.top_left(), Point::new(-32., -32.));
+    let mut found = Vec::new();
+    tree.find_range(Point::new(-31., -31.), R32::new(0.5), |identity, _, _| {
+        found.push(*identity);
+    });
+    assert_eq!(found, vec![0]);
+
+    let mut far_away = Vec::new();
+    tree.recenter_reporting(Point::new(-32., -32.), R32::new(100.), |identity, point| {
+        far_away.push((*identity, point));
+    });
+    assert_eq!(tree.rect.top_left(), Point::zero());
+    assert_eq!(far_away, vec![(200, Point::new(5000., 1.))]);
+    assert_eq!(tree.iter_out_of_range().count(), 2);
+}
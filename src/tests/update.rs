@@ -1,13 +1,12 @@
 #![allow(clippy::cast_precision_loss, clippy::too_many_lines)]
 #![cfg(test)]
 
-use std::collections::BTreeMap;
-
 use crate::{
     bucket::Bucket,
     index::Index,
+    outside::Outside,
     tests::{ip, ipv},
-    Point, QuadTree,
+    Point, QuadTree, Quadrant,
 };
 use smallvec::smallvec;
 
@@ -93,7 +92,7 @@ fn validate_out_of_range() {
         tree.items,
         vec![Bucket::Owned(smallvec![ipv(1, 1.0, 1.0, 1)])]
     );
-    assert_eq!(tree.outside_of_range, BTreeMap::default());
+    assert_eq!(tree.outside_of_range, Outside::default());
     tree.insert(ip(1, 11.0, 11.0), 1);
     assert_eq!(tree.items, vec![Bucket::Owned(smallvec![])]);
     assert_eq!(
@@ -105,7 +104,7 @@ fn validate_out_of_range() {
         tree.items,
         vec![Bucket::Owned(smallvec![ipv(1, 1.0, 1.0, 1)])]
     );
-    assert_eq!(tree.outside_of_range, BTreeMap::default());
+    assert_eq!(tree.outside_of_range, Outside::default());
 }
 
 #[test]
@@ -116,10 +115,10 @@ fn validate_update() {
         tree.items,
         vec![Bucket::Owned(smallvec![ipv(1, 1.0, 1.0, 1)])]
     );
-    assert_eq!(tree.outside_of_range, BTreeMap::default());
+    assert_eq!(tree.outside_of_range, Outside::default());
     assert_eq!(
         tree.identity_to_point,
-        [(1, (Point::new(1., 1.), Some(Index::ROOT)))]
+        [(1, (Point::new(1., 1.), Some(Index::ROOT), 1))]
             .into_iter()
             .collect()
     );
@@ -129,10 +128,10 @@ fn validate_update() {
         tree.items,
         vec![Bucket::Owned(smallvec![ipv(1, 1.0, 1.0, 2)])]
     );
-    assert_eq!(tree.outside_of_range, BTreeMap::default());
+    assert_eq!(tree.outside_of_range, Outside::default());
     assert_eq!(
         tree.identity_to_point,
-        [(1, (Point::new(1., 1.), Some(Index::ROOT)))]
+        [(1, (Point::new(1., 1.), Some(Index::ROOT), 1))]
             .into_iter()
             .collect()
     );
@@ -145,7 +144,7 @@ fn validate_update() {
     );
     assert_eq!(
         tree.identity_to_point,
-        [(1, (Point::new(11., 11.), None))].into_iter().collect()
+        [(1, (Point::new(11., 11.), None, 2))].into_iter().collect()
     );
 
     assert!(tree.update_point_and_value(1, Point::new(1.0, 1.0), |v| *v += 1));
@@ -153,11 +152,186 @@ fn validate_update() {
         tree.items,
         vec![Bucket::Owned(smallvec![ipv(1, 1.0, 1.0, 4)])]
     );
-    assert_eq!(tree.outside_of_range, BTreeMap::default());
+    assert_eq!(tree.outside_of_range, Outside::default());
     assert_eq!(
         tree.identity_to_point,
-        [(1, (Point::new(1., 1.), Some(Index::ROOT)))]
+        [(1, (Point::new(1., 1.), Some(Index::ROOT), 3))]
             .into_iter()
             .collect()
     );
 }
+
+#[test]
+fn upsert() {
+    let mut tree = QuadTree::<u32, u32, 4>::sized_around_origin(Point::new(10., 10.));
+    let on_existing = |_: &mut u32, _| panic!("the entry does not exist yet");
+    tree.upsert(1, Point::new(1., 1.), || 10, on_existing);
+    tree.upsert(2, Point::new(20., 20.), || 20, on_existing);
+
+    let mut old_points = Vec::new();
+    for (identity, point) in [(1, Point::new(-5., 3.)), (2, Point::new(2., 2.))] {
+        tree.upsert(
+            identity,
+            point,
+            || panic!("the entry exists already"),
+            |value, old_point| {
+                *value += 1;
+                old_points.push(old_point);
+            },
+        );
+    }
+    assert_eq!(old_points, vec![Point::new(1., 1.), Point::new(20., 20.)]);
+    assert_eq!(tree.get(&1), Some((Point::new(-5., 3.), &11)));
+    assert_eq!(tree.get(&2), Some((Point::new(2., 2.), &21)));
+    assert!(tree.outside_of_range.is_empty());
+}
+
+#[test]
+fn update_in_leaf() {
+    let mut tree = QuadTree::<u32, u32, 1>::new(Point::zero(), Point::new(8., 8.));
+    tree.insert(ip(1, 1., 1.), 1);
+    tree.insert(ip(2, 5., 5.), 2);
+    let items = tree.items.clone();
+
+    // moves inside of a leaf keep the layout of the tree, also onto the edge of the tree
+    for point in [Point::new(3.9, 0.), Point::new(0., 3.9), Point::new(2., 2.)] {
+        assert!(tree.update(1, point));
+        assert_eq!(
+            tree.path_of(&1).unwrap().collect::<Vec<_>>(),
+            [Quadrant::TopLeft]
+        );
+    }
+    assert!(tree.update(2, Point::new(8., 8.)));
+    assert_eq!(
+        tree.path_of(&2).unwrap().collect::<Vec<_>>(),
+        [Quadrant::BottomRight]
+    );
+    assert!(tree.update(2, Point::new(5., 5.)));
+    assert!(tree.update(1, Point::new(1., 1.)));
+    assert_eq!(tree.items, items);
+
+    // a point on the middle line is in the right leaf
+    assert!(tree.update_point_and_value(1, Point::new(4., 1.), |value| *value = 10));
+    assert_eq!(
+        tree.path_of(&1).unwrap().collect::<Vec<_>>(),
+        [Quadrant::TopRight]
+    );
+    assert_eq!(tree.get(&1), Some((Point::new(4., 1.), &10)));
+}
+
+#[test]
+fn entry_mut() {
+    let mut tree = QuadTree::<u32, u32, 4>::sized_around_origin(Point::new(10., 10.));
+    for n in 1..5 {
+        tree.insert(ip(n, n as f32, n as f32), n);
+    }
+    let weak = tree.weak_ref(&1).unwrap();
+
+    // only the value is changed, so the entry stays where it is
+    {
+        let mut entry = tree.entry_mut(&1).unwrap();
+        assert_eq!(entry.identity(), &1);
+        assert_eq!(entry.point(), Point::new(1., 1.));
+        *entry += 10;
+        assert_eq!(*entry, 11);
+    }
+    assert_eq!(tree.get(&1), Some((Point::new(1., 1.), &11)));
+    assert!(weak.upgrade(&tree).is_some());
+
+    // the new position is in the same leaf, so it is changed in place
+    {
+        let mut entry = tree.entry_mut(&1).unwrap();
+        entry.set_point(Point::new(2.5, 2.5));
+        assert_eq!(entry.point(), Point::new(2.5, 2.5));
+        assert_eq!(*entry, 11);
+    }
+    assert_eq!(
+        tree.items,
+        vec![Bucket::Owned(smallvec![
+            ipv(1, 2.5, 2.5, 11),
+            ipv(2, 2.0, 2.0, 2),
+            ipv(3, 3.0, 3.0, 3),
+            ipv(4, 4.0, 4.0, 4),
+        ])]
+    );
+    assert!(weak.upgrade(&tree).is_none());
+
+    // out of the range of the tree
+    {
+        let mut entry = tree.entry_mut(&2).unwrap();
+        *entry = 20;
+        entry.set_point(Point::new(20., 20.));
+    }
+    assert_eq!(tree.get(&2), Some((Point::new(20., 20.), &20)));
+    assert_eq!(tree.node_of(&2), None);
+    assert_eq!(
+        tree.outside_of_range,
+        Outside::from_iter([(2, (20, Point::new(20., 20.)))])
+    );
+
+    // and back in
+    tree.entry_mut(&2).unwrap().set_point(Point::new(-5., -5.));
+    assert_eq!(tree.get(&2), Some((Point::new(-5., -5.), &20)));
+    assert_eq!(tree.outside_of_range, Outside::default());
+    assert_eq!(tree.len(), 4);
+
+    assert!(tree.entry_mut(&5).is_none());
+}
+
+fn snap(point: Point) -> Point {
+    Point::new(point.x.raw().round(), point.y.raw().round())
+}
+
+#[test]
+fn position_filter() {
+    let mut tree = QuadTree::<u32, u32, 4>::sized_around_origin(Point::new(10., 10.));
+    tree.insert(ip(1, 1.4, 2.6), 1);
+    tree.set_position_filter(Some(snap));
+    assert!(tree.position_filter().is_some());
+    // entries that are already in the tree are not moved
+    assert_eq!(tree.get(&1), Some((Point::new(1.4, 2.6), &1)));
+
+    tree.insert(ip(2, 1.4, 2.6), 2);
+    assert_eq!(tree.get(&2), Some((Point::new(1., 3.), &2)));
+    assert!(tree.update(1, Point::new(5.2, -5.2)));
+    assert_eq!(tree.get(&1), Some((Point::new(5., -5.), &1)));
+    tree.upsert(3, Point::new(-2.5, 0.4), || 3, |_, _| {});
+    assert_eq!(tree.get(&3), Some((Point::new(-3., 0.), &3)));
+    tree.upsert(3, Point::new(12.2, 0.4), || 3, |_, _| {});
+    assert_eq!(tree.get(&3), Some((Point::new(12., 0.), &3)));
+    assert_eq!(
+        tree.outside_of_range,
+        Outside::from_iter([(3, (3, Point::new(12., 0.)))])
+    );
+
+    // a small step is filtered back onto the same position
+    assert_eq!(tree.translate_group([1, 2], Point::new(0.4, 0.4)), 2);
+    assert_eq!(tree.get(&1), Some((Point::new(5., -5.), &1)));
+    assert_eq!(tree.translate_group([1, 2], Point::new(0.6, 0.6)), 2);
+    assert_eq!(tree.get(&1), Some((Point::new(6., -4.), &1)));
+    assert_eq!(tree.get(&2), Some((Point::new(2., 4.), &2)));
+
+    {
+        let mut entry = tree.entry_mut(&2).unwrap();
+        entry.set_point(Point::new(2.2, 3.8));
+        assert_eq!(entry.point(), Point::new(2., 4.));
+    }
+    assert_eq!(tree.get(&2), Some((Point::new(2., 4.), &2)));
+
+    // the buckets agree with the identity map
+    assert_eq!(
+        tree.items,
+        vec![Bucket::Owned(smallvec![
+            ipv(1, 6., -4., 1),
+            ipv(2, 2., 4., 2),
+        ])]
+    );
+
+    // moving the whole tree is not filtered
+    tree.translate_all(Point::new(0.5, 0.5));
+    assert_eq!(tree.get(&1), Some((Point::new(6.5, -3.5), &1)));
+
+    tree.set_position_filter(None);
+    tree.insert(ip(4, 1.4, 2.6), 4);
+    assert_eq!(tree.get(&4), Some((Point::new(1.4, 2.6), &4)));
+}
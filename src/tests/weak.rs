@@ -0,0 +1,42 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{tests::ip, Point, QuadTree, R32};
+
+#[test]
+fn upgrade() {
+    let mut tree = QuadTree::<u32, u32, 4>::new(Point::zero(), Point::new(16., 16.));
+    for n in 0..10 {
+        tree.insert(ip(n, n as f32, 1.), n);
+    }
+    tree.insert(ip(100, -1., -1.), 100);
+    assert_eq!(tree.weak_ref(&200), None);
+    let outside = tree.weak_ref(&100).unwrap();
+    let kept = tree.weak_ref(&1).unwrap();
+    let moved = tree.weak_ref(&2).unwrap();
+    let removed = tree.weak_ref(&3).unwrap();
+    let reinserted = tree.weak_ref(&4).unwrap();
+    assert_eq!(kept.identity(), &1);
+
+    assert!(tree.update_point_and_value(1, Point::new(1., 1.), |value| *value += 10));
+    assert!(tree.update(2, Point::new(2.5, 1.)));
+    tree.remove(&3);
+    tree.remove(&4);
+    tree.insert(ip(4, 4., 1.), 4);
+    tree.translate_all(Point::new(10., 0.));
+
+    assert_eq!(kept.upgrade(&tree), Some((Point::new(11., 1.), &11)));
+    assert_eq!(outside.upgrade(&tree), Some((Point::new(9., -1.), &100)));
+    assert_eq!(moved.upgrade(&tree), None);
+    assert_eq!(removed.upgrade(&tree), None);
+    assert_eq!(reinserted.upgrade(&tree), None);
+    let moved = tree.weak_ref(&2).unwrap();
+    assert_eq!(moved.upgrade(&tree), Some((Point::new(12.5, 1.), &2)));
+
+    tree.migrate_to_bounds(tree.suggest_bounds());
+    assert_eq!(kept.upgrade(&tree), Some((Point::new(11., 1.), &11)));
+    assert_eq!(tree.translate_group([1], Point::new(0.5, 0.)), 1);
+    assert_eq!(kept.upgrade(&tree), None);
+    let mut found = 0;
+    tree.find_range(Point::new(11.5, 1.), R32::new(0.1), |_, _, _| found += 1);
+    assert_eq!(found, 1);
+}
@@ -0,0 +1,80 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::{r64, Point64, QuadTree64};
+
+/// A tree that covers the earth in meters, where `f32` has a precision of 2 meters at the edges
+fn planet() -> QuadTree64<u32, u32, 4> {
+    QuadTree64::new(Point64::new(-2e7, -2e7), Point64::new(2e7, 2e7))
+}
+
+fn found(tree: &QuadTree64<u32, u32, 4>, center: Point64, range: f64) -> Vec<u32> {
+    let mut found = Vec::new();
+    tree.find_range(center, r64(range), |identity, point, value| {
+        assert_eq!(tree.get(identity), Some((point, value)));
+        found.push(*identity);
+    });
+    found.sort_unstable();
+    found
+}
+
+#[test]
+fn exact_positions() {
+    let mut tree = planet();
+    tree.insert(1, Point64::new(1.9e7 + 0.25, 5.), 10);
+    tree.insert(2, Point64::new(1.9e7 + 0.75, 5.), 20);
+    tree.insert(3, Point64::new(1.9e7 + 1.5, 5.), 30);
+    assert_eq!(tree.len(), 3);
+    assert_eq!(tree.get(&2), Some((Point64::new(1.9e7 + 0.75, 5.), &20)));
+
+    let center = Point64::new(1.9e7, 5.);
+    assert_eq!(found(&tree, center, 0.5), vec![1]);
+    // entries at exactly the range are found
+    assert_eq!(found(&tree, center, 0.75), vec![1, 2]);
+    assert_eq!(found(&tree, center, 1.5), vec![1, 2, 3]);
+
+    assert!(tree.update(2, Point64::new(1.9e7 + 0.125, 5.)));
+    assert!(!tree.update(4, center));
+    assert_eq!(found(&tree, center, 0.2), vec![2]);
+    assert_eq!(
+        tree.try_remove(&2),
+        Some((20, Point64::new(1.9e7 + 0.125, 5.)))
+    );
+    assert_eq!(found(&tree, center, 0.5), vec![1]);
+
+    // far outside of the range of `f32`
+    tree.insert(4, Point64::new(1e300, 1e300), 40);
+    tree.insert(5, Point64::new(1e300, 1e300 + 1e290), 50);
+    assert_eq!(found(&tree, Point64::new(1e300, 1e300), 1.), vec![4]);
+    assert_eq!(found(&tree, center, 0.5), vec![1]);
+    assert_eq!(tree.inner().iter_out_of_range().count(), 2);
+    assert_eq!(tree.len(), 4);
+}
+
+#[test]
+fn same_as_brute_force() {
+    let mut tree = planet();
+    let mut entries = Vec::new();
+    for n in 0..500 {
+        // clusters of entries that are a few centimeters apart
+        let x = f64::from(n / 10 * 7919 % 4000) * 1e4 - 1.99e7 + f64::from(n % 10) * 0.03;
+        let y = f64::from(n / 10 * 104_729 % 4000) * 1e4 - 1.99e7 - f64::from(n % 7) * 0.05;
+        let point = Point64::new(x, y);
+        tree.insert(n, point, n);
+        entries.push((n, point));
+    }
+    for (center, range) in [
+        (entries[0].1, 0.1),
+        (entries[123].1, 0.04),
+        (entries[456].1, 1e5),
+        (Point64::new(0., 0.), 1e7),
+        (Point64::new(3e7, 3e7), 2e7),
+    ] {
+        let expected = entries
+            .iter()
+            .filter(|(_, point)| point.distance_squared(center) <= range * range)
+            .map(|(identity, _)| *identity)
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(found(&tree, center, range), expected);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    tests::{ip, ipv, rect},
+    Point, QuadTree, ZippedNode,
+};
+
+fn tree<const N: usize>() -> QuadTree<u32, u32, N> {
+    QuadTree::new(Point::zero(), Point::new(16., 16.))
+}
+
+#[test]
+fn same_layout() {
+    let mut first = tree::<2>();
+    let mut second = tree::<2>();
+    for (n, (x, y)) in (0..).zip([(1., 1.), (2., 2.), (14., 14.)]) {
+        first.insert(ip(n, x, y), 0);
+        second.insert(ip(n + 10, x + 1., y + 1.), 1);
+    }
+
+    let top_left = rect(0., 0., 8., 8.);
+    let bottom_right = rect(8., 8., 16., 16.);
+    let nodes = first.zip(&second).collect::<Vec<_>>();
+    assert_eq!(
+        nodes,
+        vec![
+            ZippedNode {
+                rect: top_left,
+                first: &[ipv(0, 1., 1., 0), ipv(1, 2., 2., 0)][..],
+                first_leaf: top_left,
+                second: &[ipv(10, 2., 2., 1), ipv(11, 3., 3., 1)][..],
+                second_leaf: top_left,
+            },
+            ZippedNode {
+                rect: bottom_right,
+                first: &[ipv(2, 14., 14., 0)][..],
+                first_leaf: bottom_right,
+                second: &[ipv(12, 15., 15., 1)][..],
+                second_leaf: bottom_right,
+            },
+        ]
+    );
+}
+
+#[test]
+fn different_layouts() {
+    let mut first = tree::<2>();
+    for (n, (x, y)) in (0..).zip([(1., 1.), (5., 5.), (9., 1.)]) {
+        first.insert(ip(n, x, y), 0);
+    }
+    let mut second = QuadTree::<&str, u32, 8>::new(Point::zero(), Point::new(16., 16.));
+    second.insert(ip(10, 12., 4.), "hazard");
+
+    // the root of `second` is a leaf, so it is returned with every leaf of `first` below it
+    let root = rect(0., 0., 16., 16.);
+    let nodes = first
+        .zip(&second)
+        .map(|node| {
+            assert_eq!(node.second, &[ipv(10, 12., 4., "hazard")][..]);
+            assert_eq!(node.second_leaf, root);
+            assert_eq!(node.first_leaf, node.rect);
+            (node.rect, node.first.len())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        nodes,
+        vec![
+            (rect(0., 0., 8., 8.), 2),
+            (rect(8., 0., 16., 8.), 1),
+            (rect(0., 8., 8., 16.), 0),
+            (rect(8., 8., 16., 16.), 0),
+        ]
+    );
+
+    // the other way around, the leaves of `first` become the larger side
+    let node = second.zip(&first).next().unwrap();
+    assert_eq!(node.first_leaf, root);
+    assert_eq!(node.second_leaf, rect(0., 0., 8., 8.));
+}
+
+#[test]
+fn skips_empty_areas() {
+    let mut first = tree::<2>();
+    assert_eq!(first.zip(&tree::<2>()).count(), 0);
+
+    for (n, (x, y)) in (0..).zip([(1., 1.), (2., 2.), (14., 14.)]) {
+        first.insert(ip(n, x, y), 0);
+    }
+    // entries outside of the trees are not part of any area
+    first.insert(ip(20, 30., 30.), 0);
+    let nodes = first
+        .zip(&tree::<2>())
+        .map(|node| node.rect)
+        .collect::<Vec<_>>();
+    assert_eq!(nodes, vec![rect(0., 0., 8., 8.), rect(8., 8., 16., 16.)]);
+}
+
+#[test]
+#[should_panic = "Quad trees must cover the same area to be zipped"]
+fn different_areas() {
+    let other = QuadTree::<u32, u32, 2>::new(Point::zero(), Point::new(8., 8.));
+    let _ = tree::<2>().zip(&other);
+}
@@ -0,0 +1,222 @@
+//! Interop with web map tiles, for trees whose rect matches the extent of a map projection, e.g. web mercator.
+//!
+//! Tile `(zoom, x, y)` is the node that is `zoom` splits deep, `x` tiles from the left and `y` tiles from the top. Its quadkey is the path of quadrants from the root to that node, with one digit per quadrant: `0` for [`Quadrant::TopLeft`], `1` for [`Quadrant::TopRight`], `2` for [`Quadrant::BottomLeft`] and `3` for [`Quadrant::BottomRight`]. This is the same numbering that the quadrants of this crate use internally.
+
+use crate::{bucket::Bucket, index::Index, point::Quadrant, Point, QuadTree, Rect};
+use std::collections::BTreeMap;
+
+/// The deepest zoom level that fits in a `u32` tile coordinate
+const MAX_ZOOM: u8 = 31;
+
+/// The coordinates of a tile, see the [module documentation](self)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TileCoord {
+    /// The amount of splits from the root to this tile
+    pub zoom: u8,
+    /// The amount of tiles to the left of this tile
+    pub x: u32,
+    /// The amount of tiles above this tile
+    pub y: u32,
+}
+
+impl TileCoord {
+    /// The quadkey of this tile, see [`tile_to_quadkey`]
+    #[must_use]
+    pub fn quadkey(self) -> Option<String> {
+        tile_to_quadkey(self.zoom, self.x, self.y)
+    }
+
+    fn child(self, quadrant: Quadrant) -> TileCoord {
+        TileCoord {
+            zoom: self.zoom + 1,
+            x: (self.x << 1) | (quadrant as u32 & 1),
+            y: (self.y << 1) | (quadrant as u32 >> 1),
+        }
+    }
+
+    /// The position of this tile on the Z-order curve of its zoom level, which sorts tiles the same as their quadkeys
+    fn morton(self) -> u64 {
+        (0..self.zoom).rev().fold(0, |key, level| {
+            (key << 2) | u64::from(quadrant_bits(self.x, self.y, level))
+        })
+    }
+}
+
+/// The entries of a tile with their position, as found by [`QuadTree::tiles_in_rect`]
+pub type TileEntries<'a, T, ID> = Vec<(&'a ID, Point, &'a T)>;
+
+/// Get the quadkey of tile `(zoom, x, y)`, e.g. `"213"` for tile `(3, 3, 5)`.
+///
+/// Returns `None` if `zoom` is larger than 31, or if `x` or `y` are not smaller than `2 ^ zoom`.
+#[must_use]
+pub fn tile_to_quadkey(zoom: u8, x: u32, y: u32) -> Option<String> {
+    if zoom > MAX_ZOOM || x >> zoom != 0 || y >> zoom != 0 {
+        return None;
+    }
+    let quadkey = (0..zoom)
+        .rev()
+        .map(|level| char::from(b'0' + quadrant_bits(x, y, level)))
+        .collect();
+    Some(quadkey)
+}
+
+/// The bits of the quadrant at `level` of the path to tile `(x, y)`, where level 0 is the deepest level
+fn quadrant_bits(x: u32, y: u32, level: u8) -> u8 {
+    u8::from((x >> level) & 1 == 1) | (u8::from((y >> level) & 1 == 1) << 1)
+}
+
+/// Get the tile `(zoom, x, y)` of a quadkey, the inverse of [`tile_to_quadkey`].
+///
+/// Returns `None` if `quadkey` contains anything other than the digits `0` to `3`, or if it is longer than 31 digits.
+#[must_use]
+pub fn quadkey_to_tile(quadkey: &str) -> Option<(u8, u32, u32)> {
+    let zoom = u8::try_from(quadkey.len())
+        .ok()
+        .filter(|zoom| *zoom <= MAX_ZOOM)?;
+    let (mut x, mut y) = (0, 0);
+    for digit in quadkey.bytes() {
+        let digit = match digit {
+            b'0'..=b'3' => u32::from(digit - b'0'),
+            _ => return None,
+        };
+        x = (x << 1) | (digit & 1);
+        y = (y << 1) | (digit >> 1);
+    }
+    Some((zoom, x, y))
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N> {
+    /// Get the `(x, y)` coordinates of the tile at `zoom` that contains `point`.
+    ///
+    /// Points on the edge between two tiles are in the tile to the right or bottom, the same as for the nodes of this tree.
+    ///
+    /// Returns `None` if `point` is outside of this tree, or if `zoom` is larger than 31.
+    #[must_use]
+    pub fn tile_of(&self, point: Point, zoom: u8) -> Option<(u32, u32)> {
+        if zoom > MAX_ZOOM || !self.rect.contains(point) {
+            return None;
+        }
+        let mut rect = self.rect;
+        let (mut x, mut y) = (0, 0);
+        for _ in 0..zoom {
+            let (child_rect, quadrant) = rect.get_quadrant(point);
+            x = (x << 1) | (quadrant as u32 & 1);
+            y = (y << 1) | (quadrant as u32 >> 1);
+            rect = child_rect;
+        }
+        Some((x, y))
+    }
+
+    /// Call `callback` for every entry in tile `(zoom, x, y)`, see [`QuadTree::tile_of`].
+    ///
+    /// Entries that are outside of this tree are never in a tile. Nothing is found if `zoom` is larger than 31, or if `x` or `y` are not smaller than `2 ^ zoom`.
+    pub fn find_in_tile<'a>(
+        &'a self,
+        zoom: u8,
+        x: u32,
+        y: u32,
+        mut callback: impl FnMut(&'a ID, Point, &'a T),
+    ) {
+        if zoom > MAX_ZOOM || x >> zoom != 0 || y >> zoom != 0 {
+            return;
+        }
+        let mut index = Index::ROOT;
+        for level in (0..zoom).rev() {
+            match self.items.get(index.to_idx()) {
+                Some(Bucket::Nested) => {
+                    index = index.child_at(Quadrant::from_bits(quadrant_bits(x, y, level)));
+                }
+                // this leaf is larger than the tile, so only some of its entries are in the tile
+                Some(Bucket::Owned(entries)) => {
+                    for (ip, value) in entries {
+                        if self.tile_of(ip.point, zoom) == Some((x, y)) {
+                            callback(&ip.identity, ip.point, value);
+                        }
+                    }
+                    return;
+                }
+                None => return,
+            }
+        }
+        self.for_each_entry_below(index, &mut callback);
+    }
+
+    /// Find all entries inside of `rect`, grouped by the tile at `zoom` that contains them, see [`QuadTree::tile_of`]. Points on the edge of `rect` are inside of it.
+    ///
+    /// Tiles without entries are skipped. The tiles are sorted by their quadkey, and the entries of every tile are in the order that they are stored in. Entries that are outside of this tree are never in a tile. Nothing is found if `zoom` is larger than 31.
+    #[must_use]
+    pub fn tiles_in_rect(&self, rect: Rect, zoom: u8) -> Vec<(TileCoord, TileEntries<'_, T, ID>)> {
+        let mut tiles = Vec::new();
+        if zoom <= MAX_ZOOM {
+            self.tiles_in_rect_inner(
+                Index::ROOT,
+                self.rect,
+                TileCoord::default(),
+                rect,
+                zoom,
+                &mut tiles,
+            );
+        }
+        tiles
+    }
+
+    fn tiles_in_rect_inner<'a>(
+        &'a self,
+        index: Index,
+        node_rect: Rect,
+        tile: TileCoord,
+        rect: Rect,
+        zoom: u8,
+        tiles: &mut Vec<(TileCoord, TileEntries<'a, T, ID>)>,
+    ) {
+        if !rect.touches(node_rect) {
+            return;
+        }
+        if tile.zoom == zoom {
+            let mut entries = Vec::new();
+            self.for_each_entry_below(index, &mut |identity, point, value| {
+                if rect.contains(point) {
+                    entries.push((identity, point, value));
+                }
+            });
+            if !entries.is_empty() {
+                tiles.push((tile, entries));
+            }
+            return;
+        }
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Nested) => {
+                for quadrant in Quadrant::all() {
+                    self.tiles_in_rect_inner(
+                        index.child_at(quadrant),
+                        node_rect.get_child_at(quadrant),
+                        tile.child(quadrant),
+                        rect,
+                        zoom,
+                        tiles,
+                    );
+                }
+            }
+            // this leaf is larger than a tile, so its entries are spread over multiple tiles
+            Some(Bucket::Owned(entries)) => {
+                let mut leaf_tiles = BTreeMap::<u64, (TileCoord, Vec<_>)>::new();
+                for (ip, value) in entries {
+                    if !rect.contains(ip.point) {
+                        continue;
+                    }
+                    let Some((x, y)) = self.tile_of(ip.point, zoom) else {
+                        continue;
+                    };
+                    let tile = TileCoord { zoom, x, y };
+                    leaf_tiles
+                        .entry(tile.morton())
+                        .or_insert_with(|| (tile, Vec::new()))
+                        .1
+                        .push((&ip.identity, ip.point, value));
+                }
+                tiles.extend(leaf_tiles.into_values());
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! An opt-in layer for workloads that remove and reinsert the same identities over and over, e.g. every frame.
+//!
+//! Removing an entry from a [`TombstoneQuadTree`] only marks it as a tombstone, which queries skip. Inserting the same identity again turns the tombstone back into an entry, which is an update instead of a removal and an insertion. The tombstones are only removed from the tree, and the nodes merged again, by [`TombstoneQuadTree::vacuum`].
+
+use crate::{IdentityPoint, Point, QuadTree, RangeBoundary, R32};
+use std::collections::BTreeSet;
+
+/// A value in a [`TombstoneQuadTree`], which is skipped by queries once it is removed
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Slot<T> {
+    value: T,
+    removed: bool,
+}
+
+/// A [`QuadTree`] whose removals are deferred to [`TombstoneQuadTree::vacuum`], see the [module documentation](self).
+///
+/// Only the most common methods are wrapped. The tombstones still take memory until they are vacuumed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TombstoneQuadTree<T, ID, const N: usize> {
+    tree: QuadTree<Slot<T>, ID, N>,
+    tombstones: BTreeSet<ID>,
+}
+
+impl<T, ID, const N: usize> TombstoneQuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Create a new tree which covers the area between `top_left` and `bottom_right`, see [`QuadTree::new`]
+    #[must_use]
+    pub fn new(top_left: Point, bottom_right: Point) -> Self {
+        Self {
+            tree: QuadTree::new(top_left, bottom_right),
+            tombstones: BTreeSet::new(),
+        }
+    }
+
+    /// The amount of entries in this tree, without the tombstones
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tree.identity_to_point.len() - self.tombstones.len()
+    }
+
+    /// Returns `true` if this tree has no entries, not counting the tombstones
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The amount of tombstones that [`TombstoneQuadTree::vacuum`] would remove
+    #[must_use]
+    pub fn tombstones(&self) -> usize {
+        self.tombstones.len()
+    }
+
+    /// Insert a value `value` at the given `point`, see [`QuadTree::insert`]. If `point.identity` is a tombstone, it is updated and turned back into an entry.
+    pub fn insert(&mut self, point: IdentityPoint<ID>, value: T) {
+        self.tombstones.remove(&point.identity);
+        self.tree.insert(
+            point,
+            Slot {
+                value,
+                removed: false,
+            },
+        );
+    }
+
+    /// Update the given identity to the new point, see [`QuadTree::update`].
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise. Tombstones are not updated.
+    pub fn update(&mut self, identity: ID, point: Point) -> bool {
+        self.update_point_and_value(identity, point, |_| {})
+    }
+
+    /// Update the given identity to the new point, with the opportunity to update the value, see [`QuadTree::update_point_and_value`].
+    ///
+    /// Will return `true` if the identity was found and updated, `false` otherwise. Tombstones are not updated.
+    pub fn update_point_and_value(
+        &mut self,
+        identity: ID,
+        point: Point,
+        callback: impl FnOnce(&mut T),
+    ) -> bool {
+        if self.tombstones.contains(&identity) {
+            return false;
+        }
+        self.tree
+            .update_point_and_value(identity, point, |slot| callback(&mut slot.value))
+    }
+
+    /// Mark the entry with the given identity as a tombstone. The entry stays in the tree until the next [`TombstoneQuadTree::vacuum`], but queries skip it.
+    ///
+    /// Will return `true` if the identity was found, `false` if it does not exist or already is a tombstone.
+    pub fn remove(&mut self, identity: &ID) -> bool {
+        if self.tombstones.contains(identity) {
+            return false;
+        }
+        let Some((point, _, _)) = self.tree.identity_to_point.get(identity) else {
+            return false;
+        };
+        // the value is only updated in place, which never moves the entry
+        let point = *point;
+        self.tree
+            .update_point_and_value(identity.clone(), point, |slot| slot.removed = true);
+        self.tombstones.insert(identity.clone());
+        true
+    }
+
+    /// Remove all tombstones from the tree, which also merges the nodes that became too small. Returns the amount of removed tombstones.
+    pub fn vacuum(&mut self) -> usize {
+        let tombstones = std::mem::take(&mut self.tombstones);
+        for identity in &tombstones {
+            self.tree.try_remove(identity);
+        }
+        tombstones.len()
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, see [`QuadTree::find_range`]. Tombstones are skipped.
+    pub fn find_range<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        callback: impl FnMut(&ID, Point, &'a T),
+    ) {
+        self.find_range_with_boundary(center, range, RangeBoundary::Inclusive, callback);
+    }
+
+    /// Find all entries within `range` of point `center`, see [`QuadTree::find_range_with_boundary`]. Tombstones are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `boundary` is a negative [`RangeBoundary::Tolerance`].
+    pub fn find_range_with_boundary<'a>(
+        &'a self,
+        center: Point,
+        range: R32,
+        boundary: RangeBoundary,
+        mut callback: impl FnMut(&ID, Point, &'a T),
+    ) {
+        self.tree
+            .find_range_with_boundary(center, range, boundary, |identity, point, slot| {
+                if !slot.removed {
+                    callback(identity, point, &slot.value);
+                }
+            });
+    }
+}
@@ -0,0 +1,99 @@
+//! Moving and scaling a whole tree, e.g. to re-center a floating origin.
+
+use crate::{expect_consistent, point::clamped_r32, Bucket, Index, Point, QuadTree, Rect, R32};
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Move the rect of this tree and every entry in it by `delta`.
+    ///
+    /// The positions of the entries relative to each other and to the tree stay the same, so this keeps the layout of the tree and only rewrites the positions. Only the few entries that end up on the other side of an edge because of rounding are moved to another leaf.
+    ///
+    /// A delta that is saved by [`QuadTree::save_incremental`] after this can only be applied to a tree that covers the new area.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a new position does not fit in a float.
+    pub fn translate_all(&mut self, delta: Point) {
+        self.transform_all(|point| point + delta);
+    }
+
+    /// Scale the rect of this tree and every entry in it by `factor`, around the origin. See [`QuadTree::translate_all`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `factor` is not larger than 0, or if a new position does not fit in a float.
+    pub fn scale_all(&mut self, factor: R32) {
+        assert!(
+            factor > 0.0,
+            "Scale factor {factor} should be larger than 0"
+        );
+        self.transform_all(|point| Point::new_noisy_float(point.x * factor, point.y * factor));
+    }
+
+    /// Move the origin of this tree to `new_origin`, for engines with a floating origin: every position, also of the entries outside of this tree, becomes relative to `new_origin`. See [`QuadTree::translate_all`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a new position does not fit in a float.
+    pub fn recenter(&mut self, new_origin: Point) {
+        self.translate_all(-new_origin);
+    }
+
+    /// The same as [`QuadTree::recenter`], and pass every entry that is more than `max_distance` away from the new origin to `on_far_away`, with its new position. Those entries are not removed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a new position does not fit in a float.
+    pub fn recenter_reporting(
+        &mut self,
+        new_origin: Point,
+        max_distance: R32,
+        mut on_far_away: impl FnMut(&ID, Point),
+    ) {
+        self.recenter(new_origin);
+        let max_distance_squared = clamped_r32(max_distance.raw() * max_distance.raw());
+        for (identity, (point, _, _)) in &self.identity_to_point {
+            if Point::zero().distance_squared_to(*point) > max_distance_squared {
+                on_far_away(identity, *point);
+            }
+        }
+    }
+
+    /// Apply `transform`, which must keep the order of all coordinates, to the rect and every entry
+    fn transform_all(&mut self, transform: impl Fn(Point) -> Point) {
+        self.rect = Rect::new(
+            transform(self.rect.top_left()),
+            transform(self.rect.bottom_right()),
+        );
+        for bucket in &mut self.items {
+            if let Bucket::Owned(entries) = bucket {
+                for (ip, _) in entries {
+                    ip.point = transform(ip.point);
+                }
+            }
+        }
+        self.outside_of_range.transform_points(&transform);
+        let mut misplaced = Vec::new();
+        for (identity, (point, index, _)) in &mut self.identity_to_point {
+            *point = transform(*point);
+            // rounding keeps the order of two coordinates, but can make them equal
+            let in_place = match index {
+                Some(index) => self
+                    .rect
+                    .get_index_rect(*index)
+                    .contains_as_leaf(self.rect, *point),
+                None => !self.rect.contains(*point),
+            };
+            if !in_place {
+                misplaced.push((identity.clone(), *point));
+            }
+        }
+        self.dirty.touch(Index::ROOT);
+        self.dirty.touch_outside();
+        for (identity, point) in misplaced {
+            expect_consistent(self.move_entry(identity, point, |_| {}));
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! [`QuadTree::validate`], which checks the internal state of a tree, e.g. of a tree that was loaded from a file that may be damaged.
+
+use crate::{
+    bucket::Bucket, error::CorruptionError, index::Index, point::Quadrant, QuadTree, Rect,
+};
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Check that the internal state of this tree is consistent: every entry is in the leaf that covers its position, every entry outside of the tree is really outside of it, and the position and leaf that are stored for every identity match the entry.
+    ///
+    /// This visits every node and every entry, so it is meant for debugging and for trees that were loaded from an untrusted source, not for every frame.
+    ///
+    /// # Errors
+    ///
+    /// Will return the first inconsistency that is found.
+    pub fn validate(&self) -> Result<(), CorruptionError> {
+        let in_leaves = self.validate_node(Index::ROOT, self.rect)?;
+        for (identity, (_, point)) in &self.outside_of_range {
+            if self.rect.contains(*point) {
+                return Err(CorruptionError::new(
+                    "entry outside of range lies inside of the tree",
+                ));
+            }
+            if self
+                .identity_to_point
+                .get(identity)
+                .map(|(point, index, _)| (*point, *index))
+                != Some((*point, None))
+            {
+                return Err(CorruptionError::new(
+                    "entry outside of range has the wrong identity entry",
+                ));
+            }
+        }
+        if in_leaves + self.outside_of_range.len() != self.identity_to_point.len() {
+            return Err(CorruptionError::new("identity has no entry"));
+        }
+        Ok(())
+    }
+
+    /// Validate the subtree at `index`, and return the amount of entries in it
+    fn validate_node(&self, index: Index, rect: Rect) -> Result<usize, CorruptionError> {
+        match self.items.get(index.to_idx()) {
+            Some(Bucket::Owned(entries)) => {
+                for (ip, _) in entries {
+                    if !rect.contains_as_leaf(self.rect, ip.point) {
+                        return Err(CorruptionError::new("entry lies outside of its leaf"));
+                    }
+                    if self
+                        .identity_to_point
+                        .get(&ip.identity)
+                        .map(|(point, index, _)| (*point, *index))
+                        != Some((ip.point, Some(index)))
+                    {
+                        return Err(CorruptionError::new("entry has the wrong identity entry"));
+                    }
+                }
+                Ok(entries.len())
+            }
+            Some(Bucket::Nested) => {
+                let mut count = 0;
+                for quadrant in Quadrant::all() {
+                    let child = index
+                        .checked_child_at(quadrant)
+                        .ok_or(CorruptionError::new("nested node at the maximum depth"))?;
+                    count += self.validate_node(child, rect.get_child_at(quadrant))?;
+                }
+                Ok(count)
+            }
+            // the vec is not grown for empty leaves at the end of it
+            None => Ok(0),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! [`WeakEntryRef`], a reference to an entry that does not borrow its tree, e.g. for an event queue whose events can outlive the entries they are about.
+
+use crate::{Point, QuadTree};
+
+/// A reference to an entry of a [`QuadTree`] that does not borrow the tree, created by [`QuadTree::weak_ref`].
+///
+/// Every entry has a generation, which changes when the entry is inserted or moved. [`WeakEntryRef::upgrade`] only finds the entry if its generation is still the same, so a reference to an entry that was removed, moved, or removed and inserted again can not be upgraded anymore.
+///
+/// The generation stays the same when only the value of an entry is updated, when [`QuadTree::translate_all`], [`QuadTree::scale_all`] or [`QuadTree::recenter`] move the whole tree, and when [`QuadTree::migrate_to_bounds`] rebuilds it. Loading a delta with [`QuadTree::apply_incremental`] gives every entry in a loaded node a new generation.
+///
+/// A reference should only be upgraded with the tree that created it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WeakEntryRef<ID> {
+    identity: ID,
+    generation: u64,
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Create a [`WeakEntryRef`] to the entry with the given identity, or `None` if it is not in this tree
+    #[must_use]
+    pub fn weak_ref(&self, identity: &ID) -> Option<WeakEntryRef<ID>> {
+        let (_, _, generation) = self.identity_to_point.get(identity)?;
+        Some(WeakEntryRef {
+            identity: identity.clone(),
+            generation: *generation,
+        })
+    }
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N> {
+    /// Take the generation for an entry that is inserted or moved
+    pub(crate) fn new_generation(&mut self) -> u64 {
+        self.next_generation += 1;
+        self.next_generation
+    }
+}
+
+impl<ID> WeakEntryRef<ID>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// The identity of the referenced entry
+    #[must_use]
+    pub fn identity(&self) -> &ID {
+        &self.identity
+    }
+
+    /// The position and value of the referenced entry, or `None` if it was removed or moved since this reference was created
+    #[must_use]
+    pub fn upgrade<'a, T, const N: usize>(
+        &self,
+        tree: &'a QuadTree<T, ID, N>,
+    ) -> Option<(Point, &'a T)> {
+        let (_, _, generation) = tree.identity_to_point.get(&self.identity)?;
+        if *generation != self.generation {
+            return None;
+        }
+        tree.get(&self.identity)
+    }
+}
@@ -0,0 +1,160 @@
+//! [`QuadTree64`], a tree with `f64` positions for areas that are too large for the precision of `f32`, e.g. a whole planet in meters.
+//!
+//! The nodes are the same as those of a [`QuadTree`]: every position is stored relative to the middle of the tree as a [`Point`], which decides the leaf of an entry, and the exact `f64` position is stored next to its value. A query collects the entries that may be in range with the `f32` positions, and then checks their exact positions, so the results are the same as if everything was computed in `f64`. Entries that are closer together than the precision of `f32` at the edge of the tree end up in the same leaf, which only makes queries slower.
+
+use crate::{IdentityPoint, Point, QuadTree, RangeOptions, R32};
+use noisy_float::types::{r64, R64};
+
+/// A position of a [`QuadTree64`]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Point64 {
+    /// The horizontal coordinate
+    pub x: R64,
+    /// The vertical coordinate
+    pub y: R64,
+}
+
+impl Point64 {
+    /// Create a point at `x, y`. In debug mode this will panic when `x` or `y` are `NaN` or `Infinity`
+    #[must_use]
+    pub fn new(x: f64, y: f64) -> Self {
+        Self {
+            x: r64(x),
+            y: r64(y),
+        }
+    }
+
+    /// The squared distance between this point and `other`, which is infinite if it is too large for an `f64`
+    #[must_use]
+    pub fn distance_squared(self, other: Self) -> f64 {
+        let (dx, dy) = (self.x.raw() - other.x.raw(), self.y.raw() - other.y.raw());
+        dx * dx + dy * dy
+    }
+}
+
+/// A [`QuadTree`] with `f64` positions, see the [module documentation](self).
+///
+/// Only the most common methods are wrapped, because every method of a [`QuadTree`] that takes or returns a position needs an `f64` version here.
+#[derive(Clone, Debug)]
+pub struct QuadTree64<T, ID, const N: usize> {
+    tree: QuadTree<(T, Point64), ID, N>,
+    /// The middle of the tree, which is `0, 0` for the inner tree
+    origin: Point64,
+    /// How far the `f32` distance between two positions in the tree can be off from the exact distance, because of the rounding of the positions
+    slack: f32,
+}
+
+impl<T, ID, const N: usize> QuadTree64<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Create a new tree which covers the area between `top_left` and `bottom_right`, see [`QuadTree::new`]
+    #[must_use]
+    pub fn new(top_left: Point64, bottom_right: Point64) -> Self {
+        let origin = Point64 {
+            x: (top_left.x + bottom_right.x) / 2.,
+            y: (top_left.y + bottom_right.y) / 2.,
+        };
+        let (top_left, bottom_right) = (to_local(origin, top_left), to_local(origin, bottom_right));
+        let largest = [top_left.x, top_left.y, bottom_right.x, bottom_right.y]
+            .into_iter()
+            .map(|n| n.raw().abs())
+            .fold(0., f32::max);
+        Self {
+            tree: QuadTree::new(top_left, bottom_right),
+            origin,
+            // both positions are rounded by at most half an ULP per coordinate, and an ULP is at most `EPSILON` times the largest coordinate
+            slack: 4. * f32::EPSILON * largest,
+        }
+    }
+
+    /// The tree with the positions relative to the middle of this tree, for the queries that are not wrapped. The values are stored with their exact position.
+    #[must_use]
+    pub fn inner(&self) -> &QuadTree<(T, Point64), ID, N> {
+        &self.tree
+    }
+
+    /// The amount of entries in this tree, including the entries outside of its range
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns `true` if this tree has no entries, also none outside of its range
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Insert a new entry, see [`QuadTree::insert`]
+    pub fn insert(&mut self, identity: ID, point: Point64, value: T) {
+        self.tree.insert(
+            IdentityPoint {
+                identity,
+                point: to_local(self.origin, point),
+            },
+            (value, point),
+        );
+    }
+
+    /// Move an entry to `point`, see [`QuadTree::update`]
+    pub fn update(&mut self, identity: ID, point: Point64) -> bool {
+        self.tree
+            .update_point_and_value(identity, to_local(self.origin, point), |(_, exact)| {
+                *exact = point;
+            })
+    }
+
+    /// Remove an entry, see [`QuadTree::try_remove`]
+    pub fn try_remove(&mut self, identity: &ID) -> Option<(T, Point64)> {
+        self.tree.try_remove(identity).map(|(value, _)| value)
+    }
+
+    /// The position and value of the entry with the given identity, see [`QuadTree::get`]
+    #[must_use]
+    pub fn get(&self, identity: &ID) -> Option<(Point64, &T)> {
+        self.tree
+            .get(identity)
+            .map(|(_, (value, point))| (*point, value))
+    }
+
+    /// Find all entries with a distance less than `range` away from point `center`, including the entries that are exactly `range` away, see [`QuadTree::find_range`]. The distances are computed with the exact positions.
+    #[allow(clippy::cast_possible_truncation)] // the range is clamped to the range of `f32`
+    pub fn find_range<'a>(
+        &'a self,
+        center: Point64,
+        range: R64,
+        mut callback: impl FnMut(&ID, Point64, &'a T),
+    ) {
+        let range_squared = range.raw() * range.raw();
+        let mut check = |identity: &ID, (value, point): &'a (T, Point64)| {
+            if point.distance_squared(center) <= range_squared {
+                callback(identity, *point, value);
+            }
+        };
+        // a center outside of the range of `f32` is clamped towards the tree, which only makes the distances to the entries in it shorter
+        let local_center = to_local(self.origin, center);
+        let center_slack =
+            2. * f32::EPSILON * local_center.x.raw().abs().max(local_center.y.raw().abs());
+        let local_range = (range.raw() as f32).min(f32::MAX / 4.) * (1. + 4. * f32::EPSILON);
+        self.tree.find_range_with_options(
+            local_center,
+            R32::new(local_range + self.slack + center_slack),
+            RangeOptions::new().include_out_of_range(false),
+            |identity, _, value| check(identity, value),
+        );
+        // the positions outside of the tree can be too far away to be compared as `f32`
+        for (identity, _, value) in self.tree.iter_out_of_range() {
+            check(identity, value);
+        }
+    }
+}
+
+/// `point` relative to `origin` as a [`Point`] of the inner tree, clamped to the range of `f32`
+#[allow(clippy::cast_possible_truncation)]
+fn to_local(origin: Point64, point: Point64) -> Point {
+    Point::new_clamped(
+        (point.x - origin.x).raw() as f32,
+        (point.y - origin.y).raw() as f32,
+    )
+}
@@ -0,0 +1,151 @@
+//! [`ZippedQuery`], which walks two trees with the same rect at the same time, to compare two frames or two sets of entries area by area.
+
+use crate::{bucket::Bucket, index::Index, point::Quadrant, IdentityPoint, QuadTree, Rect};
+use smallvec::SmallVec;
+
+/// A node of one of the two trees of a [`ZippedQuery`] that still has to be visited
+enum Side<'a, E> {
+    /// A node that has not been looked at yet
+    Node(Index),
+    /// A leaf that covers the node, with the rect of that leaf
+    Leaf(&'a [E], Rect),
+}
+
+impl<E> Clone for Side<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for Side<'_, E> {}
+
+/// The nodes that still have to be visited. A node is only pushed with its 3 siblings, so even the deepest tree needs at most `15 * 3 + 4` nodes, and this never allocates.
+type Stack<'a, T, U, ID> = SmallVec<
+    [(
+        Rect,
+        Side<'a, (IdentityPoint<ID>, T)>,
+        Side<'a, (IdentityPoint<ID>, U)>,
+    ); 64],
+>;
+
+/// The entries of both trees in the same area, returned by [`ZippedQuery`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ZippedNode<'a, T, U, ID> {
+    /// The area, which is the smaller one of the two leaves
+    pub rect: Rect,
+    /// The entries of the leaf of the first tree that covers `rect`
+    pub first: &'a [(IdentityPoint<ID>, T)],
+    /// The rect of the leaf of the first tree, which is larger than `rect` if the second tree is split further here
+    pub first_leaf: Rect,
+    /// The entries of the leaf of the second tree that covers `rect`
+    pub second: &'a [(IdentityPoint<ID>, U)],
+    /// The rect of the leaf of the second tree, which is larger than `rect` if the first tree is split further here
+    pub second_leaf: Rect,
+}
+
+impl<T, U, ID> Clone for ZippedNode<'_, T, U, ID> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, U, ID> Copy for ZippedNode<'_, T, U, ID> {}
+
+/// An iterator over the leaves of two trees with the same rect, in lockstep, created by [`QuadTree::zip`]
+pub struct ZippedQuery<'a, T, U, ID, const N: usize, const M: usize> {
+    first: &'a QuadTree<T, ID, N>,
+    second: &'a QuadTree<U, ID, M>,
+    stack: Stack<'a, T, U, ID>,
+}
+
+impl<T, ID, const N: usize> QuadTree<T, ID, N>
+where
+    ID: Ord + std::fmt::Debug + Clone,
+{
+    /// Walk this tree and `other` at the same time, and return the entries of both trees for every area where either of them has entries. This visits every node of both trees once, which is much cheaper than a query in `other` for every entry of this tree.
+    ///
+    /// Where one tree is split further than the other, every smaller leaf is returned with the larger leaf of the other tree, so the entries of the larger leaf are returned more than once, see [`ZippedNode::first_leaf`]. Entries outside of the range of the trees are not returned, see [`QuadTree::iter_out_of_range`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `other` does not cover the same area as this tree.
+    #[must_use]
+    pub fn zip<'a, U, const M: usize>(
+        &'a self,
+        other: &'a QuadTree<U, ID, M>,
+    ) -> ZippedQuery<'a, T, U, ID, N, M> {
+        assert!(
+            self.rect == other.rect,
+            "Quad trees must cover the same area to be zipped"
+        );
+        let mut stack = Stack::new();
+        stack.push((self.rect, Side::Node(Index::ROOT), Side::Node(Index::ROOT)));
+        ZippedQuery {
+            first: self,
+            second: other,
+            stack,
+        }
+    }
+}
+
+/// Look at the node of `side` with `rect`, and turn it into a [`Side::Leaf`] if it is a leaf, so only nested nodes are left as a [`Side::Node`]. A missing node is an empty leaf.
+fn resolve<'a, T, ID, const N: usize>(
+    side: Side<'a, (IdentityPoint<ID>, T)>,
+    rect: Rect,
+    items: &'a [Bucket<T, ID, N>],
+) -> Side<'a, (IdentityPoint<ID>, T)> {
+    match side {
+        Side::Node(index) => match items.get(index.to_idx()) {
+            Some(Bucket::Nested) => side,
+            Some(Bucket::Owned(entries)) => Side::Leaf(entries, rect),
+            None => Side::Leaf(&[], rect),
+        },
+        Side::Leaf(..) => side,
+    }
+}
+
+/// The side of the child at `quadrant` of a resolved `side`: the same leaf if it is a leaf, or the child of the nested node
+fn child<E>(side: Side<'_, E>, quadrant: Quadrant) -> Option<Side<'_, E>> {
+    match side {
+        Side::Node(index) => index.checked_child_at(quadrant).map(Side::Node),
+        Side::Leaf(..) => Some(side),
+    }
+}
+
+impl<'a, T, U, ID, const N: usize, const M: usize> Iterator for ZippedQuery<'a, T, U, ID, N, M> {
+    type Item = ZippedNode<'a, T, U, ID>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((rect, first, second)) = self.stack.pop() {
+            let first = resolve(first, rect, &self.first.items);
+            let second = resolve(second, rect, &self.second.items);
+            match (first, second) {
+                (Side::Leaf(first, first_leaf), Side::Leaf(second, second_leaf)) => {
+                    if first.is_empty() && second.is_empty() {
+                        continue;
+                    }
+                    return Some(ZippedNode {
+                        rect,
+                        first,
+                        first_leaf,
+                        second,
+                        second_leaf,
+                    });
+                }
+                (first, second) => {
+                    // the children are pushed in reverse, so they are visited in the same order as `find_range`
+                    for quadrant in Quadrant::all().into_iter().rev() {
+                        // a nested node at the maximum depth is corrupt, and is skipped instead of panicking
+                        if let (Some(first), Some(second)) =
+                            (child(first, quadrant), child(second, quadrant))
+                        {
+                            self.stack
+                                .push((rect.get_child_at(quadrant), first, second));
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}